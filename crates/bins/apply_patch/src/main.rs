@@ -0,0 +1,134 @@
+use formats::patch::{self, OpResult, Patch};
+use formats::{
+    archive::PakFile,
+    file_formats::{lrent::LrentFile, tple::TpleFile},
+};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::{env, ffi::OsString, path::Path};
+
+fn print_usage() {
+    println!("Usage: apply_patch <patch.json> <files or directories...> [options]");
+    println!("Applies a patch to .lrent / .tple files, writing <name>_out.<ext>.");
+    println!("Options:");
+    println!("  /force    also write the output if some operations failed");
+}
+
+/// Applies the patch to a single file, returns the archive to write
+fn apply(path: &Path, patch: &Patch) -> Result<(PakFile, Vec<OpResult>), String> {
+    let in_data = File::open(path).map_err(|e| e.to_string())?;
+    let mut in_data = BufReader::new(in_data);
+    let arch = PakFile::load(&mut in_data).map_err(|e| format!("loading archive failed: {e}"))?;
+    // As long as some contents remain unparsed, we need to retain the original string indices:
+    let mut out = PakFile::new();
+    out.strings = arch.strings.clone();
+
+    let lower = path.to_string_lossy().to_lowercase();
+    let results = if lower.ends_with(".lrent") {
+        let mut lrent = LrentFile::load(arch).map_err(|e| format!("loading lrent failed: {e}"))?;
+        let results = patch::apply_lrent(patch, &mut lrent).map_err(|e| e.to_string())?;
+        lrent
+            .save(&mut out)
+            .map_err(|e| format!("writing lrent failed: {e}"))?;
+        results
+    } else {
+        let mut tple = TpleFile::load(arch).map_err(|e| format!("loading tple failed: {e}"))?;
+        let results = patch::apply_tple(patch, &mut tple);
+        tple.save(&mut out)
+            .map_err(|e| format!("writing tple failed: {e}"))?;
+        results
+    };
+    Ok((out, results))
+}
+
+fn main() {
+    println!("Chromosome Toolkit - R1 - Apply patch");
+    let mut queue = VecDeque::new();
+    let mut force = false;
+    for arg in env::args().skip(1) {
+        if arg == "/force" {
+            force = true;
+        } else {
+            queue.push_back(arg);
+        }
+    }
+    let Some(patch_arg) = queue.pop_front() else {
+        print_usage();
+        return;
+    };
+    if queue.is_empty() {
+        print_usage();
+        return;
+    }
+    let patch_os = OsString::from(&patch_arg);
+    let patch: Patch = match File::open(Path::new(&patch_os))
+        .map_err(|e| e.to_string())
+        .and_then(|f| serde_json::from_reader(BufReader::new(f)).map_err(|e| e.to_string()))
+    {
+        Ok(p) => p,
+        Err(e) => {
+            println!("Loading patch failed: {e}");
+            return;
+        }
+    };
+    if !patch.description.is_empty() {
+        println!("{}", patch.description);
+    }
+
+    while let Some(arg) = queue.pop_front() {
+        println!("{}", arg);
+        let os_arg = OsString::from(&arg);
+        let path = Path::new(&os_arg);
+        if !path.exists() {
+            println!("not found");
+            continue;
+        }
+
+        if path.is_dir() {
+            if let Ok(dir) = path.read_dir() {
+                for file in dir.flatten() {
+                    let meta = file.metadata().unwrap();
+                    let path = file.path().to_string_lossy().to_string();
+                    let lower = path.to_lowercase();
+                    if meta.is_dir() || lower.ends_with(".lrent") || lower.ends_with(".tple") {
+                        queue.push_back(path);
+                    }
+                }
+            } else {
+                println!("Reading dir failed");
+            }
+            continue;
+        }
+
+        let (arch, results) = match apply(path, &patch) {
+            Ok(r) => r,
+            Err(e) => {
+                println!("{e}, skipping");
+                continue;
+            }
+        };
+        for result in &results {
+            println!("  {result}");
+        }
+        let failed = results.iter().filter(|r| r.failed()).count();
+        if failed > 0 && !force {
+            println!("{failed} operations failed, not writing output (use /force to write anyway)");
+            continue;
+        }
+
+        let ext_start = arg.rfind('.').unwrap();
+        let out_arg = format!("{}_out{}", &arg[..ext_start], &arg[ext_start..]);
+        let out_os = OsString::from(&out_arg);
+        let out_file = File::create(Path::new(&out_os)).expect("Unable to open output file");
+        let mut out_file = BufWriter::new(out_file);
+        match arch.save(&mut out_file) {
+            Ok(_) => {}
+            Err(e) => {
+                println!("writing archive failed: {e}");
+            }
+        }
+        out_file.flush().unwrap();
+        println!("done");
+    }
+}
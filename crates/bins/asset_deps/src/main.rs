@@ -0,0 +1,347 @@
+use formats::deps::{AssetFile, DepGraph, NodeKind, asset_key};
+use formats::{
+    archive::PakFile,
+    file_formats::{
+        lrent::LrentFile,
+        tple::TpleFile,
+        volume::VolumeFile,
+        world::{SectorFile, WorldFile},
+        xmac::XmacFile,
+    },
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Cursor, Write};
+use std::path::PathBuf;
+use std::{env, ffi::OsString, path::Path};
+
+fn print_usage() {
+    println!("Usage: asset_deps <files or directories...> [options]");
+    println!(
+        "Builds the dependency graph of the resources referenced by .wrl, .sec, .lrent, .tple and ._xmac files."
+    );
+    println!("Options:");
+    println!(
+        "  /assets=path   game volume (.pak), or directory of volumes and/or extracted assets (._xmac, ._ximg):"
+    );
+    println!(
+        "                 reports missing and unused assets, and adds the textures of referenced meshes"
+    );
+    println!(
+        "                 (the volume layout has not been verified against the game's volumes yet)"
+    );
+    println!(
+        "  /users=name    print everything that uses the asset, e.g. /users=Obj_Barrel_01_Diffuse"
+    );
+    println!("  /json=file     write the graph as json");
+    println!("  /dot=file      write the graph in graphviz format");
+}
+
+fn load_archive(path: &Path) -> Option<PakFile> {
+    let in_data = match File::open(path) {
+        Ok(f) => f,
+        Err(e) => {
+            println!("opening {} failed: {e}", path.display());
+            return None;
+        }
+    };
+    let mut in_data = BufReader::new(in_data);
+    match PakFile::load(&mut in_data) {
+        Ok(a) => Some(a),
+        Err(e) => {
+            println!("loading archive {} failed: {e}", path.display());
+            None
+        }
+    }
+}
+
+fn load_xmac(path: &Path) -> Option<XmacFile> {
+    let in_data = File::open(path).ok()?;
+    let mut in_data = BufReader::new(in_data);
+    match XmacFile::load(&mut in_data) {
+        Ok(xmac) => Some(xmac),
+        Err(e) => {
+            println!("loading xmac {} failed: {e}", path.display());
+            None
+        }
+    }
+}
+
+fn load_volume(path: &Path) -> Option<VolumeFile> {
+    let in_data = match File::open(path) {
+        Ok(f) => f,
+        Err(e) => {
+            println!("opening {} failed: {e}", path.display());
+            return None;
+        }
+    };
+    match VolumeFile::load(&mut BufReader::new(in_data)) {
+        Ok(volume) => Some(volume),
+        Err(e) => {
+            println!("loading volume {} failed: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Only the kinds of assets the graph can contain, e.g. no sounds
+fn is_asset(name: &str) -> bool {
+    name.ends_with("._xmac") || name.ends_with("._ximg") || name.ends_with("._xmat")
+}
+
+/// The assets the game can find, like its virtual file system: extracted files and the contents of volumes
+#[derive(Default)]
+struct Assets {
+    files: Vec<AssetFile>,
+    volumes: Vec<(PathBuf, VolumeFile)>,
+    /// Volume and entry index of each file, `None` for extracted files
+    sources: Vec<Option<(usize, usize)>>,
+}
+
+impl Assets {
+    /// Lists the volume at `path`, or the extracted assets and volumes in the directory `path`
+    fn list(path: &Path) -> Self {
+        let mut assets = Self::default();
+        let paths = if path.is_dir() {
+            list_files(path, |name| is_asset(name) || name.ends_with(".pak"))
+        } else {
+            vec![path.to_path_buf()]
+        };
+        for path in paths {
+            let name = path.to_string_lossy().to_string();
+            if !name.to_lowercase().ends_with(".pak") {
+                assets.files.push(AssetFile::from_path(&name));
+                assets.sources.push(None);
+                continue;
+            }
+            let Some(volume) = load_volume(&path) else {
+                continue;
+            };
+            let volume_idx = assets.volumes.len();
+            for (entry_idx, file) in AssetFile::from_volume(&name, &volume)
+                .into_iter()
+                .enumerate()
+            {
+                if is_asset(&file.path.to_lowercase()) {
+                    assets.files.push(file);
+                    assets.sources.push(Some((volume_idx, entry_idx)));
+                }
+            }
+            assets.volumes.push((path, volume));
+        }
+        assets
+    }
+
+    fn load_xmac(&self, idx: usize) -> Option<XmacFile> {
+        let file = &self.files[idx];
+        let Some((volume_idx, entry_idx)) = self.sources[idx] else {
+            return load_xmac(Path::new(&OsString::from(&file.path)));
+        };
+        let (volume_path, volume) = &self.volumes[volume_idx];
+        let mut in_data = BufReader::new(File::open(volume_path).ok()?);
+        let result = volume
+            .read_file(&mut in_data, &volume.files[entry_idx])
+            .and_then(|data| XmacFile::load(&mut Cursor::new(data)));
+        match result {
+            Ok(xmac) => Some(xmac),
+            Err(e) => {
+                println!("loading xmac {} failed: {e}", file.path);
+                None
+            }
+        }
+    }
+}
+
+fn add_file(graph: &mut DepGraph, path: &Path) {
+    let name = path.to_string_lossy().to_string();
+    let lower = name.to_lowercase();
+    if lower.ends_with("._xmac") {
+        if let Some(xmac) = load_xmac(path) {
+            let file_name = path.file_name().unwrap().to_string_lossy();
+            graph.add_xmac(&file_name, &xmac);
+        }
+        return;
+    }
+    let Some(arch) = load_archive(path) else {
+        return;
+    };
+    if lower.ends_with(".wrl") {
+        match WorldFile::load(arch).and_then(|world| Ok(world.sectors()?.to_vec())) {
+            Ok(sectors) => graph.add_world(&name, &sectors),
+            Err(e) => println!("loading world {name} failed: {e}"),
+        }
+    } else if lower.ends_with(".sec") {
+        match SectorFile::load(arch).and_then(|sector| Ok(sector.layers()?.to_vec())) {
+            Ok(layers) => graph.add_sector(&name, &layers),
+            Err(e) => println!("loading sector {name} failed: {e}"),
+        }
+    } else if lower.ends_with(".lrent") {
+        match LrentFile::load(arch) {
+            Ok(lrent) => {
+                if let Some(ctx) = lrent.get_context() {
+                    graph.add_entities(&name, NodeKind::Entity, &ctx.entities);
+                }
+            }
+            Err(e) => println!("loading lrent {name} failed: {e}"),
+        }
+    } else {
+        match TpleFile::load(arch) {
+            Ok(tple) => graph.add_entities(&name, NodeKind::Template, &tple.entities),
+            Err(e) => println!("loading tple {name} failed: {e}"),
+        }
+    }
+}
+
+/// Recursively lists all files in `dir` whose name matches `filter`
+fn list_files(dir: &Path, filter: impl Fn(&str) -> bool) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut queue = VecDeque::from([dir.to_path_buf()]);
+    while let Some(dir) = queue.pop_front() {
+        let Ok(entries) = dir.read_dir() else {
+            println!("Reading dir {} failed", dir.display());
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                queue.push_back(path);
+            } else if filter(&path.to_string_lossy().to_lowercase()) {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+/// Loads the xmac files of all meshes in the graph from the assets
+fn add_mesh_textures(graph: &mut DepGraph, assets: &Assets) {
+    let xmacs = assets
+        .files
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| f.extension == "_xmac")
+        .map(|(idx, f)| (f.key.as_str(), idx))
+        .collect::<HashMap<_, _>>();
+    let meshes = graph
+        .nodes
+        .iter()
+        .filter(|n| n.kind == NodeKind::Mesh)
+        .map(|n| n.id.clone())
+        .collect::<HashSet<_>>();
+    for mesh in meshes {
+        let Some(idx) = xmacs.get(mesh.as_str()) else {
+            continue;
+        };
+        if let Some(xmac) = assets.load_xmac(*idx) {
+            graph.add_xmac(&mesh, &xmac);
+        }
+    }
+}
+
+fn format_path(graph: &DepGraph, path: &[usize]) -> String {
+    path.iter()
+        .map(|idx| graph.nodes[*idx].label.as_str())
+        .collect::<Vec<_>>()
+        .join(" > ")
+}
+
+fn main() {
+    println!("Chromosome Toolkit - R1 - Asset dependencies");
+    let mut inputs = VecDeque::new();
+    let mut assets_arg = None;
+    let mut users_args = Vec::new();
+    let mut json_arg = None;
+    let mut dot_arg = None;
+    for arg in env::args().skip(1) {
+        if let Some(value) = arg.strip_prefix("/assets=") {
+            assets_arg = Some(value.to_string());
+        } else if let Some(value) = arg.strip_prefix("/users=") {
+            users_args.push(value.to_string());
+        } else if let Some(value) = arg.strip_prefix("/json=") {
+            json_arg = Some(value.to_string());
+        } else if let Some(value) = arg.strip_prefix("/dot=") {
+            dot_arg = Some(value.to_string());
+        } else {
+            inputs.push_back(arg);
+        }
+    }
+    if inputs.is_empty() {
+        print_usage();
+        return;
+    }
+
+    let mut graph = DepGraph::new();
+    while let Some(arg) = inputs.pop_front() {
+        let os_arg = OsString::from(&arg);
+        let path = Path::new(&os_arg);
+        if !path.exists() {
+            println!("{arg} not found");
+            continue;
+        }
+        if path.is_dir() {
+            let files = list_files(path, |name| {
+                [".wrl", ".sec", ".lrent", ".tple", "._xmac"]
+                    .iter()
+                    .any(|ext| name.ends_with(ext))
+            });
+            inputs.extend(files.iter().map(|f| f.to_string_lossy().to_string()));
+            continue;
+        }
+        add_file(&mut graph, path);
+    }
+
+    if let Some(assets_arg) = &assets_arg {
+        let assets_os = OsString::from(assets_arg);
+        let assets = Assets::list(Path::new(&assets_os));
+        add_mesh_textures(&mut graph, &assets);
+
+        let report = graph.check(&assets.files);
+        for idx in &report.missing {
+            let node = &graph.nodes[*idx];
+            let used_by = graph
+                .user_paths(*idx)
+                .first()
+                .map(|path| format_path(&graph, path))
+                .unwrap_or_default();
+            println!("MISSING {:?} {} (used by {used_by})", node.kind, node.label);
+        }
+        for file in &report.unused {
+            println!("UNUSED {file}");
+        }
+        println!(
+            "{} missing, {} unused",
+            report.missing.len(),
+            report.unused.len()
+        );
+    }
+
+    for name in &users_args {
+        let key = asset_key(name);
+        let nodes = (0..graph.nodes.len())
+            .filter(|idx| graph.nodes[*idx].kind.is_asset() && graph.nodes[*idx].id == key)
+            .collect::<Vec<_>>();
+        if nodes.is_empty() {
+            println!("{name} is not used");
+        }
+        for idx in nodes {
+            println!("{name} ({:?}) is used by:", graph.nodes[idx].kind);
+            for path in graph.user_paths(idx) {
+                println!("  {}", format_path(&graph, &path));
+            }
+        }
+    }
+
+    println!("{} nodes, {} edges", graph.nodes.len(), graph.edges.len());
+    if let Some(json_arg) = &json_arg {
+        let out_os = OsString::from(json_arg);
+        let out_file = File::create(Path::new(&out_os)).expect("Unable to open output file");
+        let mut out_file = BufWriter::new(out_file);
+        serde_json::to_writer_pretty(&mut out_file, &graph).unwrap();
+        out_file.flush().unwrap();
+    }
+    if let Some(dot_arg) = &dot_arg {
+        let out_os = OsString::from(dot_arg);
+        std::fs::write(Path::new(&out_os), graph.to_dot()).expect("Unable to write output file");
+    }
+    println!("done");
+}
@@ -0,0 +1,128 @@
+use formats::diff::{self, Diff};
+use formats::{
+    archive::PakFile,
+    file_formats::{lrent::LrentFile, tple::TpleFile},
+};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fs::File;
+use std::io::{BufReader, Write};
+use std::{env, ffi::OsString, path::Path};
+
+fn print_usage() {
+    println!("Usage: entity_diff <old> <new> [options]");
+    println!("Compares two .lrent or .tple files (binary or .json dumps) entity by entity.");
+    println!("Options:");
+    println!("  /json           write a machine-readable patch to <new>.diff.json");
+    println!(
+        "  /tolerance=x    tolerance for transform comparisons (default {})",
+        diff::DEFAULT_TOLERANCE
+    );
+}
+
+/// Loads either a json dump or a binary file
+fn load_file<T: DeserializeOwned>(
+    arg: &str,
+    load_bin: fn(PakFile) -> formats::error::Result<T>,
+) -> Option<T> {
+    let os_arg = OsString::from(arg);
+    let path = Path::new(&os_arg);
+    let in_data = match File::open(path) {
+        Ok(f) => f,
+        Err(e) => {
+            println!("opening {arg} failed: {e}");
+            return None;
+        }
+    };
+    let mut in_data = BufReader::new(in_data);
+    if arg.to_lowercase().ends_with(".json") {
+        return match serde_json::from_reader(in_data) {
+            Ok(r) => Some(r),
+            Err(e) => {
+                println!("Parsing JSON {arg} failed: {e}");
+                None
+            }
+        };
+    }
+    let arch = match PakFile::load(&mut in_data) {
+        Ok(a) => a,
+        Err(e) => {
+            println!("loading archive {arg} failed: {e}");
+            return None;
+        }
+    };
+    match load_bin(arch) {
+        Ok(r) => Some(r),
+        Err(e) => {
+            println!("loading {arg} failed: {e}");
+            None
+        }
+    }
+}
+
+fn output<T: Serialize>(diff: &Diff<T>, new_arg: &str, write_json: bool) {
+    if diff.is_empty() {
+        println!("No differences");
+    } else {
+        println!("{diff}");
+    }
+    if !write_json {
+        return;
+    }
+    let out_arg = format!("{new_arg}.diff.json");
+    let out_os = OsString::from(&out_arg);
+    let mut out_file = File::create(Path::new(&out_os)).expect("Unable to open output file");
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b"  ");
+    let mut ser = serde_json::Serializer::with_formatter(&mut out_file, formatter);
+    diff.serialize(&mut ser).unwrap();
+    out_file.flush().unwrap();
+    println!("Patch written to {out_arg}");
+}
+
+fn main() {
+    println!("Chromosome Toolkit - R1 - Entity diff");
+    let mut positional = Vec::new();
+    let mut write_json = false;
+    let mut tolerance = diff::DEFAULT_TOLERANCE;
+    for arg in env::args().skip(1) {
+        if arg == "/json" {
+            write_json = true;
+        } else if let Some(value) = arg.strip_prefix("/tolerance=") {
+            let Ok(v) = value.parse::<f32>() else {
+                println!("Invalid tolerance {value}");
+                return;
+            };
+            tolerance = v;
+        } else {
+            positional.push(arg);
+        }
+    }
+    let [old_arg, new_arg] = positional.as_slice() else {
+        print_usage();
+        return;
+    };
+
+    let lower = new_arg.to_lowercase();
+    if lower.contains(".lrent") {
+        let Some(old) = load_file(old_arg, LrentFile::load) else {
+            return;
+        };
+        let Some(new) = load_file(new_arg, LrentFile::load) else {
+            return;
+        };
+        match diff::diff_lrent(&old, &new, tolerance) {
+            Ok(diff) => output(&diff, new_arg, write_json),
+            Err(e) => println!("diff failed: {e}"),
+        }
+    } else if lower.contains(".tple") {
+        let Some(old) = load_file(old_arg, TpleFile::load) else {
+            return;
+        };
+        let Some(new) = load_file(new_arg, TpleFile::load) else {
+            return;
+        };
+        output(&diff::diff_tple(&old, &new, tolerance), new_arg, write_json);
+    } else {
+        println!("Unknown file type {new_arg}, expected .lrent or .tple");
+    }
+}
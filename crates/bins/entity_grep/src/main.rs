@@ -0,0 +1,93 @@
+use formats::query::{Query, QueryMatch};
+use formats::{
+    archive::PakFile,
+    file_formats::{lrent::LrentFile, tple::TpleFile},
+};
+use std::collections::VecDeque;
+use std::{env, ffi::OsString, path::Path};
+
+fn print_usage() {
+    println!("Usage: entity_grep <query> <files or directories...>");
+    println!("Searches .lrent and .tple files for matching entities / templates.");
+    println!("Query terms (all have to match): <key><op><value>");
+    println!(
+        "  keys: name, guid, class, near (x,y,z,radius in cm) or a property name (optionally Class.Property)"
+    );
+    println!("  ops:  = != < <= > >= ~ (contains)");
+    println!("Example: entity_grep \"class=gCItem_PS Value>500\" data/");
+}
+
+fn search(path: &Path, query: &Query) -> Result<Vec<QueryMatch>, String> {
+    let in_data = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut in_data = std::io::BufReader::new(in_data);
+    let arch = PakFile::load(&mut in_data).map_err(|e| format!("loading archive failed: {e}"))?;
+    let lower = path.to_string_lossy().to_lowercase();
+    if lower.ends_with(".lrent") {
+        let lrent = LrentFile::load(arch).map_err(|e| format!("loading lrent failed: {e}"))?;
+        Ok(lrent
+            .get_context()
+            .map(|ctx| query.run_context(ctx))
+            .unwrap_or_default())
+    } else {
+        let tple = TpleFile::load(arch).map_err(|e| format!("loading tple failed: {e}"))?;
+        Ok(query.run_tple(&tple))
+    }
+}
+
+fn main() {
+    println!("Chromosome Toolkit - R1 - Entity grep");
+    let mut queue = env::args().skip(1).collect::<VecDeque<_>>();
+    let Some(query_arg) = queue.pop_front() else {
+        print_usage();
+        return;
+    };
+    if queue.is_empty() {
+        print_usage();
+        return;
+    }
+    let query = match Query::parse(&query_arg) {
+        Ok(q) => q,
+        Err(e) => {
+            println!("Invalid query: {e}");
+            return;
+        }
+    };
+
+    // Loading is quite verbose, so results are collected and printed at the end:
+    let mut results = Vec::new();
+    while let Some(arg) = queue.pop_front() {
+        let os_arg = OsString::from(&arg);
+        let path = Path::new(&os_arg);
+        if !path.exists() {
+            println!("{arg} not found");
+            continue;
+        }
+
+        if path.is_dir() {
+            if let Ok(dir) = path.read_dir() {
+                for file in dir.flatten() {
+                    let meta = file.metadata().unwrap();
+                    let path = file.path().to_string_lossy().to_string();
+                    let lower = path.to_lowercase();
+                    if meta.is_dir() || lower.ends_with(".lrent") || lower.ends_with(".tple") {
+                        queue.push_back(path);
+                    }
+                }
+            } else {
+                println!("Reading dir failed");
+            }
+            continue;
+        }
+
+        match search(path, &query) {
+            Ok(matches) => results.extend(matches.into_iter().map(|m| (arg.clone(), m))),
+            Err(e) => println!("{arg}: {e}, skipping"),
+        }
+    }
+
+    println!("----");
+    for (file, result) in &results {
+        println!("{file} > {result}");
+    }
+    println!("{} matches", results.len());
+}
@@ -0,0 +1,176 @@
+use formats::merge::{self, Merged};
+use formats::{
+    archive::PakFile,
+    file_formats::{lrent::LrentFile, tple::TpleFile},
+};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::{env, ffi::OsString, path::Path};
+
+fn print_usage() {
+    println!("Usage: entity_merge <base> <mod a> <mod b> [options]");
+    println!(
+        "Merges the changes of two modified .lrent or .tple files made against the same base."
+    );
+    println!("If both change the same value, mod a takes precedence and a conflict is reported.");
+    println!("Options:");
+    println!("  /out=path       output file (default <mod a>_merged)");
+    println!(
+        "  /tolerance=x    tolerance for transform comparisons (default {})",
+        formats::diff::DEFAULT_TOLERANCE
+    );
+}
+
+fn load_archive(arg: &str) -> Option<PakFile> {
+    let os_arg = OsString::from(arg);
+    let in_data = match File::open(Path::new(&os_arg)) {
+        Ok(f) => f,
+        Err(e) => {
+            println!("opening {arg} failed: {e}");
+            return None;
+        }
+    };
+    let mut in_data = BufReader::new(in_data);
+    match PakFile::load(&mut in_data) {
+        Ok(a) => Some(a),
+        Err(e) => {
+            println!("loading archive {arg} failed: {e}");
+            None
+        }
+    }
+}
+
+fn load_all<T>(
+    args: &[String],
+    load: fn(PakFile) -> formats::error::Result<T>,
+) -> Option<(Vec<T>, Vec<Vec<String>>)> {
+    let mut files = Vec::new();
+    let mut strings = Vec::new();
+    for arg in args {
+        let arch = load_archive(arg)?;
+        strings.push(arch.strings.clone());
+        match load(arch) {
+            Ok(f) => files.push(f),
+            Err(e) => {
+                println!("loading {arg} failed: {e}");
+                return None;
+            }
+        }
+    }
+    Some((files, strings))
+}
+
+/// As long as some contents remain unparsed, we need to retain the original string indices.
+/// This is only possible for both mods if one string table extends the other,
+/// otherwise `None` is returned if any unparsed data was taken from mod b.
+fn pick_strings<T>(merged: &Merged<T>, strings: &[Vec<String>]) -> Option<Vec<String>> {
+    let (a, b) = (&strings[1], &strings[2]);
+    if b.starts_with(a) {
+        return Some(b.clone());
+    }
+    if !a.starts_with(b) && !merged.raw_data_from_b.is_empty() {
+        println!(
+            "The string tables of both mods are incompatible, but unparsed data was taken from mod b:"
+        );
+        for location in &merged.raw_data_from_b {
+            println!("  {location}");
+        }
+        println!("Its string references would be invalid, not writing a merged file");
+        return None;
+    }
+    Some(a.clone())
+}
+
+fn main() {
+    println!("Chromosome Toolkit - R1 - Entity merge");
+    let mut positional = Vec::new();
+    let mut out_arg = None;
+    let mut tolerance = formats::diff::DEFAULT_TOLERANCE;
+    for arg in env::args().skip(1) {
+        if let Some(value) = arg.strip_prefix("/out=") {
+            out_arg = Some(value.to_string());
+        } else if let Some(value) = arg.strip_prefix("/tolerance=") {
+            let Ok(v) = value.parse::<f32>() else {
+                println!("Invalid tolerance {value}");
+                return;
+            };
+            tolerance = v;
+        } else {
+            positional.push(arg);
+        }
+    }
+    if positional.len() != 3 {
+        print_usage();
+        return;
+    }
+    let a_arg = &positional[1];
+    let lower = a_arg.to_lowercase();
+
+    let mut arch = PakFile::new();
+    let conflicts = if lower.ends_with(".lrent") {
+        let Some((files, strings)) = load_all(&positional, LrentFile::load) else {
+            return;
+        };
+        let merged = match merge::merge_lrent(&files[0], &files[1], &files[2], tolerance) {
+            Ok(m) => m,
+            Err(e) => {
+                println!("merge failed: {e}");
+                return;
+            }
+        };
+        let Some(strings) = pick_strings(&merged, &strings) else {
+            return;
+        };
+        arch.strings = strings;
+        if let Err(e) = merged.result.save(&mut arch) {
+            println!("writing lrent failed: {e}");
+            return;
+        }
+        merged.conflicts
+    } else if lower.ends_with(".tple") {
+        let Some((files, strings)) = load_all(&positional, TpleFile::load) else {
+            return;
+        };
+        let merged = merge::merge_tple(&files[0], &files[1], &files[2]);
+        let Some(strings) = pick_strings(&merged, &strings) else {
+            return;
+        };
+        arch.strings = strings;
+        if let Err(e) = merged.result.save(&mut arch) {
+            println!("writing tple failed: {e}");
+            return;
+        }
+        merged.conflicts
+    } else {
+        println!("Unknown file type {a_arg}, expected .lrent or .tple");
+        return;
+    };
+
+    if conflicts.is_empty() {
+        println!("Merged without conflicts");
+    } else {
+        println!("{} conflicts, kept the version of mod a:", conflicts.len());
+        for conflict in &conflicts {
+            println!("  {conflict}");
+        }
+    }
+
+    let out_arg = out_arg.unwrap_or_else(|| {
+        let ext_start = a_arg.rfind('.').unwrap();
+        format!("{}_merged{}", &a_arg[..ext_start], &a_arg[ext_start..])
+    });
+    if positional.contains(&out_arg) {
+        panic!("In == out path");
+    }
+    let out_os = OsString::from(&out_arg);
+    let out_file = File::create(Path::new(&out_os)).expect("Unable to open output file");
+    let mut out_file = BufWriter::new(out_file);
+    match arch.save(&mut out_file) {
+        Ok(_) => {}
+        Err(e) => {
+            println!("writing archive failed: {e}");
+        }
+    }
+    out_file.flush().unwrap();
+    println!("done");
+}
@@ -0,0 +1,173 @@
+use formats::{
+    archive::PakFile,
+    file_formats::{lrent::LrentFile, tple::TpleFile},
+};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::{env, ffi::OsString, path::Path};
+
+mod translation;
+
+fn print_usage() {
+    println!("Usage: gltf_to_lrent <scene.gltf> <file.lrent> [options]");
+    println!(
+        "Entities are matched to nodes by the GUID in the node extras (as written by lrent_to_gltf)."
+    );
+    println!("Collision shapes exported with /collision are updated from their node transforms,");
+    println!("lights exported with /lights from their KHR_lights_punctual values.");
+    println!("Options:");
+    println!("  /tple=file      template file used for new entities, can be given multiple times");
+    println!("  /create         create entities for unmatched nodes that have a template GUID");
+    println!("  /tolerance=x    ignore position changes below x (game units, default 0.01)");
+}
+
+fn load_archive(path: &Path) -> Option<PakFile> {
+    let in_data = match File::open(path) {
+        Ok(f) => f,
+        Err(e) => {
+            println!("opening {} failed: {e}", path.display());
+            return None;
+        }
+    };
+    let mut in_data = BufReader::new(in_data);
+    match PakFile::load(&mut in_data) {
+        Ok(a) => Some(a),
+        Err(e) => {
+            println!("loading archive {} failed: {e}", path.display());
+            None
+        }
+    }
+}
+
+fn main() {
+    println!("Chromosome Toolkit - R1 - GLTF to LRENT");
+    let mut positional = Vec::new();
+    let mut tple_args = Vec::new();
+    let mut create = false;
+    let mut tolerance = 0.01;
+    for arg in env::args().skip(1) {
+        if let Some(value) = arg.strip_prefix("/tple=") {
+            tple_args.push(value.to_string());
+        } else if &arg == "/create" {
+            create = true;
+        } else if let Some(value) = arg.strip_prefix("/tolerance=") {
+            let Ok(v) = value.parse::<f32>() else {
+                println!("Invalid tolerance {value}");
+                return;
+            };
+            tolerance = v;
+        } else {
+            positional.push(arg);
+        }
+    }
+    let [gltf_arg, lrent_arg] = positional.as_slice() else {
+        print_usage();
+        return;
+    };
+    if create && tple_args.is_empty() {
+        println!("/create needs at least one /tple= file");
+        return;
+    }
+
+    let gltf_os = OsString::from(gltf_arg);
+    let document = match gltf::Gltf::open(Path::new(&gltf_os)) {
+        Ok(gltf) => gltf.document,
+        Err(e) => {
+            println!("loading glTF failed: {e}");
+            return;
+        }
+    };
+
+    let mut templates = Vec::new();
+    for tple_arg in &tple_args {
+        let tple_os = OsString::from(tple_arg);
+        let Some(tple_arch) = load_archive(Path::new(&tple_os)) else {
+            return;
+        };
+        match TpleFile::load(tple_arch) {
+            Ok(t) => templates.push(t),
+            Err(e) => {
+                println!("loading tple {tple_arg} failed: {e}");
+                return;
+            }
+        }
+    }
+
+    let lrent_os = OsString::from(lrent_arg);
+    let Some(lrent_arch) = load_archive(Path::new(&lrent_os)) else {
+        return;
+    };
+    // As long as some contents remain unparsed, we need to retain the original string indices:
+    let prefill_strings = lrent_arch.strings.clone();
+    let mut lrent = match LrentFile::load(lrent_arch) {
+        Ok(l) => l,
+        Err(e) => {
+            println!("loading lrent failed: {e}");
+            return;
+        }
+    };
+
+    let report =
+        match translation::gltf_to_lrent(&document, &mut lrent, &templates, create, tolerance) {
+            Ok(report) => report,
+            Err(e) => {
+                println!("import failed: {e}");
+                return;
+            }
+        };
+    println!("----");
+    for moved in &report.moved {
+        println!("moved   {moved}");
+    }
+    for created in &report.created {
+        println!("created {created}");
+    }
+    for reshaped in &report.reshaped {
+        println!("reshaped {reshaped}");
+    }
+    for relit in &report.relit {
+        println!("relit   {relit}");
+    }
+    for skipped in &report.skipped {
+        println!("skipped {skipped}");
+    }
+    println!(
+        "{} moved, {} unchanged, {} created, {} reshaped, {} relit, {} skipped",
+        report.moved.len(),
+        report.unchanged,
+        report.created.len(),
+        report.reshaped.len(),
+        report.relit.len(),
+        report.skipped.len()
+    );
+    if !report.created.is_empty() {
+        println!(
+            "Warning: unparsed property sets of new entities are copied as-is, string references inside may be invalid in the lrent"
+        );
+    }
+
+    let mut arch = PakFile::new();
+    arch.strings = prefill_strings;
+    if let Err(e) = lrent.save(&mut arch) {
+        println!("writing lrent failed: {e}");
+        return;
+    }
+
+    let out_arg = lrent_arg
+        .replace(".lrent", "_out.lrent")
+        .replace(".LRENT", "_out.LRENT");
+    if &out_arg == lrent_arg {
+        panic!("In == out path");
+    }
+    let out_os = OsString::from(&out_arg);
+    let out_file = File::create(Path::new(&out_os)).expect("Unable to open output file");
+    let mut out_file = BufWriter::new(out_file);
+    match arch.save(&mut out_file) {
+        Ok(_) => {}
+        Err(e) => {
+            println!("writing archive failed: {e}");
+        }
+    }
+    out_file.flush().unwrap();
+    println!("done");
+}
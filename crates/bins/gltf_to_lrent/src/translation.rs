@@ -0,0 +1,380 @@
+use std::collections::HashSet;
+
+use formats::{
+    error::*,
+    file_formats::{lrent::LrentFile, tple::TpleFile},
+    light::Light,
+    types::{
+        GenClass, Mat3, Mat4, Quat, Vec3, Vec4, collision::ShapeGeometry, property_set::PropertySet,
+    },
+};
+use uuid::Uuid;
+
+/// Tolerance for the rotation/scale part of transforms, translation uses the caller's tolerance
+const ROTATION_TOLERANCE: f32 = 0.0001;
+
+/// Entity data stored in node extras by lrent_to_gltf
+struct NodeInfo {
+    guid: Option<Uuid>,
+    template: Option<Uuid>,
+    /// Index in the eCCollisionShape_PS of the parent entity
+    collision_shape: Option<usize>,
+    /// Light node of the parent entity
+    light: Option<serde_json::Value>,
+}
+
+impl NodeInfo {
+    fn from_node(node: &gltf::Node) -> Self {
+        let extras = node
+            .extras()
+            .as_ref()
+            .and_then(|raw| serde_json::from_str::<serde_json::Value>(raw.get()).ok());
+        let get_id = |key: &str| {
+            let value = extras.as_ref()?.get(key)?.as_str()?;
+            Uuid::parse_str(value.trim_matches(['{', '}'])).ok()
+        };
+        Self {
+            guid: get_id("guid"),
+            template: get_id("template"),
+            collision_shape: extras
+                .as_ref()
+                .and_then(|extras| extras.get("collision_shape")?.as_u64())
+                .map(|idx| idx as usize),
+            light: extras.filter(|extras| extras.get("light").is_some()),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub moved: Vec<String>,
+    pub unchanged: usize,
+    pub created: Vec<String>,
+    pub reshaped: Vec<String>,
+    pub relit: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+struct Importer<'a> {
+    lrent: &'a mut LrentFile,
+    templates: &'a [TpleFile],
+    create: bool,
+    tolerance: f32,
+    used: HashSet<Uuid>,
+    report: ImportReport,
+}
+
+/// Updates the transforms of entities that are matched to nodes of `document` by the GUID in their extras.
+/// If `create` is set, nodes with an unknown (or duplicated) GUID and a template GUID are added as new entities,
+/// using the first of `templates` that contains the template.
+/// Collision shape nodes below matched entities update the shapes from their transform,
+/// light nodes update the light properties.
+/// `tolerance` (in game units) is used to ignore rounding errors of the unit conversion.
+pub fn gltf_to_lrent(
+    document: &gltf::Document,
+    lrent: &mut LrentFile,
+    templates: &[TpleFile],
+    create: bool,
+    tolerance: f32,
+) -> Result<ImportReport> {
+    let Some(scene) = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+    else {
+        return Err(Error::InvalidStructure(
+            "glTF contains no scene".to_string(),
+        ));
+    };
+    if lrent.get_context().is_none() {
+        return Err(Error::InvalidStructure(
+            "lrent does not contain an eCEntityDynamicContext".to_string(),
+        ));
+    }
+    let mut importer = Importer {
+        lrent,
+        templates,
+        create,
+        tolerance,
+        used: HashSet::new(),
+        report: ImportReport::default(),
+    };
+    for node in scene.nodes() {
+        importer.import_node(&node, Mat4::IDENTITY, None)?;
+    }
+    if let Some(ctx) = importer.lrent.get_context_mut() {
+        ctx.update_bounding_box();
+    }
+    Ok(importer.report)
+}
+
+/// Node transform in game units, GLTF is usually in m, game is in cm
+fn from_gltf_matrix(matrix: Mat4) -> Mat4 {
+    let mut matrix = matrix;
+    matrix.w_axis = (matrix.w_axis.truncate() * 100.0).extend(matrix.w_axis.w);
+    matrix
+}
+
+impl Importer<'_> {
+    fn import_node(
+        &mut self,
+        node: &gltf::Node,
+        parent_world: Mat4,
+        parent_entity: Option<usize>,
+    ) -> Result<()> {
+        let world = parent_world * Mat4::from_cols_array_2d(&node.transform().matrix());
+        let info = NodeInfo::from_node(node);
+        let name = node.name().unwrap_or("<unnamed>");
+
+        let mut entity = None;
+        if let Some(guid) = info.guid
+            && !self.used.contains(&guid)
+        {
+            let ctx = self.lrent.get_context_mut().unwrap();
+            if let Some(idx) = ctx.entities.iter().position(|e| e.id == guid) {
+                self.used.insert(guid);
+                let parent_matrix = match ctx.get_parent(idx) {
+                    Some(parent) => ctx.get_world_matrix(parent),
+                    None => Mat4::IDENTITY,
+                };
+                let local_matrix = parent_matrix.inverse() * from_gltf_matrix(world);
+                if transform_changed(
+                    &ctx.entities[idx].local_matrix,
+                    &local_matrix,
+                    self.tolerance,
+                ) {
+                    ctx.set_local_matrix(idx, local_matrix);
+                    self.report.moved.push(format!("{name} ({guid})"));
+                } else {
+                    self.report.unchanged += 1;
+                }
+                entity = Some(idx);
+            }
+        }
+        if entity.is_none() && (info.guid.is_some() || info.template.is_some()) {
+            entity = self.create_entity(name, &info, world, parent_entity)?;
+        }
+
+        for child in node.children() {
+            let child_info = NodeInfo::from_node(&child);
+            if let Some(extras) = &child_info.light {
+                match entity {
+                    Some(idx) => self.import_light(&child, extras, idx),
+                    None => self.report.skipped.push(format!(
+                        "{}: light without entity",
+                        child.name().unwrap_or("<unnamed>")
+                    )),
+                }
+                continue;
+            }
+            if let Some(shape_idx) = child_info.collision_shape {
+                match entity {
+                    Some(idx) => self.import_collision_shape(&child, idx, shape_idx),
+                    None => self.report.skipped.push(format!(
+                        "{}: collision shape without entity",
+                        child.name().unwrap_or("<unnamed>")
+                    )),
+                }
+                continue;
+            }
+            self.import_node(&child, world, entity.or(parent_entity))?;
+        }
+        Ok(())
+    }
+
+    /// Applies a light node (as written by lrent_to_gltf) to the light properties of the entity.
+    /// The node translation is the light offset, the other values come from KHR_lights_punctual and the extras.
+    fn import_light(&mut self, node: &gltf::Node, extras: &serde_json::Value, entity: usize) {
+        let name = node.name().unwrap_or("<unnamed>");
+        let ctx = self.lrent.get_context_mut().unwrap();
+        let entity = &mut ctx.entities[entity];
+        let Some(mut light) = Light::from_entity(entity) else {
+            self.report
+                .skipped
+                .push(format!("{name}: entity has no light"));
+            return;
+        };
+        if let Some(gltf_light) = node.light() {
+            light.color = gltf_light.color();
+            light.intensity = gltf_light.intensity();
+            if let Some(range) = gltf_light.range() {
+                light.range = Some(range * 100.0);
+            }
+        }
+        if let Some(old_offset) = light.offset {
+            let (translation, _, _) = node.transform().decomposed();
+            let offset = Vec3::from_array(translation) * 100.0;
+            // keep the exact value if only rounding differs:
+            if !old_offset.abs_diff_eq(offset, self.tolerance) {
+                light.offset = Some(offset);
+            }
+        }
+        let value = |key: &str| extras.get(key).cloned().filter(|v| !v.is_null());
+        if let Some(v) = value("cast_shadows").and_then(|v| v.as_bool()) {
+            light.cast_shadows = Some(v);
+        }
+        if let Some(v) = value("decay_duration").and_then(|v| v.as_f64()) {
+            light.decay_duration = Some(v as f32);
+        }
+        if let Some(v) = value("effect").and_then(|v| serde_json::from_value(v).ok()) {
+            light.effect = Some(v);
+        }
+        if let Some(v) = value("shadow_caster").and_then(|v| serde_json::from_value(v).ok()) {
+            light.shadow_caster = Some(v);
+        }
+        let changed = light.apply(entity);
+        if changed.is_empty() {
+            self.report.unchanged += 1;
+        } else {
+            self.report
+                .relit
+                .push(format!("{name}: {}", changed.join(", ")));
+        }
+    }
+
+    /// Applies the transform of a collision shape node (as written by lrent_to_gltf) to the shape:
+    /// translation and rotation are the center and orientation, the scale resizes the shape.
+    fn import_collision_shape(&mut self, node: &gltf::Node, entity: usize, shape_idx: usize) {
+        let name = node.name().unwrap_or("<unnamed>");
+        let (translation, rotation, scale) = node.transform().decomposed();
+        let center = Vec3::from_array(translation) * 100.0;
+        let orientation = Mat3::from_quat(Quat::from_array(rotation));
+        let scale = Vec3::from_array(scale);
+
+        let ctx = self.lrent.get_context_mut().unwrap();
+        let shape = ctx.entities[entity]
+            .geo_entity
+            .entity
+            .accessors
+            .iter_mut()
+            .find_map(|acc| match &mut acc.object.object.class {
+                GenClass::PropertySet(PropertySet::CollisionShapes(set)) => {
+                    set.shapes.get_mut(shape_idx)
+                }
+                _ => None,
+            });
+        let Some(shape) = shape else {
+            self.report
+                .skipped
+                .push(format!("{name}: collision shape {shape_idx} not found"));
+            return;
+        };
+        let geometry = match &shape.geometry {
+            ShapeGeometry::Box(obb) => {
+                let mut obb = *obb;
+                obb.center = center;
+                obb.orientation = orientation;
+                obb.extent *= scale;
+                ShapeGeometry::Box(obb)
+            }
+            ShapeGeometry::Sphere(sphere) => {
+                let mut sphere = *sphere;
+                sphere.center = center;
+                sphere.radius *= scale.max_element();
+                ShapeGeometry::Sphere(sphere)
+            }
+            ShapeGeometry::Capsule(capsule) => {
+                let mut capsule = *capsule;
+                capsule.center = center;
+                capsule.orientation = orientation;
+                capsule.radius *= scale.x.max(scale.z);
+                capsule.height *= scale.y;
+                ShapeGeometry::Capsule(capsule)
+            }
+            ShapeGeometry::Point(_) => ShapeGeometry::Point(center),
+            ShapeGeometry::Mesh(_) | ShapeGeometry::Opaque(_) => {
+                self.report.unchanged += 1;
+                return;
+            }
+        };
+        if geometry_changed(&shape.geometry, &geometry, self.tolerance) {
+            shape.set_geometry(geometry);
+            self.report.reshaped.push(name.to_string());
+        } else {
+            self.report.unchanged += 1;
+        }
+    }
+
+    fn create_entity(
+        &mut self,
+        name: &str,
+        info: &NodeInfo,
+        world: Mat4,
+        parent: Option<usize>,
+    ) -> Result<Option<usize>> {
+        let template_id = match (self.create, info.template) {
+            (true, Some(id)) => id,
+            (false, _) => {
+                self.report
+                    .skipped
+                    .push(format!("{name}: no matching entity"));
+                return Ok(None);
+            }
+            (true, None) => {
+                self.report
+                    .skipped
+                    .push(format!("{name}: no matching entity and no template"));
+                return Ok(None);
+            }
+        };
+        let Some((tple, template)) = self.templates.iter().find_map(|tple| {
+            let template = tple.entities.iter().find(|t| t.id.id == template_id)?;
+            Some((tple, template))
+        }) else {
+            self.report
+                .skipped
+                .push(format!("{name}: template {template_id} not found"));
+            return Ok(None);
+        };
+
+        let ctx = self.lrent.get_context().unwrap();
+        let parent_matrix = match parent {
+            Some(parent) => ctx.get_world_matrix(parent),
+            None => Mat4::IDENTITY,
+        };
+        let local_matrix = parent_matrix.inverse() * from_gltf_matrix(world);
+        let properties = tple.resolve_properties(template);
+        let idx = self
+            .lrent
+            .instantiate_template(template, properties, local_matrix, parent)?;
+        let ctx = self.lrent.get_context().unwrap();
+        self.report.created.push(format!(
+            "{name}: #{idx} {} ({}) from template {}",
+            ctx.entities[idx].name, ctx.entities[idx].id, template.name
+        ));
+        Ok(Some(idx))
+    }
+}
+
+fn transform_changed(old: &Mat4, new: &Mat4, tolerance: f32) -> bool {
+    let axes_equal = [
+        (old.x_axis, new.x_axis),
+        (old.y_axis, new.y_axis),
+        (old.z_axis, new.z_axis),
+    ]
+    .iter()
+    .all(|(a, b): &(Vec4, Vec4)| a.abs_diff_eq(*b, ROTATION_TOLERANCE));
+    !axes_equal || !old.w_axis.abs_diff_eq(new.w_axis, tolerance)
+}
+
+fn geometry_changed(old: &ShapeGeometry, new: &ShapeGeometry, tolerance: f32) -> bool {
+    let mat3_equal = |a: &Mat3, b: &Mat3| a.abs_diff_eq(*b, ROTATION_TOLERANCE);
+    let near = |a: f32, b: f32| (a - b).abs() <= tolerance;
+    match (old, new) {
+        (ShapeGeometry::Box(a), ShapeGeometry::Box(b)) => {
+            !a.center.abs_diff_eq(b.center, tolerance)
+                || !a.extent.abs_diff_eq(b.extent, tolerance)
+                || !mat3_equal(&a.orientation, &b.orientation)
+        }
+        (ShapeGeometry::Sphere(a), ShapeGeometry::Sphere(b)) => {
+            !a.center.abs_diff_eq(b.center, tolerance) || !near(a.radius, b.radius)
+        }
+        (ShapeGeometry::Capsule(a), ShapeGeometry::Capsule(b)) => {
+            !a.center.abs_diff_eq(b.center, tolerance)
+                || !mat3_equal(&a.orientation, &b.orientation)
+                || !near(a.radius, b.radius)
+                || !near(a.height, b.height)
+        }
+        (ShapeGeometry::Point(a), ShapeGeometry::Point(b)) => !a.abs_diff_eq(*b, tolerance),
+        _ => old != new,
+    }
+}
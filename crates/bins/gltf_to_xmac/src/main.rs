@@ -9,13 +9,55 @@ use std::{
 };
 
 use formats::file_formats::xmac::XmacFile;
+use translation::MeshOptimization;
 
 mod translation;
 
 fn main() {
     println!("Chromosome Toolkit - R1 - GLTF to XMAC");
     let mut queue = env::args().skip(1).collect::<VecDeque<_>>();
+    let mut generated_lods = 0;
+    let mut optimization: Option<MeshOptimization> = None;
     while let Some(arg) = queue.pop_front() {
+        // Generate simplified LODs for meshes without a <node>_LOD<level> mesh, up to this level:
+        if let Some(value) = arg.strip_prefix("/lods=") {
+            match value.parse() {
+                Ok(value) => generated_lods = value,
+                Err(_) => println!("Invalid LOD count {value}"),
+            }
+            continue;
+        }
+        // Weld vertices, remove degenerate triangles and reorder them for the vertex cache and overdraw:
+        if &arg == "/optimize" {
+            optimization.get_or_insert_default();
+            continue;
+        }
+        // Welding tolerances for positions (cm), normals and tangents, and UVs, implies /optimize:
+        if let Some(value) = arg.strip_prefix("/weld=") {
+            match value
+                .split(',')
+                .map(str::parse)
+                .collect::<std::result::Result<Vec<f32>, _>>()
+                .as_deref()
+            {
+                Ok(&[position, normal, uv]) => {
+                    let optimization = optimization.get_or_insert_default();
+                    optimization.position_tolerance = position;
+                    optimization.normal_tolerance = normal;
+                    optimization.uv_tolerance = uv;
+                }
+                _ => println!("Invalid weld tolerances {value}, expected <position>,<normal>,<uv>"),
+            }
+            continue;
+        }
+        // Split submeshes using more bones than the skinning shader supports, implies /optimize:
+        if let Some(value) = arg.strip_prefix("/maxbones=") {
+            match value.parse() {
+                Ok(value) => optimization.get_or_insert_default().max_bones = Some(value),
+                Err(_) => println!("Invalid bone count {value}"),
+            }
+            continue;
+        }
         println!("{}", arg);
         let os_arg = OsString::from(&arg);
         let path = Path::new(&os_arg);
@@ -67,12 +109,19 @@ fn main() {
                     .unwrap()
                     .to_string(),
                 file_time,
+                generated_lods,
+                optimization,
             )
             .unwrap();
             println!("Translation done");
             result
         };
 
+        if let Err(e) = xmac.sanity_check() {
+            println!("Not writing invalid XMAC: {e}");
+            continue;
+        }
+
         let out_os = OsString::from(&out_arg);
         let out_path = Path::new(&out_os);
 
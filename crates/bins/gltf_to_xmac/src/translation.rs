@@ -1,19 +1,27 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     time::SystemTime,
 };
 
 use formats::{
     file_formats::xmac::{
         chunks::{
+            attachment_nodes::XmacAttachmentNodes,
             info::XmacInfo,
+            limit::XmacLimit,
             material::{
                 XmacLayerBlendMode, XmacMaterialLayerType, XmacMaterialTransparencyType,
                 XmacStandardMaterialLayer, XmacStdMaterial,
             },
             material_info::XmacMaterialInfo,
             mesh::{XmacMesh, XmacMeshAttrib, XmacMeshAttribLayer, XmacMeshSubmesh},
-            nodes::{XmacNode, XmacNodeFlags, XmacNodeId, XmacNodes},
+            mesh_lod_levels::XmacMeshLodLevels,
+            morph_targets::{
+                MeshDeformDelta, MeshDeformDeltas, MorphTarget, PhonemeSet, XmacMorphTargets,
+            },
+            node_groups::{XmacNodeGroup, XmacNodeGroups},
+            node_motion_sources::XmacNodeMotionSources,
+            nodes::{XmacNode, XmacNodeFlags, XmacNodeId, XmacNodeOBB, XmacNodes},
             skinning_info::{SkinInfluence, TableEntry, XmacSkinningInfo},
             XmacChunk,
         },
@@ -28,12 +36,23 @@ use crate::ConvError;
 
 use super::Result;
 
+mod optimize;
+pub use optimize::MeshOptimization;
+
 #[derive(Default)]
 struct TempData {
     /// from gltf node idx to xmac node idx
     node_mapping: HashMap<usize, usize>,
     /// gltf mesh idx to xmac node idx
     mesh_nodes: HashMap<usize, usize>,
+    /// gltf node idx, name of the node it is a LOD of, and LOD level
+    lod_nodes: Vec<(usize, String, u32)>,
+    /// gltf node idx and name of the node it is the collision mesh of
+    collision_nodes: Vec<(usize, String)>,
+    /// node, collision flag, vertices and submeshes of every xmac mesh, kept to generate LODs from
+    mesh_data: Vec<(usize, bool, VertexData, Vec<XmacMeshSubmesh>)>,
+    /// applied to every mesh before it is written, if set
+    optimization: Option<MeshOptimization>,
 }
 
 pub fn gltf_to_xmac(
@@ -42,10 +61,15 @@ pub fn gltf_to_xmac(
     _textures: Vec<gltf::image::Data>,
     file_name: String,
     file_time: SystemTime,
+    generated_lods: u32,
+    optimization: Option<MeshOptimization>,
 ) -> Result<XmacFile> {
     let mut result = XmacFile::new(file_time);
 
-    let mut tmp = TempData::default();
+    let mut tmp = TempData {
+        optimization,
+        ..Default::default()
+    };
 
     result.chunks.push(XmacChunk::Info(XmacInfo {
         unknown1: 0,
@@ -69,8 +93,10 @@ pub fn gltf_to_xmac(
     translate_nodes(&gltf, &mut tmp, &mut result)?;
     translate_materials(&gltf, &mut result)?;
     translate_meshes(&gltf, &buffer, &mut tmp, &mut result)?; //includes skinning
-
-    // translate_morphs(input, &mut outputs)?;
+    translate_obbs(&tmp, &mut result);
+    translate_lods(&gltf, &buffer, &mut tmp, &mut result, generated_lods)?;
+    translate_node_extras(&gltf, &tmp, &mut result)?;
+    translate_morphs(&gltf, &buffer, &tmp, &mut result)?;
 
     let (min, max) = bounding_box(result.get_mesh_chunks()[0].get_position_attrib().unwrap());
     result.res.props.push(Property {
@@ -102,14 +128,15 @@ fn bounding_box(positions: &[Vec3]) -> (Vec3, Vec3) {
 }
 
 fn translate_nodes(gltf: &gltf::Document, tmp: &mut TempData, output: &mut XmacFile) -> Result<()> {
-    let mut nodes = Vec::new();
+    let mut nodes: Vec<XmacNode> = Vec::new();
     // maps child gltf id -> parent xmac id
-    let mut parents = HashMap::new();
+    let mut parents: HashMap<usize, usize> = HashMap::new();
     let skeleton_nodes = gltf
         .skins()
         .flat_map(|s| s.joints())
         .map(|s| s.index())
         .collect::<HashSet<_>>();
+    let lod_regex = regex::Regex::new(r"^(.+)_LOD(\d+)$").unwrap();
     for gltf_node in gltf.nodes() {
         let gltf_node_idx = gltf_node.index();
         let name = gltf_node
@@ -117,11 +144,45 @@ fn translate_nodes(gltf: &gltf::Document, tmp: &mut TempData, output: &mut XmacF
             .map(str::to_string)
             .unwrap_or_else(|| format!("Node{gltf_node_idx}"));
 
+        // Meshes named <node>_LOD<level> become LOD levels of <node>'s mesh instead of nodes:
+        if gltf_node.mesh().is_some()
+            && gltf_node.children().len() == 0
+            && let Some(captures) = lod_regex.captures(&name)
+            && let Ok(level) = captures[2].parse::<u32>()
+            && level > 0
+        {
+            println!(
+                "Node {gltf_node_idx} ({name}) is LOD {level} of {}",
+                &captures[1]
+            );
+            tmp.lod_nodes
+                .push((gltf_node_idx, captures[1].to_string(), level));
+            continue;
+        }
+
+        // Meshes named <node>_COLLISION or tagged in their extras become the collision mesh of <node>:
+        if is_collision_node(&gltf_node) {
+            let base_name = match name.strip_suffix(COLLISION_SUFFIX) {
+                Some(base_name) => Some(base_name.to_string()),
+                None => parents
+                    .get(&gltf_node_idx)
+                    .map(|parent_idx| nodes[*parent_idx].name.clone()),
+            };
+            if let Some(base_name) = base_name {
+                println!("Node {gltf_node_idx} ({name}) is the collision mesh of {base_name}");
+                tmp.collision_nodes.push((gltf_node_idx, base_name));
+                continue;
+            }
+        }
+
         // Only include nodes
-        // - used for meshes
+        // - used for meshes (including collision meshes)
         // - used in a skelton
         // in the xmac
-        if gltf_node.mesh().is_none() && !skeleton_nodes.contains(&gltf_node_idx) {
+        if gltf_node.mesh().is_none()
+            && !skeleton_nodes.contains(&gltf_node_idx)
+            && !gltf_node.children().any(|child| is_collision_node(&child))
+        {
             println!("Dropping Node {gltf_node_idx} ({name})");
 
             continue;
@@ -164,10 +225,10 @@ fn translate_nodes(gltf: &gltf::Document, tmp: &mut TempData, output: &mut XmacF
             unknown3: -1,
             unknown4: -1,
             parent_idx,
-            child_count: gltf_node.children().len() as u32,
+            child_count: 0,
             flags: XmacNodeFlags::IncludeInBoundsCalc,
             unknown5: [0, 0, 0],
-            oriented_bounding_box: Mat4::IDENTITY, // TODO: Calculate actual bounding box
+            oriented_bounding_box: Mat4::IDENTITY, // fitted to the mesh data in translate_obbs
             unknown6: 1.0,
         };
         let xmac_node_id = nodes.len();
@@ -180,14 +241,180 @@ fn translate_nodes(gltf: &gltf::Document, tmp: &mut TempData, output: &mut XmacF
             tmp.mesh_nodes.insert(mesh.index(), xmac_node_id);
         }
     }
+    // Counted afterwards, dropped and LOD nodes aren't children in the xmac:
+    for parent_idx in nodes
+        .iter()
+        .filter_map(|n| n.parent_idx)
+        .collect::<Vec<_>>()
+    {
+        nodes[parent_idx].child_count += 1;
+    }
     let nodes_chunk = XmacNodes { nodes };
     output.chunks.push(XmacChunk::Nodes(nodes_chunk));
 
     Ok(())
 }
 
+const COLLISION_SUFFIX: &str = "_COLLISION";
+
+/// Collision meshes are exported by xmac_to_gltf as mesh nodes without children,
+/// named `<node>_COLLISION` and tagged with `xmac_collision` in their extras.
+/// Either one is enough to recognize them.
+fn is_collision_node(gltf_node: &gltf::Node) -> bool {
+    gltf_node.mesh().is_some()
+        && gltf_node.children().len() == 0
+        && (gltf_node
+            .name()
+            .is_some_and(|name| name.ends_with(COLLISION_SUFFIX))
+            || is_collision_tagged(gltf_node))
+}
+
+fn is_collision_tagged(gltf_node: &gltf::Node) -> bool {
+    parse_extras(gltf_node.extras())
+        .get("xmac_collision")
+        .and_then(|v| v.as_bool())
+        .unwrap_or_default()
+}
+
+/// Maps the names of the xmac nodes to their index
+fn node_ids_by_name(output: &XmacFile) -> HashMap<String, usize> {
+    output
+        .get_nodes_chunk()
+        .unwrap()
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(idx, node)| (node.name.clone(), idx))
+        .collect()
+}
+
+fn parse_extras(extras: &gltf::json::Extras) -> serde_json::Map<String, serde_json::Value> {
+    extras
+        .as_ref()
+        .and_then(|raw| serde_json::from_str(raw.get()).ok())
+        .unwrap_or_default()
+}
+
+/// Rebuilds node groups, attachment nodes, limits and motion sources from the extras written by xmac_to_gltf
+fn translate_node_extras(
+    gltf: &gltf::Document,
+    tmp: &TempData,
+    output: &mut XmacFile,
+) -> Result<()> {
+    let mut node_extras = BTreeMap::new();
+    let mut xmac_ids = HashMap::new();
+    for gltf_node in gltf.nodes() {
+        if let Some(xmac_idx) = tmp.node_mapping.get(&gltf_node.index()) {
+            node_extras.insert(*xmac_idx, parse_extras(gltf_node.extras()));
+            if let Some(name) = gltf_node.name() {
+                xmac_ids.insert(name.to_string(), *xmac_idx);
+            }
+        }
+    }
+    let node_count = output.get_nodes_chunk().map(|n| n.nodes.len()).unwrap_or(0);
+
+    // Groups are listed in the scene extras, nodes not exported to the xmac are dropped from them:
+    let scene_extras = gltf
+        .default_scene()
+        .or_else(|| gltf.scenes().next())
+        .map(|scene| parse_extras(scene.extras()))
+        .unwrap_or_default();
+    let mut groups: Vec<XmacNodeGroup> = scene_extras
+        .get("node_groups")
+        .and_then(|groups| groups.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|group| {
+            Some(XmacNodeGroup {
+                name: group.get("name")?.as_str()?.to_string(),
+                disabled_on_default: group
+                    .get("disabled_on_default")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
+                nodes: Vec::new(),
+            })
+        })
+        .collect();
+    let mut attachments = Vec::new();
+    let mut limits = Vec::new();
+    let mut motion_sources = vec![None; node_count];
+    let mut motion_source_flags = None;
+
+    for (xmac_idx, extras) in &node_extras {
+        let node_idx = *xmac_idx as u16;
+        for group_name in extras
+            .get("node_groups")
+            .and_then(|names| names.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|name| name.as_str())
+        {
+            let group = match groups.iter_mut().position(|g| g.name == group_name) {
+                Some(pos) => &mut groups[pos],
+                None => {
+                    groups.push(XmacNodeGroup {
+                        name: group_name.to_string(),
+                        disabled_on_default: false,
+                        nodes: Vec::new(),
+                    });
+                    groups.last_mut().unwrap()
+                }
+            };
+            group.nodes.push(node_idx);
+        }
+        if extras.get("attachment").and_then(|v| v.as_bool()) == Some(true) {
+            attachments.push(node_idx);
+        }
+        if let Some(serde_json::Value::Object(limit)) = extras.get("limit") {
+            let mut limit = limit.clone();
+            limit.insert("node_id".to_string(), (*xmac_idx).into());
+            let limit: XmacLimit = serde_json::from_value(serde_json::Value::Object(limit))
+                .map_err(|e| {
+                    ConvError::InvalidData(format!("Invalid limit of node {xmac_idx}: {e}"))
+                })?;
+            limits.push(limit);
+        }
+        if let Some(source) = extras.get("motion_source").and_then(|v| v.as_str()) {
+            match xmac_ids.get(source) {
+                Some(source_idx) => motion_sources[*xmac_idx] = Some(*source_idx as u16),
+                None => println!("Warn: Motion source {source} of node {xmac_idx} is not exported"),
+            }
+        }
+        if let Some(flags) = extras.get("motion_source_flags").and_then(|v| v.as_u64()) {
+            motion_source_flags.get_or_insert_with(|| vec![0; node_count])[*xmac_idx] = flags as u8;
+        }
+    }
+
+    groups.retain(|group| !group.nodes.is_empty());
+    if !groups.is_empty() {
+        output.chunks.push(XmacChunk::NodeGroups(XmacNodeGroups {
+            groups,
+            padded: true,
+        }));
+    }
+    if !attachments.is_empty() {
+        output
+            .chunks
+            .push(XmacChunk::AttachmentNodes(XmacAttachmentNodes {
+                nodes: attachments,
+            }));
+    }
+    output
+        .chunks
+        .extend(limits.into_iter().map(XmacChunk::Limit));
+    if motion_sources.iter().any(Option::is_some) || motion_source_flags.is_some() {
+        output
+            .chunks
+            .push(XmacChunk::NodeMotionSources(XmacNodeMotionSources {
+                sources: motion_sources,
+                flags: motion_source_flags,
+            }));
+    }
+    Ok(())
+}
+
 type TempSkinData = Vec<[(u32, f32); 8]>;
-#[derive(Default)]
+#[derive(Default, Clone)]
 struct VertexData {
     positions: Vec<Vec3>,
     normals: Vec<Vec3>,
@@ -208,144 +435,643 @@ fn translate_meshes(
 ) -> Result<()> {
     for gltf_mesh in gltf.meshes() {
         let gltf_mesh_idx = gltf_mesh.index();
+        // Meshes only used by LOD nodes are translated with the LODs
+        let Some(&mesh_node_idx) = tmp.mesh_nodes.get(&gltf_mesh_idx) else {
+            continue;
+        };
         let gltf_node = gltf
             .nodes()
-            .find(|n| n.mesh().is_some_and(|m| m.index() == gltf_mesh_idx))
+            .find(|n| {
+                n.mesh().is_some_and(|m| m.index() == gltf_mesh_idx)
+                    && tmp.node_mapping.contains_key(&n.index())
+            })
             .unwrap();
-        let mesh_node_idx = *tmp.mesh_nodes.get(&gltf_mesh_idx).unwrap();
         let mesh_node = &output.get_nodes_chunk().unwrap().nodes[mesh_node_idx];
+        // older exports kept collision meshes on their own node
+        let is_collision_mesh = mesh_node.name.to_lowercase().contains("collision");
 
-        let mut submeshes = Vec::with_capacity(gltf_mesh.primitives().len());
-        let mut vertices = VertexData::default();
+        let (vertices, submeshes) = read_mesh(&gltf_mesh, &gltf_node, buffer, tmp)?;
+        // Vertices that move differently must not be welded
+        let morph_deltas = match tmp.optimization {
+            Some(_) => read_morph_deltas(&gltf_mesh, buffer, vertices.positions.len())?,
+            None => Vec::new(),
+        };
+        push_mesh(
+            vertices,
+            submeshes,
+            &morph_deltas,
+            mesh_node_idx,
+            is_collision_mesh,
+            tmp,
+            output,
+        );
+    }
 
-        // If multiple submeshes point to the same vertex buffer, their indices will keep counting
-        // We need indices to be in range 0..submesh_vertex_count though per submesh
-        let mut vertices_in_buffer_used = HashMap::<usize, u32>::new();
-        let mut prev_used_buffer = None;
-
-        for prim in gltf_mesh.primitives() {
-            if prim.mode() != gltf::json::mesh::Mode::Triangles {
-                return Err(ConvError::NotImplemented(format!(
-                    "Only Triangle meshes are supported, found {:?}",
-                    prim.mode()
-                )));
+    let node_ids = node_ids_by_name(output);
+    for (gltf_node_idx, base_name) in std::mem::take(&mut tmp.collision_nodes) {
+        let Some(&mesh_node_idx) = node_ids.get(&base_name) else {
+            println!("Dropping collision mesh of {base_name}, there is no such node");
+            continue;
+        };
+        let gltf_node = gltf.nodes().nth(gltf_node_idx).unwrap();
+        let gltf_mesh = gltf_node.mesh().unwrap();
+        let (vertices, submeshes) = read_mesh(&gltf_mesh, &gltf_node, buffer, tmp)?;
+        push_mesh(vertices, submeshes, &[], mesh_node_idx, true, tmp, output);
+    }
+
+    Ok(())
+}
+
+/// Adds the mesh and its skinning info, and keeps its data for the LODs
+fn push_mesh(
+    vertices: VertexData,
+    submeshes: Vec<XmacMeshSubmesh>,
+    morph_deltas: &[Vec<[Vec3; 3]>],
+    mesh_node_idx: usize,
+    is_collision_mesh: bool,
+    tmp: &mut TempData,
+    output: &mut XmacFile,
+) {
+    tmp.mesh_data.push((
+        mesh_node_idx,
+        is_collision_mesh,
+        vertices.clone(),
+        submeshes.clone(),
+    ));
+
+    let (mesh, skin) = build_mesh(
+        vertices,
+        submeshes,
+        mesh_node_idx,
+        is_collision_mesh,
+        tmp.optimization.as_ref(),
+        morph_deltas,
+    );
+    output.chunks.push(XmacChunk::Mesh(mesh));
+    if let Some(skin) = skin {
+        output.chunks.push(XmacChunk::SkinningInfo(skin));
+    }
+}
+
+/// Fits the bounding box of every joint around the vertices it influences, in its bind pose space.
+/// Vertices without influences belong to the node of their mesh, nodes without vertices keep the identity.
+fn translate_obbs(tmp: &TempData, output: &mut XmacFile) {
+    let Some(nodes) = output.get_nodes_chunk() else {
+        return;
+    };
+    let world_transforms = node_world_transforms(nodes);
+    let mut node_points = vec![Vec::new(); nodes.nodes.len()];
+    for (mesh_node_idx, _, vertices, _) in &tmp.mesh_data {
+        let mesh_transform = world_transforms[*mesh_node_idx];
+        for (vertex_idx, position) in vertices.positions.iter().enumerate() {
+            let position = mesh_transform.transform_point3(*position);
+            let influences = [
+                (&vertices.joints, &vertices.weights),
+                (&vertices.joints2, &vertices.weights2),
+            ]
+            .into_iter()
+            .filter_map(|(joints, weights)| {
+                Some((joints.get(vertex_idx)?, weights.get(vertex_idx)?))
+            })
+            .flat_map(|(joints, weights)| joints.iter().zip(weights));
+            let mut influenced = false;
+            for (joint, weight) in influences {
+                if *weight > 0.0
+                    && let Some(points) = node_points.get_mut(*joint as usize)
+                {
+                    points.push(position);
+                    influenced = true;
+                }
             }
+            if !influenced {
+                node_points[*mesh_node_idx].push(position);
+            }
+        }
+    }
 
-            let prim_start_vertex = vertices.positions.len();
-            let read = prim.reader(|buf| buffer.get(buf.index()).map(|v| v.0.as_slice()));
-
-            let prim_pos_buffer = prim
-                .attributes()
-                .find(|a| a.0 == Semantic::Positions)
-                .unwrap()
-                .1;
-            let previous_verts = vertices_in_buffer_used
-                .entry(prim_pos_buffer.index())
-                .or_insert(0);
-            if *previous_verts == 0 {
-                // These vertices have not yet been read
-                read_prim_vertex_data(&gltf_node, &prim, &read, &mut vertices, tmp)?;
-            } else if prev_used_buffer != Some(prim_pos_buffer.index()) {
-                return Err(ConvError::NotImplemented(
-                    "Vertex Buffer reuses must be consecutive".to_string(),
-                ));
+    let Some(XmacChunk::Nodes(nodes)) = output
+        .chunks
+        .iter_mut()
+        .find(|c| matches!(c, XmacChunk::Nodes(_)))
+    else {
+        return;
+    };
+    for ((node, points), transform) in nodes
+        .nodes
+        .iter_mut()
+        .zip(node_points)
+        .zip(world_transforms)
+    {
+        let to_local = transform.inverse();
+        let points = points
+            .into_iter()
+            .map(|p| to_local.transform_point3(p))
+            .collect::<Vec<_>>();
+        if let Some(obb) = XmacNodeOBB::fit(&points) {
+            node.oriented_bounding_box = obb.to_mat4();
+        }
+    }
+}
+
+/// Rest pose transforms of the nodes in the actor's space, in game units
+fn node_world_transforms(nodes: &XmacNodes) -> Vec<Mat4> {
+    let local_transform = |node: &XmacNode| {
+        Mat4::from_scale_rotation_translation(
+            node.local_scale.recip(),
+            node.rotation,
+            node.local_pos,
+        )
+    };
+    nodes
+        .nodes
+        .iter()
+        .map(|node| {
+            let mut transform = local_transform(node);
+            let mut parent_idx = node.parent_idx;
+            // bounded, in case of broken hierarchies:
+            for _ in 0..nodes.nodes.len() {
+                let Some(parent) = parent_idx.and_then(|idx| nodes.nodes.get(idx)) else {
+                    break;
+                };
+                transform = local_transform(parent) * transform;
+                parent_idx = parent.parent_idx;
+            }
+            transform
+        })
+        .collect()
+}
+
+/// Builds LOD chunks from the `<node>_LOD<level>` meshes,
+/// and simplifies the meshes for the levels up to `generated_lods` that don't have one.
+fn translate_lods(
+    gltf: &gltf::Document,
+    buffer: &[gltf::buffer::Data],
+    tmp: &mut TempData,
+    output: &mut XmacFile,
+    generated_lods: u32,
+) -> Result<()> {
+    let node_ids = node_ids_by_name(output);
+
+    let mut levels: BTreeMap<u32, Vec<XmacChunk>> = BTreeMap::new();
+    let mut authored = HashSet::new();
+    for (gltf_node_idx, base_name, level) in std::mem::take(&mut tmp.lod_nodes) {
+        let gltf_node = gltf.nodes().nth(gltf_node_idx).unwrap();
+        // LODs of collision meshes are named <node>_COLLISION_LOD<level>
+        let (base_name, is_collision_mesh) = match base_name.strip_suffix(COLLISION_SUFFIX) {
+            Some(base_name) => (base_name.to_string(), true),
+            None => {
+                let is_collision_mesh = is_collision_tagged(&gltf_node)
+                    || base_name.to_lowercase().contains("collision");
+                (base_name, is_collision_mesh)
+            }
+        };
+        let Some(&mesh_node_idx) = node_ids.get(&base_name) else {
+            println!("Dropping LOD {level} of {base_name}, there is no such node");
+            continue;
+        };
+        let gltf_mesh = gltf_node.mesh().unwrap();
+        let (vertices, submeshes) = read_mesh(&gltf_mesh, &gltf_node, buffer, tmp)?;
+        let (mesh, skin) = build_mesh(
+            vertices,
+            submeshes,
+            mesh_node_idx,
+            is_collision_mesh,
+            tmp.optimization.as_ref(),
+            &[],
+        );
+        let chunks = levels.entry(level).or_default();
+        chunks.push(XmacChunk::Mesh(mesh));
+        chunks.extend(skin.map(XmacChunk::SkinningInfo));
+        authored.insert((mesh_node_idx, is_collision_mesh, level));
+    }
+
+    for (mesh_node_idx, is_collision_mesh, vertices, submeshes) in &tmp.mesh_data {
+        let mesh_node = &output.get_nodes_chunk().unwrap().nodes[*mesh_node_idx];
+        for level in 1..=generated_lods {
+            if authored.contains(&(*mesh_node_idx, *is_collision_mesh, level)) {
+                continue;
             }
-            prev_used_buffer = Some(prim_pos_buffer.index());
+            let (vertices, submeshes) = simplify_mesh(vertices, submeshes, level);
+            if submeshes.is_empty() {
+                println!("LOD {level} of {} has no triangles left", mesh_node.name);
+                continue;
+            }
+            let (mesh, skin) = build_mesh(
+                vertices,
+                submeshes,
+                *mesh_node_idx,
+                *is_collision_mesh,
+                tmp.optimization.as_ref(),
+                &[],
+            );
+            let chunks = levels.entry(level).or_default();
+            chunks.push(XmacChunk::Mesh(mesh));
+            chunks.extend(skin.map(XmacChunk::SkinningInfo));
+        }
+    }
 
-            // actually one-past the last idx:
-            let prim_end_vertex = vertices.positions.len();
-            let prim_new_vertices = prim_end_vertex - prim_start_vertex;
+    for (lod_level, chunks) in levels {
+        output
+            .chunks
+            .push(XmacChunk::MeshLodLevels(XmacMeshLodLevels {
+                lod_level,
+                multiply_order: output.multiply_order,
+                chunks,
+            }));
+    }
+    Ok(())
+}
 
-            let indices: Vec<u32> = if let Some(indices) = read.read_indices() {
-                if *previous_verts == 0 {
-                    indices.into_u32().collect()
-                } else {
-                    indices.into_u32().map(|i| i - *previous_verts).collect()
-                }
-            } else {
-                assert_ne!(
-                    prim_new_vertices, 0,
-                    "Encountered Prim with reused buffer and no indices!"
-                );
-                (0..prim_new_vertices as u32).collect()
-            };
-            let prim_vertices = *indices.iter().max().unwrap() + 1;
-            if prim_vertices + *previous_verts > prim_end_vertex as u32 {
-                return Err(ConvError::InvalidData(format!(
-                    "Indices in submesh {} exceed vertex count \
-                    (range {} + {prim_vertices} of {prim_end_vertex})",
-                    prim.index(),
-                    *previous_verts
-                )));
+/// Translates the morph targets of all meshes, targets with the same name are merged into one.
+/// Ranges and phonemes are taken from the mesh extras written by xmac_to_gltf,
+/// the phoneme falls back to the target name if it names one.
+/// Collision meshes can't be deformed, their morph targets are skipped.
+fn translate_morphs(
+    gltf: &gltf::Document,
+    buffer: &[gltf::buffer::Data],
+    tmp: &TempData,
+    output: &mut XmacFile,
+) -> Result<()> {
+    let mut targets: Vec<MorphTarget> = Vec::new();
+    for gltf_mesh in gltf.meshes() {
+        let Some(&mesh_node_idx) = tmp.mesh_nodes.get(&gltf_mesh.index()) else {
+            continue;
+        };
+        if gltf_mesh
+            .primitives()
+            .all(|prim| prim.morph_targets().len() == 0)
+        {
+            continue;
+        }
+        let mesh_name = gltf_mesh.name().unwrap_or("mesh");
+        let node_id = XmacNodeId(mesh_node_idx as u32);
+        let Some(mesh) = output.get_mesh_chunk(node_id) else {
+            if output.get_collision_mesh_chunk(node_id).is_some() {
+                println!("Skipping the morph targets of collision mesh {mesh_name}");
+                continue;
             }
+            return Err(ConvError::MandatoryDataMissing(format!(
+                "No XMAC mesh was created for {mesh_name}"
+            )));
+        };
+        let orig_verts = mesh.get_orig_vert().ok_or_else(|| {
+            ConvError::MandatoryDataMissing(format!(
+                "Mesh of {mesh_name} has no original vertex numbers"
+            ))
+        })?;
+        let mesh_deltas = read_morph_deltas(&gltf_mesh, buffer, mesh.orig_verts_count as usize)?;
+
+        let extras = parse_extras(gltf_mesh.extras());
+        let extra = |key: &str, target_idx: usize| {
+            extras
+                .get(key)
+                .and_then(|v| v.as_array())
+                .and_then(|v| v.get(target_idx))
+                .cloned()
+        };
 
-            filter_weightless_joints(&mut vertices, prim_start_vertex);
+        for (target_idx, deltas) in mesh_deltas.into_iter().enumerate() {
+            let name = extra("targetNames", target_idx)
+                .and_then(|v| v.as_str().map(str::to_string))
+                .unwrap_or_else(|| format!("{mesh_name}_morph{target_idx}"));
 
-            let joints1 = indices
+            // Deltas are read per original vertex, each render vertex moves with its original one
+            let deltas = orig_verts
                 .iter()
-                .flat_map(|i| vertices.joints[(*i + *previous_verts) as usize].iter());
-            let joints2 = indices.iter().flat_map(|i| {
-                vertices
-                    .joints2
-                    .get((*i + *previous_verts) as usize)
-                    .unwrap_or(&[u32::MAX; 4])
-                    .iter()
+                .enumerate()
+                .filter_map(|(vertex_id, orig)| {
+                    let [position_delta, normal_delta, tangent_delta] = deltas[*orig as usize];
+                    (position_delta != Vec3::ZERO
+                        || normal_delta != Vec3::ZERO
+                        || tangent_delta != Vec3::ZERO)
+                        .then_some(MeshDeformDelta {
+                            vertex_id: vertex_id as u32,
+                            position_delta,
+                            normal_delta,
+                            tangent_delta,
+                        })
+                })
+                .collect::<Vec<_>>();
+            if deltas.is_empty() {
+                println!("Morph target {name} does not move {mesh_name}, skipping it");
+                continue;
+            }
+            let deform = MeshDeformDeltas { node_id, deltas };
+
+            if let Some(target) = targets.iter_mut().find(|t| t.name == name) {
+                target.mesh_deform_deltas.push(deform);
+                continue;
+            }
+            let (range_min, range_max) = extra("targetRanges", target_idx)
+                .and_then(|v| serde_json::from_value::<(f32, f32)>(v).ok())
+                .unwrap_or((0.0, 1.0));
+            let phoneme_set = extra("targetPhonemes", target_idx)
+                .and_then(|v| v.as_u64())
+                .map(|bits| PhonemeSet::from_bits_truncate(bits as u32))
+                .or_else(|| PhonemeSet::from_name(&name))
+                .unwrap_or(PhonemeSet::None);
+            targets.push(MorphTarget {
+                name,
+                range_min,
+                range_max,
+                mesh_deform_deltas: vec![deform],
+                phoneme_set,
+                unknown1: 0,
             });
-            let mut bones: Vec<_> = joints1
-                .chain(joints2)
-                .copied()
-                .filter(|j| *j < u32::MAX)
-                .collect();
-            bones.sort();
-            bones.dedup();
-
-            *previous_verts += prim_vertices;
-
-            let submesh = XmacMeshSubmesh {
-                indices,
-                bones,
-                vertices_count: prim_vertices,
-                material_idx: prim
-                    .material()
-                    .index()
-                    .expect("Implicit materials are not supported")
-                    as u32,
-            };
-            submeshes.push(submesh);
         }
+    }
 
-        let orig_verts_count = vertices.positions.len() as u32;
+    if !targets.is_empty() {
+        output
+            .chunks
+            .push(XmacChunk::MorphTargets(XmacMorphTargets {
+                targets,
+                unknown: 0,
+            }));
+    }
+    Ok(())
+}
+
+/// Reads the position (in cm), normal and tangent deltas of every morph target of the mesh,
+/// indexed by original vertex in the same order read_mesh reads the vertices.
+fn read_morph_deltas(
+    gltf_mesh: &gltf::Mesh,
+    buffer: &[gltf::buffer::Data],
+    orig_verts_count: usize,
+) -> Result<Vec<Vec<[Vec3; 3]>>> {
+    let mut targets: Vec<Vec<[Vec3; 3]>> = Vec::new();
+    let mut read_buffers = HashSet::new();
+    let mut start_vertex = 0;
+    for prim in gltf_mesh.primitives() {
+        let prim_pos_buffer = prim
+            .attributes()
+            .find(|a| a.0 == Semantic::Positions)
+            .unwrap()
+            .1;
+        // Primitives reusing a vertex buffer share its vertices and morph targets
+        if !read_buffers.insert(prim_pos_buffer.index()) {
+            continue;
+        }
+        let prim_vertex_range = start_vertex..start_vertex + prim_pos_buffer.count();
+        if prim_vertex_range.end > orig_verts_count {
+            return Err(ConvError::InvalidData(format!(
+                "Primitive {} has more vertices than its mesh",
+                prim.index()
+            )));
+        }
+        let read = prim.reader(|buf| buffer.get(buf.index()).map(|v| v.0.as_slice()));
+        for (target_idx, (positions, normals, tangents)) in read.read_morph_targets().enumerate() {
+            if targets.len() <= target_idx {
+                targets.push(vec![[Vec3::ZERO; 3]; orig_verts_count]);
+            }
+            let target = &mut targets[target_idx][prim_vertex_range.clone()];
+            for (delta, position) in target.iter_mut().zip(positions.into_iter().flatten()) {
+                delta[0] = Vec3::from_array(position) * 100.0; //m to cm
+            }
+            for (delta, normal) in target.iter_mut().zip(normals.into_iter().flatten()) {
+                delta[1] = Vec3::from_array(normal);
+            }
+            for (delta, tangent) in target.iter_mut().zip(tangents.into_iter().flatten()) {
+                delta[2] = Vec3::from_array(tangent);
+            }
+        }
+        start_vertex = prim_vertex_range.end;
+    }
+    Ok(targets)
+}
 
-        let mut vertex_attribute_layers = Vec::new();
-        let mut weight_data = None;
-        mesh_vertices_to_attrib_layers(vertices, &mut vertex_attribute_layers, &mut weight_data);
+/// Reads the vertices of all primitives, every primitive becomes a submesh
+fn read_mesh(
+    gltf_mesh: &gltf::Mesh,
+    gltf_node: &gltf::Node,
+    buffer: &[gltf::buffer::Data],
+    tmp: &mut TempData,
+) -> Result<(VertexData, Vec<XmacMeshSubmesh>)> {
+    let mut submeshes = Vec::with_capacity(gltf_mesh.primitives().len());
+    let mut vertices = VertexData::default();
+
+    // If multiple submeshes point to the same vertex buffer, their indices will keep counting
+    // We need indices to be in range 0..submesh_vertex_count though per submesh
+    let mut vertices_in_buffer_used = HashMap::<usize, u32>::new();
+    let mut prev_used_buffer = None;
+
+    for prim in gltf_mesh.primitives() {
+        if prim.mode() != gltf::json::mesh::Mode::Triangles {
+            return Err(ConvError::NotImplemented(format!(
+                "Only Triangle meshes are supported, found {:?}",
+                prim.mode()
+            )));
+        }
 
-        let is_collision_mesh = mesh_node.name.to_lowercase().contains("collision");
+        let prim_start_vertex = vertices.positions.len();
+        let read = prim.reader(|buf| buffer.get(buf.index()).map(|v| v.0.as_slice()));
 
-        let mesh = XmacMesh {
-            vertex_attribute_layers,
-            submeshes,
-            node_id: XmacNodeId(mesh_node_idx as u32),
-            orig_verts_count,
-            is_collision_mesh,
-            unknown1: [0; 3],
-        };
-        output.chunks.push(XmacChunk::Mesh(mesh));
+        let prim_pos_buffer = prim
+            .attributes()
+            .find(|a| a.0 == Semantic::Positions)
+            .unwrap()
+            .1;
+        let previous_verts = vertices_in_buffer_used
+            .entry(prim_pos_buffer.index())
+            .or_insert(0);
+        if *previous_verts == 0 {
+            // These vertices have not yet been read
+            read_prim_vertex_data(gltf_node, &prim, &read, &mut vertices, tmp)?;
+        } else if prev_used_buffer != Some(prim_pos_buffer.index()) {
+            return Err(ConvError::NotImplemented(
+                "Vertex Buffer reuses must be consecutive".to_string(),
+            ));
+        }
+        prev_used_buffer = Some(prim_pos_buffer.index());
+
+        // actually one-past the last idx:
+        let prim_end_vertex = vertices.positions.len();
+        let prim_new_vertices = prim_end_vertex - prim_start_vertex;
 
-        if let Some(skin) = weight_data {
-            translate_skinning(output, mesh_node_idx, is_collision_mesh, skin);
+        let indices: Vec<u32> = if let Some(indices) = read.read_indices() {
+            if *previous_verts == 0 {
+                indices.into_u32().collect()
+            } else {
+                indices.into_u32().map(|i| i - *previous_verts).collect()
+            }
+        } else {
+            assert_ne!(
+                prim_new_vertices, 0,
+                "Encountered Prim with reused buffer and no indices!"
+            );
+            (0..prim_new_vertices as u32).collect()
+        };
+        let prim_vertices = *indices.iter().max().unwrap() + 1;
+        if prim_vertices + *previous_verts > prim_end_vertex as u32 {
+            return Err(ConvError::InvalidData(format!(
+                "Indices in submesh {} exceed vertex count \
+                (range {} + {prim_vertices} of {prim_end_vertex})",
+                prim.index(),
+                *previous_verts
+            )));
         }
+
+        filter_weightless_joints(&mut vertices, prim_start_vertex);
+
+        let joints1 = indices
+            .iter()
+            .flat_map(|i| vertices.joints[(*i + *previous_verts) as usize].iter());
+        let joints2 = indices.iter().flat_map(|i| {
+            vertices
+                .joints2
+                .get((*i + *previous_verts) as usize)
+                .unwrap_or(&[u32::MAX; 4])
+                .iter()
+        });
+        let mut bones: Vec<_> = joints1
+            .chain(joints2)
+            .copied()
+            .filter(|j| *j < u32::MAX)
+            .collect();
+        bones.sort();
+        bones.dedup();
+
+        *previous_verts += prim_vertices;
+
+        let submesh = XmacMeshSubmesh {
+            indices,
+            bones,
+            vertices_count: prim_vertices,
+            material_idx: prim
+                .material()
+                .index()
+                .expect("Implicit materials are not supported") as u32,
+        };
+        submeshes.push(submesh);
     }
 
-    Ok(())
+    Ok((vertices, submeshes))
+}
+
+/// Builds the mesh and its skinning info, optimizing the mesh first if requested.
+/// The vertices are the original vertices, the skinning table is indexed by them.
+fn build_mesh(
+    vertices: VertexData,
+    submeshes: Vec<XmacMeshSubmesh>,
+    mesh_node_idx: usize,
+    is_collision_mesh: bool,
+    optimization: Option<&MeshOptimization>,
+    morph_deltas: &[Vec<[Vec3; 3]>],
+) -> (XmacMesh, Option<XmacSkinningInfo>) {
+    let orig_verts_count = vertices.positions.len() as u32;
+    let skin_data = vertex_skin_data(&vertices);
+
+    let (vertices, original_vertex_numbers, submeshes) = match optimization {
+        Some(optimization) => {
+            match optimize::optimize_mesh(&vertices, &submeshes, morph_deltas, optimization) {
+                (_, _, optimized) if optimized.is_empty() => {
+                    println!("Optimizing would remove all triangles, keeping the mesh as it is");
+                    (vertices, (0..orig_verts_count).collect(), submeshes)
+                }
+                optimized => optimized,
+            }
+        }
+        None => (vertices, (0..orig_verts_count).collect(), submeshes),
+    };
+
+    let mut vertex_attribute_layers = Vec::new();
+    mesh_vertices_to_attrib_layers(
+        vertices,
+        original_vertex_numbers,
+        &mut vertex_attribute_layers,
+    );
+
+    let mesh = XmacMesh {
+        vertex_attribute_layers,
+        submeshes,
+        node_id: XmacNodeId(mesh_node_idx as u32),
+        orig_verts_count,
+        is_collision_mesh,
+        unknown1: [0; 3],
+    };
+    let skin = Some(translate_skinning(
+        mesh_node_idx,
+        is_collision_mesh,
+        skin_data,
+    ));
+    (mesh, skin)
+}
+
+/// Size of the clustering grid cells for LOD 1 relative to the mesh size, doubled for every further level
+const LOD_CELL_FRACTION: f32 = 0.02;
+
+/// Simplifies a mesh by vertex clustering: all vertices of a submesh in the same grid cell are merged
+/// into the first one, triangles that collapse are removed. Submeshes without triangles are dropped.
+/// Vertices no triangle uses are dropped as well, and the bones are reduced to the joints of the kept vertices.
+fn simplify_mesh(
+    vertices: &VertexData,
+    submeshes: &[XmacMeshSubmesh],
+    level: u32,
+) -> (VertexData, Vec<XmacMeshSubmesh>) {
+    let (min, max) = bounding_box(&vertices.positions);
+    let cell_size = (max - min).length() * LOD_CELL_FRACTION * 2f32.powi(level as i32 - 1);
+    let cell_size = cell_size.max(f32::EPSILON);
+
+    let mut result = VertexData::default();
+    let mut result_submeshes = Vec::new();
+    let mut submesh_start = 0;
+    for submesh in submeshes {
+        let submesh_range = submesh_start..submesh_start + submesh.vertices_count as usize;
+        submesh_start = submesh_range.end;
+
+        let mut referenced = vec![false; submesh_range.len()];
+        for idx in &submesh.indices {
+            referenced[*idx as usize] = true;
+        }
+        let mut cells = HashMap::new();
+        let mut kept = Vec::new();
+        let remap = submesh_range
+            .zip(referenced)
+            .map(|(vertex, referenced)| {
+                if !referenced {
+                    return u32::MAX;
+                }
+                let cell = (vertices.positions[vertex] / cell_size).floor().as_ivec3();
+                *cells.entry(cell).or_insert_with(|| {
+                    kept.push(vertex);
+                    kept.len() as u32 - 1
+                })
+            })
+            .collect::<Vec<_>>();
+        let indices = submesh
+            .indices
+            .chunks_exact(3)
+            .map(|tri| [tri[0], tri[1], tri[2]].map(|idx| remap[idx as usize]))
+            .filter(|tri| tri[0] != tri[1] && tri[1] != tri[2] && tri[0] != tri[2])
+            .flatten()
+            .collect::<Vec<_>>();
+        if indices.is_empty() {
+            continue;
+        }
+        let mut bones = Vec::new();
+        for vertex in &kept {
+            result.push_vertex(vertices, *vertex);
+            let joints = vertices.joints.get(*vertex).into_iter();
+            bones.extend(joints.chain(vertices.joints2.get(*vertex)).flatten());
+        }
+        bones.retain(|joint| *joint < u32::MAX);
+        bones.sort();
+        bones.dedup();
+        result_submeshes.push(XmacMeshSubmesh {
+            indices,
+            bones,
+            vertices_count: kept.len() as u32,
+            material_idx: submesh.material_idx,
+        });
+    }
+    (result, result_submeshes)
 }
 
 fn translate_skinning(
-    output: &mut XmacFile,
     mesh_node_idx: usize,
     is_collision_mesh: bool,
     skin: TempSkinData,
-) {
+) -> XmacSkinningInfo {
     let mut table_entries = Vec::new();
     let mut influences = Vec::new();
     let mut local_bones = HashSet::new();
@@ -371,15 +1097,27 @@ fn translate_skinning(
         });
     }
 
-    let skin = XmacSkinningInfo {
+    XmacSkinningInfo {
         node_id: XmacNodeId(mesh_node_idx as u32),
         influences,
         table_entries,
         local_bones: local_bones.len() as u32,
         is_for_collision_mesh: is_collision_mesh,
         unknown1: [0, 0, 0],
-    };
-    output.chunks.push(XmacChunk::SkinningInfo(skin));
+    }
+}
+
+impl VertexData {
+    fn push_vertex(&mut self, src: &VertexData, idx: usize) {
+        self.positions.push(src.positions[idx]);
+        self.normals.extend(src.normals.get(idx));
+        self.tangents.extend(src.tangents.get(idx));
+        self.uvs.extend(src.uvs.get(idx));
+        self.joints.extend(src.joints.get(idx));
+        self.weights.extend(src.weights.get(idx));
+        self.joints2.extend(src.joints2.get(idx));
+        self.weights2.extend(src.weights2.get(idx));
+    }
 }
 
 fn filter_weightless_joints(vertices: &mut VertexData, prim_start_vertex: usize) {
@@ -407,22 +1145,19 @@ fn filter_weightless_joints(vertices: &mut VertexData, prim_start_vertex: usize)
 
 fn mesh_vertices_to_attrib_layers(
     vertices: VertexData,
+    original_vertex_numbers: Vec<u32>,
     vertex_attribute_layers: &mut Vec<XmacMeshAttribLayer>,
-    weight_data: &mut Option<TempSkinData>,
 ) {
     let VertexData {
         positions,
         normals,
         tangents,
         uvs,
-        joints,
-        weights,
-        joints2,
-        weights2,
+        ..
     } = vertices;
 
     vertex_attribute_layers.push(XmacMeshAttribLayer {
-        attribs: XmacMeshAttrib::OriginalVertexNumbers((0..positions.len() as u32).collect()),
+        attribs: XmacMeshAttrib::OriginalVertexNumbers(original_vertex_numbers),
         flag1: false,
         unknown1: [0, 0, 0],
     });
@@ -453,12 +1188,14 @@ fn mesh_vertices_to_attrib_layers(
             unknown1: [0, 0, 0],
         });
     }
-    let joints = joints.into_iter();
-    let mut joints2 = joints2.into_iter();
-    let mut weights = weights.into_iter();
-    let mut weights2 = weights2.into_iter();
+}
+
+fn vertex_skin_data(vertices: &VertexData) -> TempSkinData {
+    let mut joints2 = vertices.joints2.iter().copied();
+    let mut weights = vertices.weights.iter().copied();
+    let mut weights2 = vertices.weights2.iter().copied();
     let mut temp_skin = Vec::new();
-    for joint in joints {
+    for joint in vertices.joints.iter().copied() {
         let joint2 = joints2.next().unwrap_or([u32::MAX; 4]);
         let weight = weights.next().unwrap();
         let weight2 = weights2.next().unwrap_or_default();
@@ -474,7 +1211,7 @@ fn mesh_vertices_to_attrib_layers(
             (joint2[3], weight2[3]),
         ]);
     }
-    *weight_data = Some(temp_skin);
+    temp_skin
 }
 
 fn read_prim_vertex_data<'a, 's, T: Clone + Fn(gltf::Buffer<'a>) -> Option<&'s [u8]>>(
@@ -620,16 +1357,59 @@ fn read_prim_vertex_data<'a, 's, T: Clone + Fn(gltf::Buffer<'a>) -> Option<&'s [
 }
 
 fn translate_materials(gltf: &gltf::Document, output: &mut XmacFile) -> Result<()> {
+    let info_chunk_idx = output.chunks.len();
     output
         .chunks
         .push(XmacChunk::MaterialInfo(XmacMaterialInfo::new(
             gltf.materials().len(),
         )));
+    let mut other_materials = 0;
+    // Layer and attribute set chunks are written after all materials:
+    let mut material_extra_chunks = Vec::new();
 
     for gltf_material in gltf.materials() {
         let material_idx = gltf_material
             .index()
             .expect("Implicit material encountered") as u16;
+
+        let extras = parse_extras(gltf_material.extras());
+        for key in ["xmac_layers", "xmac_attribute_sets"] {
+            for entry in extras
+                .get(key)
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+            {
+                let chunk = serde_json::from_value(entry.clone()).map_err(|e| {
+                    ConvError::InvalidData(format!("Invalid {key} of material {material_idx}: {e}"))
+                })?;
+                match chunk {
+                    XmacChunk::StdMaterialLayer(mut layer) => {
+                        layer.material_id = material_idx;
+                        material_extra_chunks.push(XmacChunk::StdMaterialLayer(layer));
+                    }
+                    XmacChunk::MaterialAttributeSet(mut set) => {
+                        set.material_index = material_idx as u32;
+                        material_extra_chunks.push(XmacChunk::MaterialAttributeSet(set));
+                    }
+                    _ => {
+                        println!("Warn: Ignoring unexpected {key} entry of material {material_idx}")
+                    }
+                }
+            }
+        }
+        // Placeholders for fx and generic materials, as written by xmac_to_gltf:
+        if let Some(material) = extras.get("xmac_material") {
+            let chunk = serde_json::from_value(material.clone()).map_err(|e| {
+                ConvError::InvalidData(format!("Invalid xmac_material {material_idx}: {e}"))
+            })?;
+            if let XmacChunk::FxMaterial(_) | XmacChunk::GenericMaterial(_) = chunk {
+                output.chunks.push(chunk);
+                other_materials += 1;
+                continue;
+            }
+            println!("Warn: Ignoring unexpected xmac_material of material {material_idx}");
+        }
         let name = gltf_material
             .name()
             .map(str::to_string)
@@ -694,6 +1474,11 @@ fn translate_materials(gltf: &gltf::Document, output: &mut XmacFile) -> Result<(
         };
         output.chunks.push(XmacChunk::StdMaterial(mat));
     }
+    if let XmacChunk::MaterialInfo(info) = &mut output.chunks[info_chunk_idx] {
+        info.std_materials -= other_materials;
+        info.fx_materials = other_materials;
+    }
+    output.chunks.extend(material_extra_chunks);
     Ok(())
 }
 
@@ -773,3 +1558,272 @@ fn gltf_normal_to_xmac_layer(
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `size`x`size` grid of vertices with two triangles per cell, skinned to one of two joints by column.
+    fn grid(
+        vertices: &mut VertexData,
+        size: u32,
+        offset: Vec3,
+        joints: [u32; 2],
+    ) -> XmacMeshSubmesh {
+        for y in 0..size {
+            for x in 0..size {
+                vertices
+                    .positions
+                    .push(offset + Vec3::new(x as f32, y as f32, 0.0) * 0.1);
+                let joint = joints[(x >= size / 2) as usize];
+                vertices.joints.push([joint, u32::MAX, u32::MAX, u32::MAX]);
+                vertices.weights.push([1.0, 0.0, 0.0, 0.0]);
+            }
+        }
+        let mut indices = Vec::new();
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let i = y * size + x;
+                indices.extend([i, i + 1, i + size, i + 1, i + size + 1, i + size]);
+            }
+        }
+        XmacMeshSubmesh {
+            indices,
+            bones: joints.to_vec(),
+            vertices_count: size * size,
+            material_idx: 0,
+        }
+    }
+
+    fn sample_mesh() -> (VertexData, Vec<XmacMeshSubmesh>) {
+        let mut vertices = VertexData::default();
+        let mut submeshes = vec![
+            grid(&mut vertices, 20, Vec3::ZERO, [0, 1]),
+            grid(&mut vertices, 10, Vec3::new(3.0, 0.0, 0.0), [2, 3]),
+        ];
+        // an unused vertex with a joint the submesh doesn't list
+        vertices.positions.push(Vec3::new(3.5, 0.5, 0.1));
+        vertices.joints.push([4, u32::MAX, u32::MAX, u32::MAX]);
+        vertices.weights.push([1.0, 0.0, 0.0, 0.0]);
+        submeshes[1].vertices_count += 1;
+        (vertices, submeshes)
+    }
+
+    fn check_submeshes(vertices: &VertexData, submeshes: &[XmacMeshSubmesh], all_bones: &[u32]) {
+        let mut submesh_start = 0;
+        for submesh in submeshes {
+            assert!(!submesh.indices.is_empty());
+            assert_eq!(submesh.indices.len() % 3, 0);
+            assert!(
+                submesh
+                    .indices
+                    .iter()
+                    .all(|idx| *idx < submesh.vertices_count)
+            );
+            assert!(submesh.bones.iter().all(|bone| all_bones.contains(bone)));
+            let submesh_end = submesh_start + submesh.vertices_count as usize;
+            for joints in &vertices.joints[submesh_start..submesh_end] {
+                let mut joints = joints.iter().filter(|joint| **joint < u32::MAX);
+                assert!(joints.all(|joint| submesh.bones.contains(joint)));
+            }
+            submesh_start = submesh_end;
+        }
+        assert_eq!(submesh_start, vertices.positions.len());
+        assert_eq!(vertices.joints.len(), vertices.positions.len());
+        assert_eq!(vertices.weights.len(), vertices.positions.len());
+    }
+
+    #[test]
+    fn simplified_lods_stay_in_bounds() {
+        let (vertices, submeshes) = sample_mesh();
+        let mut previous_vertices = vertices.positions.len();
+        for level in 1..=3 {
+            let (lod_vertices, lod_submeshes) = simplify_mesh(&vertices, &submeshes, level);
+            assert!(!lod_submeshes.is_empty());
+            check_submeshes(&lod_vertices, &lod_submeshes, &[0, 1, 2, 3]);
+            assert!(lod_vertices.positions.len() < previous_vertices);
+            previous_vertices = lod_vertices.positions.len();
+        }
+    }
+
+    #[test]
+    fn simplified_lods_build_valid_meshes() {
+        let (vertices, submeshes) = sample_mesh();
+        let (vertices, submeshes) = simplify_mesh(&vertices, &submeshes, 2);
+        let vertices_count = vertices.positions.len() as u32;
+        let (mesh, skin) = build_mesh(vertices, submeshes, 0, false, None, &[]);
+        let skin = skin.unwrap();
+        assert_eq!(mesh.orig_verts_count, vertices_count);
+        assert_eq!(skin.table_entries.len(), vertices_count as usize);
+
+        let mut submesh_start = 0;
+        for submesh in &mesh.submeshes {
+            assert!(
+                submesh
+                    .indices
+                    .iter()
+                    .all(|idx| *idx < submesh.vertices_count)
+            );
+            let submesh_end = submesh_start + submesh.vertices_count as usize;
+            for entry in &skin.table_entries[submesh_start..submesh_end] {
+                let start = entry.start_idx as usize;
+                let influences = &skin.influences[start..start + entry.num_elements as usize];
+                assert!(
+                    influences
+                        .iter()
+                        .all(|i| submesh.bones.contains(&(i.node_idx as u32)))
+                );
+            }
+            submesh_start = submesh_end;
+        }
+        assert_eq!(submesh_start, vertices_count as usize);
+    }
+
+    /// Two meshes sharing a triangle whose morph targets are sparse accessors: the first target moves vertex 2,
+    /// the second one vertex 0.
+    fn morph_gltf() -> (gltf::Document, Vec<gltf::buffer::Data>) {
+        let mut data = Vec::new();
+        for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            data.extend(value.to_le_bytes());
+        }
+        for (vertex, delta) in [(2u32, [0.0f32, 0.0, 0.5]), (0, [0.1, 0.0, 0.0])] {
+            data.extend(vertex.to_le_bytes());
+            for value in delta {
+                data.extend(value.to_le_bytes());
+            }
+        }
+        let sparse_target = |offset: usize| {
+            serde_json::json!({
+                "componentType": 5126,
+                "count": 3,
+                "type": "VEC3",
+                "sparse": {
+                    "count": 1,
+                    "indices": {"bufferView": 1, "byteOffset": offset, "componentType": 5125},
+                    "values": {"bufferView": 1, "byteOffset": offset + 4},
+                },
+            })
+        };
+        let primitive = serde_json::json!({
+            "attributes": {"POSITION": 0},
+            "targets": [{"POSITION": 1}, {"POSITION": 2}],
+        });
+        let json = serde_json::json!({
+            "asset": {"version": "2.0"},
+            "buffers": [{"byteLength": data.len()}],
+            "bufferViews": [
+                {"buffer": 0, "byteLength": 36},
+                {"buffer": 0, "byteOffset": 36, "byteLength": 32},
+            ],
+            "accessors": [
+                {
+                    "bufferView": 0,
+                    "componentType": 5126,
+                    "count": 3,
+                    "type": "VEC3",
+                    "min": [0.0, 0.0, 0.0],
+                    "max": [1.0, 1.0, 0.0],
+                },
+                sparse_target(0),
+                sparse_target(16),
+            ],
+            "meshes": [
+                {
+                    "name": "Head",
+                    "primitives": [primitive.clone()],
+                    "extras": {
+                        "targetNames": ["Smile", "M_B_P_X"],
+                        "targetRanges": [[-1.0, 2.0]],
+                        "targetPhonemes": [PhonemeSet::AA_AO_OW.bits()],
+                    },
+                },
+                {"name": "Head_COLLISION", "primitives": [primitive]},
+            ],
+        });
+        let gltf = gltf::Gltf::from_slice(&serde_json::to_vec(&json).unwrap()).unwrap();
+        (gltf.document, vec![gltf::buffer::Data(data)])
+    }
+
+    fn morph_mesh(
+        node_idx: u32,
+        original_vertex_numbers: Vec<u32>,
+        is_collision_mesh: bool,
+    ) -> XmacChunk {
+        XmacChunk::Mesh(XmacMesh {
+            vertex_attribute_layers: vec![XmacMeshAttribLayer {
+                attribs: XmacMeshAttrib::OriginalVertexNumbers(original_vertex_numbers),
+                flag1: false,
+                unknown1: [0; 3],
+            }],
+            submeshes: Vec::new(),
+            node_id: XmacNodeId(node_idx),
+            orig_verts_count: 3,
+            is_collision_mesh,
+            unknown1: [0; 3],
+        })
+    }
+
+    #[test]
+    fn morph_targets_follow_original_vertices() {
+        let (gltf, buffers) = morph_gltf();
+        let tmp = TempData {
+            mesh_nodes: HashMap::from([(0, 0), (1, 1)]),
+            ..Default::default()
+        };
+        let mut output = XmacFile::new(SystemTime::UNIX_EPOCH);
+        // vertex 2 was split into render vertices 1 and 3:
+        output.chunks.push(morph_mesh(0, vec![0, 2, 1, 2], false));
+        output.chunks.push(morph_mesh(1, vec![0, 1, 2], true));
+        translate_morphs(&gltf, &buffers, &tmp, &mut output).unwrap();
+
+        let Some(XmacChunk::MorphTargets(morphs)) = output.chunks.last() else {
+            panic!("no morph targets were added");
+        };
+        let [smile, mbp] = morphs.targets.as_slice() else {
+            panic!("expected two targets, got {}", morphs.targets.len());
+        };
+        assert_eq!(smile.name, "Smile");
+        assert_eq!((smile.range_min, smile.range_max), (-1.0, 2.0));
+        assert_eq!(smile.phoneme_set.bits(), PhonemeSet::AA_AO_OW.bits());
+        // the collision mesh is skipped:
+        assert_eq!(smile.mesh_deform_deltas.len(), 1);
+        let deform = &smile.mesh_deform_deltas[0];
+        assert_eq!(deform.node_id, XmacNodeId(0));
+        let moved = deform
+            .deltas
+            .iter()
+            .map(|d| (d.vertex_id, d.position_delta))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            moved,
+            [
+                (1, Vec3::new(0.0, 0.0, 50.0)),
+                (3, Vec3::new(0.0, 0.0, 50.0))
+            ]
+        );
+
+        assert_eq!(mbp.name, "M_B_P_X");
+        assert_eq!((mbp.range_min, mbp.range_max), (0.0, 1.0));
+        assert_eq!(mbp.phoneme_set.bits(), PhonemeSet::M_B_P_X.bits());
+        let deform = &mbp.mesh_deform_deltas[0];
+        assert_eq!(deform.deltas.len(), 1);
+        assert_eq!(deform.deltas[0].vertex_id, 0);
+        assert!(
+            deform.deltas[0]
+                .position_delta
+                .abs_diff_eq(Vec3::new(10.0, 0.0, 0.0), 1e-5)
+        );
+    }
+
+    #[test]
+    fn morph_targets_need_a_mesh() {
+        let (gltf, buffers) = morph_gltf();
+        let tmp = TempData {
+            mesh_nodes: HashMap::from([(0, 0)]),
+            ..Default::default()
+        };
+        let mut output = XmacFile::new(SystemTime::UNIX_EPOCH);
+        let result = translate_morphs(&gltf, &buffers, &tmp, &mut output);
+        assert!(matches!(result, Err(ConvError::MandatoryDataMissing(_))));
+    }
+}
@@ -0,0 +1,576 @@
+use std::collections::{BTreeSet, HashMap};
+
+use formats::{file_formats::xmac::chunks::mesh::XmacMeshSubmesh, types::Vec3};
+
+use super::VertexData;
+
+/// Settings of the optional optimization stage run on every mesh before it is written
+#[derive(Debug, Clone, Copy)]
+pub struct MeshOptimization {
+    /// Maximum distance of welded positions, in cm
+    pub position_tolerance: f32,
+    /// Maximum distance of welded normals and tangents
+    pub normal_tolerance: f32,
+    /// Maximum distance of welded texture coordinates
+    pub uv_tolerance: f32,
+    /// Submeshes using more bones are split, None keeps them whole
+    pub max_bones: Option<usize>,
+}
+
+impl Default for MeshOptimization {
+    fn default() -> Self {
+        Self {
+            position_tolerance: 0.01,
+            normal_tolerance: 0.01,
+            uv_tolerance: 0.0001,
+            max_bones: None,
+        }
+    }
+}
+
+/// Skinning weights of welded vertices may differ by this much
+const WEIGHT_TOLERANCE: f32 = 0.001;
+/// Size of the simulated post-transform vertex cache
+const CACHE_SIZE: usize = 32;
+
+/// Optimizes the submeshes of a mesh read by read_mesh, returning the render vertices,
+/// the original vertex each of them was made from, and the new submeshes.
+///
+/// Within every submesh, vertices matching in all attributes, skinning weights and morph deltas are welded,
+/// triangles that collapse are removed and the rest are reordered for the vertex cache and then for overdraw.
+/// Submeshes with too many bones are split into several submeshes with the same material.
+/// The original vertices are left untouched, so the skinning table and morph deltas indexed by them stay valid.
+pub(super) fn optimize_mesh(
+    vertices: &VertexData,
+    submeshes: &[XmacMeshSubmesh],
+    morph_deltas: &[Vec<[Vec3; 3]>],
+    settings: &MeshOptimization,
+) -> (VertexData, Vec<u32>, Vec<XmacMeshSubmesh>) {
+    let mut result = VertexData::default();
+    let mut original_vertex_numbers = Vec::new();
+    let mut result_submeshes = Vec::new();
+    let mut submesh_start = 0;
+    for (submesh_idx, submesh) in submeshes.iter().enumerate() {
+        let submesh_vertices = submesh_start..submesh_start + submesh.vertices_count as usize;
+        submesh_start = submesh_vertices.end;
+
+        let (kept, remap) = weld_vertices(vertices, submesh_vertices, morph_deltas, settings);
+        let positions = kept
+            .iter()
+            .map(|v| vertices.positions[*v])
+            .collect::<Vec<_>>();
+        let triangles = submesh
+            .indices
+            .chunks_exact(3)
+            .map(|tri| [tri[0], tri[1], tri[2]].map(|idx| remap[idx as usize]))
+            .filter(|tri| !is_degenerate(tri, &positions))
+            .collect::<Vec<_>>();
+        let removed = submesh.indices.len() / 3 - triangles.len();
+        if removed > 0 {
+            println!("Removed {removed} degenerate triangles from submesh {submesh_idx}");
+        }
+        if triangles.is_empty() {
+            continue;
+        }
+
+        let triangles = optimize_vertex_cache(&triangles, kept.len());
+        let triangles = optimize_overdraw(triangles, &positions);
+
+        let vertex_bones = |vertex: u32| {
+            let vertex = kept[vertex as usize];
+            vertices
+                .joints
+                .get(vertex)
+                .into_iter()
+                .chain(vertices.joints2.get(vertex))
+                .flatten()
+                .copied()
+                .filter(|j| *j < u32::MAX)
+        };
+        for group in split_by_bones(&triangles, vertex_bones, settings.max_bones) {
+            // Vertices are stored in the order the triangles first use them
+            let mut local = HashMap::new();
+            let mut bones = BTreeSet::new();
+            let indices = group
+                .iter()
+                .flatten()
+                .map(|vertex| {
+                    let next = local.len() as u32;
+                    *local.entry(*vertex).or_insert_with(|| {
+                        let orig = kept[*vertex as usize];
+                        result.push_vertex(vertices, orig);
+                        original_vertex_numbers.push(orig as u32);
+                        bones.extend(vertex_bones(*vertex));
+                        next
+                    })
+                })
+                .collect::<Vec<_>>();
+            result_submeshes.push(XmacMeshSubmesh {
+                indices,
+                bones: bones.into_iter().collect(),
+                vertices_count: local.len() as u32,
+                material_idx: submesh.material_idx,
+            });
+        }
+    }
+    (result, original_vertex_numbers, result_submeshes)
+}
+
+/// Welds the vertices in the range, returning the kept vertices and the kept index of every vertex in the range
+fn weld_vertices(
+    vertices: &VertexData,
+    range: std::ops::Range<usize>,
+    morph_deltas: &[Vec<[Vec3; 3]>],
+    settings: &MeshOptimization,
+) -> (Vec<usize>, Vec<u32>) {
+    let cell_size = settings.position_tolerance.max(0.001);
+    let cell = |vertex: usize| {
+        (vertices.positions[vertex] / cell_size)
+            .floor()
+            .as_ivec3()
+            .to_array()
+    };
+    let mut cells: HashMap<[i32; 3], Vec<u32>> = HashMap::new();
+    let mut kept = Vec::new();
+    let remap = range
+        .map(|vertex| {
+            let [x, y, z] = cell(vertex);
+            let neighbours = (-1..=1).flat_map(|dx| {
+                (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| [x + dx, y + dy, z + dz]))
+            });
+            for neighbour in neighbours {
+                if let Some(candidates) = cells.get(&neighbour)
+                    && let Some(found) = candidates.iter().find(|k| {
+                        can_weld(vertices, kept[**k as usize], vertex, morph_deltas, settings)
+                    })
+                {
+                    return *found;
+                }
+            }
+            kept.push(vertex);
+            let idx = kept.len() as u32 - 1;
+            cells.entry([x, y, z]).or_default().push(idx);
+            idx
+        })
+        .collect();
+    (kept, remap)
+}
+
+fn can_weld(
+    vertices: &VertexData,
+    a: usize,
+    b: usize,
+    morph_deltas: &[Vec<[Vec3; 3]>],
+    settings: &MeshOptimization,
+) -> bool {
+    fn close<T: Copy>(values: &[T], a: usize, b: usize, distance: impl Fn(T, T) -> bool) -> bool {
+        values.is_empty() || distance(values[a], values[b])
+    }
+    let skin_matches = |joints: &[[u32; 4]], weights: &[[f32; 4]]| {
+        close(joints, a, b, |a, b| a == b)
+            && close(weights, a, b, |a, b| {
+                a.iter()
+                    .zip(b)
+                    .all(|(a, b)| (a - b).abs() <= WEIGHT_TOLERANCE)
+            })
+    };
+    close(&vertices.positions, a, b, |a, b| {
+        a.distance(b) <= settings.position_tolerance
+    }) && close(&vertices.normals, a, b, |a, b| {
+        a.distance(b) <= settings.normal_tolerance
+    }) && close(&vertices.tangents, a, b, |a, b| {
+        a.w == b.w && a.truncate().distance(b.truncate()) <= settings.normal_tolerance
+    }) && close(&vertices.uvs, a, b, |a, b| {
+        a.distance(b) <= settings.uv_tolerance
+    }) && skin_matches(&vertices.joints, &vertices.weights)
+        && skin_matches(&vertices.joints2, &vertices.weights2)
+        && morph_deltas.iter().all(|target| {
+            close(target, a, b, |a, b| {
+                a[0].distance(b[0]) <= settings.position_tolerance
+                    && a[1].distance(b[1]) <= settings.normal_tolerance
+                    && a[2].distance(b[2]) <= settings.normal_tolerance
+            })
+        })
+}
+
+/// Triangles with repeated vertices or without area
+fn is_degenerate(tri: &[u32; 3], positions: &[Vec3]) -> bool {
+    if tri[0] == tri[1] || tri[1] == tri[2] || tri[0] == tri[2] {
+        return true;
+    }
+    let [a, b, c] = tri.map(|v| positions[v as usize]);
+    (b - a).cross(c - a).length_squared() <= f32::EPSILON * f32::EPSILON
+}
+
+/// Scores a vertex for optimize_vertex_cache, by its position in the cache and its remaining triangles
+fn vertex_cache_score(cache_position: Option<usize>, remaining_triangles: usize) -> f32 {
+    if remaining_triangles == 0 {
+        return -1.0;
+    }
+    let cache_score = match cache_position {
+        None => 0.0,
+        // The vertices of the last triangle are penalized a little, to avoid strip-like ordering
+        Some(position) if position < 3 => 0.75,
+        Some(position) => (1.0 - (position - 3) as f32 / (CACHE_SIZE - 3) as f32).powf(1.5),
+    };
+    // Vertices with few triangles left are preferred, so they can leave the cache
+    cache_score + 2.0 / (remaining_triangles as f32).sqrt()
+}
+
+/// Reorders the triangles for the post-transform vertex cache, following Tom Forsyth's
+/// "Linear-Speed Vertex Cache Optimisation": the triangle with the best scoring vertices is emitted next.
+fn optimize_vertex_cache(triangles: &[[u32; 3]], vertex_count: usize) -> Vec<[u32; 3]> {
+    let mut vertex_triangles = vec![Vec::new(); vertex_count];
+    for (tri_idx, tri) in triangles.iter().enumerate() {
+        for vertex in tri {
+            vertex_triangles[*vertex as usize].push(tri_idx);
+        }
+    }
+    let mut cache_positions = vec![None; vertex_count];
+    let mut vertex_scores = vertex_triangles
+        .iter()
+        .map(|t| vertex_cache_score(None, t.len()))
+        .collect::<Vec<_>>();
+    let triangle_score = |tri: &[u32; 3], vertex_scores: &[f32]| {
+        tri.iter().map(|v| vertex_scores[*v as usize]).sum::<f32>()
+    };
+    let mut triangle_scores = triangles
+        .iter()
+        .map(|tri| triangle_score(tri, &vertex_scores))
+        .collect::<Vec<_>>();
+
+    let mut emitted = vec![false; triangles.len()];
+    let mut cache: Vec<u32> = Vec::new();
+    let mut result = Vec::with_capacity(triangles.len());
+    let mut next_unemitted = 0;
+    while result.len() < triangles.len() {
+        let best = cache
+            .iter()
+            .flat_map(|v| vertex_triangles[*v as usize].iter().copied())
+            .max_by(|a, b| triangle_scores[*a].total_cmp(&triangle_scores[*b]));
+        let best = best.unwrap_or_else(|| {
+            // Nothing left around the cache, continue with the next triangle in the input order
+            while emitted[next_unemitted] {
+                next_unemitted += 1;
+            }
+            next_unemitted
+        });
+        let tri = triangles[best];
+        result.push(tri);
+        emitted[best] = true;
+
+        for vertex in tri {
+            let remaining = &mut vertex_triangles[vertex as usize];
+            remaining.retain(|t| *t != best);
+        }
+        let mut new_cache = tri.to_vec();
+        new_cache.extend(cache.iter().filter(|v| !tri.contains(v)));
+        let evicted = new_cache.split_off(new_cache.len().min(CACHE_SIZE));
+        for vertex in &evicted {
+            cache_positions[*vertex as usize] = None;
+        }
+        for (position, vertex) in new_cache.iter().enumerate() {
+            cache_positions[*vertex as usize] = Some(position);
+        }
+        for vertex in new_cache.iter().chain(&evicted) {
+            let vertex = *vertex as usize;
+            vertex_scores[vertex] =
+                vertex_cache_score(cache_positions[vertex], vertex_triangles[vertex].len());
+        }
+        for vertex in new_cache.iter().chain(&evicted) {
+            for tri_idx in &vertex_triangles[*vertex as usize] {
+                triangle_scores[*tri_idx] = triangle_score(&triangles[*tri_idx], &vertex_scores);
+            }
+        }
+        cache = new_cache;
+    }
+    result
+}
+
+/// Reorders clusters of triangles so that those facing away from the mesh center are drawn first,
+/// as they are the most likely to occlude the others. Clusters start where the vertex cache
+/// order has to start over anyway, so the vertex cache efficiency is kept.
+fn optimize_overdraw(triangles: Vec<[u32; 3]>, positions: &[Vec3]) -> Vec<[u32; 3]> {
+    let mut clusters: Vec<Vec<[u32; 3]>> = Vec::new();
+    let mut cache: Vec<u32> = Vec::new();
+    for tri in triangles {
+        let misses = tri.iter().filter(|v| !cache.contains(v)).count();
+        if misses == 3 || clusters.is_empty() {
+            clusters.push(Vec::new());
+        }
+        clusters.last_mut().unwrap().push(tri);
+        for vertex in tri {
+            if !cache.contains(&vertex) {
+                cache.insert(0, vertex);
+            }
+        }
+        cache.truncate(CACHE_SIZE);
+    }
+    if clusters.len() < 2 {
+        return clusters.into_iter().flatten().collect();
+    }
+
+    // Area weighted centroid and normal of every cluster
+    let cluster_shapes = clusters
+        .iter()
+        .map(|cluster| {
+            let mut centroid = Vec3::ZERO;
+            let mut normal = Vec3::ZERO;
+            let mut area = 0.0;
+            for tri in cluster {
+                let [a, b, c] = tri.map(|v| positions[v as usize]);
+                let tri_normal = (b - a).cross(c - a);
+                let tri_area = tri_normal.length();
+                centroid += (a + b + c) / 3.0 * tri_area;
+                normal += tri_normal;
+                area += tri_area;
+            }
+            (
+                centroid / area.max(f32::EPSILON),
+                area,
+                normal.normalize_or_zero(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let total_area = cluster_shapes.iter().map(|s| s.1).sum::<f32>();
+    let mesh_centroid = cluster_shapes
+        .iter()
+        .map(|(centroid, area, _)| *centroid * *area)
+        .sum::<Vec3>()
+        / total_area.max(f32::EPSILON);
+
+    let mut order = (0..clusters.len()).collect::<Vec<_>>();
+    let facing = |cluster: usize| {
+        let (centroid, _, normal) = cluster_shapes[cluster];
+        (centroid - mesh_centroid).dot(normal)
+    };
+    order.sort_by(|a, b| facing(*b).total_cmp(&facing(*a)));
+    order
+        .into_iter()
+        .flat_map(|cluster| std::mem::take(&mut clusters[cluster]))
+        .collect()
+}
+
+/// Splits the triangles into consecutive groups that each use at most max_bones bones.
+/// A triangle that uses more bones by itself gets a group of its own.
+fn split_by_bones<I: Iterator<Item = u32>>(
+    triangles: &[[u32; 3]],
+    vertex_bones: impl Fn(u32) -> I,
+    max_bones: Option<usize>,
+) -> Vec<Vec<[u32; 3]>> {
+    let Some(max_bones) = max_bones else {
+        return vec![triangles.to_vec()];
+    };
+    let mut groups: Vec<Vec<[u32; 3]>> = Vec::new();
+    let mut group_bones = BTreeSet::new();
+    for tri in triangles {
+        let tri_bones = tri
+            .iter()
+            .flat_map(|v| vertex_bones(*v))
+            .collect::<BTreeSet<_>>();
+        if tri_bones.len() > max_bones {
+            println!(
+                "A triangle uses {} bones, more than the limit of {max_bones}",
+                tri_bones.len()
+            );
+        }
+        if groups.is_empty() || group_bones.union(&tri_bones).count() > max_bones {
+            groups.push(Vec::new());
+            group_bones.clear();
+        }
+        group_bones.extend(tri_bones);
+        groups.last_mut().unwrap().push(*tri);
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use formats::types::Vec2;
+
+    use super::*;
+
+    /// An unindexed strip of quads along x, every vertex is skinned to the joint of its x coordinate
+    fn quad_strip(quads: u32) -> (VertexData, XmacMeshSubmesh) {
+        let mut vertices = VertexData::default();
+        for quad in 0..quads {
+            let x = quad as f32;
+            let corners = [[x, 0.0], [x + 1.0, 0.0], [x, 1.0], [x + 1.0, 1.0]];
+            for corner in [0, 1, 2, 1, 3, 2] {
+                let [x, y] = corners[corner];
+                vertices.positions.push(Vec3::new(x, y, 0.0));
+                vertices
+                    .joints
+                    .push([x as u32, u32::MAX, u32::MAX, u32::MAX]);
+                vertices.weights.push([1.0, 0.0, 0.0, 0.0]);
+            }
+        }
+        let vertices_count = vertices.positions.len() as u32;
+        let submesh = XmacMeshSubmesh {
+            indices: (0..vertices_count).collect(),
+            bones: (0..=quads).collect(),
+            vertices_count,
+            material_idx: 0,
+        };
+        (vertices, submesh)
+    }
+
+    /// The triangles as positions, starting with the smallest one so the winding is kept
+    fn triangle_positions(
+        vertices: &VertexData,
+        submeshes: &[XmacMeshSubmesh],
+    ) -> Vec<[[i32; 3]; 3]> {
+        let mut result = Vec::new();
+        let mut submesh_start = 0;
+        for submesh in submeshes {
+            for tri in submesh.indices.chunks_exact(3) {
+                let tri = [tri[0], tri[1], tri[2]].map(|idx| {
+                    let position = vertices.positions[submesh_start + idx as usize];
+                    (position * 1000.0).round().as_ivec3().to_array()
+                });
+                let first = (0..3).min_by_key(|i| tri[*i]).unwrap();
+                result.push([0, 1, 2].map(|i| tri[(first + i) % 3]));
+            }
+            submesh_start += submesh.vertices_count as usize;
+        }
+        result.sort();
+        result
+    }
+
+    /// Checks the optimized mesh is consistent and references the original vertices it was made from
+    fn check_optimized(
+        original: &VertexData,
+        (vertices, original_vertex_numbers, submeshes): &(
+            VertexData,
+            Vec<u32>,
+            Vec<XmacMeshSubmesh>,
+        ),
+    ) {
+        assert_eq!(original_vertex_numbers.len(), vertices.positions.len());
+        for (vertex, orig) in original_vertex_numbers.iter().enumerate() {
+            assert!((*orig as usize) < original.positions.len());
+            assert_eq!(
+                vertices.positions[vertex],
+                original.positions[*orig as usize]
+            );
+            assert_eq!(vertices.joints[vertex], original.joints[*orig as usize]);
+        }
+        let mut submesh_start = 0;
+        for submesh in submeshes {
+            assert!(
+                submesh
+                    .indices
+                    .iter()
+                    .all(|idx| *idx < submesh.vertices_count)
+            );
+            let submesh_end = submesh_start + submesh.vertices_count as usize;
+            for joints in &vertices.joints[submesh_start..submesh_end] {
+                let mut joints = joints.iter().filter(|joint| **joint < u32::MAX);
+                assert!(joints.all(|joint| submesh.bones.contains(joint)));
+            }
+            submesh_start = submesh_end;
+        }
+        assert_eq!(submesh_start, vertices.positions.len());
+    }
+
+    #[test]
+    fn welds_matching_vertices() {
+        let (vertices, submesh) = quad_strip(4);
+        let submeshes = [submesh];
+        let optimized = optimize_mesh(&vertices, &submeshes, &[], &MeshOptimization::default());
+        check_optimized(&vertices, &optimized);
+        // Every corner of the strip is left once
+        assert_eq!(optimized.0.positions.len(), 10);
+        assert_eq!(optimized.2.len(), 1);
+        assert_eq!(
+            triangle_positions(&optimized.0, &optimized.2),
+            triangle_positions(&vertices, &submeshes)
+        );
+    }
+
+    #[test]
+    fn keeps_vertices_with_different_attributes() {
+        let (mut vertices, submesh) = quad_strip(1);
+        vertices.uvs = (0..6).map(|i| Vec2::new(i as f32, 0.0)).collect();
+        let submeshes = [submesh];
+        let optimized = optimize_mesh(&vertices, &submeshes, &[], &MeshOptimization::default());
+        check_optimized(&vertices, &optimized);
+        assert_eq!(optimized.0.positions.len(), 6);
+
+        // Morph targets that move the vertices differently prevent welding as well
+        let (vertices, submesh) = quad_strip(1);
+        let deltas = (0..6)
+            .map(|i| [Vec3::new(0.0, 0.0, i as f32), Vec3::ZERO, Vec3::ZERO])
+            .collect();
+        let optimized = optimize_mesh(
+            &vertices,
+            &[submesh],
+            &[deltas],
+            &MeshOptimization::default(),
+        );
+        assert_eq!(optimized.0.positions.len(), 6);
+    }
+
+    #[test]
+    fn removes_degenerate_triangles() {
+        let (mut vertices, mut submesh) = quad_strip(2);
+        let expected = triangle_positions(&vertices, std::slice::from_ref(&submesh));
+        // a triangle with a repeated vertex, and one without area
+        let start = submesh.vertices_count;
+        for x in [0.0, 1.0, 2.0] {
+            vertices.positions.push(Vec3::new(x, 0.0, 0.0));
+            vertices
+                .joints
+                .push([x as u32, u32::MAX, u32::MAX, u32::MAX]);
+            vertices.weights.push([1.0, 0.0, 0.0, 0.0]);
+        }
+        submesh
+            .indices
+            .extend([0, 1, 0, start, start + 1, start + 2]);
+        submesh.vertices_count += 3;
+        let submeshes = [submesh];
+
+        let optimized = optimize_mesh(&vertices, &submeshes, &[], &MeshOptimization::default());
+        check_optimized(&vertices, &optimized);
+        assert_eq!(triangle_positions(&optimized.0, &optimized.2), expected);
+
+        // Nothing is left of a submesh with only degenerate triangles
+        let submesh = XmacMeshSubmesh {
+            indices: vec![0, 1, 2],
+            bones: vec![0, 1, 2],
+            vertices_count: 3,
+            material_idx: 0,
+        };
+        let mut line = VertexData::default();
+        for vertex in start as usize..start as usize + 3 {
+            line.push_vertex(&vertices, vertex);
+        }
+        let optimized = optimize_mesh(&line, &[submesh], &[], &MeshOptimization::default());
+        assert!(optimized.2.is_empty());
+    }
+
+    #[test]
+    fn splits_submeshes_by_bones() {
+        let (vertices, submesh) = quad_strip(8);
+        let submeshes = [submesh];
+        for max_bones in 2..=5 {
+            let settings = MeshOptimization {
+                max_bones: Some(max_bones),
+                ..Default::default()
+            };
+            let optimized = optimize_mesh(&vertices, &submeshes, &[], &settings);
+            check_optimized(&vertices, &optimized);
+            let (_, _, split) = &optimized;
+            // every quad uses two bones, so at least this many are needed:
+            assert!(split.len() >= 8_usize.div_ceil(max_bones - 1));
+            for submesh in split {
+                assert!(submesh.bones.len() <= max_bones);
+                assert_eq!(submesh.material_idx, 0);
+            }
+            assert_eq!(
+                triangle_positions(&optimized.0, split),
+                triangle_positions(&vertices, &submeshes)
+            );
+        }
+    }
+}
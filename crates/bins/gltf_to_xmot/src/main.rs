@@ -0,0 +1,179 @@
+use std::{
+    env,
+    ffi::OsString,
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use formats::file_formats::{
+    xmac::XmacFile,
+    xmot::{XmotChunk, XmotChunkType, XmotFile},
+};
+
+mod translation;
+
+fn print_usage() {
+    println!("Usage: gltf_to_xmot <animations.gltf> <actor._xmac> <template._xmot> [options]");
+    println!("Converts every animation of the GLTF file to a motion for the actor.");
+    println!("Animated nodes are matched to the actor's nodes by name, all of them have to exist.");
+    println!(
+        "Changes relative to the GLTF rest pose are applied to the actor's bind pose, so both skeletons may differ."
+    );
+    println!(
+        "The resource header and motion info are taken from the template, e.g. a similar motion of the actor."
+    );
+    println!("Output is written to <animation name>_out._xmot next to the GLTF file.");
+    println!("Options:");
+    println!("  /animation=name  only convert this animation");
+    println!(
+        "  /tolerance=x     remove keys that differ at most x from the interpolated value (cm for positions)"
+    );
+}
+
+fn load_template(path: &Path) -> Option<XmotFile> {
+    let in_data = match File::open(path) {
+        Ok(f) => f,
+        Err(e) => {
+            println!("opening {} failed: {e}", path.display());
+            return None;
+        }
+    };
+    let mut in_data = BufReader::new(in_data);
+    match XmotFile::load(&mut in_data) {
+        Ok(xmot) => Some(xmot),
+        Err(e) => {
+            println!("loading xmot {} failed: {e}", path.display());
+            None
+        }
+    }
+}
+
+fn main() {
+    println!("Chromosome Toolkit - R1 - GLTF to XMOT");
+    let mut paths = Vec::new();
+    let mut animation_filter = None;
+    let mut tolerance = None;
+    for arg in env::args().skip(1) {
+        if let Some(name) = arg.strip_prefix("/animation=") {
+            animation_filter = Some(name.to_string());
+        } else if let Some(value) = arg.strip_prefix("/tolerance=") {
+            match value.parse::<f32>() {
+                Ok(value) if value >= 0.0 => tolerance = Some(value),
+                _ => {
+                    println!("Invalid tolerance {value}");
+                    return;
+                }
+            }
+        } else {
+            paths.push(OsString::from(arg));
+        }
+    }
+    let [gltf_path, actor_path, template_path] = paths.as_slice() else {
+        print_usage();
+        return;
+    };
+    let gltf_path = Path::new(gltf_path);
+    let template_path = Path::new(template_path);
+
+    let (gltf, buffers, _) = match gltf::import(gltf_path) {
+        Ok(imported) => imported,
+        Err(e) => {
+            println!("loading {} failed: {e}", gltf_path.display());
+            return;
+        }
+    };
+    let actor = match File::open(actor_path).map(BufReader::new) {
+        Ok(mut in_data) => match XmacFile::load(&mut in_data) {
+            Ok(actor) => actor,
+            Err(e) => {
+                println!("loading xmac failed: {e}");
+                return;
+            }
+        },
+        Err(e) => {
+            println!("opening {} failed: {e}", Path::new(actor_path).display());
+            return;
+        }
+    };
+    let Some(actor_nodes) = actor.get_nodes_chunk() else {
+        println!("The actor has no nodes");
+        return;
+    };
+
+    let mut converted = 0;
+    for (anim_idx, animation) in gltf.animations().enumerate() {
+        let name = animation
+            .name()
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("Animation{anim_idx}"));
+        if animation_filter
+            .as_ref()
+            .is_some_and(|filter| filter != &name)
+        {
+            continue;
+        }
+        println!("{name}");
+        let sub_motions =
+            match translation::gltf_to_xmot(&animation, &buffers, actor_nodes, tolerance) {
+                Ok(sub_motions) => sub_motions,
+                Err(e) => {
+                    println!("converting {name} failed: {e}");
+                    continue;
+                }
+            };
+        let key_count: usize = sub_motions
+            .sub_motions
+            .iter()
+            .map(|sub| sub.pos_keys.len() + sub.rot_keys.len() + sub.scale_keys.len())
+            .sum();
+        println!("{} nodes, {key_count} keys", sub_motions.sub_motions.len());
+
+        // The template's motion data and events are replaced, everything else is kept:
+        let Some(mut xmot) = load_template(template_path) else {
+            return;
+        };
+        let is_motion_data = |chunk: &XmotChunk| match chunk {
+            XmotChunk::SubMotions(_) => true,
+            XmotChunk::Unknown(unknown) => [
+                XmotChunkType::MotionEventTable,
+                XmotChunkType::SubMotion,
+                XmotChunkType::WaveletInfo,
+                XmotChunkType::MorphSubMotions,
+            ]
+            .iter()
+            .any(|ty| *ty as u32 == unknown.type_id),
+        };
+        let insert_at = xmot
+            .chunks
+            .iter()
+            .position(is_motion_data)
+            .unwrap_or(xmot.chunks.len());
+        xmot.chunks.retain(|chunk| !is_motion_data(chunk));
+        xmot.chunks
+            .insert(insert_at, XmotChunk::SubMotions(sub_motions));
+
+        let out_path = gltf_path.with_file_name(format!("{name}_out._xmot"));
+        let out_file = File::create(&out_path).expect("Unable to open output file");
+        let mut out_file = BufWriter::new(out_file);
+        xmot.save(&mut out_file).unwrap();
+        out_file.flush().unwrap();
+        converted += 1;
+    }
+    println!("{converted} motions converted");
+}
+
+#[derive(Debug)]
+pub enum ConvError {
+    MandatoryDataMissing(String),
+    InvalidData(String),
+}
+
+type Result<T> = std::result::Result<T, ConvError>;
+
+impl std::error::Error for ConvError {}
+impl std::fmt::Display for ConvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
@@ -0,0 +1,492 @@
+use std::collections::{BTreeMap, HashMap};
+
+use formats::{
+    file_formats::{
+        xmac::chunks::nodes::{XmacNode, XmacNodes},
+        xmot::{XmotKey, XmotSubMotion, XmotSubMotions},
+    },
+    types::{Mat4, Quat, Vec3, Vec4},
+};
+use gltf::{
+    animation::{Interpolation, Property, util::ReadOutputs},
+    scene::Transform,
+};
+
+use super::{ConvError, Result};
+
+/// Keyframes of one glTF channel, rotations as XYZW, translations and scales with w = 0
+struct Curve {
+    interpolation: Interpolation,
+    times: Vec<f32>,
+    /// For cubic splines, every key has an in-tangent, the value and an out-tangent
+    values: Vec<Vec4>,
+}
+
+#[derive(Default)]
+struct NodeCurves {
+    translation: Option<Curve>,
+    rotation: Option<Curve>,
+    scale: Option<Curve>,
+}
+
+impl Curve {
+    fn key_value(&self, idx: usize) -> Vec4 {
+        match self.interpolation {
+            Interpolation::CubicSpline => self.values[idx * 3 + 1],
+            _ => self.values[idx],
+        }
+    }
+
+    fn sample(&self, time: f32, is_rotation: bool) -> Vec4 {
+        let last = self.times.len() - 1;
+        let next = self.times.partition_point(|t| *t <= time);
+        if next == 0 {
+            return self.key_value(0);
+        }
+        if next > last {
+            return self.key_value(last);
+        }
+        let prev = next - 1;
+        let delta = self.times[next] - self.times[prev];
+        let s = (time - self.times[prev]) / delta;
+        let (a, b) = (self.key_value(prev), self.key_value(next));
+        match self.interpolation {
+            Interpolation::Step => a,
+            Interpolation::Linear if is_rotation => {
+                Vec4::from(Quat::from_vec4(a).slerp(Quat::from_vec4(b), s))
+            }
+            Interpolation::Linear => a.lerp(b, s),
+            Interpolation::CubicSpline => {
+                let out_tangent = self.values[prev * 3 + 2] * delta;
+                let in_tangent = self.values[next * 3] * delta;
+                let (s2, s3) = (s * s, s * s * s);
+                let value = a * (2.0 * s3 - 3.0 * s2 + 1.0)
+                    + out_tangent * (s3 - 2.0 * s2 + s)
+                    + b * (-2.0 * s3 + 3.0 * s2)
+                    + in_tangent * (s3 - s2);
+                if is_rotation {
+                    value.normalize()
+                } else {
+                    value
+                }
+            }
+        }
+    }
+}
+
+/// Maps values relative to the rest pose of a glTF node onto the bind pose of an actor node, in the units of XMOT
+struct Retarget {
+    rest_pos: Vec3,
+    rest_rotation: Quat,
+    rest_scale: Vec3,
+    bind_pos: Vec3,
+    bind_rotation: Quat,
+    bind_scale: Vec3,
+}
+
+impl Retarget {
+    fn new(node: &gltf::Node, actor_node: &XmacNode) -> Self {
+        let (rest_scale, rest_rotation, rest_pos) = match node.transform() {
+            Transform::Matrix { matrix } => {
+                Mat4::from_cols_array_2d(&matrix).to_scale_rotation_translation()
+            }
+            Transform::Decomposed {
+                translation,
+                rotation,
+                scale,
+            } => (
+                Vec3::from_array(scale),
+                Quat::from_array(rotation),
+                Vec3::from_array(translation),
+            ),
+        };
+        Self {
+            rest_pos,
+            rest_rotation: rest_rotation.normalize(),
+            rest_scale,
+            bind_pos: actor_node.local_pos,
+            bind_rotation: actor_node.rotation.normalize(),
+            bind_scale: actor_node.local_scale,
+        }
+    }
+
+    /// The offset from the rest position is applied to the bind position, m to cm
+    fn position(&self, value: Vec3) -> Vec3 {
+        self.bind_pos + (value - self.rest_pos) * 100.0
+    }
+
+    /// The rotation relative to the rest rotation is applied to the bind rotation
+    fn rotation(&self, value: Quat) -> Quat {
+        (self.bind_rotation * self.rest_rotation.inverse() * value.normalize()).normalize()
+    }
+
+    /// The factor relative to the rest scale is applied to the bind scale, which is inverted (see xmac_to_gltf)
+    fn scale(&self, value: Vec3) -> Vec3 {
+        self.bind_scale * self.rest_scale / value
+    }
+}
+
+/// Converts a glTF animation to sub motions for the nodes of `actor`, matched by name.
+///
+/// Every channel is sampled at the key times of all channels of its node, the animation is shifted to start at 0.
+/// The animation is retargeted: changes relative to the rest pose of the glTF node are applied to the actor's bind pose,
+/// properties without a channel keep the bind pose.
+/// Keys that can be interpolated from their neighbours within `tolerance` (default: exactly) are removed.
+pub fn gltf_to_xmot(
+    animation: &gltf::Animation,
+    buffers: &[gltf::buffer::Data],
+    actor: &XmacNodes,
+    tolerance: Option<f32>,
+) -> Result<XmotSubMotions> {
+    let mut curves: BTreeMap<usize, (gltf::Node, NodeCurves)> = BTreeMap::new();
+    for channel in animation.channels() {
+        let node = channel.target().node();
+        let reader = channel.reader(|buf| buffers.get(buf.index()).map(|v| v.0.as_slice()));
+        let times = reader
+            .read_inputs()
+            .ok_or_else(|| ConvError::MandatoryDataMissing("Channel without inputs".to_string()))?
+            .collect::<Vec<_>>();
+        let values: Vec<Vec4> = match reader.read_outputs() {
+            Some(ReadOutputs::Translations(values)) | Some(ReadOutputs::Scales(values)) => {
+                values.map(|v| Vec3::from_array(v).extend(0.0)).collect()
+            }
+            Some(ReadOutputs::Rotations(values)) => {
+                values.into_f32().map(Vec4::from_array).collect()
+            }
+            Some(ReadOutputs::MorphTargetWeights(_)) => {
+                println!(
+                    "Note: Morph target weights of node {} are not supported, skipping",
+                    node.name().unwrap_or_default()
+                );
+                continue;
+            }
+            None => {
+                return Err(ConvError::MandatoryDataMissing(
+                    "Channel without outputs".to_string(),
+                ));
+            }
+        };
+        let interpolation = channel.sampler().interpolation();
+        let per_key = if interpolation == Interpolation::CubicSpline {
+            3
+        } else {
+            1
+        };
+        if times.is_empty() || values.len() != times.len() * per_key {
+            return Err(ConvError::InvalidData(format!(
+                "Channel {} has {} times but {} values",
+                channel.index(),
+                times.len(),
+                values.len()
+            )));
+        }
+        let curve = Curve {
+            interpolation,
+            times,
+            values,
+        };
+        let (_, node_curves) = curves
+            .entry(node.index())
+            .or_insert_with(|| (node.clone(), NodeCurves::default()));
+        match channel.target().property() {
+            Property::Translation => node_curves.translation = Some(curve),
+            Property::Rotation => node_curves.rotation = Some(curve),
+            Property::Scale => node_curves.scale = Some(curve),
+            Property::MorphTargetWeights => unreachable!(),
+        }
+    }
+
+    let actor_ids = actor
+        .into_iter()
+        .enumerate()
+        .map(|(idx, node)| (node.name.as_str(), idx))
+        .collect::<HashMap<_, _>>();
+    let missing = curves
+        .values()
+        .map(|(node, _)| node.name().unwrap_or_default())
+        .filter(|name| !actor_ids.contains_key(name))
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        return Err(ConvError::InvalidData(format!(
+            "Animated nodes are missing in the actor: {}",
+            missing.join(", ")
+        )));
+    }
+
+    let start_time = curves
+        .values()
+        .flat_map(|(_, c)| [&c.translation, &c.rotation, &c.scale])
+        .flatten()
+        .map(|curve| curve.times[0])
+        .fold(f32::MAX, f32::min);
+
+    let mut sub_motions = Vec::new();
+    for (node, node_curves) in curves.values() {
+        let actor_node = &actor.nodes[actor_ids[node.name().unwrap_or_default()]];
+        let retarget = Retarget::new(node, actor_node);
+
+        let mut times = [
+            &node_curves.translation,
+            &node_curves.rotation,
+            &node_curves.scale,
+        ]
+        .into_iter()
+        .flatten()
+        .flat_map(|curve| curve.times.iter().copied())
+        .collect::<Vec<_>>();
+        times.sort_by(f32::total_cmp);
+        times.dedup_by(|a, b| (*a - *b).abs() < 1e-5);
+
+        let sample = |curve: &Option<Curve>, is_rotation| -> Vec<(f32, Vec4)> {
+            let Some(curve) = curve else {
+                return Vec::new();
+            };
+            times
+                .iter()
+                .map(|t| (t - start_time, curve.sample(*t, is_rotation)))
+                .collect()
+        };
+        let pos_keys = sample(&node_curves.translation, false)
+            .into_iter()
+            .map(|(time, v)| XmotKey {
+                time,
+                value: retarget.position(v.truncate()),
+            })
+            .collect::<Vec<_>>();
+        let mut rot_keys = sample(&node_curves.rotation, true)
+            .into_iter()
+            .map(|(time, v)| XmotKey {
+                time,
+                value: retarget.rotation(Quat::from_vec4(v)),
+            })
+            .collect::<Vec<_>>();
+        let scale_keys = sample(&node_curves.scale, false)
+            .into_iter()
+            .map(|(time, v)| XmotKey {
+                time,
+                value: retarget.scale(v.truncate()),
+            })
+            .collect::<Vec<_>>();
+        // keep consecutive rotations in the same hemisphere, so that interpolating them takes the short way
+        for idx in 1..rot_keys.len() {
+            if rot_keys[idx - 1].value.dot(rot_keys[idx].value) < 0.0 {
+                rot_keys[idx].value = -rot_keys[idx].value;
+            }
+        }
+
+        let mut sub = XmotSubMotion::new(
+            &actor_node.name,
+            actor_node.rotation,
+            actor_node.local_pos,
+            actor_node.local_scale,
+        );
+        if let Some(key) = pos_keys.first() {
+            sub.pose_pos = key.value;
+        }
+        if let Some(key) = rot_keys.first() {
+            sub.pose_rot = key.value;
+        }
+        if let Some(key) = scale_keys.first() {
+            sub.pose_scale = key.value;
+        }
+        sub.pos_keys = pos_keys;
+        sub.rot_keys = rot_keys;
+        sub.scale_keys = scale_keys;
+
+        let tolerance = tolerance.unwrap_or(0.0);
+        sub.max_error = tolerance;
+        reduce_keys(&mut sub.pos_keys, tolerance, Vec3::lerp, |a, b| {
+            (a - b).abs().max_element()
+        });
+        reduce_keys(&mut sub.rot_keys, tolerance, Quat::slerp, |a, b| {
+            (Vec4::from(a) - Vec4::from(b)).abs().max_element()
+        });
+        reduce_keys(&mut sub.scale_keys, tolerance, Vec3::lerp, |a, b| {
+            (a - b).abs().max_element()
+        });
+        sub_motions.push(sub);
+    }
+    Ok(XmotSubMotions { sub_motions })
+}
+
+/// Removes keys that differ at most `tolerance` from the interpolation of the remaining keys.
+/// A track that doesn't change at all is removed completely, the pose holds its value.
+fn reduce_keys<T: Copy>(
+    keys: &mut Vec<XmotKey<T>>,
+    tolerance: f32,
+    interpolate: impl Fn(T, T, f32) -> T,
+    difference: impl Fn(T, T) -> f32,
+) {
+    if keys.is_empty() {
+        return;
+    }
+    if keys
+        .iter()
+        .all(|key| difference(key.value, keys[0].value) <= tolerance)
+    {
+        keys.clear();
+        return;
+    }
+    let mut reduced = vec![keys[0]];
+    let mut anchor = 0;
+    for idx in 1..keys.len() - 1 {
+        let (from, to) = (keys[anchor], keys[idx + 1]);
+        let removable = keys[anchor + 1..=idx].iter().all(|key| {
+            let s = (key.time - from.time) / (to.time - from.time);
+            difference(interpolate(from.value, to.value, s), key.value) <= tolerance
+        });
+        if !removable {
+            reduced.push(keys[idx]);
+            anchor = idx;
+        }
+    }
+    reduced.push(keys[keys.len() - 1]);
+    *keys = reduced;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve(interpolation: Interpolation, times: &[f32], values: &[Vec4]) -> Curve {
+        Curve {
+            interpolation,
+            times: times.to_vec(),
+            values: values.to_vec(),
+        }
+    }
+
+    fn keys(values: &[(f32, f32)]) -> Vec<XmotKey<Vec3>> {
+        values
+            .iter()
+            .map(|(time, x)| XmotKey {
+                time: *time,
+                value: Vec3::new(*x, 0.0, 0.0),
+            })
+            .collect()
+    }
+
+    fn reduce(keys: &mut Vec<XmotKey<Vec3>>, tolerance: f32) {
+        reduce_keys(keys, tolerance, Vec3::lerp, |a, b| {
+            (a - b).abs().max_element()
+        });
+    }
+
+    #[test]
+    fn sample_linear_and_step() {
+        let values = [Vec4::ZERO, Vec4::new(2.0, 4.0, 0.0, 0.0)];
+        let linear = curve(Interpolation::Linear, &[1.0, 2.0], &values);
+        assert_eq!(linear.sample(1.5, false), Vec4::new(1.0, 2.0, 0.0, 0.0));
+        // clamped outside of the keys:
+        assert_eq!(linear.sample(0.0, false), values[0]);
+        assert_eq!(linear.sample(3.0, false), values[1]);
+        assert_eq!(linear.sample(2.0, false), values[1]);
+
+        let step = curve(Interpolation::Step, &[1.0, 2.0], &values);
+        assert_eq!(step.sample(1.9, false), values[0]);
+        assert_eq!(step.sample(2.0, false), values[1]);
+    }
+
+    #[test]
+    fn sample_rotation_slerps() {
+        let quarter = Quat::from_rotation_z(std::f32::consts::FRAC_PI_2);
+        let rotations = curve(
+            Interpolation::Linear,
+            &[0.0, 1.0],
+            &[Vec4::from(Quat::IDENTITY), Vec4::from(quarter)],
+        );
+        let half = Quat::from_vec4(rotations.sample(0.5, true));
+        assert!(half.abs_diff_eq(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4), 1e-5));
+    }
+
+    #[test]
+    fn sample_cubic_spline() {
+        // in-tangent, value, out-tangent per key, a slope of 1 over 2 seconds matches the line between the keys
+        let slope = Vec4::new(1.0, 0.0, 0.0, 0.0);
+        let cubic = curve(
+            Interpolation::CubicSpline,
+            &[0.0, 2.0],
+            &[slope, Vec4::ZERO, slope, slope, slope * 2.0, slope],
+        );
+        assert!(cubic.sample(0.5, false).abs_diff_eq(slope * 0.5, 1e-5));
+        assert_eq!(cubic.sample(2.0, false), slope * 2.0);
+
+        // flat tangents ease in and out:
+        let flat = curve(
+            Interpolation::CubicSpline,
+            &[0.0, 2.0],
+            &[
+                Vec4::ZERO,
+                Vec4::ZERO,
+                Vec4::ZERO,
+                Vec4::ZERO,
+                slope,
+                Vec4::ZERO,
+            ],
+        );
+        assert!(flat.sample(1.0, false).abs_diff_eq(slope * 0.5, 1e-5));
+        assert!(flat.sample(0.5, false).x < 0.25);
+    }
+
+    #[test]
+    fn reduce_linear_keys() {
+        let mut linear = keys(&[(0.0, 0.0), (1.0, 1.0), (2.0, 2.0), (3.0, 3.0)]);
+        reduce(&mut linear, 0.0);
+        assert_eq!(linear, keys(&[(0.0, 0.0), (3.0, 3.0)]));
+
+        let mut constant = keys(&[(0.0, 1.0), (1.0, 1.05), (2.0, 1.0)]);
+        reduce(&mut constant, 0.1);
+        assert!(constant.is_empty());
+    }
+
+    #[test]
+    fn reduce_keeps_keys_beyond_tolerance() {
+        let peak = keys(&[(0.0, 0.0), (1.0, 0.5), (2.0, 2.0), (3.0, 0.0)]);
+        let mut reduced = peak.clone();
+        reduce(&mut reduced, 0.6);
+        assert_eq!(reduced, keys(&[(0.0, 0.0), (2.0, 2.0), (3.0, 0.0)]));
+        let mut exact = peak.clone();
+        reduce(&mut exact, 0.0);
+        assert_eq!(exact, peak);
+    }
+
+    #[test]
+    fn retarget_onto_bind_pose() {
+        let rest_rotation = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
+        let bind_rotation = Quat::from_rotation_y(0.3);
+        let retarget = Retarget {
+            rest_pos: Vec3::new(0.0, 1.0, 0.0),
+            rest_rotation,
+            rest_scale: Vec3::ONE,
+            bind_pos: Vec3::new(0.0, 90.0, 5.0),
+            bind_rotation,
+            bind_scale: Vec3::splat(0.5),
+        };
+        // the rest pose maps to the bind pose
+        assert!(
+            retarget
+                .position(Vec3::new(0.0, 1.0, 0.0))
+                .abs_diff_eq(Vec3::new(0.0, 90.0, 5.0), 1e-4)
+        );
+        assert!(
+            retarget
+                .rotation(rest_rotation)
+                .abs_diff_eq(bind_rotation, 1e-5)
+        );
+        assert_eq!(retarget.scale(Vec3::ONE), Vec3::splat(0.5));
+
+        // offsets are kept:
+        assert!(
+            retarget
+                .position(Vec3::new(0.1, 1.0, 0.0))
+                .abs_diff_eq(Vec3::new(10.0, 90.0, 5.0), 1e-4)
+        );
+        let turn = Quat::from_rotation_z(0.2);
+        assert!(
+            retarget
+                .rotation(rest_rotation * turn)
+                .abs_diff_eq(bind_rotation * turn, 1e-5)
+        );
+        assert_eq!(retarget.scale(Vec3::splat(2.0)), Vec3::splat(0.25));
+    }
+}
@@ -0,0 +1,288 @@
+use formats::merge::MergeEntity;
+use formats::types::{
+    GenClass,
+    properties::enums::EquipSlot,
+    property_set::{Inventory, InventorySlotIdx, PropertySet},
+};
+use formats::{
+    archive::PakFile,
+    file_formats::{lrent::LrentFile, tple::TpleFile},
+};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::{env, ffi::OsString, path::Path};
+
+fn print_usage() {
+    println!(
+        "Usage: inventory_edit <file.lrent|file.tple> <entity name or guid> [command] [options]"
+    );
+    println!("Commands:");
+    println!("  list                    print stacks and slots (default)");
+    println!("  add <item> [amount]     add items (default 1)");
+    println!("  remove <item> [amount]  remove items (default the whole stack)");
+    println!("  equip <item> <slot>     equip to a gEEquipSlot, e.g. MeleeWeapon, Armor, Helmet");
+    println!("  unequip <item>");
+    println!(
+        "Items are given by template GUID, or by name if the templates are loaded via /tple=."
+    );
+    println!("Modifications are written to <name>_out.<ext>.");
+    println!("Options:");
+    println!(
+        "  /tple=file    template file used to resolve item names, can be given multiple times"
+    );
+}
+
+fn load_archive(path: &Path) -> Option<PakFile> {
+    let in_data = match File::open(path) {
+        Ok(f) => f,
+        Err(e) => {
+            println!("opening {} failed: {e}", path.display());
+            return None;
+        }
+    };
+    let mut in_data = BufReader::new(in_data);
+    match PakFile::load(&mut in_data) {
+        Ok(a) => Some(a),
+        Err(e) => {
+            println!("loading archive {} failed: {e}", path.display());
+            None
+        }
+    }
+}
+
+enum Command {
+    List,
+    Add(String, i32),
+    Remove(String, Option<i32>),
+    Equip(String, EquipSlot),
+    Unequip(String),
+}
+
+impl Command {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let parse_amount = |arg: Option<&String>| {
+            arg.map(|a| a.parse::<i32>().map_err(|_| format!("Invalid amount {a}")))
+                .transpose()
+        };
+        Ok(match args {
+            [] => Command::List,
+            [cmd] if cmd == "list" => Command::List,
+            [cmd, item, rest @ ..] if cmd == "add" && rest.len() <= 1 => {
+                Command::Add(item.clone(), parse_amount(rest.first())?.unwrap_or(1))
+            }
+            [cmd, item, rest @ ..] if cmd == "remove" && rest.len() <= 1 => {
+                Command::Remove(item.clone(), parse_amount(rest.first())?)
+            }
+            [cmd, item, slot] if cmd == "equip" => Command::Equip(item.clone(), parse_slot(slot)?),
+            [cmd, item] if cmd == "unequip" => Command::Unequip(item.clone()),
+            _ => return Err(format!("Invalid command '{}'", args.join(" "))),
+        })
+    }
+
+    fn modifies(&self) -> bool {
+        !matches!(self, Command::List)
+    }
+}
+
+fn parse_slot(name: &str) -> Result<EquipSlot, String> {
+    (0..u8::MAX as u32)
+        .map_while(|v| EquipSlot::try_from(v).ok())
+        .find(|slot| format!("{slot:?}").eq_ignore_ascii_case(name))
+        .ok_or_else(|| format!("Unknown equip slot {name}"))
+}
+
+/// Looks up items by GUID or name in the loaded template files
+struct Items {
+    templates: Vec<TpleFile>,
+}
+
+impl Items {
+    fn resolve(&self, key: &str) -> Result<uuid::Uuid, String> {
+        if let Some(template) = self.templates.iter().find_map(|t| t.find_template(key)) {
+            return Ok(template.id.id);
+        }
+        uuid::Uuid::parse_str(key.trim_matches(['{', '}']))
+            .map_err(|_| format!("Item {key} is neither a GUID nor the name of a loaded template"))
+    }
+
+    fn name(&self, id: Option<uuid::Uuid>) -> String {
+        let Some(id) = id else {
+            return "<none>".to_string();
+        };
+        let key = id.to_string();
+        match self.templates.iter().find_map(|t| t.find_template(&key)) {
+            Some(template) => format!("{} ({id})", template.name),
+            None => id.to_string(),
+        }
+    }
+}
+
+fn list(inventory: &Inventory, items: &Items) {
+    println!("Stacks:");
+    for (idx, stack) in inventory.stacks.iter().enumerate() {
+        println!(
+            "  #{idx} {}x {}, quality {}, {:?}, equipped: {:?}",
+            stack.amount,
+            items.name(stack.template),
+            stack.quality,
+            stack.stack_type,
+            stack.equip_slot
+        );
+    }
+    println!("Slots:");
+    for slot in [
+        InventorySlotIdx::Head,
+        InventorySlotIdx::Body,
+        InventorySlotIdx::Helmet,
+    ] {
+        if inventory.slots.iter().any(|s| s.id == slot) {
+            println!("  {slot:?}: {}", items.name(inventory.slot_template(slot)));
+        }
+    }
+}
+
+fn run(inventory: &mut Inventory, command: &Command, items: &Items) -> Result<(), String> {
+    match command {
+        Command::List => {}
+        Command::Add(item, amount) => {
+            inventory.add_items(items.resolve(item)?, *amount);
+        }
+        Command::Remove(item, amount) => {
+            inventory
+                .remove_items(items.resolve(item)?, *amount)
+                .map_err(|e| e.to_string())?;
+        }
+        Command::Equip(item, slot) => {
+            inventory
+                .equip(items.resolve(item)?, *slot)
+                .map_err(|e| e.to_string())?;
+        }
+        Command::Unequip(item) => {
+            inventory
+                .unequip(items.resolve(item)?)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    list(inventory, items);
+    Ok(())
+}
+
+fn edit<T: MergeEntity>(
+    entities: &mut [T],
+    key: &str,
+    command: &Command,
+    items: &Items,
+) -> Result<(), String> {
+    let id = uuid::Uuid::parse_str(key.trim_matches(['{', '}'])).ok();
+    let Some(entity) = entities
+        .iter_mut()
+        .find(|e| e.name() == key || Some(e.id()) == id)
+    else {
+        return Err(format!("No entity {key}"));
+    };
+    println!("{} ({})", entity.name(), entity.id());
+    let Some(apo) = entity.accessor_mut("gCInventory_PS") else {
+        return Err("Entity has no gCInventory_PS accessor".to_string());
+    };
+    let GenClass::PropertySet(PropertySet::Inventory(inventory)) = &mut apo.object.class else {
+        return Err("gCInventory_PS could not be parsed".to_string());
+    };
+    run(inventory, command, items)
+}
+
+fn main() {
+    println!("Chromosome Toolkit - R1 - Inventory editor");
+    let mut positional = Vec::new();
+    let mut tple_args = Vec::new();
+    for arg in env::args().skip(1) {
+        if let Some(value) = arg.strip_prefix("/tple=") {
+            tple_args.push(value.to_string());
+        } else {
+            positional.push(arg);
+        }
+    }
+    let [file_arg, entity_arg, command_args @ ..] = positional.as_slice() else {
+        print_usage();
+        return;
+    };
+    let command = match Command::parse(command_args) {
+        Ok(c) => c,
+        Err(e) => {
+            println!("{e}");
+            print_usage();
+            return;
+        }
+    };
+
+    let mut items = Items {
+        templates: Vec::new(),
+    };
+    for tple_arg in &tple_args {
+        let tple_os = OsString::from(tple_arg);
+        let Some(arch) = load_archive(Path::new(&tple_os)) else {
+            return;
+        };
+        match TpleFile::load(arch) {
+            Ok(t) => items.templates.push(t),
+            Err(e) => {
+                println!("loading tple {tple_arg} failed: {e}");
+                return;
+            }
+        }
+    }
+
+    let file_os = OsString::from(file_arg);
+    let Some(arch) = load_archive(Path::new(&file_os)) else {
+        return;
+    };
+    // As long as some contents remain unparsed, we need to retain the original string indices:
+    let mut out = PakFile::new();
+    out.strings = arch.strings.clone();
+
+    let result = if file_arg.to_lowercase().ends_with(".lrent") {
+        let mut lrent = match LrentFile::load(arch) {
+            Ok(l) => l,
+            Err(e) => {
+                println!("loading lrent failed: {e}");
+                return;
+            }
+        };
+        let Some(ctx) = lrent.get_context_mut() else {
+            println!("lrent does not contain an eCEntityDynamicContext");
+            return;
+        };
+        edit(&mut ctx.entities, entity_arg, &command, &items)
+            .and_then(|_| lrent.save(&mut out).map_err(|e| e.to_string()))
+    } else {
+        let mut tple = match TpleFile::load(arch) {
+            Ok(t) => t,
+            Err(e) => {
+                println!("loading tple failed: {e}");
+                return;
+            }
+        };
+        edit(&mut tple.entities, entity_arg, &command, &items)
+            .and_then(|_| tple.save(&mut out).map_err(|e| e.to_string()))
+    };
+    if let Err(e) = result {
+        println!("{e}");
+        return;
+    }
+    if !command.modifies() {
+        return;
+    }
+
+    let ext_start = file_arg.rfind('.').unwrap();
+    let out_arg = format!("{}_out{}", &file_arg[..ext_start], &file_arg[ext_start..]);
+    let out_os = OsString::from(&out_arg);
+    let out_file = File::create(Path::new(&out_os)).expect("Unable to open output file");
+    let mut out_file = BufWriter::new(out_file);
+    match out.save(&mut out_file) {
+        Ok(_) => {}
+        Err(e) => {
+            println!("writing archive failed: {e}");
+        }
+    }
+    out_file.flush().unwrap();
+    println!("done");
+}
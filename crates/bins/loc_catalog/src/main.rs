@@ -0,0 +1,288 @@
+mod po;
+
+use formats::catalog::{Catalog, CatalogEntry, Validator};
+use formats::{
+    archive::PakFile,
+    file_formats::{lrent::LrentFile, tple::TpleFile},
+};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::{env, ffi::OsString, path::Path};
+use uuid::Uuid;
+
+fn print_usage() {
+    println!("Usage: loc_catalog extract <catalog.po|catalog.csv> <files or directories...>");
+    println!(
+        "       loc_catalog inject <catalog.po|catalog.csv> <files or directories...> [/force]"
+    );
+    println!("       loc_catalog validate <catalog.po|catalog.csv> <files or directories...>");
+    println!("Collects the localized strings (*LocString properties) of .lrent / .tple files.");
+    println!("inject writes the translations of a catalog back, writing <name>_out.<ext>.");
+    println!(
+        "validate reports untranslated strings and catalog entries that are not used anymore."
+    );
+    println!("Options:");
+    println!("  /force    also write the output if some entries could not be applied");
+}
+
+enum EntityFile {
+    Lrent(LrentFile),
+    Tple(TpleFile),
+}
+
+/// Loads a lrent / tple file, returns the archive to write it to
+fn load(path: &Path) -> Result<(EntityFile, PakFile), String> {
+    let in_data = File::open(path).map_err(|e| e.to_string())?;
+    let mut in_data = BufReader::new(in_data);
+    let arch = PakFile::load(&mut in_data).map_err(|e| format!("loading archive failed: {e}"))?;
+    // As long as some contents remain unparsed, we need to retain the original string indices:
+    let mut out = PakFile::new();
+    out.strings = arch.strings.clone();
+
+    let lower = path.to_string_lossy().to_lowercase();
+    let file = if lower.ends_with(".lrent") {
+        let lrent = LrentFile::load(arch).map_err(|e| format!("loading lrent failed: {e}"))?;
+        if lrent.get_context().is_none() {
+            return Err("lrent does not contain an eCEntityDynamicContext".to_string());
+        }
+        EntityFile::Lrent(lrent)
+    } else {
+        EntityFile::Tple(TpleFile::load(arch).map_err(|e| format!("loading tple failed: {e}"))?)
+    };
+    Ok((file, out))
+}
+
+/// Expands directories to the contained .lrent / .tple files
+fn collect_files(args: &[String]) -> Vec<String> {
+    let mut queue = args.iter().cloned().collect::<VecDeque<_>>();
+    let mut files = Vec::new();
+    while let Some(arg) = queue.pop_front() {
+        let os_arg = OsString::from(&arg);
+        let path = Path::new(&os_arg);
+        if !path.exists() {
+            println!("{arg} not found");
+            continue;
+        }
+        if !path.is_dir() {
+            files.push(arg);
+            continue;
+        }
+        if let Ok(dir) = path.read_dir() {
+            for file in dir.flatten() {
+                let meta = file.metadata().unwrap();
+                let path = file.path().to_string_lossy().to_string();
+                let lower = path.to_lowercase();
+                if meta.is_dir() || lower.ends_with(".lrent") || lower.ends_with(".tple") {
+                    queue.push_back(path);
+                }
+            }
+        } else {
+            println!("Reading dir {arg} failed");
+        }
+    }
+    files
+}
+
+fn is_csv(path: &str) -> bool {
+    path.to_lowercase().ends_with(".csv")
+}
+
+const CSV_HEADER: [&str; 5] = ["key", "owner", "guid", "property", "translation"];
+const NO_CONTEXT: &str = "lrent does not contain an eCEntityDynamicContext";
+
+fn read_catalog(path: &str) -> Result<Catalog, String> {
+    if !is_csv(path) {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        return po::read(&text);
+    }
+    let mut reader = csv::Reader::from_path(path).map_err(|e| e.to_string())?;
+    let mut catalog = Catalog::default();
+    for (idx, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| e.to_string())?;
+        let field = |i: usize| record.get(i).unwrap_or_default().to_string();
+        let owner_id = Uuid::parse_str(field(2).trim_matches(['{', '}']))
+            .map_err(|_| format!("row {}: '{}' is not a valid GUID", idx + 1, field(2)))?;
+        catalog.entries.push(CatalogEntry {
+            key: field(0),
+            owner: field(1),
+            owner_id,
+            path: field(3),
+            translation: field(4),
+        });
+    }
+    Ok(catalog)
+}
+
+fn write_catalog(catalog: &Catalog, path: &str) -> Result<(), String> {
+    if !is_csv(path) {
+        return std::fs::write(path, po::write(catalog)).map_err(|e| e.to_string());
+    }
+    let mut writer = csv::Writer::from_path(path).map_err(|e| e.to_string())?;
+    writer.write_record(CSV_HEADER).map_err(|e| e.to_string())?;
+    for entry in &catalog.entries {
+        writer
+            .write_record([
+                entry.key.as_str(),
+                &entry.owner,
+                &format!("{{{}}}", entry.owner_id),
+                &entry.path,
+                &entry.translation,
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())
+}
+
+fn extract(catalog_arg: &str, files: &[String]) {
+    let mut catalog = Catalog::default();
+    for file in files {
+        match load(Path::new(&OsString::from(file))) {
+            Ok((EntityFile::Lrent(lrent), _)) => match lrent.get_context() {
+                Some(ctx) => catalog.extract(&ctx.entities),
+                None => {
+                    println!("{file}: {NO_CONTEXT}, skipping");
+                    continue;
+                }
+            },
+            Ok((EntityFile::Tple(tple), _)) => catalog.extract(&tple.entities),
+            Err(e) => {
+                println!("{file}: {e}, skipping");
+                continue;
+            }
+        }
+        println!("{file}: {} strings total", catalog.entries.len());
+    }
+    match write_catalog(&catalog, catalog_arg) {
+        Ok(()) => println!("done"),
+        Err(e) => println!("writing {catalog_arg} failed: {e}"),
+    }
+}
+
+fn inject(catalog: &Catalog, files: &[String], force: bool) {
+    for file in files {
+        println!("{file}");
+        let (entity_file, mut out) = match load(Path::new(&OsString::from(file))) {
+            Ok(f) => f,
+            Err(e) => {
+                println!("{e}, skipping");
+                continue;
+            }
+        };
+        let (report, saved) = match entity_file {
+            EntityFile::Lrent(mut lrent) => {
+                let Some(ctx) = lrent.get_context_mut() else {
+                    println!("{NO_CONTEXT}, skipping");
+                    continue;
+                };
+                let report = catalog.inject(&mut ctx.entities);
+                (report, lrent.save(&mut out))
+            }
+            EntityFile::Tple(mut tple) => {
+                let report = catalog.inject(&mut tple.entities);
+                (report, tple.save(&mut out))
+            }
+        };
+        for conflict in &report.conflicts {
+            println!("  CONFLICT {conflict}");
+        }
+        println!(
+            "  {} strings translated, {} already translated",
+            report.changed.len(),
+            report.unchanged
+        );
+        if let Err(e) = saved {
+            println!("writing file failed: {e}");
+            continue;
+        }
+        if report.changed.is_empty() {
+            continue;
+        }
+        if !report.conflicts.is_empty() && !force {
+            println!(
+                "{} entries could not be applied, not writing output (use /force to write anyway)",
+                report.conflicts.len()
+            );
+            continue;
+        }
+
+        let ext_start = file.rfind('.').unwrap();
+        let out_arg = format!("{}_out{}", &file[..ext_start], &file[ext_start..]);
+        let out_os = OsString::from(&out_arg);
+        let out_file = File::create(Path::new(&out_os)).expect("Unable to open output file");
+        let mut out_file = BufWriter::new(out_file);
+        match out.save(&mut out_file) {
+            Ok(_) => {}
+            Err(e) => {
+                println!("writing archive failed: {e}");
+            }
+        }
+        out_file.flush().unwrap();
+    }
+    println!("done");
+}
+
+fn validate(catalog: &Catalog, files: &[String]) {
+    let mut validator = Validator::new(catalog);
+    for file in files {
+        match load(Path::new(&OsString::from(file))) {
+            Ok((EntityFile::Lrent(lrent), _)) => match lrent.get_context() {
+                Some(ctx) => validator.check(&ctx.entities),
+                None => println!("{file}: {NO_CONTEXT}, skipping"),
+            },
+            Ok((EntityFile::Tple(tple), _)) => validator.check(&tple.entities),
+            Err(e) => println!("{file}: {e}, skipping"),
+        }
+    }
+    let validation = validator.finish();
+    for entry in &validation.missing {
+        println!("MISSING {entry}");
+    }
+    for entry in &validation.unused {
+        println!("UNUSED {entry}");
+    }
+    println!(
+        "{} missing, {} unused",
+        validation.missing.len(),
+        validation.unused.len()
+    );
+}
+
+fn main() {
+    println!("Chromosome Toolkit - R1 - Localization catalog");
+    let mut positional = Vec::new();
+    let mut force = false;
+    for arg in env::args().skip(1) {
+        if arg == "/force" {
+            force = true;
+        } else {
+            positional.push(arg);
+        }
+    }
+    let [command, catalog_arg, file_args @ ..] = positional.as_slice() else {
+        print_usage();
+        return;
+    };
+    if file_args.is_empty() {
+        print_usage();
+        return;
+    }
+    let files = collect_files(file_args);
+    if command == "extract" {
+        extract(catalog_arg, &files);
+        return;
+    }
+
+    let catalog = match read_catalog(catalog_arg) {
+        Ok(c) => c,
+        Err(e) => {
+            println!("Loading catalog failed: {e}");
+            return;
+        }
+    };
+    match command.as_str() {
+        "inject" => inject(&catalog, &files, force),
+        "validate" => validate(&catalog, &files),
+        _ => print_usage(),
+    }
+}
@@ -0,0 +1,194 @@
+//! Minimal gettext PO reader / writer.
+//! The owner GUID and property path are stored in `msgctxt` as `{guid}/<class>.<property>`,
+//! the owner name as extracted comment.
+use std::fmt::Write;
+
+use formats::catalog::{Catalog, CatalogEntry};
+use uuid::Uuid;
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some(c) => result.push(c),
+            None => {}
+        }
+    }
+    result
+}
+
+pub fn write(catalog: &Catalog) -> String {
+    let mut out = String::new();
+    out.push_str("msgid \"\"\nmsgstr \"\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n");
+    for entry in &catalog.entries {
+        let _ = write!(
+            out,
+            "\n#. {}\nmsgctxt \"{{{}}}/{}\"\nmsgid \"{}\"\nmsgstr \"{}\"\n",
+            entry.owner.replace('\n', " "),
+            entry.owner_id,
+            escape(&entry.path),
+            escape(&entry.key),
+            escape(&entry.translation)
+        );
+    }
+    out
+}
+
+#[derive(Default)]
+struct PoEntry {
+    comment: String,
+    context: Option<String>,
+    id: Option<String>,
+    text: Option<String>,
+}
+
+enum Field {
+    None,
+    Context,
+    Id,
+    Text,
+}
+
+impl PoEntry {
+    fn into_entry(self, line: usize) -> Result<Option<CatalogEntry>, String> {
+        let (Some(context), Some(key)) = (self.context, self.id) else {
+            // the header and obsolete entries have no context
+            return Ok(None);
+        };
+        let Some((guid, path)) = context.split_once('/') else {
+            return Err(format!(
+                "line {line}: msgctxt is not {{guid}}/<class>.<property>"
+            ));
+        };
+        let owner_id = Uuid::parse_str(guid.trim_matches(['{', '}']))
+            .map_err(|_| format!("line {line}: '{guid}' is not a valid GUID"))?;
+        Ok(Some(CatalogEntry {
+            key,
+            owner: self.comment,
+            owner_id,
+            path: path.to_string(),
+            translation: self.text.unwrap_or_default(),
+        }))
+    }
+}
+
+pub fn read(text: &str) -> Result<Catalog, String> {
+    let mut catalog = Catalog::default();
+    let mut current = PoEntry::default();
+    let mut field = Field::None;
+    let mut entry_line = 1;
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let quoted = |rest: &str| {
+            let rest = rest.trim();
+            match rest.strip_prefix('"').and_then(|r| r.strip_suffix('"')) {
+                Some(inner) => Ok(unescape(inner)),
+                None => Err(format!("line {}: expected a quoted string", idx + 1)),
+            }
+        };
+        if line.is_empty() {
+            let entry = std::mem::take(&mut current).into_entry(entry_line)?;
+            catalog.entries.extend(entry);
+            field = Field::None;
+            entry_line = idx + 2;
+        } else if let Some(comment) = line.strip_prefix("#.") {
+            current.comment = comment.trim().to_string();
+        } else if line.starts_with('#') {
+            continue;
+        } else if let Some(rest) = line.strip_prefix("msgctxt") {
+            current.context = Some(quoted(rest)?);
+            field = Field::Context;
+        } else if let Some(rest) = line.strip_prefix("msgid") {
+            current.id = Some(quoted(rest)?);
+            field = Field::Id;
+        } else if let Some(rest) = line.strip_prefix("msgstr") {
+            current.text = Some(quoted(rest)?);
+            field = Field::Text;
+        } else {
+            // continuation of a multi-line string
+            let continued = quoted(line)?;
+            let target = match field {
+                Field::Context => &mut current.context,
+                Field::Id => &mut current.id,
+                Field::Text => &mut current.text,
+                Field::None => return Err(format!("line {}: unexpected string", idx + 1)),
+            };
+            target.get_or_insert_default().push_str(&continued);
+        }
+    }
+    catalog.entries.extend(current.into_entry(entry_line)?);
+    Ok(catalog)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(key: &str, translation: &str) -> CatalogEntry {
+        CatalogEntry {
+            key: key.to_string(),
+            owner: "It_Letter".to_string(),
+            owner_id: Uuid::from_u128(1),
+            path: "gCItem_PS.Text".to_string(),
+            translation: translation.to_string(),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let catalog = Catalog {
+            entries: vec![
+                entry(
+                    "Dear \"Sir\",\n\tC:\\letter",
+                    "Sehr geehrter \"Herr\",\n\tC:\\brief",
+                ),
+                entry("untranslated", ""),
+            ],
+        };
+        let text = write(&catalog);
+        assert!(text.contains("msgctxt \"{00000000-0000-0000-0000-000000000001}/gCItem_PS.Text\""));
+        assert_eq!(read(&text), Ok(catalog));
+    }
+
+    #[test]
+    fn read_multi_line_strings() {
+        let text = "# translator comment\n\
+                    #. It_Book\n\
+                    msgctxt \"{00000000-0000-0000-0000-000000000002}/\"\n\
+                    \"gCBook_PS.Text\"\n\
+                    msgid \"\"\n\
+                    \"Chapter \"\n\
+                    \"1\"\n\
+                    msgstr \"Kapitel 1\"\n";
+        let catalog = read(text).unwrap();
+        assert_eq!(catalog.entries.len(), 1);
+        let entry = &catalog.entries[0];
+        assert_eq!(entry.owner, "It_Book");
+        assert_eq!(entry.owner_id, Uuid::from_u128(2));
+        assert_eq!(entry.path, "gCBook_PS.Text");
+        assert_eq!(entry.key, "Chapter 1");
+        assert_eq!(entry.translation, "Kapitel 1");
+    }
+
+    #[test]
+    fn read_errors() {
+        assert!(read("msgctxt \"no-guid\"\nmsgid \"a\"\n").is_err());
+        assert!(read("msgctxt \"{nope}/gCItem_PS.Text\"\nmsgid \"a\"\n").is_err());
+        assert!(read("msgid unquoted\n").is_err());
+        assert!(read("\"stray string\"\n").is_err());
+    }
+}
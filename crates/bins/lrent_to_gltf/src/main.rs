@@ -0,0 +1,137 @@
+use std::{
+    collections::VecDeque,
+    env,
+    ffi::OsString,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use formats::{archive::PakFile, file_formats::lrent::LrentFile};
+mod translation;
+
+fn main() {
+    println!("Chromosome Toolkit - R1 - LRENT to GLTF");
+    let mut queue = VecDeque::new();
+    let mut mesh_dir = None;
+    let mut include_textures = false;
+    let mut navigation = false;
+    let mut collision = false;
+    let mut lights = false;
+    for arg in env::args().skip(1) {
+        if let Some(value) = arg.strip_prefix("/meshdir=") {
+            mesh_dir = Some(PathBuf::from(value));
+        } else if &arg == "/includetextures" {
+            include_textures = true;
+        } else if &arg == "/navigation" {
+            navigation = true;
+        } else if &arg == "/collision" {
+            collision = true;
+        } else if &arg == "/lights" {
+            lights = true;
+        } else {
+            queue.push_back(arg);
+        }
+    }
+    if queue.is_empty() {
+        println!("Usage: lrent_to_gltf <files or dirs> [options]");
+        println!("Options:");
+        println!("  /meshdir=dir        directory searched (recursively) for ._xmac meshes,");
+        println!("                      entities without a mesh are exported as boxes");
+        println!("  /includetextures    convert the textures of found meshes");
+        println!(
+            "  /navigation         export navigation zones, paths and offsets as lines / points"
+        );
+        println!(
+            "  /collision          export collision shapes as wireframes, gltf_to_lrent reads them back"
+        );
+        println!(
+            "  /lights             export lights (KHR_lights_punctual), gltf_to_lrent reads them back"
+        );
+        return;
+    }
+    if mesh_dir.is_none() {
+        println!("No /meshdir given, exporting placeholder boxes only");
+    }
+
+    let mut meshes = translation::MeshLibrary::new(mesh_dir.as_deref(), include_textures);
+    while let Some(arg) = queue.pop_front() {
+        println!("{}", arg);
+        let os_arg = OsString::from(&arg);
+        let path = Path::new(&os_arg);
+        if !path.exists() {
+            println!("not found");
+            continue;
+        }
+
+        if path.is_dir() {
+            if let Ok(dir) = path.read_dir() {
+                for file in dir.flatten() {
+                    let meta = file.metadata().unwrap();
+                    let path = file.path().to_string_lossy().to_string();
+                    if meta.is_dir() || path.ends_with(".lrent") || path.ends_with(".LRENT") {
+                        queue.push_back(path);
+                    }
+                }
+            } else {
+                println!("Reading dir failed");
+            }
+            continue;
+        }
+
+        let in_data = std::fs::File::open(path).unwrap();
+        let mut in_data = std::io::BufReader::new(in_data);
+        let arch = match PakFile::load(&mut in_data) {
+            Ok(a) => a,
+            Err(e) => {
+                println!("loading archive failed: {e}, skipping");
+                continue;
+            }
+        };
+        drop(in_data);
+
+        let lrent = match LrentFile::load(arch) {
+            Ok(a) => a,
+            Err(e) => {
+                println!("loading lrent failed: {e}, skipping");
+                continue;
+            }
+        };
+        let Some(context) = lrent.get_context() else {
+            println!("lrent does not contain an eCEntityDynamicContext, skipping");
+            continue;
+        };
+        println!("Parsing finished, {} entities", context.entities.len());
+
+        let out_arg = arg.replace(".lrent", ".gltf").replace(".LRENT", ".gltf");
+        if out_arg == arg {
+            panic!("In == out path");
+        }
+        let out_os = OsString::from(&out_arg);
+        let out_path = Path::new(&out_os);
+        let out_bin = out_path.with_extension("bin");
+
+        let gltf = match translation::lrent_to_gltf(
+            context,
+            &out_bin,
+            &mut meshes,
+            navigation,
+            collision,
+            lights,
+        ) {
+            Ok(gltf) => gltf,
+            Err(e) => {
+                println!("translation failed: {e}, skipping");
+                continue;
+            }
+        };
+        println!("Translation done");
+
+        let out_file = File::create(out_path).expect("Unable to open output file");
+        let mut out_file = BufWriter::new(out_file);
+        gltf.to_writer_pretty(&mut out_file).unwrap();
+        out_file.flush().unwrap();
+
+        println!("done");
+    }
+}
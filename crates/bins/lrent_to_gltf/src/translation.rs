@@ -0,0 +1,942 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::File,
+    io::{BufReader, BufWriter, Seek, Write},
+    path::{Path, PathBuf},
+};
+
+use formats::{
+    file_formats::xmac::XmacFile,
+    helpers::{write_f32, write_u16},
+    light::{Light, LightKind},
+    types::{
+        EntityDynamicContext, GenClass, Mat4, Quat, Vec3,
+        collision::{CollisionShape, ShapeGeometry},
+        entity::DynamicEntity,
+        properties::PropData,
+        property_set::PropertySet,
+    },
+};
+use gltf::json::{
+    Accessor as GltfAccessor, Buffer as GltfBuffer, Index as GltfIndex, Material as GltfMaterial,
+    Mesh as GltfMesh, Node as GltfNode, Root as GltfRoot, Scene as GltfScene, Skin as GltfSkin,
+    material::{PbrBaseColorFactor, PbrMetallicRoughness},
+    mesh::Primitive as GltfPrimitive,
+    texture::Info as GltfTexInfo,
+    validation::{Checked::Valid as GltfValid, USize64},
+};
+use xmac_to_gltf::{ConvError, Result, translation::xmac_to_gltf};
+
+/// Finds and converts the xmac files referenced by `eCMesh_PS`.
+/// Every mesh is only converted once per output directory, its buffer is written next to the scene.
+pub struct MeshLibrary {
+    /// lowercase file name without extension -> path
+    files: HashMap<String, PathBuf>,
+    include_textures: bool,
+    converted: HashMap<(PathBuf, String), Option<GltfRoot>>,
+}
+
+impl MeshLibrary {
+    pub fn new(mesh_dir: Option<&Path>, include_textures: bool) -> Self {
+        let mut files = HashMap::new();
+        let mut queue = mesh_dir
+            .map(Path::to_path_buf)
+            .into_iter()
+            .collect::<VecDeque<_>>();
+        while let Some(dir) = queue.pop_front() {
+            let Ok(entries) = dir.read_dir() else {
+                println!("Reading dir {} failed", dir.display());
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    queue.push_back(path);
+                } else if let Some(name) = path.file_name().and_then(|n| n.to_str())
+                    && name.to_lowercase().ends_with("._xmac")
+                {
+                    files.insert(mesh_key(name), path);
+                }
+            }
+        }
+        Self {
+            files,
+            include_textures,
+            converted: HashMap::new(),
+        }
+    }
+
+    /// Converted glTF of the mesh resource `name`, `None` if it can't be found or converted
+    fn get(&mut self, name: &str, out_dir: &Path) -> Option<&GltfRoot> {
+        let key = (out_dir.to_path_buf(), mesh_key(name));
+        if !self.converted.contains_key(&key) {
+            let converted = match self.files.get(&key.1) {
+                Some(path) => match self.convert(path, &out_dir.join(format!("{}.bin", key.1))) {
+                    Ok(gltf) => Some(gltf),
+                    Err(e) => {
+                        println!("Converting mesh {} failed: {e}", path.display());
+                        None
+                    }
+                },
+                None => {
+                    println!("Mesh {name} not found, using placeholder");
+                    None
+                }
+            };
+            self.converted.insert(key.clone(), converted);
+        }
+        self.converted[&key].as_ref()
+    }
+
+    fn convert(&self, path: &Path, buffer_path: &Path) -> Result<GltfRoot> {
+        let mut in_data = BufReader::new(File::open(path)?);
+        let xmac = XmacFile::load(&mut in_data)
+            .map_err(|e| ConvError::InvalidData(format!("Loading xmac failed: {e:?}")))?;
+        xmac_to_gltf(&xmac, buffer_path, self.include_textures)
+    }
+}
+
+/// Resource names may or may not contain a path and extension, e.g. `Obj_Barrel_01._xmac`.
+/// Only the extension is cut, names can contain dots themselves.
+fn mesh_key(name: &str) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+    stem.to_lowercase()
+}
+
+/// The mesh resource referenced by the `eCMesh_PS` accessor of an entity
+pub fn mesh_resource(entity: &DynamicEntity) -> Option<&str> {
+    entity
+        .geo_entity
+        .entity
+        .accessors
+        .iter()
+        .map(|acc| &acc.object.object)
+        .filter(|obj| obj.get_class_name() == "eCMesh_PS")
+        .flat_map(|obj| obj.props.iter())
+        .find_map(|prop| match &*prop.data {
+            PropData::MeshResourceString(name) if !name.is_empty() => Some(name.as_str()),
+            _ => None,
+        })
+}
+
+/// Resources of a converted mesh that have already been copied into the scene
+struct ImportedMesh {
+    mesh_offset: usize,
+    accessor_offset: usize,
+}
+
+struct Outputs<'a> {
+    gltf: &'a mut GltfRoot,
+    buffer: GltfIndex<GltfBuffer>,
+    buffer_file: BufWriter<File>,
+    imported: HashMap<String, ImportedMesh>,
+    placeholder: Option<GltfIndex<GltfMesh>>,
+    line_materials: HashMap<&'static str, GltfIndex<GltfMaterial>>,
+}
+
+/// If `navigation` is set, the geometry of navigation zones, paths and offsets is exported as lines / points.
+/// If `collision` is set, collision shapes are exported as wireframes.
+/// If `lights` is set, lights are exported using KHR_lights_punctual.
+pub fn lrent_to_gltf(
+    input: &EntityDynamicContext,
+    buffer_path: &Path,
+    meshes: &mut MeshLibrary,
+    navigation: bool,
+    collision: bool,
+    lights: bool,
+) -> Result<GltfRoot> {
+    let mut gltf = GltfRoot::default();
+    gltf.asset.generator = Some(format!(
+        "ChromosomeToolkit {}, v{}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    ));
+
+    // Buffer for placeholder geometry:
+    let buffer = gltf.push(GltfBuffer {
+        byte_length: USize64(0),
+        extensions: Default::default(),
+        extras: Default::default(),
+        name: None,
+        uri: Some(
+            buffer_path
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned(),
+        ),
+    });
+    let buffer_file = BufWriter::new(File::create(buffer_path)?);
+    let out_dir = buffer_path.parent().unwrap_or(Path::new("."));
+
+    let mut outputs = Outputs {
+        gltf: &mut gltf,
+        buffer,
+        buffer_file,
+        imported: HashMap::new(),
+        placeholder: None,
+        line_materials: HashMap::new(),
+    };
+
+    translate_entities(input, &mut outputs)?;
+    translate_hierarchy(input, &mut outputs)?;
+    translate_meshes(input, meshes, out_dir, &mut outputs)?;
+    if navigation {
+        translate_navigation(input, &mut outputs)?;
+    }
+    if collision {
+        translate_collision(input, &mut outputs)?;
+    }
+    if lights {
+        translate_lights(input, &mut outputs);
+    }
+
+    let length = outputs.buffer_file.stream_position()?;
+    outputs.buffer_file.flush()?;
+    let buffer = outputs.buffer.value();
+    drop(outputs);
+    if length > 0 {
+        gltf.buffers[buffer].byte_length = USize64(length);
+    } else {
+        // Buffers must not be empty, drop it if no placeholder geometry was written
+        gltf.buffers.remove(buffer);
+        for view in &mut gltf.buffer_views {
+            if view.buffer.value() > buffer {
+                view.buffer = GltfIndex::new(view.buffer.value() as u32 - 1);
+            }
+        }
+        std::fs::remove_file(buffer_path)?;
+    }
+
+    Ok(gltf)
+}
+
+/// Transform in glTF units, game is in cm, GLTF usually in m
+fn to_gltf_matrix(matrix: Mat4) -> Option<[f32; 16]> {
+    let mut matrix = matrix;
+    matrix.w_axis = (matrix.w_axis.truncate() * 0.01).extend(matrix.w_axis.w);
+    if matrix != Mat4::IDENTITY {
+        Some(matrix.to_cols_array())
+    } else {
+        None
+    }
+}
+
+/// Creates one node per entity, node indices match the entity indices
+fn translate_entities(input: &EntityDynamicContext, outputs: &mut Outputs) -> Result<()> {
+    for entity in &input.entities {
+        let classes = entity
+            .geo_entity
+            .entity
+            .accessors
+            .iter()
+            .map(|acc| acc.object.object.get_class_name())
+            .collect::<Vec<_>>();
+        let mut extras = serde_json::Map::new();
+        extras.insert("guid".to_string(), entity.id.to_string().into());
+        extras.insert("name".to_string(), entity.name.clone().into());
+        extras.insert("class".to_string(), classes.join(", ").into());
+        if let Some(template) = &entity.creator.id {
+            extras.insert("template".to_string(), template.id.to_string().into());
+        }
+        if let Some(mesh) = mesh_resource(entity) {
+            extras.insert("mesh".to_string(), mesh.into());
+        }
+        let raw_extras = serde_json::to_string(&serde_json::Value::Object(extras)).unwrap();
+
+        outputs.gltf.push(GltfNode {
+            name: Some(entity.name.clone()),
+            matrix: to_gltf_matrix(entity.local_matrix),
+            extras: Some(gltf::json::extras::RawValue::from_string(raw_extras).unwrap()),
+            ..GltfNode::default()
+        });
+    }
+    Ok(())
+}
+
+fn translate_hierarchy(input: &EntityDynamicContext, outputs: &mut Outputs) -> Result<()> {
+    let mut roots = Vec::new();
+    for entity_idx in 0..input.entities.len() {
+        let node = GltfIndex::new(entity_idx as u32);
+        match input.get_parent(entity_idx) {
+            Some(parent) if parent < input.entities.len() && parent != entity_idx => {
+                outputs.gltf.nodes[parent]
+                    .children
+                    .get_or_insert_default()
+                    .push(node);
+            }
+            Some(parent) => {
+                return Err(ConvError::InvalidData(format!(
+                    "Entity {entity_idx} has invalid parent {parent}"
+                )));
+            }
+            None => roots.push(node),
+        }
+    }
+    outputs.gltf.push(GltfScene {
+        extensions: None,
+        extras: None,
+        name: None,
+        nodes: roots,
+    });
+    Ok(())
+}
+
+fn translate_meshes(
+    input: &EntityDynamicContext,
+    meshes: &mut MeshLibrary,
+    out_dir: &Path,
+    outputs: &mut Outputs,
+) -> Result<()> {
+    for (entity_idx, entity) in input.entities.iter().enumerate() {
+        let mesh_name = mesh_resource(entity);
+        let mesh =
+            mesh_name.and_then(|name| meshes.get(name, out_dir).map(|gltf| (mesh_key(name), gltf)));
+        let children = match mesh {
+            Some((key, part)) => instantiate_mesh(&key, part, outputs),
+            None => match placeholder_node(entity, outputs)? {
+                Some(node) => vec![node],
+                None => continue,
+            },
+        };
+        outputs.gltf.nodes[entity_idx]
+            .children
+            .get_or_insert_default()
+            .extend(children);
+    }
+    Ok(())
+}
+
+fn shift<T>(idx: GltfIndex<T>, offset: usize) -> GltfIndex<T> {
+    GltfIndex::new((idx.value() + offset) as u32)
+}
+
+fn shift_info(info: &mut Option<GltfTexInfo>, offset: usize) {
+    if let Some(info) = info {
+        info.index = shift(info.index, offset);
+    }
+}
+
+/// Copies buffers, materials and meshes of a converted xmac into the scene
+fn import_mesh(part: &GltfRoot, outputs: &mut Outputs) -> ImportedMesh {
+    let gltf = &mut *outputs.gltf;
+    for ext in &part.extensions_used {
+        if !gltf.extensions_used.contains(ext) {
+            gltf.extensions_used.push(ext.clone());
+        }
+    }
+
+    let buffer_offset = gltf.buffers.len();
+    gltf.buffers.extend(part.buffers.iter().cloned());
+
+    let view_offset = gltf.buffer_views.len();
+    for view in &part.buffer_views {
+        let mut view = view.clone();
+        view.buffer = shift(view.buffer, buffer_offset);
+        gltf.buffer_views.push(view);
+    }
+
+    let accessor_offset = gltf.accessors.len();
+    for accessor in &part.accessors {
+        let mut accessor = accessor.clone();
+        accessor.buffer_view = accessor.buffer_view.map(|v| shift(v, view_offset));
+        if let Some(sparse) = &mut accessor.sparse {
+            sparse.indices.buffer_view = shift(sparse.indices.buffer_view, view_offset);
+            sparse.values.buffer_view = shift(sparse.values.buffer_view, view_offset);
+        }
+        gltf.accessors.push(accessor);
+    }
+
+    let sampler_offset = gltf.samplers.len();
+    gltf.samplers.extend(part.samplers.iter().cloned());
+
+    let image_offset = gltf.images.len();
+    for image in &part.images {
+        let mut image = image.clone();
+        image.buffer_view = image.buffer_view.map(|v| shift(v, view_offset));
+        gltf.images.push(image);
+    }
+
+    let texture_offset = gltf.textures.len();
+    for texture in &part.textures {
+        let mut texture = texture.clone();
+        texture.sampler = texture.sampler.map(|s| shift(s, sampler_offset));
+        texture.source = shift(texture.source, image_offset);
+        gltf.textures.push(texture);
+    }
+
+    let material_offset = gltf.materials.len();
+    for material in &part.materials {
+        let mut material = material.clone();
+        let pbr = &mut material.pbr_metallic_roughness;
+        shift_info(&mut pbr.base_color_texture, texture_offset);
+        shift_info(&mut pbr.metallic_roughness_texture, texture_offset);
+        shift_info(&mut material.emissive_texture, texture_offset);
+        if let Some(normal) = &mut material.normal_texture {
+            normal.index = shift(normal.index, texture_offset);
+        }
+        if let Some(occlusion) = &mut material.occlusion_texture {
+            occlusion.index = shift(occlusion.index, texture_offset);
+        }
+        if let Some(specular) = material
+            .extensions
+            .as_mut()
+            .and_then(|e| e.specular.as_mut())
+        {
+            shift_info(&mut specular.specular_texture, texture_offset);
+            shift_info(&mut specular.specular_color_texture, texture_offset);
+        }
+        gltf.materials.push(material);
+    }
+
+    let mesh_offset = gltf.meshes.len();
+    for mesh in &part.meshes {
+        let mut mesh = mesh.clone();
+        for primitive in &mut mesh.primitives {
+            for accessor in primitive.attributes.values_mut() {
+                *accessor = shift(*accessor, accessor_offset);
+            }
+            primitive.indices = primitive.indices.map(|i| shift(i, accessor_offset));
+            primitive.material = primitive.material.map(|m| shift(m, material_offset));
+            for target in primitive.targets.iter_mut().flatten() {
+                target.positions = target.positions.map(|a| shift(a, accessor_offset));
+                target.normals = target.normals.map(|a| shift(a, accessor_offset));
+                target.tangents = target.tangents.map(|a| shift(a, accessor_offset));
+            }
+        }
+        gltf.meshes.push(mesh);
+    }
+
+    ImportedMesh {
+        mesh_offset,
+        accessor_offset,
+    }
+}
+
+/// Copies the node tree (and skins) of a converted xmac into the scene.
+/// Nodes can't be shared between parents, so every instance gets its own copy,
+/// while meshes and buffers are only imported once.
+/// Returns the root nodes of the copy.
+fn instantiate_mesh(key: &str, part: &GltfRoot, outputs: &mut Outputs) -> Vec<GltfIndex<GltfNode>> {
+    if !outputs.imported.contains_key(key) {
+        let imported = import_mesh(part, outputs);
+        outputs.imported.insert(key.to_string(), imported);
+    }
+    let imported = &outputs.imported[key];
+    let gltf = &mut *outputs.gltf;
+
+    let node_offset = gltf.nodes.len();
+    let skin_offset = gltf.skins.len();
+    for skin in &part.skins {
+        gltf.skins.push(GltfSkin {
+            inverse_bind_matrices: skin
+                .inverse_bind_matrices
+                .map(|a| shift(a, imported.accessor_offset)),
+            joints: skin.joints.iter().map(|j| shift(*j, node_offset)).collect(),
+            skeleton: skin.skeleton.map(|s| shift(s, node_offset)),
+            ..skin.clone()
+        });
+    }
+    for node in &part.nodes {
+        let mut node = node.clone();
+        node.children = node
+            .children
+            .map(|children| children.iter().map(|c| shift(*c, node_offset)).collect());
+        node.mesh = node.mesh.map(|m| shift(m, imported.mesh_offset));
+        node.skin = node.skin.map(|s| shift(s, skin_offset));
+        gltf.nodes.push(node);
+    }
+
+    part.scenes
+        .iter()
+        .flat_map(|scene| scene.nodes.iter())
+        .map(|node| shift(*node, node_offset))
+        .collect()
+}
+
+/// Box node sized to the local bounds of the entity, `None` if the bounds are empty
+fn placeholder_node(
+    entity: &DynamicEntity,
+    outputs: &mut Outputs,
+) -> Result<Option<GltfIndex<GltfNode>>> {
+    let size = entity.local_bound.max - entity.local_bound.min;
+    if size.cmple(Vec3::ZERO).any() {
+        return Ok(None);
+    }
+    let mesh = match outputs.placeholder {
+        Some(mesh) => mesh,
+        None => {
+            let mesh = write_placeholder_mesh(outputs)?;
+            outputs.placeholder = Some(mesh);
+            mesh
+        }
+    };
+    let node = outputs.gltf.push(GltfNode {
+        name: Some(format!("{}_bounds", entity.name)),
+        mesh: Some(mesh),
+        translation: Some((entity.local_bound.center() * 0.01).to_array()),
+        scale: Some((size * 0.01).to_array()),
+        ..GltfNode::default()
+    });
+    Ok(Some(node))
+}
+
+/// Unit cube centered at the origin
+fn write_placeholder_mesh(outputs: &mut Outputs) -> Result<GltfIndex<GltfMesh>> {
+    use gltf::json::accessor::{ComponentType, GenericComponentType, Type};
+
+    const CORNERS: [[f32; 3]; 8] = [
+        [-0.5, -0.5, -0.5],
+        [0.5, -0.5, -0.5],
+        [0.5, 0.5, -0.5],
+        [-0.5, 0.5, -0.5],
+        [-0.5, -0.5, 0.5],
+        [0.5, -0.5, 0.5],
+        [0.5, 0.5, 0.5],
+        [-0.5, 0.5, 0.5],
+    ];
+    const INDICES: [u16; 36] = [
+        0, 2, 1, 0, 3, 2, // -z
+        4, 5, 6, 4, 6, 7, // +z
+        0, 1, 5, 0, 5, 4, // -y
+        3, 7, 6, 3, 6, 2, // +y
+        0, 4, 7, 0, 7, 3, // -x
+        1, 2, 6, 1, 6, 5, // +x
+    ];
+    const POSITIONS_SIZE: usize = CORNERS.len() * std::mem::size_of::<[f32; 3]>();
+    const INDICES_SIZE: usize = INDICES.len() * std::mem::size_of::<u16>();
+
+    let start_offset = outputs.buffer_file.stream_position()?;
+    for corner in CORNERS.iter().flatten() {
+        write_f32(&mut outputs.buffer_file, *corner)?;
+    }
+    for index in INDICES {
+        write_u16(&mut outputs.buffer_file, index)?;
+    }
+
+    let position_view = outputs.gltf.push(gltf::json::buffer::View {
+        buffer: outputs.buffer,
+        byte_length: USize64::from(POSITIONS_SIZE),
+        byte_offset: Some(USize64(start_offset)),
+        byte_stride: None,
+        extensions: Default::default(),
+        extras: Default::default(),
+        name: None,
+        target: Some(GltfValid(gltf::json::buffer::Target::ArrayBuffer)),
+    });
+    let index_view = outputs.gltf.push(gltf::json::buffer::View {
+        buffer: outputs.buffer,
+        byte_length: USize64::from(INDICES_SIZE),
+        byte_offset: Some(USize64(start_offset + POSITIONS_SIZE as u64)),
+        byte_stride: None, //Non-Vertexbuffers must not have stride
+        extensions: Default::default(),
+        extras: Default::default(),
+        name: None,
+        target: Some(GltfValid(gltf::json::buffer::Target::ElementArrayBuffer)),
+    });
+    let positions = outputs.gltf.push(GltfAccessor {
+        buffer_view: Some(position_view),
+        byte_offset: Some(USize64(0)),
+        count: USize64::from(CORNERS.len()),
+        component_type: GltfValid(GenericComponentType(ComponentType::F32)),
+        extensions: Default::default(),
+        extras: Default::default(),
+        type_: GltfValid(Type::Vec3),
+        min: Some(gltf::json::Value::from(vec![-0.5, -0.5, -0.5])),
+        max: Some(gltf::json::Value::from(vec![0.5, 0.5, 0.5])),
+        name: None,
+        normalized: false,
+        sparse: None,
+    });
+    let indices = outputs.gltf.push(GltfAccessor {
+        buffer_view: Some(index_view),
+        byte_offset: Some(USize64(0)),
+        count: USize64::from(INDICES.len()),
+        component_type: GltfValid(GenericComponentType(ComponentType::U16)),
+        extensions: Default::default(),
+        extras: Default::default(),
+        type_: GltfValid(Type::Scalar),
+        min: None,
+        max: None,
+        name: None,
+        normalized: false,
+        sparse: None,
+    });
+
+    let material = outputs.gltf.push(GltfMaterial {
+        name: Some("placeholder".to_string()),
+        alpha_mode: GltfValid(gltf::json::material::AlphaMode::Blend),
+        pbr_metallic_roughness: PbrMetallicRoughness {
+            base_color_factor: PbrBaseColorFactor([1.0, 0.0, 1.0, 0.5]),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+
+    let mut attributes = std::collections::BTreeMap::new();
+    attributes.insert(GltfValid(gltf::json::mesh::Semantic::Positions), positions);
+    Ok(outputs.gltf.push(GltfMesh {
+        extensions: Default::default(),
+        extras: Default::default(),
+        name: Some("placeholder".to_string()),
+        primitives: vec![GltfPrimitive {
+            attributes,
+            extensions: Default::default(),
+            extras: Default::default(),
+            indices: Some(indices),
+            material: Some(material),
+            mode: GltfValid(gltf::json::mesh::Mode::Triangles),
+            targets: None,
+        }],
+        weights: None,
+    }))
+}
+
+/// Adds a child node with the navigation geometry to every entity with a navigation property set.
+/// The geometry is relative to the entity, so the node is not transformed.
+fn translate_navigation(input: &EntityDynamicContext, outputs: &mut Outputs) -> Result<()> {
+    use gltf::json::mesh::Mode;
+
+    for (entity_idx, entity) in input.entities.iter().enumerate() {
+        for acc in &entity.geo_entity.entity.accessors {
+            let GenClass::PropertySet(set) = &acc.object.object.class else {
+                continue;
+            };
+            let (kind, points, mode, extras) = match set {
+                PropertySet::NavZone(zone) => (
+                    "nav_zone",
+                    zone.points.clone(),
+                    Mode::LineLoop,
+                    serde_json::json!({
+                        "radius": zone.radius,
+                        "radius_offset": zone.radius_offset.to_array(),
+                        "zone_is_ccw": zone.zone_is_ccw,
+                    }),
+                ),
+                PropertySet::NavPath(path) => (
+                    "nav_path",
+                    path.points.clone(),
+                    Mode::LineStrip,
+                    serde_json::json!({
+                        "radius": path.radius,
+                        "zone_a": path.zone_a.zone.id.as_ref().map(|id| id.id.to_string()),
+                        "zone_b": path.zone_b.zone.id.as_ref().map(|id| id.id.to_string()),
+                    }),
+                ),
+                PropertySet::NavOffset(offset) => (
+                    "nav_offset",
+                    offset
+                        .offset_pose
+                        .iter()
+                        .map(|pose| pose.position)
+                        .collect(),
+                    Mode::Points,
+                    serde_json::json!({
+                        "ani_direction": offset
+                            .ani_direction
+                            .iter()
+                            .map(|d| format!("{d:?}"))
+                            .collect::<Vec<_>>(),
+                        "offset_circle": offset.offset_circle,
+                    }),
+                ),
+                _ => continue,
+            };
+            if points.is_empty() {
+                continue;
+            }
+            let mesh = write_line_mesh(kind, &points, mode, outputs)?;
+            let node = outputs.gltf.push(GltfNode {
+                name: Some(format!("{}_{kind}", entity.name)),
+                mesh: Some(mesh),
+                extras: Some(
+                    gltf::json::extras::RawValue::from_string(extras.to_string()).unwrap(),
+                ),
+                ..GltfNode::default()
+            });
+            outputs.gltf.nodes[entity_idx]
+                .children
+                .get_or_insert_default()
+                .push(node);
+        }
+    }
+    Ok(())
+}
+
+fn write_line_mesh(
+    kind: &'static str,
+    points: &[Vec3],
+    mode: gltf::json::mesh::Mode,
+    outputs: &mut Outputs,
+) -> Result<GltfIndex<GltfMesh>> {
+    use gltf::json::accessor::{ComponentType, GenericComponentType, Type};
+
+    let points = points.iter().map(|p| *p * 0.01).collect::<Vec<_>>();
+    let min = points.iter().fold(Vec3::MAX, |acc, p| acc.min(*p));
+    let max = points.iter().fold(Vec3::MIN, |acc, p| acc.max(*p));
+
+    let start_offset = outputs.buffer_file.stream_position()?;
+    for point in &points {
+        for value in point.to_array() {
+            write_f32(&mut outputs.buffer_file, value)?;
+        }
+    }
+    let position_view = outputs.gltf.push(gltf::json::buffer::View {
+        buffer: outputs.buffer,
+        byte_length: USize64::from(points.len() * std::mem::size_of::<[f32; 3]>()),
+        byte_offset: Some(USize64(start_offset)),
+        byte_stride: None,
+        extensions: Default::default(),
+        extras: Default::default(),
+        name: None,
+        target: Some(GltfValid(gltf::json::buffer::Target::ArrayBuffer)),
+    });
+    let positions = outputs.gltf.push(GltfAccessor {
+        buffer_view: Some(position_view),
+        byte_offset: Some(USize64(0)),
+        count: USize64::from(points.len()),
+        component_type: GltfValid(GenericComponentType(ComponentType::F32)),
+        extensions: Default::default(),
+        extras: Default::default(),
+        type_: GltfValid(Type::Vec3),
+        min: Some(gltf::json::Value::from(min.to_array().to_vec())),
+        max: Some(gltf::json::Value::from(max.to_array().to_vec())),
+        name: None,
+        normalized: false,
+        sparse: None,
+    });
+
+    let material = match outputs.line_materials.get(kind) {
+        Some(material) => *material,
+        None => {
+            let color = match kind {
+                "nav_zone" => [0.0, 1.0, 0.0, 1.0],
+                "nav_path" => [1.0, 1.0, 0.0, 1.0],
+                "collision" => [1.0, 0.0, 0.0, 1.0],
+                _ => [0.0, 1.0, 1.0, 1.0],
+            };
+            let material = outputs.gltf.push(GltfMaterial {
+                name: Some(kind.to_string()),
+                emissive_factor: gltf::json::material::EmissiveFactor([
+                    color[0], color[1], color[2],
+                ]),
+                pbr_metallic_roughness: PbrMetallicRoughness {
+                    base_color_factor: PbrBaseColorFactor(color),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+            outputs.line_materials.insert(kind, material);
+            material
+        }
+    };
+
+    let mut attributes = std::collections::BTreeMap::new();
+    attributes.insert(GltfValid(gltf::json::mesh::Semantic::Positions), positions);
+    Ok(outputs.gltf.push(GltfMesh {
+        extensions: Default::default(),
+        extras: Default::default(),
+        name: Some(kind.to_string()),
+        primitives: vec![GltfPrimitive {
+            attributes,
+            extensions: Default::default(),
+            extras: Default::default(),
+            indices: None,
+            material: Some(material),
+            mode: GltfValid(mode),
+            targets: None,
+        }],
+        weights: None,
+    }))
+}
+
+/// Adds a child node per collision shape. Center and orientation of the shape are stored in the node transform,
+/// so the shapes can be edited by moving, rotating and scaling the node (see gltf_to_lrent).
+fn translate_collision(input: &EntityDynamicContext, outputs: &mut Outputs) -> Result<()> {
+    use gltf::json::mesh::Mode;
+
+    for (entity_idx, entity) in input.entities.iter().enumerate() {
+        for acc in &entity.geo_entity.entity.accessors {
+            let GenClass::PropertySet(PropertySet::CollisionShapes(set)) = &acc.object.object.class
+            else {
+                continue;
+            };
+            for (shape_idx, shape) in set.shapes.iter().enumerate() {
+                let (center, orientation) = match &shape.geometry {
+                    ShapeGeometry::Box(obb) => (obb.center, Quat::from_mat3(&obb.orientation)),
+                    ShapeGeometry::Capsule(capsule) => {
+                        (capsule.center, Quat::from_mat3(&capsule.orientation))
+                    }
+                    ShapeGeometry::Sphere(sphere) => (sphere.center, Quat::IDENTITY),
+                    ShapeGeometry::Point(point) => (*point, Quat::IDENTITY),
+                    ShapeGeometry::Mesh(_) | ShapeGeometry::Opaque(_) => {
+                        (Vec3::ZERO, Quat::IDENTITY)
+                    }
+                };
+                let lines = collision_lines(shape);
+                let mesh = match (&shape.geometry, lines.is_empty()) {
+                    (ShapeGeometry::Point(_), _) => Some(write_line_mesh(
+                        "collision",
+                        &[Vec3::ZERO],
+                        Mode::Points,
+                        outputs,
+                    )?),
+                    (_, false) => Some(write_line_mesh("collision", &lines, Mode::Lines, outputs)?),
+                    (_, true) => None,
+                };
+                let mut extras = serde_json::json!({
+                    "collision_shape": shape_idx,
+                    "shape_type": shape.shape_type().map(|ty| format!("{ty:?}")),
+                    "group": shape.group().map(|group| format!("{group:?}")),
+                });
+                if let ShapeGeometry::Mesh(name) = &shape.geometry {
+                    extras["mesh"] = serde_json::Value::from(name.as_str());
+                }
+                let node = outputs.gltf.push(GltfNode {
+                    name: Some(format!("{}_collision_{shape_idx}", entity.name)),
+                    mesh,
+                    translation: (center != Vec3::ZERO).then(|| (center * 0.01).to_array()),
+                    rotation: (orientation != Quat::IDENTITY)
+                        .then(|| gltf::json::scene::UnitQuaternion(orientation.to_array())),
+                    extras: Some(
+                        gltf::json::extras::RawValue::from_string(extras.to_string()).unwrap(),
+                    ),
+                    ..GltfNode::default()
+                });
+                outputs.gltf.nodes[entity_idx]
+                    .children
+                    .get_or_insert_default()
+                    .push(node);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Wireframe of a shape relative to its center and orientation, as pairs of points
+fn collision_lines(shape: &CollisionShape) -> Vec<Vec3> {
+    const SEGMENTS: usize = 24;
+    // Points of a circle in the plane of `u` and `v`, from `start` to `end` (in full turns)
+    let arc = |lines: &mut Vec<Vec3>, center: Vec3, u: Vec3, v: Vec3, start: f32, end: f32| {
+        let segments = ((end - start) * SEGMENTS as f32).ceil() as usize;
+        let point = |idx: usize| {
+            let angle =
+                (start + (end - start) * idx as f32 / segments as f32) * std::f32::consts::TAU;
+            center + u * angle.cos() + v * angle.sin()
+        };
+        for idx in 0..segments {
+            lines.push(point(idx));
+            lines.push(point(idx + 1));
+        }
+    };
+
+    let mut lines = Vec::new();
+    match &shape.geometry {
+        ShapeGeometry::Box(obb) => {
+            let corner = |idx: usize| {
+                let sign = |bit: usize| if idx & bit != 0 { 1.0 } else { -1.0 };
+                obb.extent * Vec3::new(sign(1), sign(2), sign(4))
+            };
+            for idx in 0..8 {
+                for bit in [1, 2, 4] {
+                    if idx & bit == 0 {
+                        lines.push(corner(idx));
+                        lines.push(corner(idx | bit));
+                    }
+                }
+            }
+        }
+        ShapeGeometry::Sphere(sphere) => {
+            let r = sphere.radius;
+            arc(&mut lines, Vec3::ZERO, Vec3::X * r, Vec3::Y * r, 0.0, 1.0);
+            arc(&mut lines, Vec3::ZERO, Vec3::Y * r, Vec3::Z * r, 0.0, 1.0);
+            arc(&mut lines, Vec3::ZERO, Vec3::Z * r, Vec3::X * r, 0.0, 1.0);
+        }
+        ShapeGeometry::Capsule(capsule) => {
+            let r = capsule.radius;
+            let top = Vec3::Y * capsule.height * 0.5;
+            for end in [top, -top] {
+                arc(&mut lines, end, Vec3::Z * r, Vec3::X * r, 0.0, 1.0);
+            }
+            for side in [Vec3::X * r, Vec3::Z * r, -Vec3::X * r, -Vec3::Z * r] {
+                lines.push(top + side);
+                lines.push(-top + side);
+            }
+            arc(&mut lines, top, Vec3::X * r, Vec3::Y * r, 0.0, 0.5);
+            arc(&mut lines, top, Vec3::Z * r, Vec3::Y * r, 0.0, 0.5);
+            arc(&mut lines, -top, Vec3::X * r, Vec3::Y * r, 0.5, 1.0);
+            arc(&mut lines, -top, Vec3::Z * r, Vec3::Y * r, 0.5, 1.0);
+        }
+        ShapeGeometry::Point(_) | ShapeGeometry::Mesh(_) | ShapeGeometry::Opaque(_) => {}
+    }
+    lines
+}
+
+/// Adds a child node with a KHR_lights_punctual light to every entity with a light property set.
+/// Static ambient lights are exported as point lights. Directional lights shine along the local z axis
+/// of the entity, glTF uses -z, so their node is turned around.
+/// Values without a glTF equivalent are stored in the node extras, gltf_to_lrent reads all of them back.
+fn translate_lights(input: &EntityDynamicContext, outputs: &mut Outputs) {
+    use gltf::json::extensions::scene::khr_lights_punctual;
+
+    for (entity_idx, entity) in input.entities.iter().enumerate() {
+        let Some(light) = Light::from_entity(entity) else {
+            continue;
+        };
+        let ty = match light.kind {
+            LightKind::Directional => khr_lights_punctual::Type::Directional,
+            _ => khr_lights_punctual::Type::Point,
+        };
+        let gltf_lights: &mut Vec<khr_lights_punctual::Light> = outputs.gltf.as_mut();
+        gltf_lights.push(khr_lights_punctual::Light {
+            color: light.color,
+            extensions: None,
+            extras: Default::default(),
+            intensity: light.intensity,
+            name: Some(entity.name.clone()),
+            range: light
+                .range
+                .filter(|_| ty == khr_lights_punctual::Type::Point)
+                .map(|range| range * 0.01),
+            spot: None,
+            type_: GltfValid(ty),
+        });
+        let light_idx = GltfIndex::new(gltf_lights.len() as u32 - 1);
+
+        let extras = serde_json::json!({
+            "light": light.kind.class_name(),
+            "cast_shadows": light.cast_shadows,
+            "decay_duration": light.decay_duration,
+            "effect": light.effect,
+            "shadow_caster": light.shadow_caster,
+        });
+        let rotation = (light.kind == LightKind::Directional).then(|| {
+            gltf::json::scene::UnitQuaternion(
+                Quat::from_rotation_y(std::f32::consts::PI).to_array(),
+            )
+        });
+        let node = outputs.gltf.push(GltfNode {
+            name: Some(format!("{}_light", entity.name)),
+            translation: light.offset.map(|offset| (offset * 0.01).to_array()),
+            rotation,
+            extensions: Some(gltf::json::extensions::scene::Node {
+                khr_lights_punctual: Some(khr_lights_punctual::KhrLightsPunctual {
+                    light: light_idx,
+                }),
+                ..Default::default()
+            }),
+            extras: Some(gltf::json::extras::RawValue::from_string(extras.to_string()).unwrap()),
+            ..GltfNode::default()
+        });
+        outputs.gltf.nodes[entity_idx]
+            .children
+            .get_or_insert_default()
+            .push(node);
+    }
+    let gltf_lights: &[khr_lights_punctual::Light] = outputs.gltf.as_ref();
+    let extension = "KHR_lights_punctual".to_string();
+    if !gltf_lights.is_empty() && !outputs.gltf.extensions_used.contains(&extension) {
+        outputs.gltf.extensions_used.push(extension);
+    }
+}
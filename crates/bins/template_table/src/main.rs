@@ -0,0 +1,283 @@
+use calamine::{Data, Reader};
+use formats::table::PropertyTable;
+use formats::{archive::PakFile, file_formats::tple::TpleFile};
+use rust_xlsxwriter::{Format, Workbook};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::{env, ffi::OsString, path::Path};
+
+fn print_usage() {
+    println!("Usage: template_table export <file.tple> <table.csv|table.xlsx> [options]");
+    println!("       template_table import <file.tple> <table.csv|table.xlsx> [options]");
+    println!(
+        "Export writes one row per template and one column per property (<class>.<property>)."
+    );
+    println!(
+        "Import applies all changed cells of an edited table, writing <name>_out.tple. Rows are matched by GUID."
+    );
+    println!("Options:");
+    println!(
+        "  /class=gCItem_PS   only export the given property set, can be given multiple times"
+    );
+    println!("  /force             also write the output if some cells could not be applied");
+}
+
+fn load_tple(path: &Path) -> Result<(TpleFile, PakFile), String> {
+    let in_data =
+        File::open(path).map_err(|e| format!("opening {} failed: {e}", path.display()))?;
+    let mut in_data = BufReader::new(in_data);
+    let arch = PakFile::load(&mut in_data).map_err(|e| format!("loading archive failed: {e}"))?;
+    // As long as some contents remain unparsed, we need to retain the original string indices:
+    let mut out = PakFile::new();
+    out.strings = arch.strings.clone();
+    let tple = TpleFile::load(arch).map_err(|e| format!("loading tple failed: {e}"))?;
+    Ok((tple, out))
+}
+
+fn is_xlsx(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("xlsx"))
+}
+
+fn write_csv(table: &PropertyTable, path: &Path) -> Result<(), String> {
+    let mut writer = csv::Writer::from_path(path).map_err(|e| e.to_string())?;
+    writer
+        .write_record(&table.columns)
+        .map_err(|e| e.to_string())?;
+    for row in &table.rows {
+        writer.write_record(row).map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())
+}
+
+fn read_csv(path: &Path) -> Result<PropertyTable, String> {
+    let mut reader = csv::Reader::from_path(path).map_err(|e| e.to_string())?;
+    let columns = reader
+        .headers()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(str::to_string)
+        .collect();
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| e.to_string())?;
+        rows.push(record.iter().map(str::to_string).collect());
+    }
+    Ok(PropertyTable {
+        columns,
+        rows,
+        numeric: Vec::new(),
+    })
+}
+
+fn write_xlsx(table: &PropertyTable, path: &Path) -> Result<(), String> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    let header = Format::new().set_bold();
+    for (col, column) in table.columns.iter().enumerate() {
+        worksheet
+            .write_string_with_format(0, col as u16, column, &header)
+            .map_err(|e| e.to_string())?;
+    }
+    for (row_idx, row) in table.rows.iter().enumerate() {
+        let row_idx = row_idx as u32 + 1;
+        for (col, text) in row.iter().enumerate() {
+            // Numeric properties are written as numbers, so they can be used in formulas.
+            // Everything else stays text, even if it looks like a number (e.g. "007"):
+            let result = match text.parse::<f64>() {
+                Ok(number) if number.is_finite() && table.is_numeric(col) => {
+                    worksheet.write_number(row_idx, col as u16, number)
+                }
+                _ => worksheet.write_string(row_idx, col as u16, text),
+            };
+            result.map_err(|e| e.to_string())?;
+        }
+    }
+    worksheet
+        .set_freeze_panes(1, 2)
+        .map_err(|e| e.to_string())?;
+    workbook.save(path).map_err(|e| e.to_string())
+}
+
+fn read_xlsx(path: &Path) -> Result<PropertyTable, String> {
+    let mut workbook = calamine::open_workbook_auto(path).map_err(|e| e.to_string())?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or("workbook contains no sheet")?
+        .map_err(|e| e.to_string())?;
+    let mut rows = range.rows().map(|row| {
+        row.iter()
+            .map(|cell| match cell {
+                // integral values are shown without fraction, as exported:
+                Data::Float(v) if v.fract() == 0.0 && v.abs() < 1e15 => (*v as i64).to_string(),
+                Data::Empty => String::new(),
+                cell => cell.to_string(),
+            })
+            .collect::<Vec<_>>()
+    });
+    let columns = rows.next().ok_or("sheet is empty")?;
+    Ok(PropertyTable {
+        columns,
+        rows: rows.collect(),
+        numeric: Vec::new(),
+    })
+}
+
+fn export(tple: &TpleFile, classes: &[String], table_path: &Path) -> Result<(), String> {
+    let table = PropertyTable::export(&tple.entities, classes);
+    println!(
+        "{} templates, {} columns",
+        table.rows.len(),
+        table.columns.len()
+    );
+    if is_xlsx(table_path) {
+        write_xlsx(&table, table_path)
+    } else {
+        write_csv(&table, table_path)
+    }
+}
+
+/// Returns whether the output should be written
+fn import(tple: &mut TpleFile, table_path: &Path, force: bool) -> Result<bool, String> {
+    let table = if is_xlsx(table_path) {
+        read_xlsx(table_path)?
+    } else {
+        read_csv(table_path)?
+    };
+    let report = table.apply(&mut tple.entities).map_err(|e| e.to_string())?;
+    for changed in &report.changed {
+        println!("  changed {changed}");
+    }
+    println!(
+        "{} cells changed, {} unchanged",
+        report.changed.len(),
+        report.unchanged
+    );
+    for error in &report.errors {
+        println!("  ERROR {error}");
+    }
+    if !report.errors.is_empty() && !force {
+        println!(
+            "{} cells could not be applied, not writing output (use /force to write anyway)",
+            report.errors.len()
+        );
+        return Ok(false);
+    }
+    Ok(!report.changed.is_empty())
+}
+
+fn main() {
+    println!("Chromosome Toolkit - R1 - Template table");
+    let mut positional = Vec::new();
+    let mut classes = Vec::new();
+    let mut force = false;
+    for arg in env::args().skip(1) {
+        if let Some(class) = arg.strip_prefix("/class=") {
+            classes.push(class.to_string());
+        } else if arg == "/force" {
+            force = true;
+        } else {
+            positional.push(arg);
+        }
+    }
+    let [command, tple_arg, table_arg] = positional.as_slice() else {
+        print_usage();
+        return;
+    };
+    if command != "export" && command != "import" {
+        print_usage();
+        return;
+    }
+    let tple_os = OsString::from(tple_arg);
+    let table_os = OsString::from(table_arg);
+    let table_path = Path::new(&table_os);
+    let (mut tple, mut out) = match load_tple(Path::new(&tple_os)) {
+        Ok(t) => t,
+        Err(e) => {
+            println!("{e}");
+            return;
+        }
+    };
+
+    if command == "export" {
+        match export(&tple, &classes, table_path) {
+            Ok(()) => println!("done"),
+            Err(e) => println!("writing {table_arg} failed: {e}"),
+        }
+        return;
+    }
+
+    match import(&mut tple, table_path, force) {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(e) => {
+            println!("reading {table_arg} failed: {e}");
+            return;
+        }
+    }
+    if let Err(e) = tple.save(&mut out) {
+        println!("writing tple failed: {e}");
+        return;
+    }
+    let ext_start = tple_arg.rfind('.').unwrap();
+    let out_arg = format!("{}_out{}", &tple_arg[..ext_start], &tple_arg[ext_start..]);
+    let out_os = OsString::from(&out_arg);
+    let out_file = File::create(Path::new(&out_os)).expect("Unable to open output file");
+    let mut out_file = BufWriter::new(out_file);
+    match out.save(&mut out_file) {
+        Ok(_) => {}
+        Err(e) => {
+            println!("writing archive failed: {e}");
+        }
+    }
+    out_file.flush().unwrap();
+    println!("done");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xlsx_round_trip() {
+        let table = PropertyTable {
+            columns: [
+                "guid",
+                "name",
+                "gCItem_PS.Value",
+                "gCItem_PS.Weight",
+                "gCItem_PS.Code",
+            ]
+            .map(str::to_string)
+            .to_vec(),
+            rows: vec![
+                [
+                    "{00000000-0000-0000-0000-000000000001}",
+                    "1e5",
+                    "100",
+                    "2.5",
+                    "007",
+                ]
+                .map(str::to_string)
+                .to_vec(),
+                [
+                    "{00000000-0000-0000-0000-000000000002}",
+                    "It_Ring",
+                    "",
+                    "-0.125",
+                    "1e5",
+                ]
+                .map(str::to_string)
+                .to_vec(),
+            ],
+            numeric: vec![false, false, true, true, false],
+        };
+        let path = env::temp_dir().join(format!("template_table_{}.xlsx", std::process::id()));
+        write_xlsx(&table, &path).unwrap();
+        let read = read_xlsx(&path);
+        std::fs::remove_file(&path).unwrap();
+        let read = read.unwrap();
+        assert_eq!(read.columns, table.columns);
+        assert_eq!(read.rows, table.rows);
+    }
+}
@@ -0,0 +1,221 @@
+use formats::types::properties::PropData;
+use formats::types::{GenClass, Mat4, Quat, Vec3};
+use formats::{
+    archive::PakFile,
+    file_formats::{lrent::LrentFile, tple::TpleFile},
+};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::{env, ffi::OsString, path::Path};
+
+fn print_usage() {
+    println!("Usage: template_to_lrent <file.tple> <template name or guid> <file.lrent> [options]");
+    println!("Options:");
+    println!("  /pos=x,y,z      position relative to the parent (default 0,0,0)");
+    println!("  /rot=x,y,z,w    rotation quaternion (default identity)");
+    println!("  /scale=x,y,z    scale (default 1,1,1)");
+    println!("  /parent=idx     index of the parent entity in the lrent (default none)");
+}
+
+fn parse_floats<const N: usize>(value: &str) -> Option<[f32; N]> {
+    let values = value
+        .split(',')
+        .map(|v| v.trim().parse::<f32>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    values.try_into().ok()
+}
+
+fn load_archive(path: &Path) -> Option<PakFile> {
+    let in_data = match File::open(path) {
+        Ok(f) => f,
+        Err(e) => {
+            println!("opening {} failed: {e}", path.display());
+            return None;
+        }
+    };
+    let mut in_data = BufReader::new(in_data);
+    match PakFile::load(&mut in_data) {
+        Ok(a) => Some(a),
+        Err(e) => {
+            println!("loading archive {} failed: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Unparsed data keeps its original string indices, this is only possible for the data copied
+/// from the template and the data already in the lrent if one string table extends the other.
+/// Returns `None` if the tables are incompatible but unparsed data is copied from the template.
+fn pick_strings(
+    lrent_strings: &[String],
+    tple_strings: &[String],
+    unparsed: &[&str],
+) -> Option<Vec<String>> {
+    if lrent_strings.starts_with(tple_strings) || unparsed.is_empty() {
+        return Some(lrent_strings.to_vec());
+    }
+    if tple_strings.starts_with(lrent_strings) {
+        return Some(tple_strings.to_vec());
+    }
+    println!("The string tables of the tple and lrent are incompatible, but these are not parsed:");
+    for class in unparsed {
+        println!("  {class}");
+    }
+    println!("Their string references would be invalid, not writing the lrent");
+    None
+}
+
+fn main() {
+    println!("Chromosome Toolkit - R1 - Template to LRENT");
+    let mut positional = Vec::new();
+    let mut pos = Vec3::ZERO;
+    let mut rot = Quat::IDENTITY;
+    let mut scale = Vec3::ONE;
+    let mut parent = None;
+    for arg in env::args().skip(1) {
+        if let Some(value) = arg.strip_prefix("/pos=") {
+            let Some(v) = parse_floats::<3>(value) else {
+                println!("Invalid position {value}");
+                return;
+            };
+            pos = Vec3::from_array(v);
+        } else if let Some(value) = arg.strip_prefix("/rot=") {
+            let Some(v) = parse_floats::<4>(value) else {
+                println!("Invalid rotation {value}");
+                return;
+            };
+            rot = Quat::from_array(v).normalize();
+        } else if let Some(value) = arg.strip_prefix("/scale=") {
+            let Some(v) = parse_floats::<3>(value) else {
+                println!("Invalid scale {value}");
+                return;
+            };
+            scale = Vec3::from_array(v);
+        } else if let Some(value) = arg.strip_prefix("/parent=") {
+            let Ok(v) = value.parse::<usize>() else {
+                println!("Invalid parent index {value}");
+                return;
+            };
+            parent = Some(v);
+        } else {
+            positional.push(arg);
+        }
+    }
+    let [tple_arg, template_key, lrent_arg] = positional.as_slice() else {
+        print_usage();
+        return;
+    };
+
+    let tple_os = OsString::from(tple_arg);
+    let Some(tple_arch) = load_archive(Path::new(&tple_os)) else {
+        return;
+    };
+    let tple_strings = tple_arch.strings.clone();
+    let tple = match TpleFile::load(tple_arch) {
+        Ok(t) => t,
+        Err(e) => {
+            println!("loading tple failed: {e}");
+            return;
+        }
+    };
+    let Some(template) = tple.find_template(template_key) else {
+        println!("Template {template_key} not found in {tple_arg}");
+        return;
+    };
+    let properties = tple.resolve_properties(template);
+    println!(
+        "Using template {} ({}) with {} property sets",
+        template.name,
+        template.id.id,
+        properties.len()
+    );
+    let unparsed = properties
+        .iter()
+        .map(|prop| &prop.accessor_prop.object)
+        .filter(|object| {
+            matches!(object.class, GenClass::Opaque(_))
+                || object
+                    .props
+                    .iter()
+                    .any(|prop| matches!(*prop.data, PropData::Buffer(_)))
+        })
+        .map(|object| object.get_class_name())
+        .collect::<Vec<_>>();
+
+    let lrent_os = OsString::from(lrent_arg);
+    let Some(lrent_arch) = load_archive(Path::new(&lrent_os)) else {
+        return;
+    };
+    // As long as some contents remain unparsed, we need to retain the original string indices:
+    let Some(prefill_strings) = pick_strings(&lrent_arch.strings, &tple_strings, &unparsed) else {
+        return;
+    };
+    let mut lrent = match LrentFile::load(lrent_arch) {
+        Ok(l) => l,
+        Err(e) => {
+            println!("loading lrent failed: {e}");
+            return;
+        }
+    };
+
+    let local_matrix = Mat4::from_scale_rotation_translation(scale, rot, pos);
+    let idx = match lrent.instantiate_template(template, properties, local_matrix, parent) {
+        Ok(idx) => idx,
+        Err(e) => {
+            println!("creating entity failed: {e}");
+            return;
+        }
+    };
+    if let Some(entity) = lrent.get_context().map(|ctx| &ctx.entities[idx]) {
+        println!(
+            "Created entity #{idx} {} ({}), bounds {} - {}",
+            entity.name, entity.id, entity.world_bound.min, entity.world_bound.max
+        );
+    }
+
+    let mut arch = PakFile::new();
+    arch.strings = prefill_strings;
+    if let Err(e) = lrent.save(&mut arch) {
+        println!("writing lrent failed: {e}");
+        return;
+    }
+
+    let out_arg = lrent_arg
+        .replace(".lrent", "_out.lrent")
+        .replace(".LRENT", "_out.LRENT");
+    if &out_arg == lrent_arg {
+        panic!("In == out path");
+    }
+    let out_os = OsString::from(&out_arg);
+    let out_file = File::create(Path::new(&out_os)).expect("Unable to open output file");
+    let mut out_file = BufWriter::new(out_file);
+    match arch.save(&mut out_file) {
+        Ok(_) => {}
+        Err(e) => {
+            println!("writing archive failed: {e}");
+        }
+    }
+    out_file.flush().unwrap();
+    println!("done");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn string_tables_must_be_compatible_for_unparsed_data() {
+        let lrent = strings(&["Mesh", "Cup"]);
+        let tple = strings(&["Mesh", "Cup", "Chest"]);
+        let other = strings(&["Chest", "Mesh"]);
+        let unparsed = ["gCLock_PS"];
+        assert_eq!(pick_strings(&lrent, &other, &[]), Some(lrent.clone()));
+        assert_eq!(pick_strings(&tple, &lrent, &unparsed), Some(tple.clone()));
+        assert_eq!(pick_strings(&lrent, &tple, &unparsed), Some(tple.clone()));
+        assert_eq!(pick_strings(&lrent, &other, &unparsed), None);
+    }
+}
@@ -0,0 +1,154 @@
+use formats::{
+    archive::PakFile,
+    error::Result,
+    file_formats::world::{SectorFile, WorldFile},
+};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::{env, ffi::OsString, path::Path};
+
+fn print_usage() {
+    println!("Usage: world_edit <file.wrl|file.sec> [command]");
+    println!("Commands:");
+    println!(
+        "  list                        print the sectors of a world or the layers of a sector (default)"
+    );
+    println!("  add <name>                  add a sector (.wrl) or layer (.sec)");
+    println!("  remove <name>               remove a sector (.wrl) or layer (.sec)");
+    println!(
+        "  new <new.sec> [layers...]   create a new sector based on the given .sec, with the given layers"
+    );
+    println!("Names are given without file extension.");
+    println!("Modifications are written to <name>_out.<ext>, new sectors to the given path.");
+    println!(
+        "Note: the sector and world layout has not been verified against the files shipped with the game yet,"
+    );
+    println!("keep a backup and check the output in the editor.");
+}
+
+fn load_archive(path: &Path) -> Option<PakFile> {
+    let in_data = match File::open(path) {
+        Ok(f) => f,
+        Err(e) => {
+            println!("opening {} failed: {e}", path.display());
+            return None;
+        }
+    };
+    let mut in_data = BufReader::new(in_data);
+    match PakFile::load(&mut in_data) {
+        Ok(a) => Some(a),
+        Err(e) => {
+            println!("loading archive {} failed: {e}", path.display());
+            None
+        }
+    }
+}
+
+fn write_archive(out: &PakFile, path: &str) {
+    let out_os = OsString::from(path);
+    let out_file = File::create(Path::new(&out_os)).expect("Unable to open output file");
+    let mut out_file = BufWriter::new(out_file);
+    if let Err(e) = out.save(&mut out_file) {
+        println!("writing archive failed: {e}");
+        return;
+    }
+    out_file.flush().unwrap();
+    println!("written {path}");
+}
+
+fn print_names(kind: &str, names: &[String]) {
+    println!("{} {kind}(s):", names.len());
+    for name in names {
+        println!("  {name}");
+    }
+}
+
+fn edit_sector(sector: &mut SectorFile, command: &[String]) -> Result<Option<SectorFile>> {
+    match command {
+        [cmd, name] if cmd == "add" => sector.add_layer(name)?,
+        [cmd, name] if cmd == "remove" => sector.remove_layer(name)?,
+        [cmd, _, layers @ ..] if cmd == "new" => {
+            let mut new = sector.new_like()?;
+            for layer in layers {
+                new.add_layer(layer)?;
+            }
+            print_names("layer", new.layers()?);
+            return Ok(Some(new));
+        }
+        _ => {}
+    }
+    print_names("layer", sector.layers()?);
+    Ok(None)
+}
+
+fn edit_world(world: &mut WorldFile, command: &[String]) -> Result<()> {
+    match command {
+        [cmd, name] if cmd == "add" => world.add_sector(name)?,
+        [cmd, name] if cmd == "remove" => world.remove_sector(name)?,
+        _ => {}
+    }
+    print_names("sector", world.sectors()?);
+    Ok(())
+}
+
+fn main() {
+    println!("Chromosome Toolkit - R1 - World editor");
+    println!("Warning: experimental, the .wrl/.sec layout is unverified");
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let [file_arg, command @ ..] = args.as_slice() else {
+        print_usage();
+        return;
+    };
+    let is_sector = file_arg.to_lowercase().ends_with(".sec");
+    let valid = match command {
+        [] => true,
+        [cmd] => cmd == "list",
+        [cmd, _] => cmd == "add" || cmd == "remove" || (is_sector && cmd == "new"),
+        [cmd, _, ..] => is_sector && cmd == "new",
+    };
+    if !valid {
+        println!("Invalid command '{}'", command.join(" "));
+        print_usage();
+        return;
+    }
+    let modifies = !command.is_empty() && command[0] != "list";
+
+    let file_os = OsString::from(file_arg);
+    let Some(arch) = load_archive(Path::new(&file_os)) else {
+        return;
+    };
+    // As long as some contents remain unparsed, we need to retain the original string indices:
+    let mut out = PakFile::new();
+    out.strings = arch.strings.clone();
+
+    let result = if is_sector {
+        SectorFile::load(arch).and_then(|mut sector| {
+            match edit_sector(&mut sector, command)? {
+                Some(new) => new.save(&mut out)?,
+                None => sector.save(&mut out)?,
+            }
+            Ok(())
+        })
+    } else {
+        WorldFile::load(arch).and_then(|mut world| {
+            edit_world(&mut world, command)?;
+            world.save(&mut out)
+        })
+    };
+    if let Err(e) = result {
+        println!("{e}");
+        return;
+    }
+    if !modifies {
+        return;
+    }
+
+    let out_arg = if command[0] == "new" {
+        command[1].clone()
+    } else {
+        let ext_start = file_arg.rfind('.').unwrap();
+        format!("{}_out{}", &file_arg[..ext_start], &file_arg[ext_start..])
+    };
+    write_archive(&out, &out_arg);
+    println!("done");
+}
@@ -0,0 +1,141 @@
+use std::{
+    collections::VecDeque,
+    env,
+    ffi::OsString,
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use formats::file_formats::xmac::{XmacFile, chunks::XmacChunk};
+
+fn print_usage() {
+    println!("Usage: xmac_convert --endian big|little <file._xmac or folder>...");
+    println!(
+        "Rewrites actors in the given byte order, big-endian is used by the console versions."
+    );
+    println!("Output is written to <name>_out._xmac next to the input file.");
+}
+
+/// Chunks that are kept as raw bytes can't be swapped, returns a description of each
+fn raw_chunks(chunks: &[XmacChunk]) -> Vec<String> {
+    let mut raw = Vec::new();
+    for chunk in chunks {
+        match chunk {
+            XmacChunk::Unknown(unknown) => raw.push(format!(
+                "unknown chunk {}.{}",
+                unknown.type_id, unknown.version
+            )),
+            XmacChunk::MaterialAttributeSet(set) => {
+                raw.push(format!("attribute set of material {}", set.material_index))
+            }
+            XmacChunk::MeshLodLevels(lod) => raw.extend(raw_chunks(&lod.chunks)),
+            _ => {}
+        }
+    }
+    raw
+}
+
+fn convert(path: &Path, big_endian: bool) {
+    let in_data = match File::open(path) {
+        Ok(f) => f,
+        Err(e) => {
+            println!("opening failed: {e}");
+            return;
+        }
+    };
+    let mut in_data = BufReader::new(in_data);
+    let mut xmac = match XmacFile::load(&mut in_data) {
+        Ok(xmac) => xmac,
+        Err(e) => {
+            println!("loading xmac failed: {e}");
+            return;
+        }
+    };
+    drop(in_data);
+
+    if xmac.big_endian == big_endian {
+        println!("Already in the requested byte order, rewriting anyway");
+    } else {
+        for raw in raw_chunks(&xmac.chunks) {
+            println!("Warn: The {raw} is copied as is, its contents keep the old byte order");
+        }
+    }
+    xmac.big_endian = big_endian;
+
+    let arg = path.to_string_lossy();
+    let out_arg = arg.replace("._xmac", "_out._xmac");
+    if out_arg == arg {
+        println!("Not an ._xmac file");
+        return;
+    }
+    let out_file = match File::create(Path::new(&OsString::from(&out_arg))) {
+        Ok(f) => f,
+        Err(e) => {
+            println!("creating {out_arg} failed: {e}");
+            return;
+        }
+    };
+    let mut out_file = BufWriter::new(out_file);
+    if let Err(e) = xmac.save(&mut out_file).and_then(|_| Ok(out_file.flush()?)) {
+        println!("writing {out_arg} failed: {e}");
+        return;
+    }
+    println!("done");
+}
+
+fn main() {
+    println!("Chromosome Toolkit - R1 - XMAC endian conversion");
+    let mut queue = VecDeque::new();
+    let mut big_endian = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--endian" {
+            big_endian = match args.next().as_deref() {
+                Some("big") => Some(true),
+                Some("little") => Some(false),
+                _ => {
+                    print_usage();
+                    return;
+                }
+            };
+        } else {
+            queue.push_back(arg);
+        }
+    }
+    let Some(big_endian) = big_endian else {
+        print_usage();
+        return;
+    };
+    if queue.is_empty() {
+        print_usage();
+        return;
+    }
+
+    while let Some(arg) = queue.pop_front() {
+        println!("{arg}");
+        let os_arg = OsString::from(&arg);
+        let path = Path::new(&os_arg);
+        if !path.exists() {
+            println!("not found");
+            continue;
+        }
+
+        if path.is_dir() {
+            if let Ok(dir) = path.read_dir() {
+                for file in dir.flatten() {
+                    let meta = file.metadata().unwrap();
+                    let path = file.path().to_string_lossy().to_string();
+                    if meta.is_dir() || (path.ends_with("._xmac") && !path.ends_with("_out._xmac"))
+                    {
+                        queue.push_back(path);
+                    }
+                }
+            } else {
+                println!("Reading dir failed");
+            }
+            continue;
+        }
+        convert(path, big_endian);
+    }
+}
@@ -0,0 +1,24 @@
+pub mod translation;
+
+#[derive(Debug)]
+pub enum ConvError {
+    NotImplemented(String),
+    MandatoryDataMissing(String),
+    InvalidData(String),
+    IoError(std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, ConvError>;
+
+impl std::error::Error for ConvError {}
+impl std::fmt::Display for ConvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl From<std::io::Error> for ConvError {
+    fn from(value: std::io::Error) -> Self {
+        Self::IoError(value)
+    }
+}
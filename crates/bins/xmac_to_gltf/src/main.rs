@@ -9,7 +9,7 @@ use std::{
 
 use formats::file_formats::xmac::XmacFile;
 use serde::Serialize;
-mod translation;
+use xmac_to_gltf::translation;
 
 fn main() {
     println!("Chromosome Toolkit - R1 - XMAC to GLTF");
@@ -72,6 +72,10 @@ fn main() {
             xmac.serialize(&mut ser).unwrap();
             int_file.flush().unwrap();
         }
+        // Only report problems here, the export is still useful to inspect broken files
+        if let Err(e) = xmac.sanity_check() {
+            println!("Exporting invalid XMAC: {e}");
+        }
         let out_bin = OsString::from(arg.replace("._xmac", ".bin"));
 
         let gltf = translation::xmac_to_gltf(&xmac, Path::new(&out_bin), include_textures).unwrap();
@@ -89,26 +93,3 @@ fn main() {
         println!("done");
     }
 }
-
-#[derive(Debug)]
-pub enum ConvError {
-    NotImplemented(String),
-    MandatoryDataMissing(String),
-    InvalidData(String),
-    IoError(std::io::Error),
-}
-
-type Result<T> = std::result::Result<T, ConvError>;
-
-impl std::error::Error for ConvError {}
-impl std::fmt::Display for ConvError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
-    }
-}
-
-impl From<std::io::Error> for ConvError {
-    fn from(value: std::io::Error) -> Self {
-        Self::IoError(value)
-    }
-}
@@ -17,6 +17,7 @@ use formats::{
             morph_targets::MeshDeformDelta,
             nodes::XmacNodeId,
             skinning_info::XmacSkinningInfo,
+            XmacChunk,
         },
         XmacFile,
     },
@@ -96,10 +97,13 @@ pub fn xmac_to_gltf(
     };
 
     translate_nodes(input, &mut outputs)?;
+    translate_node_extras(input, &mut outputs)?;
     translate_materials(input, texture_dir, &mut outputs)?;
     translate_meshes(input, &mut outputs)?;
     translate_skinning(input, &mut outputs)?;
     translate_morphs(input, &mut outputs)?;
+    translate_collision_meshes(input, &mut outputs)?;
+    translate_lods(input, &mut outputs)?;
 
     // Apply MeshExtras to GLTF data structure:
     apply_extras(&mut outputs)?;
@@ -202,68 +206,269 @@ fn translate_nodes(input: &XmacFile, outputs: &mut Outputs) -> Result<()> {
     }
 }
 
+/// Node groups, attachment nodes, limits and motion sources as extras of the nodes they reference,
+/// the order and default state of the node groups as scene extras
+fn translate_node_extras(input: &XmacFile, outputs: &mut Outputs) -> Result<()> {
+    let node_count = outputs.gltf.nodes.len();
+    let mut node_extras = vec![Map::new(); node_count];
+    let missing_node = |what: &str, idx: usize| {
+        ConvError::InvalidData(format!("{what} references missing node {idx}"))
+    };
+
+    if let Some(groups) = input.get_node_groups_chunk() {
+        for group in &groups.groups {
+            for node_idx in group.nodes.iter().map(|idx| *idx as usize) {
+                let extras = node_extras
+                    .get_mut(node_idx)
+                    .ok_or_else(|| missing_node("Node group", node_idx))?;
+                if let serde_json::Value::Array(names) = extras
+                    .entry("node_groups")
+                    .or_insert_with(|| serde_json::Value::Array(Vec::new()))
+                {
+                    names.push(group.name.clone().into());
+                }
+            }
+        }
+        let groups = groups
+            .groups
+            .iter()
+            .map(|group| {
+                serde_json::json!({
+                    "name": group.name,
+                    "disabled_on_default": group.disabled_on_default,
+                })
+            })
+            .collect::<Vec<_>>();
+        let extras = serde_json::json!({ "node_groups": groups }).to_string();
+        outputs.gltf.scenes[0].extras =
+            Some(gltf::json::extras::RawValue::from_string(extras).unwrap());
+    }
+    if let Some(attachments) = input.get_attachment_nodes_chunk() {
+        for node_idx in attachments.nodes.iter().map(|idx| *idx as usize) {
+            node_extras
+                .get_mut(node_idx)
+                .ok_or_else(|| missing_node("Attachment", node_idx))?
+                .insert("attachment".to_string(), true.into());
+        }
+    }
+    for limit in input.get_limit_chunks() {
+        let node_idx = limit.node_id.0 as usize;
+        let mut value = serde_json::to_value(limit).unwrap();
+        if let serde_json::Value::Object(value) = &mut value {
+            value.remove("node_id");
+        }
+        node_extras
+            .get_mut(node_idx)
+            .ok_or_else(|| missing_node("Limit", node_idx))?
+            .insert("limit".to_string(), value);
+    }
+    if let Some(motion_sources) = input.get_node_motion_sources_chunk() {
+        for (node_idx, source) in motion_sources.sources.iter().enumerate() {
+            let flags = motion_sources
+                .flags
+                .as_ref()
+                .and_then(|flags| flags.get(node_idx).copied());
+            let extras = node_extras
+                .get_mut(node_idx)
+                .ok_or_else(|| missing_node("Motion source", node_idx))?;
+            if let Some(source) = source {
+                let source = *source as usize;
+                let source_name = outputs
+                    .gltf
+                    .nodes
+                    .get(source)
+                    .and_then(|node| node.name.clone())
+                    .ok_or_else(|| missing_node("Motion source", source))?;
+                extras.insert("motion_source".to_string(), source_name.into());
+            }
+            // flags are only kept for nodes that have a source or non-default flags:
+            if let Some(flags) = flags
+                && (source.is_some() || flags != 0)
+            {
+                extras.insert("motion_source_flags".to_string(), flags.into());
+            }
+        }
+    }
+
+    for (node, extras) in outputs.gltf.nodes.iter_mut().zip(node_extras) {
+        if !extras.is_empty() {
+            let raw_extras = serde_json::Value::Object(extras).to_string();
+            node.extras = Some(gltf::json::extras::RawValue::from_string(raw_extras).unwrap());
+        }
+    }
+    Ok(())
+}
+
 fn translate_meshes(input: &XmacFile, outputs: &mut Outputs) -> Result<()> {
-    for mesh in input.get_mesh_chunks() {
-        let gltf_node = &mut outputs.gltf.nodes[mesh.node_id.0 as usize];
-        let mesh_idx = outputs.gltf.meshes.len();
+    // Collision meshes are exported as separate nodes in translate_collision_meshes
+    for mesh in input
+        .get_mesh_chunks()
+        .into_iter()
+        .filter(|mesh| !mesh.is_collision_mesh)
+    {
+        let gltf_node = &outputs.gltf.nodes[mesh.node_id.0 as usize];
         if gltf_node.mesh.is_some() {
             return Err(ConvError::InvalidData(format!(
                 "Node {} is referenced by multiple meshes!",
                 mesh.node_id.0
             )));
         }
-        gltf_node.mesh = Some(GltfIndex::new(mesh_idx as u32));
 
         let mesh_name = gltf_node
             .name
             .as_ref()
             .map(|node_name| format!("{node_name}_mesh",))
             .unwrap_or_else(|| format!("mesh_{}", mesh.node_id.0));
-        let [positions, normals, tangents, uvs] = write_mesh_buffer(mesh, outputs)?;
-
-        let mut primitives = Vec::with_capacity(mesh.submeshes.len());
-        let mut index_offset = 0;
-        for submesh in &mesh.submeshes {
-            let indices = write_submesh_buffer(submesh, index_offset, outputs)?;
-            let primitive = GltfPrimitive {
-                attributes: {
-                    let mut map = BTreeMap::new();
-                    map.insert(
-                        GltfValid(gltf::json::mesh::Semantic::Positions),
-                        positions.unwrap(),
-                    );
-                    if let Some(normals) = normals {
-                        map.insert(GltfValid(gltf::json::mesh::Semantic::Normals), normals);
-                    }
-                    if let Some(tangents) = tangents {
-                        map.insert(GltfValid(gltf::json::mesh::Semantic::Tangents), tangents);
-                    }
-                    if let Some(uvs) = uvs {
-                        map.insert(GltfValid(gltf::json::mesh::Semantic::TexCoords(0)), uvs);
-                    }
-                    map
-                },
-                extensions: None,
-                extras: None,
-                indices: Some(indices),
-                material: Some(GltfIndex::new(submesh.material_idx)), //Materials are exported 1:1, so their idx is stable
-                mode: GltfValid(gltf::mesh::Mode::Triangles),
-                targets: None,
-            };
-            primitives.push(primitive);
-            index_offset += submesh.vertices_count;
-        }
+        let gltf_mesh = translate_mesh(mesh, mesh_name, outputs)?;
+        outputs.gltf.nodes[mesh.node_id.0 as usize].mesh = Some(gltf_mesh);
+    }
+    Ok(())
+}
 
-        let gltf_mesh = GltfMesh {
+fn translate_mesh(
+    mesh: &XmacMesh,
+    mesh_name: String,
+    outputs: &mut Outputs,
+) -> Result<GltfIndex<GltfMesh>> {
+    let [positions, normals, tangents, uvs] = write_mesh_buffer(mesh, outputs)?;
+
+    let mut primitives = Vec::with_capacity(mesh.submeshes.len());
+    let mut index_offset = 0;
+    for submesh in &mesh.submeshes {
+        let indices = write_submesh_buffer(submesh, index_offset, outputs)?;
+        let primitive = GltfPrimitive {
+            attributes: {
+                let mut map = BTreeMap::new();
+                map.insert(
+                    GltfValid(gltf::json::mesh::Semantic::Positions),
+                    positions.unwrap(),
+                );
+                if let Some(normals) = normals {
+                    map.insert(GltfValid(gltf::json::mesh::Semantic::Normals), normals);
+                }
+                if let Some(tangents) = tangents {
+                    map.insert(GltfValid(gltf::json::mesh::Semantic::Tangents), tangents);
+                }
+                if let Some(uvs) = uvs {
+                    map.insert(GltfValid(gltf::json::mesh::Semantic::TexCoords(0)), uvs);
+                }
+                map
+            },
             extensions: None,
             extras: None,
-            name: Some(mesh_name),
-            primitives,
-            weights: None,
+            indices: Some(indices),
+            material: Some(GltfIndex::new(submesh.material_idx)), //Materials are exported 1:1, so their idx is stable
+            mode: GltfValid(gltf::mesh::Mode::Triangles),
+            targets: None,
         };
+        primitives.push(primitive);
+        index_offset += submesh.vertices_count;
+    }
 
-        outputs.gltf.meshes.push(gltf_mesh);
-        outputs.mesh_extras.push(Map::new());
+    let gltf_mesh = GltfMesh {
+        extensions: None,
+        extras: None,
+        name: Some(mesh_name),
+        primitives,
+        weights: None,
+    };
+
+    outputs.mesh_extras.push(Map::new());
+    Ok(outputs.gltf.push(gltf_mesh))
+}
+
+/// Every collision mesh is exported as a hidden child node of the mesh's node, named `<node>_COLLISION`
+fn translate_collision_meshes(input: &XmacFile, outputs: &mut Outputs) -> Result<()> {
+    for mesh in input
+        .get_mesh_chunks()
+        .into_iter()
+        .filter(|mesh| mesh.is_collision_mesh)
+    {
+        let skin = input
+            .get_skinning_chunks()
+            .into_iter()
+            .find(|skin| skin.node_id == mesh.node_id && skin.is_for_collision_mesh);
+        let extras = serde_json::json!({ "xmac_collision": true });
+        translate_child_mesh(input, mesh, skin, "_COLLISION".to_string(), extras, outputs)?;
+    }
+    Ok(())
+}
+
+/// Every LOD mesh is exported as a child node of the mesh's node, named `<node>_LOD<level>`,
+/// or `<node>_COLLISION_LOD<level>` for collision meshes
+fn translate_lods(input: &XmacFile, outputs: &mut Outputs) -> Result<()> {
+    for lod in input.get_lod_chunks() {
+        for mesh in lod.get_mesh_chunks() {
+            let skin = lod.get_skinning_chunks().into_iter().find(|skin| {
+                skin.node_id == mesh.node_id && skin.is_for_collision_mesh == mesh.is_collision_mesh
+            });
+            let (suffix, extras) = if mesh.is_collision_mesh {
+                (
+                    format!("_COLLISION_LOD{}", lod.lod_level),
+                    serde_json::json!({ "lod_level": lod.lod_level, "xmac_collision": true }),
+                )
+            } else {
+                (
+                    format!("_LOD{}", lod.lod_level),
+                    serde_json::json!({ "lod_level": lod.lod_level }),
+                )
+            };
+            translate_child_mesh(input, mesh, skin, suffix, extras, outputs)?;
+        }
+    }
+    Ok(())
+}
+
+/// Exports a mesh that isn't the node's own one as a child node of the mesh's node.
+/// Collision meshes are hidden, as they aren't meant to be rendered.
+fn translate_child_mesh(
+    input: &XmacFile,
+    mesh: &XmacMesh,
+    skin: Option<&XmacSkinningInfo>,
+    name_suffix: String,
+    extras: serde_json::Value,
+    outputs: &mut Outputs,
+) -> Result<()> {
+    let parent_idx = mesh.node_id.0 as usize;
+    let Some(parent) = outputs.gltf.nodes.get(parent_idx) else {
+        return Err(ConvError::InvalidData(format!(
+            "Mesh{name_suffix} references missing node {parent_idx}"
+        )));
+    };
+    let node_name = format!("{}{name_suffix}", parent.name.as_deref().unwrap_or("node"));
+    let gltf_mesh = translate_mesh(mesh, format!("{node_name}_mesh"), outputs)?;
+    let visibility_extension = "KHR_node_visibility".to_string();
+    if mesh.is_collision_mesh && !outputs.gltf.extensions_used.contains(&visibility_extension) {
+        outputs
+            .gltf
+            .extensions_used
+            .push(visibility_extension.clone());
+    }
+    let extensions = mesh.is_collision_mesh.then(|| {
+        let mut visibility = Map::new();
+        visibility.insert(
+            visibility_extension,
+            serde_json::json!({ "visible": false }),
+        );
+        gltf::json::extensions::scene::Node {
+            others: visibility,
+            ..Default::default()
+        }
+    });
+    let gltf_node = outputs.gltf.push(GltfNode {
+        name: Some(node_name),
+        mesh: Some(gltf_mesh),
+        extensions,
+        extras: Some(gltf::json::extras::RawValue::from_string(extras.to_string()).unwrap()),
+        ..GltfNode::default()
+    });
+    outputs.gltf.nodes[parent_idx]
+        .children
+        .get_or_insert_default()
+        .push(gltf_node);
+
+    if let Some(skin) = skin {
+        translate_skin(input, skin, mesh, gltf_node.value(), outputs)?;
     }
     Ok(())
 }
@@ -475,56 +680,74 @@ fn write_submesh_buffer(
 }
 
 fn translate_skinning(input: &XmacFile, outputs: &mut Outputs) -> Result<()> {
-    for skin in input.get_skinning_chunks() {
+    // Collision skins are exported with their meshes in translate_collision_meshes
+    for skin in input
+        .get_skinning_chunks()
+        .into_iter()
+        .filter(|skin| !skin.is_for_collision_mesh)
+    {
         let node_id = skin.node_id;
         let skin_mesh = input.get_mesh_chunk(node_id).ok_or_else(|| {
             ConvError::MandatoryDataMissing(format!(
                 "Skinning Info for {node_id:?} is missing Mesh data!"
             ))
         })?;
-        let gltf_node = &outputs.gltf.nodes[node_id.0 as usize];
-        let skin_name = gltf_node
-            .name
-            .as_ref()
-            .map(|node_name| format!("{node_name}_skin",))
-            .unwrap_or_else(|| format!("skin_{}", node_id.0));
+        translate_skin(input, skin, skin_mesh, node_id.0 as usize, outputs)?;
+    }
+    Ok(())
+}
 
-        let (node_to_joint_idx, joints) = calculate_relevant_joint_ids(input, skin);
+/// Skins the mesh of `gltf_node_idx`, which is either the skin's node or one of its LOD or collision nodes
+fn translate_skin(
+    input: &XmacFile,
+    skin: &XmacSkinningInfo,
+    skin_mesh: &XmacMesh,
+    gltf_node_idx: usize,
+    outputs: &mut Outputs,
+) -> Result<()> {
+    let gltf_node = &outputs.gltf.nodes[gltf_node_idx];
+    let skin_name = gltf_node
+        .name
+        .as_ref()
+        .map(|node_name| format!("{node_name}_skin",))
+        .unwrap_or_else(|| format!("skin_{gltf_node_idx}"));
 
-        let inverse_binds = calculate_inv_binds(outputs.gltf, &node_to_joint_idx, &joints);
+    let (node_to_joint_idx, joints) = calculate_relevant_joint_ids(input, skin);
 
-        let (primary, secondary) =
-            write_skin_buffer(skin, skin_mesh, node_to_joint_idx, inverse_binds, outputs)?;
-        let [gltf_inversebinds, gltf_joints, gltf_weights] = primary;
+    let inverse_binds = calculate_inv_binds(outputs.gltf, &node_to_joint_idx, &joints);
 
-        let gltf_skin = GltfSkin {
-            extensions: None,
-            extras: None,
-            inverse_bind_matrices: Some(gltf_inversebinds),
-            joints,
-            name: Some(skin_name),
-            skeleton: None,
-        };
-        let gltf_skin = outputs.gltf.push(gltf_skin);
-        let gltf_node = &mut outputs.gltf.nodes[node_id.0 as usize];
-        gltf_node.skin = Some(gltf_skin);
-
-        let gltf_mesh = get_gltf_mesh_for_node(outputs.gltf, node_id);
-        for prim in gltf_mesh.primitives.iter_mut() {
-            prim.attributes.insert(
-                GltfValid(gltf::json::mesh::Semantic::Joints(0)),
-                gltf_joints,
-            );
-            prim.attributes.insert(
-                GltfValid(gltf::json::mesh::Semantic::Weights(0)),
-                gltf_weights,
-            );
-            if let Some([joints2, weights2]) = secondary {
-                prim.attributes
-                    .insert(GltfValid(gltf::json::mesh::Semantic::Joints(1)), joints2);
-                prim.attributes
-                    .insert(GltfValid(gltf::json::mesh::Semantic::Weights(1)), weights2);
-            }
+    let (primary, secondary) =
+        write_skin_buffer(skin, skin_mesh, node_to_joint_idx, inverse_binds, outputs)?;
+    let [gltf_inversebinds, gltf_joints, gltf_weights] = primary;
+
+    let gltf_skin = GltfSkin {
+        extensions: None,
+        extras: None,
+        inverse_bind_matrices: Some(gltf_inversebinds),
+        joints,
+        name: Some(skin_name),
+        skeleton: None,
+    };
+    let gltf_skin = outputs.gltf.push(gltf_skin);
+    let gltf_node = &mut outputs.gltf.nodes[gltf_node_idx];
+    gltf_node.skin = Some(gltf_skin);
+
+    let mesh_idx = gltf_node.mesh.unwrap().value();
+    let gltf_mesh = &mut outputs.gltf.meshes[mesh_idx];
+    for prim in gltf_mesh.primitives.iter_mut() {
+        prim.attributes.insert(
+            GltfValid(gltf::json::mesh::Semantic::Joints(0)),
+            gltf_joints,
+        );
+        prim.attributes.insert(
+            GltfValid(gltf::json::mesh::Semantic::Weights(0)),
+            gltf_weights,
+        );
+        if let Some([joints2, weights2]) = secondary {
+            prim.attributes
+                .insert(GltfValid(gltf::json::mesh::Semantic::Joints(1)), joints2);
+            prim.attributes
+                .insert(GltfValid(gltf::json::mesh::Semantic::Weights(1)), weights2);
         }
     }
     Ok(())
@@ -830,6 +1053,7 @@ fn translate_materials(
                 tex_coord: 0,
                 extensions: Some(GltfTexExtInfo {
                     texture_transform: xmac_mat_layer_to_transform(diffuse_layer),
+                    ..Default::default()
                 }),
                 extras: None,
             })
@@ -852,6 +1076,7 @@ fn translate_materials(
                     tex_coord: 0,
                     extensions: Some(GltfTexExtInfo {
                         texture_transform: texture_transform.clone(),
+                        ..Default::default()
                     }),
                     extras: None,
                 }),
@@ -915,12 +1140,62 @@ fn translate_materials(
                     ior: IndexOfRefraction(material.refraction_index),
                     extras: None,
                 }),
+                ..Default::default()
             }),
             extras: None,
             ..Default::default()
         };
         outputs.gltf.push(gltf_mat);
     }
+
+    // Other materials have no glTF equivalent, the placeholders keep the material indices stable:
+    for chunk in &input.chunks {
+        let name = match chunk {
+            XmacChunk::FxMaterial(material) => &material.name,
+            XmacChunk::GenericMaterial(material) => &material.name,
+            _ => continue,
+        };
+        let extras = serde_json::json!({ "xmac_material": chunk }).to_string();
+        outputs.gltf.push(GltfMaterial {
+            name: Some(name.clone()),
+            extras: Some(gltf::json::extras::RawValue::from_string(extras).unwrap()),
+            ..Default::default()
+        });
+    }
+
+    // Standalone layers and attribute sets are kept in the extras of the material they belong to:
+    let mut material_extras = vec![Map::new(); outputs.gltf.materials.len()];
+    for chunk in &input.chunks {
+        let (material_idx, key) = match chunk {
+            XmacChunk::StdMaterialLayer(layer) => (layer.material_id as usize, "xmac_layers"),
+            XmacChunk::MaterialAttributeSet(set) => {
+                (set.material_index as usize, "xmac_attribute_sets")
+            }
+            _ => continue,
+        };
+        let Some(extras) = material_extras.get_mut(material_idx) else {
+            println!("Warn: Skipping {key} entry of missing material {material_idx}");
+            continue;
+        };
+        if let serde_json::Value::Array(entries) = extras
+            .entry(key)
+            .or_insert_with(|| serde_json::Value::Array(Vec::new()))
+        {
+            entries.push(serde_json::to_value(chunk).unwrap());
+        }
+    }
+    for (material, extras) in outputs.gltf.materials.iter_mut().zip(material_extras) {
+        if !extras.is_empty() {
+            let mut merged = match &material.extras {
+                Some(raw) => serde_json::from_str::<Map<String, serde_json::Value>>(raw.get())
+                    .unwrap_or_default(),
+                None => Map::new(),
+            };
+            merged.extend(extras);
+            let raw_extras = serde_json::Value::Object(merged).to_string();
+            material.extras = Some(gltf::json::extras::RawValue::from_string(raw_extras).unwrap());
+        }
+    }
     Ok(())
 }
 
@@ -1080,12 +1355,21 @@ fn translate_morphs(input: &XmacFile, outputs: &mut Outputs) -> Result<()> {
             }
 
             let target_mesh_idx = get_gltf_mesh_idx_for_node(outputs.gltf, target_node_idx);
-            let mesh_target_names = outputs.mesh_extras[target_mesh_idx]
-                .entry("targetNames".to_string())
-                .or_insert_with(|| serde_json::Value::Array(Vec::new()))
-                .as_array_mut()
-                .unwrap();
-            mesh_target_names.push(morph.name.clone().into());
+            let mesh_extras = &mut outputs.mesh_extras[target_mesh_idx];
+            let mut push_extra = |key: &str, value: serde_json::Value| {
+                mesh_extras
+                    .entry(key.to_string())
+                    .or_insert_with(|| serde_json::Value::Array(Vec::new()))
+                    .as_array_mut()
+                    .unwrap()
+                    .push(value);
+            };
+            push_extra("targetNames", morph.name.clone().into());
+            push_extra(
+                "targetRanges",
+                serde_json::json!([morph.range_min, morph.range_max]),
+            );
+            push_extra("targetPhonemes", morph.phoneme_set.bits().into());
             let gltf_mesh = get_gltf_mesh_for_node(outputs.gltf, target_node_idx);
             gltf_mesh.weights.get_or_insert_default().push(0.0);
         }
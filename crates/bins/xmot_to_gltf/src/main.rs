@@ -0,0 +1,143 @@
+mod translation;
+
+use std::{
+    collections::VecDeque,
+    env,
+    ffi::OsString,
+    fs::File,
+    io::{BufReader, BufWriter, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use formats::file_formats::{xmac::XmacFile, xmot::XmotFile};
+use gltf::json::{Index as GltfIndex, validation::USize64};
+use translation::Outputs;
+
+fn print_usage() {
+    println!("Usage: xmot_to_gltf <actor._xmac> <motions._xmot or directories...>");
+    println!("Converts the actor and adds every motion as a GLTF animation.");
+    println!("The animations target the nodes by name, as created by xmac_to_gltf.");
+    println!("Output is written to <actor>_motions.gltf / .bin");
+}
+
+fn load_xmot(path: &Path) -> Option<XmotFile> {
+    let in_data = match File::open(path) {
+        Ok(f) => f,
+        Err(e) => {
+            println!("opening {} failed: {e}", path.display());
+            return None;
+        }
+    };
+    let mut in_data = BufReader::new(in_data);
+    match XmotFile::load(&mut in_data) {
+        Ok(xmot) => Some(xmot),
+        Err(e) => {
+            println!("loading xmot {} failed: {e}", path.display());
+            None
+        }
+    }
+}
+
+fn main() {
+    println!("Chromosome Toolkit - R1 - XMOT to GLTF");
+    let mut args = env::args().skip(1);
+    let Some(actor_arg) = args.next() else {
+        print_usage();
+        return;
+    };
+    let mut queue = args.map(PathBuf::from).collect::<VecDeque<_>>();
+    if queue.is_empty() {
+        print_usage();
+        return;
+    }
+
+    let actor_os = OsString::from(&actor_arg);
+    let actor_path = Path::new(&actor_os);
+    let in_data = match File::open(actor_path) {
+        Ok(f) => f,
+        Err(e) => {
+            println!("opening {actor_arg} failed: {e}");
+            return;
+        }
+    };
+    let mut in_data = BufReader::new(in_data);
+    let xmac = match XmacFile::load(&mut in_data) {
+        Ok(xmac) => xmac,
+        Err(e) => {
+            println!("loading xmac {actor_arg} failed: {e}");
+            return;
+        }
+    };
+    drop(in_data);
+    let Some(nodes) = xmac.get_nodes_chunk() else {
+        println!("{actor_arg} has no nodes");
+        return;
+    };
+
+    let out_arg = actor_arg.replace("._xmac", "_motions.gltf");
+    if out_arg == actor_arg {
+        panic!("In == out path");
+    }
+    let out_bin = PathBuf::from(actor_arg.replace("._xmac", "_motions.bin"));
+    let mut gltf = xmac_to_gltf::translation::xmac_to_gltf(&xmac, &out_bin, false).unwrap();
+    translation::nodes_to_trs(nodes, &mut gltf);
+
+    // The actor's buffer is complete, the keyframes are appended to it:
+    let buffer_file = File::options().write(true).open(&out_bin).unwrap();
+    let mut buffer_file = BufWriter::new(buffer_file);
+    buffer_file.seek(SeekFrom::End(0)).unwrap();
+    let mut outputs = Outputs {
+        gltf: &mut gltf,
+        buffer: GltfIndex::new(0),
+        buffer_file,
+    };
+
+    let mut motion_count = 0;
+    while let Some(path) = queue.pop_front() {
+        if path.is_dir() {
+            match path.read_dir() {
+                Ok(dir) => {
+                    for file in dir.flatten() {
+                        let path = file.path();
+                        if path.is_dir() || path.to_string_lossy().ends_with("._xmot") {
+                            queue.push_back(path);
+                        }
+                    }
+                }
+                Err(e) => println!("Reading dir {} failed: {e}", path.display()),
+            }
+            continue;
+        }
+        let Some(xmot) = load_xmot(&path) else {
+            continue;
+        };
+        let name = path
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .trim_end_matches("._xmot")
+            .to_string();
+        match translation::add_motion(&name, &xmot, nodes, &mut outputs) {
+            Ok(unknown) => {
+                motion_count += 1;
+                for node in unknown {
+                    println!("Warning: {name} animates node {node}, which the actor doesn't have");
+                }
+            }
+            Err(e) => println!("converting {name} failed: {e}"),
+        }
+    }
+
+    outputs.buffer_file.flush().unwrap();
+    let buffer_len = outputs.buffer_file.stream_position().unwrap();
+    drop(outputs);
+    gltf.buffers[0].byte_length = USize64(buffer_len);
+
+    let out_os = OsString::from(&out_arg);
+    let out_file = File::create(Path::new(&out_os)).expect("Unable to open output file");
+    let mut out_file = BufWriter::new(out_file);
+    gltf.to_writer_pretty(&mut out_file).unwrap();
+    out_file.flush().unwrap();
+
+    println!("{motion_count} motions written to {out_arg}");
+}
@@ -0,0 +1,238 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufWriter, Seek},
+};
+
+use formats::{
+    file_formats::{
+        xmac::chunks::nodes::{XmacNode, XmacNodes},
+        xmot::{XmotFile, XmotKey, XmotSubMotion},
+    },
+    helpers::write_f32,
+    types::{Quat, Vec3},
+};
+use gltf::json::{
+    Accessor as GltfAccessor, Animation as GltfAnimation, Buffer as GltfBuffer, Index as GltfIndex,
+    Root as GltfRoot,
+    accessor::{ComponentType, GenericComponentType, Type},
+    animation::{Channel, Interpolation, Property, Sampler, Target},
+    scene::UnitQuaternion,
+    validation::{Checked::Valid as GltfValid, USize64},
+};
+use xmac_to_gltf::Result;
+
+/// Differences below this are considered to be the rest pose
+const POSE_TOLERANCE: f32 = 1e-4;
+
+pub struct Outputs<'a> {
+    pub gltf: &'a mut GltfRoot,
+    pub buffer: GltfIndex<GltfBuffer>,
+    pub buffer_file: BufWriter<File>,
+}
+
+/// Animated nodes may not use a matrix, so the actor's nodes get their transform as TRS instead
+pub fn nodes_to_trs(nodes: &XmacNodes, gltf: &mut GltfRoot) {
+    for (node, gltf_node) in nodes.into_iter().zip(gltf.nodes.iter_mut()) {
+        gltf_node.matrix = None;
+        gltf_node.translation = Some(node_translation(node.local_pos).to_array());
+        gltf_node.rotation = Some(UnitQuaternion(node.rotation.to_array()));
+        gltf_node.scale = Some(node_scale(node.local_scale).to_array());
+    }
+}
+
+/// Same conversions as xmac_to_gltf: game is in cm, GLTF in m
+fn node_translation(pos: Vec3) -> Vec3 {
+    pos * 0.01
+}
+
+/// Same conversions as xmac_to_gltf: scale is inverted
+fn node_scale(scale: Vec3) -> Vec3 {
+    scale.recip()
+}
+
+/// Adds `motion` as a new animation, every sub motion targets the actor node with the same name.
+/// Returns the names of sub motions without a matching node.
+pub fn add_motion(
+    name: &str,
+    motion: &XmotFile,
+    nodes: &XmacNodes,
+    outputs: &mut Outputs,
+) -> Result<Vec<String>> {
+    let node_ids = nodes
+        .into_iter()
+        .enumerate()
+        .map(|(idx, node)| (node.name.as_str(), idx))
+        .collect::<HashMap<_, _>>();
+
+    let mut animation = GltfAnimation {
+        extensions: Default::default(),
+        extras: Default::default(),
+        channels: Vec::new(),
+        name: Some(name.to_string()),
+        samplers: Vec::new(),
+    };
+    let mut unknown = Vec::new();
+    for sub in motion.get_sub_motions() {
+        let Some(&node_idx) = node_ids.get(sub.name.as_str()) else {
+            unknown.push(sub.name.clone());
+            continue;
+        };
+        let node = &nodes.nodes[node_idx];
+        let target = GltfIndex::new(node_idx as u32);
+        for (property, track) in sub_motion_tracks(sub, node) {
+            add_channel(&mut animation, target, property, &track, outputs)?;
+        }
+    }
+    if !animation.channels.is_empty() {
+        outputs.gltf.push(animation);
+    }
+    Ok(unknown)
+}
+
+/// Keyframes of a single channel, already converted to GLTF
+struct Track {
+    times: Vec<f32>,
+    values: Vec<Vec<f32>>,
+}
+
+impl Track {
+    fn from_keys<T: Copy>(keys: &[XmotKey<T>], convert: impl Fn(T) -> Vec<f32>) -> Self {
+        Self {
+            times: keys.iter().map(|key| key.time).collect(),
+            values: keys.iter().map(|key| convert(key.value)).collect(),
+        }
+    }
+
+    fn constant(value: Vec<f32>) -> Self {
+        Self {
+            times: vec![0.0],
+            values: vec![value],
+        }
+    }
+}
+
+/// Channels with keys are always exported, otherwise the pose is only exported if it differs from the node's rest pose.
+/// Scale rotation keys are ignored, GLTF has no equivalent.
+fn sub_motion_tracks(sub: &XmotSubMotion, node: &XmacNode) -> Vec<(Property, Track)> {
+    let translation = |pos: Vec3| node_translation(pos).to_array().to_vec();
+    let rotation = |rot: Quat| rot.normalize().to_array().to_vec();
+    let scale = |scale: Vec3| node_scale(scale).to_array().to_vec();
+
+    let mut tracks = Vec::new();
+    if !sub.pos_keys.is_empty() {
+        tracks.push((
+            Property::Translation,
+            Track::from_keys(&sub.pos_keys, translation),
+        ));
+    } else if !sub
+        .pose_pos
+        .abs_diff_eq(node.local_pos, POSE_TOLERANCE * 100.0)
+    {
+        tracks.push((
+            Property::Translation,
+            Track::constant(translation(sub.pose_pos)),
+        ));
+    }
+    if !sub.rot_keys.is_empty() {
+        tracks.push((
+            Property::Rotation,
+            Track::from_keys(&sub.rot_keys, rotation),
+        ));
+    } else if sub.pose_rot.normalize().dot(node.rotation).abs() < 1.0 - POSE_TOLERANCE {
+        tracks.push((Property::Rotation, Track::constant(rotation(sub.pose_rot))));
+    }
+    if !sub.scale_keys.is_empty() {
+        tracks.push((Property::Scale, Track::from_keys(&sub.scale_keys, scale)));
+    } else if !sub.pose_scale.abs_diff_eq(node.local_scale, POSE_TOLERANCE) {
+        tracks.push((Property::Scale, Track::constant(scale(sub.pose_scale))));
+    }
+    tracks
+}
+
+fn add_channel(
+    animation: &mut GltfAnimation,
+    node: GltfIndex<gltf::json::Node>,
+    property: Property,
+    track: &Track,
+    outputs: &mut Outputs,
+) -> Result<()> {
+    let value_type = match property {
+        Property::Rotation => Type::Vec4,
+        _ => Type::Vec3,
+    };
+    let min_time = track.times.iter().copied().fold(f32::MAX, f32::min);
+    let max_time = track.times.iter().copied().fold(f32::MIN, f32::max);
+    let input = write_accessor(
+        &track.times.iter().map(|t| vec![*t]).collect::<Vec<_>>(),
+        Type::Scalar,
+        Some((vec![min_time], vec![max_time])),
+        outputs,
+    )?;
+    let output = write_accessor(&track.values, value_type, None, outputs)?;
+
+    let sampler = GltfIndex::new(animation.samplers.len() as u32);
+    animation.samplers.push(Sampler {
+        extensions: Default::default(),
+        extras: Default::default(),
+        input,
+        interpolation: GltfValid(Interpolation::Linear),
+        output,
+    });
+    animation.channels.push(Channel {
+        sampler,
+        target: Target {
+            extensions: Default::default(),
+            extras: Default::default(),
+            node,
+            path: GltfValid(property),
+        },
+        extensions: Default::default(),
+        extras: Default::default(),
+    });
+    Ok(())
+}
+
+fn write_accessor(
+    elements: &[Vec<f32>],
+    type_: Type,
+    bounds: Option<(Vec<f32>, Vec<f32>)>,
+    outputs: &mut Outputs,
+) -> Result<GltfIndex<GltfAccessor>> {
+    let start_offset = outputs.buffer_file.stream_position()?;
+    for value in elements.iter().flatten() {
+        write_f32(&mut outputs.buffer_file, *value)?;
+    }
+    let byte_length = outputs.buffer_file.stream_position()? - start_offset;
+    let view = outputs.gltf.push(gltf::json::buffer::View {
+        buffer: outputs.buffer,
+        byte_length: USize64(byte_length),
+        byte_offset: Some(USize64(start_offset)),
+        byte_stride: None,
+        extensions: Default::default(),
+        extras: Default::default(),
+        name: None,
+        target: None,
+    });
+    let (min, max) = match bounds {
+        Some((min, max)) => (
+            Some(gltf::json::Value::from(min)),
+            Some(gltf::json::Value::from(max)),
+        ),
+        None => (None, None),
+    };
+    Ok(outputs.gltf.push(GltfAccessor {
+        buffer_view: Some(view),
+        byte_offset: Some(USize64(0)),
+        count: USize64::from(elements.len()),
+        component_type: GltfValid(GenericComponentType(ComponentType::F32)),
+        extensions: Default::default(),
+        extras: Default::default(),
+        type_: GltfValid(type_),
+        min,
+        max,
+        name: None,
+        normalized: false,
+        sparse: None,
+    }))
+}
@@ -186,13 +186,18 @@ pub trait ArchiveReadTarget: Read + Seek {
 impl ArchiveReadTarget for PakFile {
     fn read_str(&mut self) -> Result<String> {
         let id = read_u16(self)? as usize;
-        let result = self.strings[id].as_str();
+        let Some(result) = self.strings.get(id) else {
+            return Err(Error::InvalidString(format!(
+                "String index {id} out of range"
+            )));
+        };
 
-        Ok(result.to_string())
+        Ok(result.clone())
     }
 }
 
 impl ArchiveReadTarget for BufReader<File> {}
+impl ArchiveReadTarget for Cursor<Vec<u8>> {}
 
 fn write_str_to_write<W: Write + ?Sized>(dst: &mut W, content: &str) -> std::io::Result<()> {
     let (str_buf, _, unmappable) = encoding_rs::WINDOWS_1252.encode(content);
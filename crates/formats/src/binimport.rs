@@ -89,6 +89,21 @@ impl BinImport for glam::Quat {
         Ok(())
     }
 }
+impl BinImport for glam::Mat3 {
+    fn load_endian<R: Read>(src: &mut R, big_endian: bool) -> Result<Self> {
+        Ok(Self {
+            x_axis: glam::Vec3::load_endian(src, big_endian)?,
+            y_axis: glam::Vec3::load_endian(src, big_endian)?,
+            z_axis: glam::Vec3::load_endian(src, big_endian)?,
+        })
+    }
+    fn save_endian<W: Write>(&self, dst: &mut W, big_endian: bool) -> Result<()> {
+        self.x_axis.save_endian(dst, big_endian)?;
+        self.y_axis.save_endian(dst, big_endian)?;
+        self.z_axis.save_endian(dst, big_endian)?;
+        Ok(())
+    }
+}
 impl BinImport for glam::Mat4 {
     fn load_endian<R: Read>(src: &mut R, big_endian: bool) -> Result<Self> {
         Ok(Self {
@@ -0,0 +1,340 @@
+//! Translation catalogs of the localized string properties (`*LocString`) of entities and templates.
+//!
+//! Each entry is identified by the owning entity / template GUID and the property path
+//! (`<class>.<property>`, e.g. `gCInfo_PS.Description`). The key is the value of the property
+//! at extraction time, so injecting a catalog twice or into a file that was changed in the
+//! meantime does not overwrite anything unexpectedly.
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use uuid::Uuid;
+
+use crate::diff::DiffEntity;
+use crate::merge::MergeEntity;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogEntry {
+    pub key: String,
+    pub owner: String,
+    pub owner_id: Uuid,
+    /// `<class>.<property>`
+    pub path: String,
+    /// Empty if not translated yet
+    pub translation: String,
+}
+
+impl fmt::Display for CatalogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}) {} = \"{}\"",
+            self.owner, self.owner_id, self.path, self.key
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Catalog {
+    pub entries: Vec<CatalogEntry>,
+}
+
+#[derive(Debug, Default)]
+pub struct InjectReport {
+    pub changed: Vec<String>,
+    pub unchanged: usize,
+    /// Entries whose property has been changed since the extraction
+    pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct Validation {
+    /// Localized strings of the files without a translated catalog entry
+    pub missing: Vec<CatalogEntry>,
+    /// Catalog entries that don't match any property of the files
+    pub unused: Vec<CatalogEntry>,
+}
+
+/// Calls `f` for every localized string property of `entity` with the property path and value
+fn for_each_loc_string<T: DiffEntity>(entity: &T, mut f: impl FnMut(String, &String)) {
+    for apo in entity.accessors() {
+        let class = apo.object.get_class_name();
+        for prop in &apo.object.props {
+            if let Some(text) = prop.data.as_loc_string() {
+                f(format!("{class}.{}", prop.name), text);
+            }
+        }
+    }
+}
+
+impl Catalog {
+    /// Appends all non-empty localized strings of `entities`
+    pub fn extract<T: DiffEntity>(&mut self, entities: &[T]) {
+        for entity in entities {
+            for_each_loc_string(entity, |path, text| {
+                if text.is_empty() {
+                    return;
+                }
+                self.entries.push(CatalogEntry {
+                    key: text.clone(),
+                    owner: entity.name().to_string(),
+                    owner_id: entity.id(),
+                    path,
+                    translation: String::new(),
+                });
+            });
+        }
+    }
+
+    /// Writes all translated entries to matching properties of `entities`.
+    /// Entries of other entities (e.g. from another file of the same catalog) are ignored.
+    pub fn inject<T: MergeEntity>(&self, entities: &mut [T]) -> InjectReport {
+        let mut report = InjectReport::default();
+        for entry in &self.entries {
+            if entry.translation.is_empty() {
+                continue;
+            }
+            let Some(entity) = entities.iter_mut().find(|e| e.id() == entry.owner_id) else {
+                continue;
+            };
+            let Some((class, property)) = entry.path.split_once('.') else {
+                report
+                    .conflicts
+                    .push(format!("{entry}: invalid property path"));
+                continue;
+            };
+            let text = entity
+                .accessor_mut(class)
+                .and_then(|apo| apo.object.props.iter_mut().find(|p| p.name == property))
+                .and_then(|prop| prop.data.as_loc_string_mut());
+            match text {
+                Some(text) if *text == entry.translation => report.unchanged += 1,
+                Some(text) if *text == entry.key => {
+                    *text = entry.translation.clone();
+                    report.changed.push(entry.to_string());
+                }
+                Some(text) => report
+                    .conflicts
+                    .push(format!("{entry}: property has been changed to \"{text}\"")),
+                None => report
+                    .conflicts
+                    .push(format!("{entry}: property does not exist")),
+            }
+        }
+        report
+    }
+
+    /// Compares the catalog to the localized strings of `entities`, see [`Validator`] for multiple files
+    pub fn validate<T: DiffEntity>(&self, entities: &[T]) -> Validation {
+        let mut validator = Validator::new(self);
+        validator.check(entities);
+        validator.finish()
+    }
+}
+
+/// Compares a catalog to the localized strings of several files.
+/// Check all files the catalog was extracted from, otherwise entries of the others are reported as unused.
+pub struct Validator<'a> {
+    catalog: &'a Catalog,
+    by_property: HashMap<(Uuid, &'a str), Vec<usize>>,
+    used: HashSet<usize>,
+    missing: Vec<CatalogEntry>,
+}
+
+impl<'a> Validator<'a> {
+    pub fn new(catalog: &'a Catalog) -> Self {
+        let mut by_property = HashMap::<_, Vec<usize>>::new();
+        for (idx, entry) in catalog.entries.iter().enumerate() {
+            by_property
+                .entry((entry.owner_id, entry.path.as_str()))
+                .or_default()
+                .push(idx);
+        }
+        Self {
+            catalog,
+            by_property,
+            used: HashSet::new(),
+            missing: Vec::new(),
+        }
+    }
+
+    pub fn check<T: DiffEntity>(&mut self, entities: &[T]) {
+        let entries = &self.catalog.entries;
+        for entity in entities {
+            for_each_loc_string(entity, |path, text| {
+                let matching = self
+                    .by_property
+                    .get(&(entity.id(), path.as_str()))
+                    .into_iter()
+                    .flatten()
+                    .copied()
+                    .find(|idx| entries[*idx].key == *text || entries[*idx].translation == *text);
+                if let Some(idx) = matching {
+                    self.used.insert(idx);
+                }
+                let translated = matching.is_some_and(|idx| !entries[idx].translation.is_empty());
+                if !text.is_empty() && !translated {
+                    self.missing.push(CatalogEntry {
+                        key: text.clone(),
+                        owner: entity.name().to_string(),
+                        owner_id: entity.id(),
+                        path,
+                        translation: String::new(),
+                    });
+                }
+            });
+        }
+    }
+
+    pub fn finish(self) -> Validation {
+        let unused = self
+            .catalog
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| !self.used.contains(idx))
+            .map(|(_, e)| e.clone())
+            .collect();
+        Validation {
+            missing: self.missing,
+            unused,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{accessor, template};
+    use crate::types::properties::PropData;
+    use crate::types::template::TemplateEntity;
+
+    const LETTER: &str = "{00000000-0000-0000-0000-000000000001}";
+    const BOOK: &str = "{00000000-0000-0000-0000-000000000002}";
+
+    fn templates() -> Vec<TemplateEntity> {
+        vec![
+            template(
+                LETTER,
+                "It_Letter",
+                vec![accessor(
+                    "gCItem_PS",
+                    &[
+                        ("Description", PropData::LocString("Letter".to_string())),
+                        ("Text", PropData::LetterLocString("Dear Sir".to_string())),
+                        ("Empty", PropData::LocString(String::new())),
+                        ("Name", PropData::String("not localized".to_string())),
+                    ],
+                )],
+            ),
+            template(
+                BOOK,
+                "It_Book",
+                vec![accessor(
+                    "gCBook_PS",
+                    &[("Text", PropData::BookLocString("Chapter 1".to_string()))],
+                )],
+            ),
+        ]
+    }
+
+    fn text(entity: &TemplateEntity, path: &str) -> String {
+        let (class, property) = path.split_once('.').unwrap();
+        let data = entity
+            .accessor(class)
+            .unwrap()
+            .object
+            .get_property(property);
+        data.unwrap().as_loc_string().unwrap().clone()
+    }
+
+    fn translated() -> Catalog {
+        let mut catalog = Catalog::default();
+        catalog.extract(&templates());
+        for entry in &mut catalog.entries {
+            entry.translation = format!("{} (de)", entry.key);
+        }
+        catalog
+    }
+
+    #[test]
+    fn extract_loc_strings() {
+        let mut catalog = Catalog::default();
+        catalog.extract(&templates());
+        let entries = catalog
+            .entries
+            .iter()
+            .map(|e| (e.owner.as_str(), e.path.as_str(), e.key.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [
+                ("It_Letter", "gCItem_PS.Description", "Letter"),
+                ("It_Letter", "gCItem_PS.Text", "Dear Sir"),
+                ("It_Book", "gCBook_PS.Text", "Chapter 1"),
+            ]
+        );
+        assert!(catalog.entries.iter().all(|e| e.translation.is_empty()));
+        assert_eq!(
+            catalog.entries[2].owner_id.to_string(),
+            BOOK.trim_matches(['{', '}'])
+        );
+    }
+
+    #[test]
+    fn inject_twice() {
+        let mut entities = templates();
+        let mut catalog = translated();
+        // untranslated entries are skipped:
+        catalog.entries[2].translation.clear();
+        let report = catalog.inject(&mut entities);
+        assert_eq!(report.changed.len(), 2);
+        assert!(report.conflicts.is_empty());
+        assert_eq!(text(&entities[0], "gCItem_PS.Text"), "Dear Sir (de)");
+        assert_eq!(text(&entities[1], "gCBook_PS.Text"), "Chapter 1");
+
+        let report = catalog.inject(&mut entities);
+        assert!(report.changed.is_empty());
+        assert_eq!(report.unchanged, 2);
+    }
+
+    #[test]
+    fn inject_reports_conflicts() {
+        let mut entities = templates();
+        entities[0].properties[0]
+            .accessor_prop
+            .object
+            .set_property("Description", PropData::LocString("Old letter".to_string()));
+        let mut catalog = translated();
+        catalog.entries[2].path = "gCBook_PS.Missing".to_string();
+        catalog.entries.push(CatalogEntry {
+            path: "invalid".to_string(),
+            ..catalog.entries[1].clone()
+        });
+        let report = catalog.inject(&mut entities);
+        assert_eq!(report.changed.len(), 1);
+        assert_eq!(report.conflicts.len(), 3, "{:?}", report.conflicts);
+        assert_eq!(text(&entities[0], "gCItem_PS.Description"), "Old letter");
+    }
+
+    #[test]
+    fn validate_files() {
+        let mut catalog = translated();
+        catalog.entries[1].translation.clear();
+        catalog.entries[2].owner_id = Uuid::from_u128(99);
+        let validation = catalog.validate(&templates());
+        let missing = validation
+            .missing
+            .iter()
+            .map(|e| e.key.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(missing, ["Dear Sir", "Chapter 1"]);
+        assert_eq!(validation.unused, [catalog.entries[2].clone()]);
+
+        // translated files match the translations:
+        let mut entities = templates();
+        translated().inject(&mut entities);
+        let validation = translated().validate(&entities);
+        assert!(validation.missing.is_empty());
+        assert!(validation.unused.is_empty());
+    }
+}
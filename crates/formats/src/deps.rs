@@ -0,0 +1,411 @@
+//! Dependency graph of the assets referenced by world / sector, lrent / tple and xmac files.
+//!
+//! Edges point from the user to the used item: world → sector → layer (lrent) or tple file →
+//! entity / template → mesh, image, material or speedtree → texture. Assets are identified by their
+//! lowercase file stem, as resource strings may or may not contain a path and an extension
+//! (e.g. `Obj_Barrel_01.xmsh` for `Obj_Barrel_01._xmac`). The same goes for worlds, sectors and layers,
+//! which reference each other by name.
+//!
+//! Assets are checked against the files the game would find, see [`DepGraph::check`]: those of a
+//! directory they were extracted to, or of the volumes (see [`crate::file_formats::volume`]).
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::diff::DiffEntity;
+use crate::file_formats::volume::VolumeFile;
+use crate::file_formats::xmac::XmacFile;
+use crate::types::properties::PropData;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    /// `.wrl` file
+    World,
+    /// `.sec` file
+    Sector,
+    /// Dynamic layer, i.e. an `.lrent` file
+    Layer,
+    /// `.tple` file
+    File,
+    Entity,
+    Template,
+    Mesh,
+    Image,
+    /// `bCImageOrMaterialResourceString`, may resolve to an image or a material
+    ImageOrMaterial,
+    SpeedTree,
+    /// Referenced by an xmac material layer
+    Texture,
+}
+
+impl NodeKind {
+    pub fn is_asset(self) -> bool {
+        !matches!(
+            self,
+            NodeKind::World
+                | NodeKind::Sector
+                | NodeKind::Layer
+                | NodeKind::File
+                | NodeKind::Entity
+                | NodeKind::Template
+        )
+    }
+
+    /// Extensions of the compiled files an asset reference can resolve to, empty if any
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            NodeKind::Mesh => &["_xmac"],
+            NodeKind::Image | NodeKind::Texture => &["_ximg"],
+            NodeKind::ImageOrMaterial => &["_ximg", "_xmat"],
+            _ => &[],
+        }
+    }
+
+    fn of_property(data: &PropData) -> Option<(NodeKind, &str)> {
+        Some(match data {
+            PropData::MeshResourceString(s) => (NodeKind::Mesh, s),
+            PropData::ImageResourceString(s) => (NodeKind::Image, s),
+            PropData::ImageOrMaterialResourceString(s) => (NodeKind::ImageOrMaterial, s),
+            PropData::SpeedTreeResourceString(s) => (NodeKind::SpeedTree, s),
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DepNode {
+    pub kind: NodeKind,
+    /// File path, entity GUID or key of an asset, world, sector or layer
+    pub id: String,
+    /// Name for display
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DepGraph {
+    pub nodes: Vec<DepNode>,
+    /// `(user, used)` node indices
+    pub edges: Vec<(usize, usize)>,
+    #[serde(skip)]
+    index: HashMap<(NodeKind, String), usize>,
+    #[serde(skip)]
+    edge_set: HashSet<(usize, usize)>,
+}
+
+/// Lowercase file stem, e.g. `obj_barrel_01` for `Meshes/Obj_Barrel_01.xmsh`
+pub fn asset_key(name: &str) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+    stem.to_lowercase()
+}
+
+/// A file of an asset directory or volume, see [`DepGraph::check`]
+#[derive(Debug, Clone)]
+pub struct AssetFile {
+    pub key: String,
+    /// Extension without the leading dot, e.g. `_xmac`
+    pub extension: String,
+    pub path: String,
+}
+
+impl AssetFile {
+    pub fn from_path(path: &str) -> Self {
+        let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+        let extension = name
+            .rsplit_once('.')
+            .map(|(_, ext)| ext)
+            .unwrap_or_default();
+        Self {
+            key: asset_key(name),
+            extension: extension.to_lowercase(),
+            path: path.to_string(),
+        }
+    }
+
+    /// The files of the volume at `volume_path`, their paths start with the volume path
+    pub fn from_volume(volume_path: &str, volume: &VolumeFile) -> Vec<Self> {
+        volume
+            .files
+            .iter()
+            .map(|entry| Self::from_path(&format!("{volume_path}/{}", entry.path)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct AssetReport {
+    /// Referenced assets without a matching file
+    pub missing: Vec<usize>,
+    /// Files that are not referenced by any node
+    pub unused: Vec<String>,
+}
+
+impl DepGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn find(&self, kind: NodeKind, id: &str) -> Option<usize> {
+        self.index.get(&(kind, id.to_string())).copied()
+    }
+
+    fn node(&mut self, kind: NodeKind, id: String, label: &str) -> usize {
+        *self.index.entry((kind, id.clone())).or_insert_with(|| {
+            self.nodes.push(DepNode {
+                kind,
+                id,
+                label: label.to_string(),
+            });
+            self.nodes.len() - 1
+        })
+    }
+
+    fn asset(&mut self, kind: NodeKind, name: &str) -> usize {
+        self.node(kind, asset_key(name), name)
+    }
+
+    fn edge(&mut self, from: usize, to: usize) {
+        if self.edge_set.insert((from, to)) {
+            self.edges.push((from, to));
+        }
+    }
+
+    /// Adds the world `file` and its sectors
+    pub fn add_world(&mut self, file: &str, sectors: &[String]) {
+        let world = self.asset(NodeKind::World, file);
+        for sector in sectors {
+            let sector = self.asset(NodeKind::Sector, sector);
+            self.edge(world, sector);
+        }
+    }
+
+    /// Adds the sector `file` and its dynamic layers
+    pub fn add_sector(&mut self, file: &str, layers: &[String]) {
+        let sector = self.asset(NodeKind::Sector, file);
+        for layer in layers {
+            let layer = self.asset(NodeKind::Layer, layer);
+            self.edge(sector, layer);
+        }
+    }
+
+    /// Adds `file` and the resource references of its entities / templates.
+    /// Entities without any references are left out.
+    pub fn add_entities<T: DiffEntity>(&mut self, file: &str, kind: NodeKind, entities: &[T]) {
+        // lrent files are the layers of a sector, and referenced by name like them
+        let file_node = match kind {
+            NodeKind::Entity => self.asset(NodeKind::Layer, file),
+            _ => self.node(NodeKind::File, file.to_string(), file),
+        };
+        for entity in entities {
+            let mut entity_node = None;
+            for apo in entity.accessors() {
+                for prop in &apo.object.props {
+                    let Some((asset_kind, name)) = NodeKind::of_property(&prop.data) else {
+                        continue;
+                    };
+                    if name.is_empty() {
+                        continue;
+                    }
+                    let from = *entity_node.get_or_insert_with(|| {
+                        let node = self.node(kind, entity.id().to_string(), entity.name());
+                        self.edge(file_node, node);
+                        node
+                    });
+                    let to = self.asset(asset_kind, name);
+                    self.edge(from, to);
+                }
+            }
+        }
+    }
+
+    /// Adds the textures used by the materials of the mesh `name`
+    pub fn add_xmac(&mut self, name: &str, xmac: &XmacFile) {
+        let mesh = self.asset(NodeKind::Mesh, name);
+        for material in xmac.get_material_chunks() {
+            for layer in &material.layers {
+                if layer.texture.is_empty() {
+                    continue;
+                }
+                let texture = self.asset(NodeKind::Texture, &layer.texture);
+                self.edge(mesh, texture);
+            }
+        }
+    }
+
+    /// Compares the referenced assets to the files of an asset directory and/or volumes
+    pub fn check(&self, files: &[AssetFile]) -> AssetReport {
+        let mut by_key = HashMap::<&str, Vec<&AssetFile>>::new();
+        for file in files {
+            by_key.entry(file.key.as_str()).or_default().push(file);
+        }
+        let mut used_files = HashSet::new();
+        let mut report = AssetReport::default();
+        for (idx, node) in self.nodes.iter().enumerate() {
+            if !node.kind.is_asset() {
+                continue;
+            }
+            let extensions = node.kind.extensions();
+            let matching = by_key
+                .get(node.id.as_str())
+                .into_iter()
+                .flatten()
+                .filter(|f| extensions.is_empty() || extensions.contains(&f.extension.as_str()))
+                .collect::<Vec<_>>();
+            if matching.is_empty() {
+                report.missing.push(idx);
+            }
+            used_files.extend(matching.iter().map(|f| f.path.as_str()));
+        }
+        report.unused = files
+            .iter()
+            .filter(|f| !used_files.contains(f.path.as_str()))
+            .map(|f| f.path.clone())
+            .collect();
+        report
+    }
+
+    /// All chains of users of node `idx`, starting at the root (usually a file) and ending at `idx`
+    pub fn user_paths(&self, idx: usize) -> Vec<Vec<usize>> {
+        let mut users = HashMap::<usize, Vec<usize>>::new();
+        for (from, to) in &self.edges {
+            users.entry(*to).or_default().push(*from);
+        }
+        let mut paths = Vec::new();
+        let mut stack = vec![vec![idx]];
+        while let Some(path) = stack.pop() {
+            let last = *path.last().unwrap();
+            match users.get(&last) {
+                Some(froms) => {
+                    for from in froms {
+                        // the graph is acyclic by construction, but don't rely on it:
+                        if path.contains(from) {
+                            continue;
+                        }
+                        let mut longer = path.clone();
+                        longer.push(*from);
+                        stack.push(longer);
+                    }
+                }
+                None => paths.push(path.into_iter().rev().collect()),
+            }
+        }
+        paths
+    }
+
+    /// Graphviz representation
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph assets {\n    rankdir=LR;\n");
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let shape = match node.kind {
+                NodeKind::World | NodeKind::Sector | NodeKind::Layer | NodeKind::File => "folder",
+                NodeKind::Entity | NodeKind::Template => "box",
+                _ => "ellipse",
+            };
+            let _ = writeln!(
+                out,
+                "    n{idx} [label=\"{}\", shape={shape}];",
+                node.label.replace('\\', "\\\\").replace('"', "\\\"")
+            );
+        }
+        for (from, to) in &self.edges {
+            let _ = writeln!(out, "    n{from} -> n{to};");
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_formats::volume::{Compression, VolumeEntry};
+    use crate::test_util::{accessor, template};
+    use crate::types::entity::DynamicEntity;
+
+    #[test]
+    fn world_to_layer() {
+        let mut graph = DepGraph::new();
+        graph.add_world("Projects/World.wrl", &["Harbour_01".to_string()]);
+        graph.add_sector(
+            "Projects/Harbour_01.sec",
+            &["Harbour_01_Dyn".to_string(), "Harbour_01_Npc".to_string()],
+        );
+        let entities: &[DynamicEntity] = &[];
+        graph.add_entities("Projects/Harbour_01_Dyn.lrent", NodeKind::Entity, entities);
+
+        // The sector and layer are the ones referenced by the world and sector
+        assert_eq!(graph.nodes.len(), 4);
+        let layer = graph.find(NodeKind::Layer, "harbour_01_dyn").unwrap();
+        let world = graph.find(NodeKind::World, "world").unwrap();
+        let sector = graph.find(NodeKind::Sector, "harbour_01").unwrap();
+        assert_eq!(graph.user_paths(layer), vec![vec![world, sector, layer]]);
+
+        // They aren't assets, so they are never missing
+        let report = graph.check(&[]);
+        assert!(report.missing.is_empty());
+    }
+
+    #[test]
+    fn keys_keep_inner_dots() {
+        assert_eq!(asset_key("Meshes/Obj_Barrel_01.xmsh"), "obj_barrel_01");
+        assert_eq!(asset_key("Obj_Barrel_01._xmac"), "obj_barrel_01");
+        assert_eq!(asset_key("Nat_Tree_1.5m._xmac"), "nat_tree_1.5m");
+        assert_eq!(asset_key("C:\\Images\\Sky"), "sky");
+    }
+
+    #[test]
+    fn check_volume_files() {
+        let mut graph = DepGraph::new();
+        let mesh = |id, name, mesh: &str| {
+            let property = PropData::MeshResourceString(mesh.to_string());
+            template(
+                id,
+                name,
+                vec![accessor("eCMesh_PS", &[("MeshFileName", property)])],
+            )
+        };
+        let templates = [
+            mesh(
+                "{00000000-0000-0000-0000-000000000001}",
+                "Tree",
+                "Nat_Tree_1.5m.xmsh",
+            ),
+            mesh(
+                "{00000000-0000-0000-0000-000000000002}",
+                "Rock",
+                "Nat_Rock.xmsh",
+            ),
+        ];
+        graph.add_entities("Nature.tple", NodeKind::Template, &templates);
+
+        let volume = VolumeFile {
+            files: ["Nature/Nat_Tree_1.5m._xmac", "Nature/Nat_Tree_2m._xmac"]
+                .map(|path| VolumeEntry {
+                    path: path.to_string(),
+                    offset: 0,
+                    stored_size: 0,
+                    size: 0,
+                    compression: Compression::None,
+                })
+                .to_vec(),
+        };
+        let files = AssetFile::from_volume("data/compiled/meshes.pak", &volume);
+        assert_eq!(
+            files[0].path,
+            "data/compiled/meshes.pak/Nature/Nat_Tree_1.5m._xmac"
+        );
+        let report = graph.check(&files);
+        let missing = report
+            .missing
+            .iter()
+            .map(|idx| graph.nodes[*idx].label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(missing, ["Nat_Rock.xmsh"]);
+        assert_eq!(
+            report.unused,
+            ["data/compiled/meshes.pak/Nature/Nat_Tree_2m._xmac"]
+        );
+    }
+}
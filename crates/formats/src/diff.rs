@@ -0,0 +1,585 @@
+//! Structural comparison of entity lists (lrent & tple files).
+//!
+//! Entities are matched by their GUID, accessors by class name and properties by name,
+//! so reordering does not show up as a change.
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::*;
+use crate::file_formats::{lrent::LrentFile, tple::TpleFile};
+use crate::types::entity::DynamicEntity;
+use crate::types::object::AccessorPropertyObject;
+use crate::types::properties::{PropData, Property};
+use crate::types::template::TemplateEntity;
+use crate::types::{GenClass, Mat4};
+
+/// Default tolerance used when comparing transforms
+pub const DEFAULT_TOLERANCE: f32 = 0.001;
+
+/// Common view on world entities and templates, used for diffing, merging and queries
+pub trait DiffEntity {
+    fn id(&self) -> Uuid;
+    fn name(&self) -> &str;
+    /// Header fields, formatted for display
+    fn header(&self) -> Vec<(&'static str, String)>;
+    fn transform(&self) -> Option<Mat4>;
+    fn accessors(&self) -> Vec<&AccessorPropertyObject>;
+
+    fn accessor(&self, class: &str) -> Option<&AccessorPropertyObject> {
+        self.accessors()
+            .into_iter()
+            .find(|apo| apo.object.get_class_name() == class)
+    }
+}
+
+impl DiffEntity for DynamicEntity {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn header(&self) -> Vec<(&'static str, String)> {
+        let geo = &self.geo_entity;
+        let entity = &geo.entity;
+        vec![
+            ("name", self.name.clone()),
+            (
+                "creator",
+                self.creator
+                    .id
+                    .as_ref()
+                    .map(|id| id.id.to_string())
+                    .unwrap_or_default(),
+            ),
+            ("enabled", entity.enabled.to_string()),
+            ("render_enabled", entity.render_enabled.to_string()),
+            ("picking_enabled", entity.picking_enabled.to_string()),
+            ("collision_enabled", entity.collision_enabled.to_string()),
+            ("insert_type", entity.insert_type.to_string()),
+            ("locked", entity.locked.to_string()),
+            (
+                "is_savegame_relevant",
+                entity.is_savegame_relevant.to_string(),
+            ),
+            ("alpha", geo.alpha.to_string()),
+            ("view_range", geo.view_range.to_string()),
+            ("cache_in_range", geo.cache_in_range.to_string()),
+            (
+                "local_bound",
+                format!("{} - {}", self.local_bound.min, self.local_bound.max),
+            ),
+            ("flag_a", entity.flag_a.to_string()),
+            ("flag_b", entity.flag_b.to_string()),
+            ("flag_c", entity.flag_c.to_string()),
+            ("flag_d", entity.flag_d.to_string()),
+            ("flag_e", entity.flag_e.to_string()),
+            ("unknown1", self.unknown1.to_string()),
+            ("geo_unknown1", geo.unknown1.to_string()),
+            ("geo_unknown2", format!("{:02x?}", geo.unknown2)),
+            (
+                "entity_unknown",
+                format!(
+                    "{:02x?}",
+                    [
+                        entity.unknown1,
+                        entity.unknown2,
+                        entity.unknown3,
+                        entity.unknown4,
+                        entity.unknown5,
+                        entity.unknown6,
+                        entity.unknown7,
+                    ]
+                ),
+            ),
+        ]
+    }
+
+    fn transform(&self) -> Option<Mat4> {
+        Some(self.local_matrix)
+    }
+
+    fn accessors(&self) -> Vec<&AccessorPropertyObject> {
+        self.geo_entity
+            .entity
+            .accessors
+            .iter()
+            .map(|acc| &acc.object)
+            .collect()
+    }
+}
+
+impl DiffEntity for TemplateEntity {
+    fn id(&self) -> Uuid {
+        self.id.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn header(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("name", self.name.clone()),
+            ("enabled", self.enabled.to_string()),
+            ("rendering_enabled", self.rendering_enabled.to_string()),
+            ("picking_enabled", self.picking_enabled.to_string()),
+            ("collision_enabled", self.collision_enabled.to_string()),
+            ("helper_parent", self.helper_parent.to_string()),
+            ("is_game_relevant", self.is_game_relevant.to_string()),
+            ("insert_type", self.insert_type.to_string()),
+            (
+                "scale_grid_percentage",
+                self.scale_grid_percentage.to_string(),
+            ),
+            (
+                "is_savegame_relevant",
+                self.is_savegame_relevant.to_string(),
+            ),
+            (
+                "ref_template",
+                self.ref_template
+                    .as_ref()
+                    .map(|id| id.id.to_string())
+                    .unwrap_or_default(),
+            ),
+            ("unknown1", self.unknown1.to_string()),
+            ("unknown2", self.unknown2.to_string()),
+            ("unknown3", self.unknown3.to_string()),
+            ("unknown5", self.unknown5.to_string()),
+            ("unknown6", self.unknown6.to_string()),
+            ("unknown7", self.unknown7.to_string()),
+            ("unknown8", self.unknown8.to_string()),
+            ("unknown9", self.unknown9.to_string()),
+            ("unused1", self.unused1.to_string()),
+            ("unused2", self.unused2.to_string()),
+        ]
+    }
+
+    fn transform(&self) -> Option<Mat4> {
+        None
+    }
+
+    fn accessors(&self) -> Vec<&AccessorPropertyObject> {
+        self.properties
+            .iter()
+            .map(|prop| &prop.accessor_prop)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Change {
+    Header {
+        field: String,
+        old: String,
+        new: String,
+    },
+    Transform {
+        old: Mat4,
+        new: Mat4,
+    },
+    /// The entity was moved in the parent hierarchy, `None` is the root
+    Parent {
+        old: Option<Uuid>,
+        new: Option<Uuid>,
+    },
+    AccessorAdded {
+        accessor: Box<AccessorPropertyObject>,
+    },
+    AccessorRemoved {
+        class: String,
+    },
+    ClassData {
+        class: String,
+        old: Box<GenClass>,
+        new: Box<GenClass>,
+    },
+    PropertyAdded {
+        class: String,
+        property: Property,
+    },
+    PropertyRemoved {
+        class: String,
+        name: String,
+    },
+    PropertyModified {
+        class: String,
+        name: String,
+        old: Box<PropData>,
+        new: Box<PropData>,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityChange<T> {
+    Added(Box<T>),
+    Removed,
+    Modified(Vec<Change>),
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EntityDiff<T> {
+    pub id: Uuid,
+    pub name: String,
+    pub change: EntityChange<T>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Diff<T> {
+    pub entities: Vec<EntityDiff<T>>,
+}
+
+impl<T> Diff<T> {
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+/// Compares the accessors of two versions of an entity
+pub fn diff_accessors(
+    old: &[&AccessorPropertyObject],
+    new: &[&AccessorPropertyObject],
+) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for old_acc in old {
+        let class = old_acc.object.get_class_name();
+        if !new.iter().any(|acc| acc.object.get_class_name() == class) {
+            changes.push(Change::AccessorRemoved {
+                class: class.to_string(),
+            });
+        }
+    }
+    for new_acc in new {
+        let class = new_acc.object.get_class_name();
+        let Some(old_acc) = old.iter().find(|acc| acc.object.get_class_name() == class) else {
+            changes.push(Change::AccessorAdded {
+                accessor: Box::new((*new_acc).clone()),
+            });
+            continue;
+        };
+        if old_acc.object.class != new_acc.object.class {
+            changes.push(Change::ClassData {
+                class: class.to_string(),
+                old: Box::new(old_acc.object.class.clone()),
+                new: Box::new(new_acc.object.class.clone()),
+            });
+        }
+        changes.extend(diff_properties(
+            class,
+            &old_acc.object.props,
+            &new_acc.object.props,
+        ));
+    }
+    changes
+}
+
+/// Compares the properties of an accessor of class `class`
+pub fn diff_properties(class: &str, old: &[Property], new: &[Property]) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for old_prop in old {
+        if !new.iter().any(|prop| prop.name == old_prop.name) {
+            changes.push(Change::PropertyRemoved {
+                class: class.to_string(),
+                name: old_prop.name.clone(),
+            });
+        }
+    }
+    for new_prop in new {
+        match old.iter().find(|prop| prop.name == new_prop.name) {
+            None => changes.push(Change::PropertyAdded {
+                class: class.to_string(),
+                property: new_prop.clone(),
+            }),
+            Some(old_prop) if old_prop.data != new_prop.data => {
+                changes.push(Change::PropertyModified {
+                    class: class.to_string(),
+                    name: new_prop.name.clone(),
+                    old: old_prop.data.clone(),
+                    new: new_prop.data.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    changes
+}
+
+/// Compares two versions of the same entity
+pub fn diff_entity<T: DiffEntity>(old: &T, new: &T, tolerance: f32) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for ((field, old_val), (_, new_val)) in old.header().into_iter().zip(new.header()) {
+        if old_val != new_val {
+            changes.push(Change::Header {
+                field: field.to_string(),
+                old: old_val,
+                new: new_val,
+            });
+        }
+    }
+    if let (Some(old_mat), Some(new_mat)) = (old.transform(), new.transform())
+        && !old_mat.abs_diff_eq(new_mat, tolerance)
+    {
+        changes.push(Change::Transform {
+            old: old_mat,
+            new: new_mat,
+        });
+    }
+    changes.extend(diff_accessors(&old.accessors(), &new.accessors()));
+    changes
+}
+
+/// Compares two entity lists, matching entities by GUID
+pub fn diff_entities<T: DiffEntity + Clone>(old: &[T], new: &[T], tolerance: f32) -> Diff<T> {
+    let old_map = old.iter().map(|e| (e.id(), e)).collect::<HashMap<_, _>>();
+    let new_map = new.iter().map(|e| (e.id(), e)).collect::<HashMap<_, _>>();
+
+    let mut entities = Vec::new();
+    for new_entity in new {
+        let id = new_entity.id();
+        let change = match old_map.get(&id) {
+            None => EntityChange::Added(Box::new(new_entity.clone())),
+            Some(old_entity) => {
+                let changes = diff_entity(*old_entity, new_entity, tolerance);
+                if changes.is_empty() {
+                    continue;
+                }
+                EntityChange::Modified(changes)
+            }
+        };
+        entities.push(EntityDiff {
+            id,
+            name: new_entity.name().to_string(),
+            change,
+        });
+    }
+    for old_entity in old {
+        if !new_map.contains_key(&old_entity.id()) {
+            entities.push(EntityDiff {
+                id: old_entity.id(),
+                name: old_entity.name().to_string(),
+                change: EntityChange::Removed,
+            });
+        }
+    }
+    Diff { entities }
+}
+
+/// Child -> parent mapping by GUID, from a list of (child, parent) indices
+pub(crate) fn parents_by_id<T: DiffEntity>(
+    entities: &[T],
+    parents: &[(i32, i32)],
+) -> HashMap<Uuid, Uuid> {
+    parents
+        .iter()
+        .filter_map(|(child, parent)| {
+            let child = entities.get(usize::try_from(*child).ok()?)?;
+            let parent = entities.get(usize::try_from(*parent).ok()?)?;
+            Some((child.id(), parent.id()))
+        })
+        .collect()
+}
+
+/// Adds the parent changes of entities present in both lists to `diff`
+fn diff_parents<T: DiffEntity>(
+    diff: &mut Diff<T>,
+    old: (&[T], &[(i32, i32)]),
+    new: (&[T], &[(i32, i32)]),
+) {
+    let old_ids = old.0.iter().map(|e| e.id()).collect::<Vec<_>>();
+    let old_parents = parents_by_id(old.0, old.1);
+    let new_parents = parents_by_id(new.0, new.1);
+    for entity in new.0 {
+        let id = entity.id();
+        if !old_ids.contains(&id) {
+            continue;
+        }
+        let (old_parent, new_parent) = (old_parents.get(&id), new_parents.get(&id));
+        if old_parent == new_parent {
+            continue;
+        }
+        let change = Change::Parent {
+            old: old_parent.copied(),
+            new: new_parent.copied(),
+        };
+        match diff.entities.iter_mut().find(|e| e.id == id) {
+            Some(EntityDiff {
+                change: EntityChange::Modified(changes),
+                ..
+            }) => changes.push(change),
+            _ => diff.entities.push(EntityDiff {
+                id,
+                name: entity.name().to_string(),
+                change: EntityChange::Modified(vec![change]),
+            }),
+        }
+    }
+}
+
+pub fn diff_lrent(old: &LrentFile, new: &LrentFile, tolerance: f32) -> Result<Diff<DynamicEntity>> {
+    let (Some(old_ctx), Some(new_ctx)) = (old.get_context(), new.get_context()) else {
+        return Err(Error::InvalidStructure(
+            "lrent does not contain an eCEntityDynamicContext".to_string(),
+        ));
+    };
+    let mut diff = diff_entities(&old_ctx.entities, &new_ctx.entities, tolerance);
+    diff_parents(
+        &mut diff,
+        (&old_ctx.entities, &old_ctx.parents),
+        (&new_ctx.entities, &new_ctx.parents),
+    );
+    Ok(diff)
+}
+
+pub fn diff_tple(old: &TpleFile, new: &TpleFile, tolerance: f32) -> Diff<TemplateEntity> {
+    let mut diff = diff_entities(&old.entities, &new.entities, tolerance);
+    diff_parents(
+        &mut diff,
+        (&old.entities, &old.parents),
+        (&new.entities, &new.parents),
+    );
+    diff
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Header { field, old, new } => write!(f, "{field}: {old} -> {new}"),
+            Change::Transform { old, new } => {
+                let (old_scale, old_rot, old_pos) = old.to_scale_rotation_translation();
+                let (new_scale, new_rot, new_pos) = new.to_scale_rotation_translation();
+                write!(
+                    f,
+                    "transform: pos {old_pos} -> {new_pos}, rot {old_rot} -> {new_rot}, scale {old_scale} -> {new_scale}"
+                )
+            }
+            Change::Parent { old, new } => {
+                let fmt_parent = |parent: &Option<Uuid>| match parent {
+                    Some(id) => format!("{{{id}}}"),
+                    None => "root".to_string(),
+                };
+                write!(f, "parent: {} -> {}", fmt_parent(old), fmt_parent(new))
+            }
+            Change::AccessorAdded { accessor } => {
+                write!(f, "+ accessor {}", accessor.object.get_class_name())
+            }
+            Change::AccessorRemoved { class } => write!(f, "- accessor {class}"),
+            Change::ClassData { class, .. } => write!(f, "{class}: class data changed"),
+            Change::PropertyAdded { class, property } => {
+                write!(f, "+ {class}.{} = {:?}", property.name, property.data)
+            }
+            Change::PropertyRemoved { class, name } => write!(f, "- {class}.{name}"),
+            Change::PropertyModified {
+                class,
+                name,
+                old,
+                new,
+            } => write!(f, "{class}.{name}: {old:?} -> {new:?}"),
+        }
+    }
+}
+
+impl<T> fmt::Display for Diff<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entity in &self.entities {
+            match &entity.change {
+                EntityChange::Added(_) => writeln!(f, "+ {{{}}} {}", entity.id, entity.name)?,
+                EntityChange::Removed => writeln!(f, "- {{{}}} {}", entity.id, entity.name)?,
+                EntityChange::Modified(changes) => {
+                    writeln!(f, "~ {{{}}} {}", entity.id, entity.name)?;
+                    for change in changes {
+                        writeln!(f, "    {change}")?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{entity, lrent, template};
+    use crate::types::Vec3;
+
+    #[test]
+    fn reparented_entity_is_modified() {
+        let old = lrent(
+            vec![
+                entity("Table", Vec3::new(10.0, 0.0, 0.0), Vec::new()),
+                entity("Shelf", Vec3::new(-10.0, 0.0, 0.0), Vec::new()),
+                entity("Cup", Vec3::new(0.0, 1.0, 0.0), Vec::new()),
+            ],
+            &[(2, 0)],
+        );
+        let mut new = old.clone();
+        new.get_context_mut().unwrap().parents = vec![(2, 1), (-1, -1)];
+        let ctx = old.get_context().unwrap();
+        let (table, shelf, cup) = (
+            ctx.entities[0].id(),
+            ctx.entities[1].id(),
+            ctx.entities[2].id(),
+        );
+
+        let diff = diff_lrent(&old, &new, DEFAULT_TOLERANCE).unwrap();
+        assert_eq!(diff.entities.len(), 1);
+        assert_eq!(diff.entities[0].id, cup);
+        let EntityChange::Modified(changes) = &diff.entities[0].change else {
+            panic!("expected a modification, got {:?}", diff.entities[0].change);
+        };
+        assert!(matches!(
+            changes.as_slice(),
+            [Change::Parent { old: Some(old), new: Some(new) }] if *old == table && *new == shelf
+        ));
+
+        // detached from its parent
+        new.get_context_mut().unwrap().parents = vec![(-1, -1)];
+        let diff = diff_lrent(&old, &new, DEFAULT_TOLERANCE).unwrap();
+        let EntityChange::Modified(changes) = &diff.entities[0].change else {
+            panic!("expected a modification, got {:?}", diff.entities[0].change);
+        };
+        assert!(matches!(
+            changes.as_slice(),
+            [Change::Parent {
+                old: Some(_),
+                new: None
+            }]
+        ));
+        assert!(
+            diff.to_string()
+                .contains(&format!("parent: {{{table}}} -> root"))
+        );
+    }
+
+    #[test]
+    fn tple_parents_are_diffed() {
+        let entities = vec![
+            template(
+                "{00000000-0000-0000-0000-000000000001}",
+                "Chest",
+                Vec::new(),
+            ),
+            template("{00000000-0000-0000-0000-000000000002}", "Lid", Vec::new()),
+        ];
+        let old = TpleFile {
+            entities: entities.clone(),
+            parents: vec![(1, 0), (-1, -1)],
+        };
+        let new = TpleFile {
+            entities,
+            parents: vec![(-1, -1)],
+        };
+        assert!(diff_tple(&old, &old.clone(), DEFAULT_TOLERANCE).is_empty());
+        let diff = diff_tple(&old, &new, DEFAULT_TOLERANCE);
+        assert_eq!(diff.entities.len(), 1);
+        assert_eq!(diff.entities[0].name, "Lid");
+    }
+}
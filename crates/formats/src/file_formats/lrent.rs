@@ -1,9 +1,11 @@
 use serde::{Deserialize, Serialize};
 
 use crate::helpers::*;
+use crate::types::template::{TemplateEntity, TemplatePropertyAccessor};
+use crate::types::{EntityDynamicContext, GenClass, Mat4, entity::DynamicEntity};
 use crate::{archive::PakFile, error::*, types::object::AccessorPropertyObject};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct LrentFile {
     pub root: AccessorPropertyObject,
 }
@@ -22,6 +24,56 @@ impl LrentFile {
         Ok(Self { root: lrent_file })
     }
 
+    /// The entity list of this file - usually nested inside the gCDynamicLayer
+    pub fn get_context(&self) -> Option<&EntityDynamicContext> {
+        let mut apo = &self.root;
+        loop {
+            match &apo.object.class {
+                GenClass::DynamicLayer(inner) => apo = inner,
+                GenClass::EntityDynamicContext(ctx) => return Some(ctx),
+                _ => return None,
+            }
+        }
+    }
+
+    pub fn get_context_mut(&mut self) -> Option<&mut EntityDynamicContext> {
+        let mut apo = &mut self.root;
+        loop {
+            match &mut apo.object.class {
+                GenClass::DynamicLayer(inner) => apo = inner,
+                GenClass::EntityDynamicContext(ctx) => return Some(ctx),
+                _ => return None,
+            }
+        }
+    }
+
+    /// Places a new entity created from `template` in this file, see [`DynamicEntity::from_template`].
+    /// Returns the index of the new entity.
+    pub fn instantiate_template(
+        &mut self,
+        template: &TemplateEntity,
+        properties: &[TemplatePropertyAccessor],
+        local_matrix: Mat4,
+        parent: Option<usize>,
+    ) -> Result<usize> {
+        let Some(ctx) = self.get_context_mut() else {
+            return Err(Error::InvalidStructure(
+                "lrent does not contain an eCEntityDynamicContext".to_string(),
+            ));
+        };
+        let parent_matrix = match parent {
+            Some(parent) if parent < ctx.entities.len() => ctx.get_world_matrix(parent),
+            _ => Mat4::IDENTITY,
+        };
+        let mut entity =
+            DynamicEntity::from_template(template, properties, local_matrix, parent_matrix)?;
+        // Keep the structure versions consistent within the file:
+        if let Some(existing) = ctx.entities.first() {
+            entity.adopt_versions(existing);
+        }
+        ctx.insert_entity(entity, parent)
+    }
+
     pub fn save(&self, arch: &mut PakFile) -> Result<()> {
         let magic = 0xd0defade;
         write_u32(arch, magic)?;
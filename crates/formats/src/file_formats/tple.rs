@@ -3,9 +3,11 @@ use std::io::{Read, Write};
 use serde::{Deserialize, Serialize};
 
 use crate::helpers::*;
+use crate::types::remove_from_hierarchy;
+use crate::types::template::TemplatePropertyAccessor;
 use crate::{archive::PakFile, error::*, types::template::TemplateEntity};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TpleFile {
     pub entities: Vec<TemplateEntity>,
 
@@ -48,6 +50,48 @@ impl TpleFile {
         Ok(Self { entities, parents })
     }
 
+    /// Looks up a template by its GUID (with or without braces) or, failing that, by name
+    pub fn find_template(&self, key: &str) -> Option<&TemplateEntity> {
+        if let Ok(id) = uuid::Uuid::parse_str(key.trim_matches(['{', '}']))
+            && let Some(entity) = self.entities.iter().find(|e| e.id.id == id)
+        {
+            return Some(entity);
+        }
+        self.entities.iter().find(|e| e.name == key)
+    }
+
+    /// Property sets used by `template`, following `ref_template` if it refers to another
+    /// template within this file.
+    pub fn resolve_properties<'a>(
+        &'a self,
+        template: &'a TemplateEntity,
+    ) -> &'a [TemplatePropertyAccessor] {
+        let mut current = template;
+        // guard against reference cycles:
+        for _ in 0..=self.entities.len() {
+            let Some(ref_id) = &current.ref_template else {
+                break;
+            };
+            match self.entities.iter().find(|e| e.id.id == ref_id.id) {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+        &current.properties
+    }
+
+    /// Removes the template at `idx`, which must not have any children
+    pub fn remove_template(&mut self, idx: usize) -> Result<TemplateEntity> {
+        if idx >= self.entities.len() {
+            return Err(Error::InvalidOp(format!(
+                "Template {idx} does not exist, file only has {} templates",
+                self.entities.len()
+            )));
+        }
+        remove_from_hierarchy(&mut self.parents, idx)?;
+        Ok(self.entities.remove(idx))
+    }
+
     pub fn save(&self, arch: &mut PakFile) -> Result<()> {
         let magic = "GENOMETP".as_bytes();
         arch.write_all(magic)?;
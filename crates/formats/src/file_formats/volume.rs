@@ -0,0 +1,326 @@
+//! Volumes (`.pak`), the archives in `data/compiled` that the game's virtual file system reads assets from.
+//!
+//! Only the directory is decoded, file contents are read on demand with [`VolumeFile::read_file`].
+//! The layout follows the community description of the Gothic 3 volume format (`G3V0`), which Risen reuses;
+//! it has not been verified against the volumes shipped with the game yet.
+use std::io::{Read, Seek, SeekFrom};
+
+use flate2::read::ZlibDecoder;
+use serde::Serialize;
+
+use crate::error::*;
+use crate::helpers::*;
+
+const PRODUCT: &[u8; 4] = b"G3V0";
+const ATTRIBUTE_DIRECTORY: u32 = 0x10;
+/// Guards against reading garbage as a deeply nested directory
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Compression {
+    None,
+    Zlib,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VolumeEntry {
+    /// Path within the volume, with `/` as separator
+    pub path: String,
+    pub offset: u64,
+    /// Size within the volume
+    pub stored_size: u64,
+    /// Size after decompression
+    pub size: u64,
+    pub compression: Compression,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VolumeFile {
+    /// All files, directories are left out
+    pub files: Vec<VolumeEntry>,
+}
+
+impl VolumeFile {
+    pub fn load<R: Read + Seek>(src: &mut R) -> Result<Self> {
+        let version = read_u32(src)?;
+        let mut product = [0u8; 4];
+        src.read_exact(&mut product)?;
+        if &product != PRODUCT || version != 0 {
+            return Err(Error::UnknownVersion(format!(
+                "Not a volume: {:?}, version {version}",
+                String::from_utf8_lossy(&product)
+            )));
+        }
+        let _revision = read_u32(src)?;
+        let encryption = read_u32(src)?;
+        if encryption != 0 {
+            return Err(Error::InvalidStructure(
+                "Encrypted volumes are not supported".to_string(),
+            ));
+        }
+        let _compression = read_u32(src)?;
+        let _reserved = read_u32(src)?;
+        let _offset_to_files = read_u64(src)?;
+        let offset_to_folders = read_u64(src)?;
+        let offset_to_volume = read_u64(src)?;
+
+        src.seek(SeekFrom::Start(offset_to_folders))?;
+        let mut volume = Self::default();
+        volume.load_entry(src, "", offset_to_volume, 0)?;
+        Ok(volume)
+    }
+
+    fn load_entry<R: Read + Seek>(
+        &mut self,
+        src: &mut R,
+        parent: &str,
+        end: u64,
+        depth: usize,
+    ) -> Result<()> {
+        if depth > MAX_DEPTH {
+            return Err(Error::InvalidStructure(
+                "Volume directory is nested too deeply".to_string(),
+            ));
+        }
+        // creation, last access and last write time:
+        src.seek(SeekFrom::Current(3 * 8))?;
+        let _file_size = read_u64(src)?;
+        let attributes = read_u32(src)?;
+        if attributes & ATTRIBUTE_DIRECTORY != 0 {
+            let name = read_string(src)?;
+            // the root is named after the volume, the paths of the files start below it:
+            let path = if depth == 0 {
+                String::new()
+            } else {
+                join(parent, &name)
+            };
+            let count = read_u32(src)?;
+            for _ in 0..count {
+                self.load_entry(src, &path, end, depth + 1)?;
+            }
+            return Ok(());
+        }
+        let offset = read_u64(src)?;
+        let stored_size = read_u64(src)?;
+        let size = read_u64(src)?;
+        let _encryption = read_u32(src)?;
+        let compression = match read_u32(src)? {
+            0 => Compression::None,
+            2 => Compression::Zlib,
+            other => {
+                return Err(Error::InvalidStructure(format!(
+                    "Unknown volume compression {other}"
+                )));
+            }
+        };
+        let name = read_string(src)?;
+        let _comment = read_string(src)?;
+        if offset
+            .checked_add(stored_size)
+            .is_none_or(|file_end| file_end > end)
+        {
+            return Err(Error::InvalidStructure(format!(
+                "Volume entry {name} exceeds the volume"
+            )));
+        }
+        self.files.push(VolumeEntry {
+            path: join(parent, &name),
+            offset,
+            stored_size,
+            size,
+            compression,
+        });
+        Ok(())
+    }
+
+    /// Case-insensitive lookup of the file at `path`
+    pub fn find(&self, path: &str) -> Option<&VolumeEntry> {
+        let path = path.replace('\\', "/");
+        self.files
+            .iter()
+            .find(|f| f.path.eq_ignore_ascii_case(&path))
+    }
+
+    /// Reads and decompresses the contents of `entry`
+    pub fn read_file<R: Read + Seek>(&self, src: &mut R, entry: &VolumeEntry) -> Result<Vec<u8>> {
+        src.seek(SeekFrom::Start(entry.offset))?;
+        let mut stored = src.take(entry.stored_size);
+        let mut data = Vec::new();
+        match entry.compression {
+            Compression::None => stored.read_to_end(&mut data)?,
+            Compression::Zlib => ZlibDecoder::new(stored).read_to_end(&mut data)?,
+        };
+        if data.len() as u64 != entry.size {
+            return Err(Error::InvalidStructure(format!(
+                "{} has {} bytes, expected {}",
+                entry.path,
+                data.len(),
+                entry.size
+            )));
+        }
+        Ok(data)
+    }
+}
+
+/// Length-prefixed string, followed by a terminating zero unless it is empty
+fn read_string<R: Read>(src: &mut R) -> Result<String> {
+    let len = read_u32(src)? as usize;
+    if len == 0 {
+        return Ok(String::new());
+    }
+    let mut data = Vec::new();
+    src.take(len as u64 + 1).read_to_end(&mut data)?;
+    if data.len() != len + 1 {
+        return Err(Error::InvalidStructure(
+            "Volume directory ends within a name".to_string(),
+        ));
+    }
+    data.pop();
+    let (name, _, _) = encoding_rs::WINDOWS_1252.decode(&data);
+    Ok(name.into_owned())
+}
+
+/// Names in the volume may be relative to their directory or contain the whole path
+fn join(parent: &str, name: &str) -> String {
+    let name = name.replace('\\', "/");
+    if parent.is_empty()
+        || name
+            .to_lowercase()
+            .starts_with(&format!("{}/", parent.to_lowercase()))
+    {
+        name
+    } else {
+        format!("{parent}/{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use flate2::{Compression as Level, write::ZlibEncoder};
+
+    use super::*;
+
+    enum Node<'a> {
+        Dir(&'a str, Vec<Node<'a>>),
+        File(&'a str, &'a [u8], bool),
+    }
+
+    fn write_string(out: &mut Vec<u8>, text: &str) {
+        write_u32(out, text.len() as u32).unwrap();
+        if !text.is_empty() {
+            out.extend_from_slice(text.as_bytes());
+            out.push(0);
+        }
+    }
+
+    fn write_entry(out: &mut Vec<u8>, data: &mut Vec<u8>, node: &Node) {
+        out.extend_from_slice(&[0; 3 * 8]);
+        match node {
+            Node::Dir(name, children) => {
+                write_u64(out, 0).unwrap();
+                write_u32(out, ATTRIBUTE_DIRECTORY).unwrap();
+                write_string(out, name);
+                write_u32(out, children.len() as u32).unwrap();
+                for child in children {
+                    write_entry(out, data, child);
+                }
+            }
+            Node::File(name, content, compressed) => {
+                let stored = if *compressed {
+                    let mut encoder = ZlibEncoder::new(Vec::new(), Level::default());
+                    encoder.write_all(content).unwrap();
+                    encoder.finish().unwrap()
+                } else {
+                    content.to_vec()
+                };
+                write_u64(out, content.len() as u64).unwrap();
+                write_u32(out, 0x20).unwrap();
+                write_u64(out, 48 + data.len() as u64).unwrap();
+                write_u64(out, stored.len() as u64).unwrap();
+                write_u64(out, content.len() as u64).unwrap();
+                write_u32(out, 0).unwrap();
+                write_u32(out, if *compressed { 2 } else { 0 }).unwrap();
+                write_string(out, name);
+                write_string(out, "");
+                data.extend(stored);
+            }
+        }
+    }
+
+    /// Header, file contents, then the directory
+    fn volume(root: &Node) -> Vec<u8> {
+        let (mut dir, mut data) = (Vec::new(), Vec::new());
+        write_entry(&mut dir, &mut data, root);
+        let mut out = Vec::new();
+        for value in [0, u32::from_le_bytes(*PRODUCT), 0, 0, 1, 0] {
+            write_u32(&mut out, value).unwrap();
+        }
+        let folders = 48 + data.len() as u64;
+        for offset in [48, folders, folders + dir.len() as u64] {
+            write_u64(&mut out, offset).unwrap();
+        }
+        out.extend(data);
+        out.extend(dir);
+        out
+    }
+
+    #[test]
+    fn list_and_read_files() {
+        let root = Node::Dir(
+            "meshes",
+            vec![
+                Node::File("Obj_Barrel_01._xmac", b"barrel", false),
+                Node::Dir(
+                    "Nature",
+                    vec![
+                        Node::File("Nat_Tree._xmac", b"tree tree tree tree", true),
+                        Node::File("Nature/Nat_Rock._xmac", b"", false),
+                    ],
+                ),
+            ],
+        );
+        let mut src = Cursor::new(volume(&root));
+        let volume = VolumeFile::load(&mut src).unwrap();
+        let paths = volume
+            .files
+            .iter()
+            .map(|f| f.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                "Obj_Barrel_01._xmac",
+                "Nature/Nat_Tree._xmac",
+                "Nature/Nat_Rock._xmac"
+            ]
+        );
+
+        let tree = volume.find("nature\\nat_tree._XMAC").unwrap();
+        assert_eq!(tree.compression, Compression::Zlib);
+        assert_eq!(
+            volume.read_file(&mut src, tree).unwrap(),
+            b"tree tree tree tree"
+        );
+        let barrel = &volume.files[0];
+        assert_eq!(volume.read_file(&mut src, barrel).unwrap(), b"barrel");
+        assert!(volume.find("Nat_Tree._xmac").is_none());
+    }
+
+    #[test]
+    fn reject_invalid_volumes() {
+        let mut data = volume(&Node::Dir(
+            "meshes",
+            vec![Node::File("Obj_Barrel_01._xmac", b"barrel", false)],
+        ));
+        assert!(VolumeFile::load(&mut Cursor::new(b"GENOMFLE".to_vec())).is_err());
+        // the file would end after the volume:
+        let mut truncated = data.clone();
+        truncated[40..48].copy_from_slice(&50u64.to_le_bytes());
+        assert!(VolumeFile::load(&mut Cursor::new(truncated)).is_err());
+        // the directory ends early:
+        data.truncate(data.len() - 4);
+        assert!(VolumeFile::load(&mut Cursor::new(data)).is_err());
+    }
+}
@@ -0,0 +1,214 @@
+//! World (`.wrl`) and sector (`.sec`) files, which tie the dynamic layers (`.lrent`) of a world together.
+//! Both are stored like lrent files: an archive containing a single accessor property object.
+//!
+//! Listing and editing sectors/layers only works if the class data was decoded, see [`crate::types::world`].
+//! Class data with a different layout is kept opaque and never rewritten, the edit functions fail instead.
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::*;
+use crate::types::GenClass;
+use crate::types::world::{Sector, World};
+use crate::{archive::PakFile, error::*, types::object::AccessorPropertyObject};
+
+const MAGIC: u32 = 0xd0defade;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SectorFile {
+    pub root: AccessorPropertyObject,
+}
+
+impl SectorFile {
+    pub fn load(mut arch: PakFile) -> Result<Self> {
+        let root = load_root(&mut arch)?;
+        Ok(Self { root })
+    }
+
+    pub fn get_sector(&self) -> Option<&Sector> {
+        match &self.root.object.class {
+            GenClass::Sector(sector) => Some(sector),
+            _ => None,
+        }
+    }
+
+    pub fn get_sector_mut(&mut self) -> Option<&mut Sector> {
+        match &mut self.root.object.class {
+            GenClass::Sector(sector) => Some(sector),
+            _ => None,
+        }
+    }
+
+    /// Names of the dynamic layers (lrent files without extension) of this sector
+    pub fn layers(&self) -> Result<&[String]> {
+        Ok(&self.sector()?.layers)
+    }
+
+    pub fn add_layer(&mut self, name: &str) -> Result<()> {
+        add_name(&mut self.sector_mut()?.layers, name, "layer")
+    }
+
+    pub fn remove_layer(&mut self, name: &str) -> Result<()> {
+        remove_name(&mut self.sector_mut()?.layers, name, "layer")
+    }
+
+    /// A new, empty sector with the same properties as this one
+    pub fn new_like(&self) -> Result<Self> {
+        let mut new = self.clone();
+        new.sector_mut()?.layers.clear();
+        Ok(new)
+    }
+
+    pub fn save(&self, arch: &mut PakFile) -> Result<()> {
+        write_u32(arch, MAGIC)?;
+        self.root.save(arch)
+    }
+
+    fn sector(&self) -> Result<&Sector> {
+        self.get_sector().ok_or_else(not_parsed("gCSector"))
+    }
+
+    fn sector_mut(&mut self) -> Result<&mut Sector> {
+        self.get_sector_mut().ok_or_else(not_parsed("gCSector"))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WorldFile {
+    pub root: AccessorPropertyObject,
+}
+
+impl WorldFile {
+    pub fn load(mut arch: PakFile) -> Result<Self> {
+        let root = load_root(&mut arch)?;
+        Ok(Self { root })
+    }
+
+    pub fn get_world(&self) -> Option<&World> {
+        match &self.root.object.class {
+            GenClass::World(world) => Some(world),
+            _ => None,
+        }
+    }
+
+    pub fn get_world_mut(&mut self) -> Option<&mut World> {
+        match &mut self.root.object.class {
+            GenClass::World(world) => Some(world),
+            _ => None,
+        }
+    }
+
+    /// Names of the sectors (sec files without extension) of this world
+    pub fn sectors(&self) -> Result<&[String]> {
+        Ok(&self.world()?.sectors)
+    }
+
+    pub fn add_sector(&mut self, name: &str) -> Result<()> {
+        add_name(&mut self.world_mut()?.sectors, name, "sector")
+    }
+
+    pub fn remove_sector(&mut self, name: &str) -> Result<()> {
+        remove_name(&mut self.world_mut()?.sectors, name, "sector")
+    }
+
+    pub fn save(&self, arch: &mut PakFile) -> Result<()> {
+        write_u32(arch, MAGIC)?;
+        self.root.save(arch)
+    }
+
+    fn world(&self) -> Result<&World> {
+        self.get_world().ok_or_else(not_parsed("gCWorld"))
+    }
+
+    fn world_mut(&mut self) -> Result<&mut World> {
+        self.get_world_mut().ok_or_else(not_parsed("gCWorld"))
+    }
+}
+
+fn load_root(arch: &mut PakFile) -> Result<AccessorPropertyObject> {
+    let magic = read_u32(arch)?;
+    if magic != MAGIC {
+        return Err(Error::InvalidStructure(format!(
+            "Unexpected magic {magic:x}, expected {MAGIC:x}"
+        )));
+    }
+    AccessorPropertyObject::load(arch)
+}
+
+fn not_parsed(class: &'static str) -> impl FnOnce() -> Error {
+    move || Error::InvalidStructure(format!("File does not contain a parsed {class}"))
+}
+
+fn add_name(names: &mut Vec<String>, name: &str, kind: &str) -> Result<()> {
+    if names.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+        return Err(Error::InvalidOp(format!("{kind} {name} already exists")));
+    }
+    names.push(name.to_string());
+    Ok(())
+}
+
+fn remove_name(names: &mut Vec<String>, name: &str, kind: &str) -> Result<()> {
+    let Some(idx) = names.iter().position(|n| n.eq_ignore_ascii_case(name)) else {
+        return Err(Error::InvalidOp(format!("{kind} {name} does not exist")));
+    };
+    names.remove(idx);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world_archive(sectors: &[&str]) -> PakFile {
+        let mut root = AccessorPropertyObject::new();
+        root.object.class = GenClass::World(World {
+            version: 1,
+            sectors: sectors.iter().map(|s| s.to_string()).collect(),
+        });
+        let mut arch = PakFile::new();
+        WorldFile { root }.save(&mut arch).unwrap();
+        arch
+    }
+
+    fn reload(mut arch: PakFile) -> PakFile {
+        arch.current_read_idx = 0;
+        arch
+    }
+
+    #[test]
+    fn world_round_trip() {
+        let arch = world_archive(&["Sector_A", "Sector_B"]);
+        let data = arch.data.clone();
+        let mut world = WorldFile::load(reload(arch)).unwrap();
+        assert_eq!(world.sectors().unwrap(), ["Sector_A", "Sector_B"]);
+
+        let mut out = PakFile::new();
+        world.save(&mut out).unwrap();
+        assert_eq!(out.data, data);
+
+        world.add_sector("Sector_C").unwrap();
+        assert!(world.add_sector("sector_c").is_err());
+        world.remove_sector("Sector_A").unwrap();
+        let mut out = PakFile::new();
+        world.save(&mut out).unwrap();
+        let world = WorldFile::load(reload(out)).unwrap();
+        assert_eq!(world.sectors().unwrap(), ["Sector_B", "Sector_C"]);
+    }
+
+    #[test]
+    fn unknown_layout_is_not_edited() {
+        let mut arch = world_archive(&["Sector_A", "Sector_B"]);
+        // the class data (version, count and string indices) is at the end, claim a third sector:
+        let count_offset = arch.data.len() - 2 * 2 - 4;
+        arch.data[count_offset..count_offset + 4].copy_from_slice(&3u32.to_le_bytes());
+        let data = arch.data.clone();
+
+        let mut world = WorldFile::load(reload(arch)).unwrap();
+        assert!(world.get_world().is_none());
+        assert!(world.sectors().is_err());
+        assert!(world.add_sector("Sector_C").is_err());
+        assert!(world.remove_sector("Sector_A").is_err());
+
+        let mut out = PakFile::new();
+        world.save(&mut out).unwrap();
+        assert_eq!(out.data, data);
+    }
+}
@@ -12,11 +12,19 @@ use crate::archive::TempWriteTarget;
 use crate::error::*;
 use crate::helpers::*;
 
+pub mod attachment_nodes;
+pub mod fx_material;
+pub mod generic_material;
 pub mod info;
+pub mod limit;
 pub mod material;
+pub mod material_attribute_set;
 pub mod material_info;
 pub mod mesh;
+pub mod mesh_lod_levels;
 pub mod morph_targets;
+pub mod node_groups;
+pub mod node_motion_sources;
 pub mod nodes;
 pub mod skinning_info;
 pub mod unknown;
@@ -28,8 +36,17 @@ pub enum XmacChunk {
     MaterialInfo(material_info::XmacMaterialInfo),
     StdMaterial(material::XmacStdMaterial),
     Mesh(mesh::XmacMesh),
+    MeshLodLevels(mesh_lod_levels::XmacMeshLodLevels),
     SkinningInfo(skinning_info::XmacSkinningInfo),
     MorphTargets(morph_targets::XmacMorphTargets),
+    StdMaterialLayer(material::XmacStandardMaterialLayer),
+    FxMaterial(fx_material::XmacFxMaterial),
+    GenericMaterial(generic_material::XmacGenericMaterial),
+    MaterialAttributeSet(material_attribute_set::XmacMaterialAttributeSet),
+    Limit(limit::XmacLimit),
+    NodeGroups(node_groups::XmacNodeGroups),
+    NodeMotionSources(node_motion_sources::XmacNodeMotionSources),
+    AttachmentNodes(attachment_nodes::XmacAttachmentNodes),
     Unknown(unknown::XmacUnknownChunk),
 }
 
@@ -113,6 +130,17 @@ impl XmacChunk {
                     src,
                     &chunk_meta,
                 ),
+                XmacChunkType::MeshLodLevels => Self::load_unknown(
+                    mesh_lod_levels::XmacMeshLodLevels::load(
+                        src,
+                        big_endian,
+                        multiply_order,
+                        &chunk_meta,
+                    )?
+                    .map(XmacChunk::MeshLodLevels),
+                    src,
+                    &chunk_meta,
+                ),
                 XmacChunkType::SkinningInfo => {
                     let meshes = Self::get_meshes(prev_chunks);
                     Self::load_unknown(
@@ -139,6 +167,89 @@ impl XmacChunk {
                     src,
                     &chunk_meta,
                 ),
+                XmacChunkType::StdMaterialLayer => Self::load_unknown(
+                    material::XmacStandardMaterialLayer::load_chunk(
+                        src,
+                        big_endian,
+                        multiply_order,
+                        &chunk_meta,
+                    )?
+                    .map(XmacChunk::StdMaterialLayer),
+                    src,
+                    &chunk_meta,
+                ),
+                XmacChunkType::FxMaterial => Self::load_unknown(
+                    fx_material::XmacFxMaterial::load(
+                        src,
+                        big_endian,
+                        multiply_order,
+                        &chunk_meta,
+                    )?
+                    .map(XmacChunk::FxMaterial),
+                    src,
+                    &chunk_meta,
+                ),
+                XmacChunkType::GenericMaterial => Self::load_unknown(
+                    generic_material::XmacGenericMaterial::load(
+                        src,
+                        big_endian,
+                        multiply_order,
+                        &chunk_meta,
+                    )?
+                    .map(XmacChunk::GenericMaterial),
+                    src,
+                    &chunk_meta,
+                ),
+                XmacChunkType::MaterialAttributeSet => Self::load_unknown(
+                    material_attribute_set::XmacMaterialAttributeSet::load(
+                        src,
+                        big_endian,
+                        multiply_order,
+                        &chunk_meta,
+                    )?
+                    .map(XmacChunk::MaterialAttributeSet),
+                    src,
+                    &chunk_meta,
+                ),
+                XmacChunkType::Limit => Self::load_unknown(
+                    limit::XmacLimit::load(src, big_endian, multiply_order, &chunk_meta)?
+                        .map(XmacChunk::Limit),
+                    src,
+                    &chunk_meta,
+                ),
+                XmacChunkType::NodeGroups => Self::load_unknown(
+                    node_groups::XmacNodeGroups::load(
+                        src,
+                        big_endian,
+                        multiply_order,
+                        &chunk_meta,
+                    )?
+                    .map(XmacChunk::NodeGroups),
+                    src,
+                    &chunk_meta,
+                ),
+                XmacChunkType::NodeMotionSources => Self::load_unknown(
+                    node_motion_sources::XmacNodeMotionSources::load(
+                        src,
+                        big_endian,
+                        multiply_order,
+                        &chunk_meta,
+                    )?
+                    .map(XmacChunk::NodeMotionSources),
+                    src,
+                    &chunk_meta,
+                ),
+                XmacChunkType::AttachmentNodes => Self::load_unknown(
+                    attachment_nodes::XmacAttachmentNodes::load(
+                        src,
+                        big_endian,
+                        multiply_order,
+                        &chunk_meta,
+                    )?
+                    .map(XmacChunk::AttachmentNodes),
+                    src,
+                    &chunk_meta,
+                ),
                 _ => {
                     println!(
                         "Unimplemented XMAC chunk {chunk_type:?}.{}@{:x}",
@@ -182,12 +293,37 @@ impl XmacChunk {
                 xmac_std_material.save(&mut data, big_endian)?
             }
             XmacChunk::Mesh(xmac_mesh) => xmac_mesh.save(&mut data, big_endian)?,
+            XmacChunk::MeshLodLevels(xmac_mesh_lod_levels) => {
+                xmac_mesh_lod_levels.save(&mut data, big_endian)?
+            }
             XmacChunk::SkinningInfo(xmac_skinning_info) => {
                 xmac_skinning_info.save(&mut data, big_endian)?
             }
             XmacChunk::MorphTargets(xmac_morph_targets) => {
                 xmac_morph_targets.save(&mut data, big_endian)?
             }
+            XmacChunk::StdMaterialLayer(xmac_layer) => {
+                xmac_layer.save_chunk(&mut data, big_endian)?
+            }
+            XmacChunk::FxMaterial(xmac_fx_material) => {
+                xmac_fx_material.save(&mut data, big_endian)?
+            }
+            XmacChunk::GenericMaterial(xmac_generic_material) => {
+                xmac_generic_material.save(&mut data, big_endian)?
+            }
+            XmacChunk::MaterialAttributeSet(xmac_attribute_set) => {
+                xmac_attribute_set.save(&mut data, big_endian)?
+            }
+            XmacChunk::Limit(xmac_limit) => xmac_limit.save(&mut data, big_endian)?,
+            XmacChunk::NodeGroups(xmac_node_groups) => {
+                xmac_node_groups.save(&mut data, big_endian)?
+            }
+            XmacChunk::NodeMotionSources(xmac_motion_sources) => {
+                xmac_motion_sources.save(&mut data, big_endian)?
+            }
+            XmacChunk::AttachmentNodes(xmac_attachment_nodes) => {
+                xmac_attachment_nodes.save(&mut data, big_endian)?
+            }
             XmacChunk::Unknown(xmac_unknown_chunk) => xmac_unknown_chunk.save(&mut data)?,
         };
         let data = data.finish();
@@ -207,12 +343,36 @@ impl XmacChunk {
         })
     }
 
-    fn get_meshes(prev_chunks: &[XmacChunk]) -> HashMap<XmacNodeId, &XmacMesh> {
+    /// For layouts that are only known from the EMotionFX sources:
+    /// rewinds and returns `None` if `load` fails or doesn't end exactly at the chunk end.
+    pub(crate) fn try_load_exact<R: ArchiveReadTarget, T>(
+        src: &mut R,
+        chunk_meta: &XmacChunkMeta,
+        load: impl FnOnce(&mut R) -> Result<T>,
+    ) -> Result<Option<T>> {
+        let start = src.stream_position()?;
+        let result = load(src);
+        let end = src.stream_position()?;
+        match result {
+            Ok(result) if end == start + chunk_meta.size as u64 => Ok(Some(result)),
+            _ => {
+                println!(
+                    "Chunk {}.{}@{start:x} doesn't match the expected layout",
+                    chunk_meta.type_id, chunk_meta.version
+                );
+                src.seek(std::io::SeekFrom::Start(start))?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Meshes by node and collision flag, a node can have both a render and a collision mesh
+    fn get_meshes(prev_chunks: &[XmacChunk]) -> HashMap<(XmacNodeId, bool), &XmacMesh> {
         prev_chunks
             .iter()
             .filter_map(|c| {
                 if let XmacChunk::Mesh(mesh) = c {
-                    Some((mesh.node_id, mesh))
+                    Some(((mesh.node_id, mesh.is_collision_mesh), mesh))
                 } else {
                     None
                 }
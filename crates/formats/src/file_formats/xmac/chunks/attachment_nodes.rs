@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+use super::XmacChunk;
+use super::XmacChunkMeta;
+use super::XmacChunkType;
+use crate::archive::ArchiveReadTarget;
+use crate::archive::ArchiveWriteTarget;
+use crate::error::*;
+use crate::helpers::*;
+
+/// Nodes that other actors (weapons, ...) can be attached to
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct XmacAttachmentNodes {
+    pub nodes: Vec<u16>,
+}
+
+impl XmacAttachmentNodes {
+    pub fn load<R: ArchiveReadTarget>(
+        src: &mut R,
+        big_endian: bool,
+        _multiply_order: bool,
+        chunk_meta: &XmacChunkMeta,
+    ) -> Result<Option<Self>> {
+        println!("Loading ATTACHMENT NODES chunk...");
+        match chunk_meta.version {
+            1 => XmacChunk::try_load_exact(src, chunk_meta, |src| {
+                let count = read_u32_endian(src, big_endian)? as usize;
+                if count * 2 + 4 != chunk_meta.size as usize {
+                    return Err(Error::InvalidStructure(format!(
+                        "{count} attachment nodes don't fit the chunk size"
+                    )));
+                }
+                let mut nodes = Vec::with_capacity(count);
+                for _idx in 0..count {
+                    nodes.push(read_u16_endian(src, big_endian)?);
+                }
+                Ok(Self { nodes })
+            }),
+            ver => {
+                println!(
+                    "Unknown XMAC attachment nodes version {ver}@{:x}, skipping",
+                    src.stream_position()?
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn save<W: ArchiveWriteTarget>(
+        &self,
+        dst: &mut W,
+        big_endian: bool,
+    ) -> Result<XmacChunkMeta> {
+        println!("Saving ATTACHMENT NODES chunk...");
+        write_u32_endian(dst, self.nodes.len() as u32, big_endian)?;
+        for node in &self.nodes {
+            write_u16_endian(dst, *node, big_endian)?;
+        }
+        Ok(XmacChunkMeta {
+            type_id: XmacChunkType::AttachmentNodes.into(),
+            size: (4 + self.nodes.len() * 2) as u32,
+            version: 1,
+        })
+    }
+}
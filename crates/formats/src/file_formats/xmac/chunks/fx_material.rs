@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+
+use super::super::{read_xmac_str, write_xmac_str};
+
+use super::XmacChunk;
+use super::XmacChunkMeta;
+use super::XmacChunkType;
+use crate::archive::ArchiveReadTarget;
+use crate::archive::ArchiveWriteTarget;
+use crate::binimport::BinImport;
+use crate::error::*;
+use crate::helpers::*;
+use crate::types::Vec4;
+
+/// Material that is rendered by an effect file, configured by named parameters
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct XmacFxMaterial {
+    pub name: String,
+    pub effect_file: String,
+    pub int_params: Vec<XmacFxParam<i32>>,
+    pub float_params: Vec<XmacFxParam<f32>>,
+    pub color_params: Vec<XmacFxParam<Vec4>>,
+    /// The value is the texture file name
+    pub bitmap_params: Vec<XmacFxParam<String>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct XmacFxParam<T> {
+    pub name: String,
+    pub value: T,
+}
+
+impl XmacFxMaterial {
+    pub fn load<R: ArchiveReadTarget>(
+        src: &mut R,
+        big_endian: bool,
+        _multiply_order: bool,
+        chunk_meta: &XmacChunkMeta,
+    ) -> Result<Option<Self>> {
+        println!("Loading FX MATERIAL chunk...");
+        match chunk_meta.version {
+            1 => XmacChunk::try_load_exact(src, chunk_meta, |src| {
+                let int_count = read_u32_endian(src, big_endian)?;
+                let float_count = read_u32_endian(src, big_endian)?;
+                let color_count = read_u32_endian(src, big_endian)?;
+                let bitmap_count = read_u32_endian(src, big_endian)?;
+                let name = read_xmac_str(src, big_endian)?;
+                let effect_file = read_xmac_str(src, big_endian)?;
+                // no preallocation, bogus counts run into invalid strings or the end of the file
+                let mut int_params = Vec::new();
+                for _idx in 0..int_count {
+                    let value = read_i32_endian(src, big_endian)?;
+                    let name = read_xmac_str(src, big_endian)?;
+                    int_params.push(XmacFxParam { name, value });
+                }
+                let mut float_params = Vec::new();
+                for _idx in 0..float_count {
+                    let value = read_f32_endian(src, big_endian)?;
+                    let name = read_xmac_str(src, big_endian)?;
+                    float_params.push(XmacFxParam { name, value });
+                }
+                let mut color_params = Vec::new();
+                for _idx in 0..color_count {
+                    let value = Vec4::load_endian(src, big_endian)?;
+                    let name = read_xmac_str(src, big_endian)?;
+                    color_params.push(XmacFxParam { name, value });
+                }
+                let mut bitmap_params = Vec::new();
+                for _idx in 0..bitmap_count {
+                    let name = read_xmac_str(src, big_endian)?;
+                    let value = read_xmac_str(src, big_endian)?;
+                    bitmap_params.push(XmacFxParam { name, value });
+                }
+                Ok(Self {
+                    name,
+                    effect_file,
+                    int_params,
+                    float_params,
+                    color_params,
+                    bitmap_params,
+                })
+            }),
+            ver => {
+                println!(
+                    "Unknown XMAC fx material version {ver}@{:x}, skipping",
+                    src.stream_position()?
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn save<W: ArchiveWriteTarget>(
+        &self,
+        dst: &mut W,
+        big_endian: bool,
+    ) -> Result<XmacChunkMeta> {
+        println!("Saving FX MATERIAL chunk...");
+        write_u32_endian(dst, self.int_params.len() as u32, big_endian)?;
+        write_u32_endian(dst, self.float_params.len() as u32, big_endian)?;
+        write_u32_endian(dst, self.color_params.len() as u32, big_endian)?;
+        write_u32_endian(dst, self.bitmap_params.len() as u32, big_endian)?;
+        let mut written = 4 * 4;
+        written += write_xmac_str(dst, &self.name, big_endian)?;
+        written += write_xmac_str(dst, &self.effect_file, big_endian)?;
+        for param in &self.int_params {
+            write_i32_endian(dst, param.value, big_endian)?;
+            written += 4 + write_xmac_str(dst, &param.name, big_endian)?;
+        }
+        for param in &self.float_params {
+            write_f32_endian(dst, param.value, big_endian)?;
+            written += 4 + write_xmac_str(dst, &param.name, big_endian)?;
+        }
+        for param in &self.color_params {
+            param.value.save_endian(dst, big_endian)?;
+            written += 16 + write_xmac_str(dst, &param.name, big_endian)?;
+        }
+        for param in &self.bitmap_params {
+            written += write_xmac_str(dst, &param.name, big_endian)?;
+            written += write_xmac_str(dst, &param.value, big_endian)?;
+        }
+        Ok(XmacChunkMeta {
+            type_id: XmacChunkType::FxMaterial.into(),
+            size: written as u32,
+            version: 1,
+        })
+    }
+}
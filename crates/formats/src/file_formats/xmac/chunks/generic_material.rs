@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+use super::super::{read_xmac_str, write_xmac_str};
+
+use super::XmacChunk;
+use super::XmacChunkMeta;
+use super::XmacChunkType;
+use crate::archive::ArchiveReadTarget;
+use crate::archive::ArchiveWriteTarget;
+use crate::error::*;
+use crate::helpers::*;
+
+/// Material without any properties, only a name for the engine to resolve
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct XmacGenericMaterial {
+    pub lod_level: u32,
+    pub name: String,
+}
+
+impl XmacGenericMaterial {
+    pub fn load<R: ArchiveReadTarget>(
+        src: &mut R,
+        big_endian: bool,
+        _multiply_order: bool,
+        chunk_meta: &XmacChunkMeta,
+    ) -> Result<Option<Self>> {
+        println!("Loading GENERIC MATERIAL chunk...");
+        match chunk_meta.version {
+            1 => XmacChunk::try_load_exact(src, chunk_meta, |src| {
+                let lod_level = read_u32_endian(src, big_endian)?;
+                let name = read_xmac_str(src, big_endian)?;
+                Ok(Self { lod_level, name })
+            }),
+            ver => {
+                println!(
+                    "Unknown XMAC generic material version {ver}@{:x}, skipping",
+                    src.stream_position()?
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn save<W: ArchiveWriteTarget>(
+        &self,
+        dst: &mut W,
+        big_endian: bool,
+    ) -> Result<XmacChunkMeta> {
+        println!("Saving GENERIC MATERIAL chunk...");
+        write_u32_endian(dst, self.lod_level, big_endian)?;
+        let written = 4 + write_xmac_str(dst, &self.name, big_endian)?;
+        Ok(XmacChunkMeta {
+            type_id: XmacChunkType::GenericMaterial.into(),
+            size: written as u32,
+            version: 1,
+        })
+    }
+}
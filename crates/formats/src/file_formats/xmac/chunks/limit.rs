@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+
+use super::XmacChunk;
+use super::XmacChunkMeta;
+use super::XmacChunkType;
+use super::nodes::XmacNodeId;
+use crate::archive::ArchiveReadTarget;
+use crate::archive::ArchiveWriteTarget;
+use crate::binimport::BinImport;
+use crate::error::*;
+use crate::helpers::*;
+use crate::types::Vec3;
+
+/// Limits of a node's local transformation, translations are in game units (cm)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct XmacLimit {
+    pub node_id: XmacNodeId,
+    pub translation_min: Vec3,
+    pub translation_max: Vec3,
+    /// Euler angles in radians
+    pub rotation_min: Vec3,
+    pub rotation_max: Vec3,
+    pub scale_min: Vec3,
+    pub scale_max: Vec3,
+    /// Which limits are active: translation x/y/z, rotation x/y/z, scale x/y/z
+    pub limit_flags: [bool; 9],
+    /// The flags are followed by 3 bytes of struct padding
+    pub padded: bool,
+}
+
+const PACKED_SIZE: u32 = 6 * 12 + 9 + 4;
+
+impl XmacLimit {
+    pub fn load<R: ArchiveReadTarget>(
+        src: &mut R,
+        big_endian: bool,
+        _multiply_order: bool,
+        chunk_meta: &XmacChunkMeta,
+    ) -> Result<Option<Self>> {
+        println!("Loading LIMIT chunk...");
+        match chunk_meta.version {
+            1 => {
+                let padded = match chunk_meta.size {
+                    PACKED_SIZE => false,
+                    size if size == PACKED_SIZE + 3 => true,
+                    size => {
+                        println!("Unexpected XMAC limit size {size}, skipping");
+                        return Ok(None);
+                    }
+                };
+                XmacChunk::try_load_exact(src, chunk_meta, |src| {
+                    let translation_min = Vec3::load_endian(src, big_endian)?;
+                    let translation_max = Vec3::load_endian(src, big_endian)?;
+                    let rotation_min = Vec3::load_endian(src, big_endian)?;
+                    let rotation_max = Vec3::load_endian(src, big_endian)?;
+                    let scale_min = Vec3::load_endian(src, big_endian)?;
+                    let scale_max = Vec3::load_endian(src, big_endian)?;
+                    let mut limit_flags = [false; 9];
+                    for flag in &mut limit_flags {
+                        *flag = read_bool(src)?;
+                    }
+                    if padded {
+                        let mut padding = [0; 3];
+                        src.read_exact(&mut padding)?;
+                    }
+                    let node_id = XmacNodeId(read_u32_endian(src, big_endian)?);
+                    Ok(Self {
+                        node_id,
+                        translation_min,
+                        translation_max,
+                        rotation_min,
+                        rotation_max,
+                        scale_min,
+                        scale_max,
+                        limit_flags,
+                        padded,
+                    })
+                })
+            }
+            ver => {
+                println!(
+                    "Unknown XMAC limit version {ver}@{:x}, skipping",
+                    src.stream_position()?
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn save<W: ArchiveWriteTarget>(
+        &self,
+        dst: &mut W,
+        big_endian: bool,
+    ) -> Result<XmacChunkMeta> {
+        println!("Saving LIMIT chunk...");
+        self.translation_min.save_endian(dst, big_endian)?;
+        self.translation_max.save_endian(dst, big_endian)?;
+        self.rotation_min.save_endian(dst, big_endian)?;
+        self.rotation_max.save_endian(dst, big_endian)?;
+        self.scale_min.save_endian(dst, big_endian)?;
+        self.scale_max.save_endian(dst, big_endian)?;
+        for flag in self.limit_flags {
+            write_bool(dst, flag)?;
+        }
+        if self.padded {
+            dst.write_all(&[0; 3])?;
+        }
+        write_u32_endian(dst, self.node_id.0, big_endian)?;
+        Ok(XmacChunkMeta {
+            type_id: XmacChunkType::Limit.into(),
+            size: PACKED_SIZE + if self.padded { 3 } else { 0 },
+            version: 1,
+        })
+    }
+}
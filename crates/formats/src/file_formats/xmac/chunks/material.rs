@@ -2,6 +2,7 @@ use num_enum::IntoPrimitive;
 use num_enum::TryFromPrimitive;
 use serde::{Deserialize, Serialize};
 
+use super::XmacChunk;
 use super::XmacChunkMeta;
 use super::XmacChunkType;
 
@@ -220,6 +221,38 @@ impl XmacStdMaterial {
 }
 
 impl XmacStandardMaterialLayer {
+    /// Standalone layer chunk, `material_id` refers to the material it belongs to
+    pub fn load_chunk<R: ArchiveReadTarget>(
+        src: &mut R,
+        big_endian: bool,
+        _multiply_order: bool,
+        chunk_meta: &XmacChunkMeta,
+    ) -> Result<Option<Self>> {
+        println!("Loading STD MATERIAL LAYER chunk...");
+        match chunk_meta.version {
+            2 => XmacChunk::try_load_exact(src, chunk_meta, |src| Self::load(src, big_endian)),
+            ver => {
+                println!(
+                    "Unknown XMAC material layer version {ver}@{:x}, skipping",
+                    src.stream_position()?
+                );
+                Ok(None)
+            }
+        }
+    }
+    pub fn save_chunk<W: ArchiveWriteTarget>(
+        &self,
+        dst: &mut W,
+        big_endian: bool,
+    ) -> Result<XmacChunkMeta> {
+        println!("Saving STD MATERIAL LAYER chunk...");
+        let written = self.save(dst, big_endian)?;
+        Ok(XmacChunkMeta {
+            type_id: XmacChunkType::StdMaterialLayer.into(),
+            size: written as u32,
+            version: 2,
+        })
+    }
     fn load<R: ArchiveReadTarget>(src: &mut R, big_endian: bool) -> Result<Self> {
         let amount = read_f32_endian(src, big_endian)?;
         let u_offset = read_f32_endian(src, big_endian)?;
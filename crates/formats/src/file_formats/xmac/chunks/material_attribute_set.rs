@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+use super::XmacChunkMeta;
+use super::XmacChunkType;
+use crate::archive::ArchiveReadTarget;
+use crate::archive::ArchiveWriteTarget;
+use crate::error::*;
+use crate::helpers::*;
+
+/// Engine specific attributes of a material.
+/// Only the header is decoded, the serialized attribute set is kept as is:
+/// its layout isn't documented, and without game actors containing one it can't be derived reliably.
+/// The attributes are therefore not available as typed values; they are exported to glTF as hex in the
+/// material's `xmac_attribute_sets` extra and written back unchanged.
+/// As the raw bytes can't be byte swapped, xmac_convert reports these chunks when changing the byte order.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct XmacMaterialAttributeSet {
+    pub material_index: u32,
+    pub lod_level: u32,
+    #[serde(with = "crate::helpers::ser_hex")]
+    pub attributes: Vec<u8>,
+}
+
+impl XmacMaterialAttributeSet {
+    pub fn load<R: ArchiveReadTarget>(
+        src: &mut R,
+        big_endian: bool,
+        _multiply_order: bool,
+        chunk_meta: &XmacChunkMeta,
+    ) -> Result<Option<Self>> {
+        println!("Loading MATERIAL ATTRIBUTE SET chunk...");
+        match chunk_meta.version {
+            1 if chunk_meta.size >= 8 => {
+                let material_index = read_u32_endian(src, big_endian)?;
+                let lod_level = read_u32_endian(src, big_endian)?;
+                let mut attributes = vec![0; chunk_meta.size as usize - 8];
+                src.read_exact(&mut attributes)?;
+                Ok(Some(Self {
+                    material_index,
+                    lod_level,
+                    attributes,
+                }))
+            }
+            ver => {
+                println!(
+                    "Unknown XMAC material attribute set version {ver}@{:x}, skipping",
+                    src.stream_position()?
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn save<W: ArchiveWriteTarget>(
+        &self,
+        dst: &mut W,
+        big_endian: bool,
+    ) -> Result<XmacChunkMeta> {
+        println!("Saving MATERIAL ATTRIBUTE SET chunk...");
+        write_u32_endian(dst, self.material_index, big_endian)?;
+        write_u32_endian(dst, self.lod_level, big_endian)?;
+        dst.write_all(&self.attributes)?;
+        Ok(XmacChunkMeta {
+            type_id: XmacChunkType::MaterialAttributeSet.into(),
+            size: (8 + self.attributes.len()) as u32,
+            version: 1,
+        })
+    }
+}
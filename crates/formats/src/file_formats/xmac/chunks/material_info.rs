@@ -29,10 +29,10 @@ impl XmacMaterialInfo {
                 let std_materials = read_u32_endian(src, big_endian)? as usize;
                 let fx_materials = read_u32_endian(src, big_endian)? as usize;
 
-                if total_materials != std_materials {
-                    return Err(Error::InvalidStructure(
-                        "Non Std-Materials are not supported right now".to_string(),
-                    ));
+                if total_materials != std_materials + fx_materials {
+                    return Err(Error::InvalidStructure(format!(
+                        "{total_materials} materials, but {std_materials} std and {fx_materials} fx materials"
+                    )));
                 }
 
                 Ok(Some(Self {
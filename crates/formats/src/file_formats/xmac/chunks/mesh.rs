@@ -73,7 +73,7 @@ pub enum XmacMeshAttribLayerType {
     ClothData = 8,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct XmacMeshSubmesh {
     pub indices: Vec<u32>,
     pub bones: Vec<u32>,
@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+
+use super::super::{load_xac, save_xac};
+use super::mesh::XmacMesh;
+use super::skinning_info::XmacSkinningInfo;
+use super::{XmacChunk, XmacChunkMeta, XmacChunkType};
+use crate::archive::{ArchiveReadTarget, ArchiveWriteTarget, TempWriteTarget};
+use crate::error::*;
+use crate::helpers::*;
+
+/// A reduced detail level of the actor's meshes.
+/// The LOD is stored as a complete embedded XAC file (without resource header),
+/// its Mesh and SkinningInfo chunks reference the nodes of the containing actor.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct XmacMeshLodLevels {
+    /// 1 for the first reduced level, 0 is the actor itself
+    pub lod_level: u32,
+    pub multiply_order: bool,
+    pub chunks: Vec<XmacChunk>,
+}
+
+impl XmacMeshLodLevels {
+    pub fn load<R: ArchiveReadTarget>(
+        src: &mut R,
+        big_endian: bool,
+        _multiply_order: bool,
+        chunk_meta: &XmacChunkMeta,
+    ) -> Result<Option<Self>> {
+        println!("Loading MESH LOD LEVELS chunk...");
+        match chunk_meta.version {
+            1 => {
+                let chunk_start = src.stream_position()?;
+                let lod_level = read_u32_endian(src, big_endian)?;
+                let size = read_u32_endian(src, big_endian)? as u64;
+                let lod_finish = src.stream_position()? + size;
+                let Some((chunks, multiply_order, _big_endian)) = load_xac(src, lod_finish)? else {
+                    println!("Embedded LOD {lod_level} is not a XAC file");
+                    src.seek(std::io::SeekFrom::Start(chunk_start))?;
+                    return Ok(None);
+                };
+                Ok(Some(Self {
+                    lod_level,
+                    multiply_order,
+                    chunks,
+                }))
+            }
+            ver => {
+                println!(
+                    "Unknown XMAC mesh LOD levels version {ver}@{:x}",
+                    src.stream_position()?
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn save<W: ArchiveWriteTarget>(
+        &self,
+        dst: &mut W,
+        big_endian: bool,
+    ) -> Result<XmacChunkMeta> {
+        println!("Saving MESH LOD LEVELS chunk...");
+        let mut lod = TempWriteTarget::new(dst);
+        save_xac(&mut lod, &self.chunks, self.multiply_order, big_endian)?;
+        let lod = lod.finish();
+
+        write_u32_endian(dst, self.lod_level, big_endian)?;
+        write_u32_endian(dst, lod.len() as u32, big_endian)?;
+        dst.write_all(&lod)?;
+        Ok(XmacChunkMeta {
+            type_id: XmacChunkType::MeshLodLevels.into(),
+            size: 8 + lod.len() as u32,
+            version: 1,
+        })
+    }
+
+    pub fn get_mesh_chunks(&self) -> Vec<&XmacMesh> {
+        self.chunks
+            .iter()
+            .filter_map(|chunk| match chunk {
+                XmacChunk::Mesh(mesh) => Some(mesh),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn get_skinning_chunks(&self) -> Vec<&XmacSkinningInfo> {
+        self.chunks
+            .iter()
+            .filter_map(|chunk| match chunk {
+                XmacChunk::SkinningInfo(skin) => Some(skin),
+                _ => None,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+use super::super::{read_xmac_str, write_xmac_str};
+
+use super::XmacChunk;
+use super::XmacChunkMeta;
+use super::XmacChunkType;
+use crate::archive::ArchiveReadTarget;
+use crate::archive::ArchiveWriteTarget;
+use crate::error::*;
+use crate::helpers::*;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct XmacNodeGroups {
+    pub groups: Vec<XmacNodeGroup>,
+    /// The group headers are written as a struct including its padding byte
+    pub padded: bool,
+}
+
+/// A set of nodes that can be enabled/disabled together, e.g. for LOD
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct XmacNodeGroup {
+    pub name: String,
+    pub disabled_on_default: bool,
+    pub nodes: Vec<u16>,
+}
+
+impl XmacNodeGroups {
+    pub fn load<R: ArchiveReadTarget>(
+        src: &mut R,
+        big_endian: bool,
+        _multiply_order: bool,
+        chunk_meta: &XmacChunkMeta,
+    ) -> Result<Option<Self>> {
+        println!("Loading NODE GROUPS chunk...");
+        match chunk_meta.version {
+            1 => {
+                for padded in [true, false] {
+                    let groups = XmacChunk::try_load_exact(src, chunk_meta, |src| {
+                        Self::load_groups(src, big_endian, padded)
+                    })?;
+                    if let Some(groups) = groups {
+                        return Ok(Some(Self { groups, padded }));
+                    }
+                }
+                Ok(None)
+            }
+            ver => {
+                println!(
+                    "Unknown XMAC node groups version {ver}@{:x}, skipping",
+                    src.stream_position()?
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    fn load_groups<R: ArchiveReadTarget>(
+        src: &mut R,
+        big_endian: bool,
+        padded: bool,
+    ) -> Result<Vec<XmacNodeGroup>> {
+        let group_count = read_u16_endian(src, big_endian)?;
+        let mut groups = Vec::with_capacity(group_count as usize);
+        for _idx in 0..group_count {
+            let node_count = read_u16_endian(src, big_endian)?;
+            let disabled_on_default = read_bool(src)?;
+            if padded {
+                read_u8(src)?;
+            }
+            let name = read_xmac_str(src, big_endian)?;
+            let mut nodes = Vec::with_capacity(node_count as usize);
+            for _idx in 0..node_count {
+                nodes.push(read_u16_endian(src, big_endian)?);
+            }
+            groups.push(XmacNodeGroup {
+                name,
+                disabled_on_default,
+                nodes,
+            });
+        }
+        Ok(groups)
+    }
+
+    pub fn save<W: ArchiveWriteTarget>(
+        &self,
+        dst: &mut W,
+        big_endian: bool,
+    ) -> Result<XmacChunkMeta> {
+        println!("Saving NODE GROUPS chunk...");
+        write_u16_endian(dst, self.groups.len() as u16, big_endian)?;
+        let mut written = 2;
+        for group in &self.groups {
+            write_u16_endian(dst, group.nodes.len() as u16, big_endian)?;
+            write_bool(dst, group.disabled_on_default)?;
+            written += 3;
+            if self.padded {
+                write_u8(dst, 0)?;
+                written += 1;
+            }
+            written += write_xmac_str(dst, &group.name, big_endian)?;
+            for node in &group.nodes {
+                write_u16_endian(dst, *node, big_endian)?;
+            }
+            written += group.nodes.len() * 2;
+        }
+        Ok(XmacChunkMeta {
+            type_id: XmacChunkType::NodeGroups.into(),
+            size: written as u32,
+            version: 1,
+        })
+    }
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+use super::XmacChunk;
+use super::XmacChunkMeta;
+use super::XmacChunkType;
+use crate::archive::ArchiveReadTarget;
+use crate::archive::ArchiveWriteTarget;
+use crate::error::*;
+use crate::helpers::*;
+
+/// Per node: the node whose motion is mirrored onto it
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct XmacNodeMotionSources {
+    /// Indexed by node, `None` if the node uses its own motion
+    pub sources: Vec<Option<u16>>,
+    /// Mirror axis flags per node, not present in older files
+    pub flags: Option<Vec<u8>>,
+}
+
+const NO_SOURCE: u16 = 0xFFFF;
+
+impl XmacNodeMotionSources {
+    pub fn load<R: ArchiveReadTarget>(
+        src: &mut R,
+        big_endian: bool,
+        _multiply_order: bool,
+        chunk_meta: &XmacChunkMeta,
+    ) -> Result<Option<Self>> {
+        println!("Loading NODE MOTION SOURCES chunk...");
+        match chunk_meta.version {
+            1 => XmacChunk::try_load_exact(src, chunk_meta, |src| {
+                let count = read_u32_endian(src, big_endian)? as usize;
+                let size = chunk_meta.size as usize;
+                let has_flags = if size == 4 + count * 2 {
+                    false
+                } else if size == 4 + count * 3 {
+                    true
+                } else {
+                    return Err(Error::InvalidStructure(format!(
+                        "{count} motion sources don't fit the chunk size"
+                    )));
+                };
+                let mut sources = Vec::with_capacity(count);
+                for _idx in 0..count {
+                    let source = read_u16_endian(src, big_endian)?;
+                    sources.push((source != NO_SOURCE).then_some(source));
+                }
+                let flags = if has_flags {
+                    let mut flags = vec![0; count];
+                    src.read_exact(&mut flags)?;
+                    Some(flags)
+                } else {
+                    None
+                };
+                Ok(Self { sources, flags })
+            }),
+            ver => {
+                println!(
+                    "Unknown XMAC node motion sources version {ver}@{:x}, skipping",
+                    src.stream_position()?
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn save<W: ArchiveWriteTarget>(
+        &self,
+        dst: &mut W,
+        big_endian: bool,
+    ) -> Result<XmacChunkMeta> {
+        println!("Saving NODE MOTION SOURCES chunk...");
+        write_u32_endian(dst, self.sources.len() as u32, big_endian)?;
+        for source in &self.sources {
+            write_u16_endian(dst, source.unwrap_or(NO_SOURCE), big_endian)?;
+        }
+        let mut written = 4 + self.sources.len() * 2;
+        if let Some(flags) = &self.flags {
+            if flags.len() != self.sources.len() {
+                return Err(Error::InvalidStructure(format!(
+                    "{} motion source flags for {} nodes",
+                    flags.len(),
+                    self.sources.len()
+                )));
+            }
+            dst.write_all(flags)?;
+            written += flags.len();
+        }
+        Ok(XmacChunkMeta {
+            type_id: XmacChunkType::NodeMotionSources.into(),
+            size: written as u32,
+            version: 1,
+        })
+    }
+}
@@ -49,7 +49,7 @@ pub struct XmacNode {
     pub unknown6: f32,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
 pub struct XmacNodeOBB {
     center: Vec4,
     extent: Vec3,
@@ -170,7 +170,7 @@ impl XmacNode {
         })?;
         let mut unknown5 = [0; 3];
         src.read_exact(&mut unknown5)?;
-        let oriented_bounding_box = Mat4::load(src)?;
+        let oriented_bounding_box = Mat4::load_endian(src, big_endian)?;
 
         let unknown6 = read_f32_endian(src, big_endian)?;
         let node_name = read_xmac_str(src, big_endian)?;
@@ -292,6 +292,44 @@ impl<'a> IntoIterator for &'a XmacNodes {
 }
 
 impl XmacNodeOBB {
+    /// `extent` is the half size along the axes of `transform`
+    pub fn new(center: Vec3, extent: Vec3, transform: Mat3) -> Self {
+        Self {
+            center: center.extend(1.0),
+            extent,
+            transform,
+        }
+    }
+
+    /// Box around `points`, aligned to their principal axes
+    pub fn fit(points: &[Vec3]) -> Option<Self> {
+        if points.is_empty() {
+            return None;
+        }
+        let mean = points.iter().sum::<Vec3>() / points.len() as f32;
+        let mut covariance = Mat3::ZERO;
+        for p in points {
+            let d = *p - mean;
+            covariance += Mat3::from_cols(d * d.x, d * d.y, d * d.z);
+        }
+        let mut axes = symmetric_eigenvectors(covariance / points.len() as f32);
+        // keep it a rotation:
+        axes.z_axis = axes.x_axis.cross(axes.y_axis);
+
+        let mut min = Vec3::splat(f32::MAX);
+        let mut max = Vec3::splat(f32::MIN);
+        for p in points {
+            let local = axes.transpose() * (*p - mean);
+            min = min.min(local);
+            max = max.max(local);
+        }
+        Some(Self::new(
+            mean + axes * ((min + max) * 0.5),
+            (max - min) * 0.5,
+            axes,
+        ))
+    }
+
     pub fn to_mat4(&self) -> Mat4 {
         Mat4 {
             x_axis: self.transform.x_axis.extend(self.extent.x),
@@ -301,3 +339,84 @@ impl XmacNodeOBB {
         }
     }
 }
+
+/// Eigenvectors of a symmetric matrix as columns, by cyclic Jacobi rotations
+fn symmetric_eigenvectors(m: Mat3) -> Mat3 {
+    // a[row][col], symmetric so the column major layout doesn't matter:
+    let mut a = m.to_cols_array_2d();
+    let mut v = Mat3::IDENTITY.to_cols_array_2d();
+    for _sweep in 0..32 {
+        let off_diagonal = a[0][1].abs() + a[0][2].abs() + a[1][2].abs();
+        let diagonal = a[0][0].abs() + a[1][1].abs() + a[2][2].abs();
+        if off_diagonal <= diagonal * 1e-7 {
+            break;
+        }
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            if a[p][q] == 0.0 {
+                continue;
+            }
+            let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+            let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+            let c = (t * t + 1.0).sqrt().recip();
+            let s = t * c;
+            // a = J^T * a * J, v = v * J
+            for row in a.iter_mut().chain(v.iter_mut()) {
+                let (rp, rq) = (row[p], row[q]);
+                row[p] = c * rp - s * rq;
+                row[q] = s * rp + c * rq;
+            }
+            let (row_p, row_q) = (a[p], a[q]);
+            a[p] = std::array::from_fn(|k| c * row_p[k] - s * row_q[k]);
+            a[q] = std::array::from_fn(|k| s * row_p[k] + c * row_q[k]);
+        }
+    }
+    Mat3::from_cols_array_2d(&v).transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn obb_encoding_round_trip() {
+        let obb = XmacNodeOBB::new(
+            Vec3::new(1.5, -2.0, 30.0),
+            Vec3::new(4.0, 0.5, 12.25),
+            Mat3::from_quat(Quat::from_euler(glam::EulerRot::XYZ, 0.3, -1.1, 2.0)),
+        );
+        let mut node = XmacNode::new("Bone".to_string(), Mat4::IDENTITY, None);
+        node.oriented_bounding_box = obb.to_mat4();
+        let decoded = node.get_obb();
+        assert_eq!(decoded, obb);
+        assert_eq!(decoded.to_mat4(), node.oriented_bounding_box);
+    }
+
+    #[test]
+    fn obb_fit_contains_points() {
+        let rotation = Mat3::from_quat(Quat::from_euler(glam::EulerRot::XYZ, 0.4, 0.2, -0.7));
+        let points = (0..64)
+            .map(|i| {
+                let corner = Vec3::new(
+                    if i & 1 == 0 { -8.0 } else { 8.0 },
+                    if i & 2 == 0 { -2.0 } else { 2.0 },
+                    (i / 4) as f32 * 0.25 - 2.0,
+                );
+                rotation * corner + Vec3::new(5.0, 6.0, 7.0)
+            })
+            .collect::<Vec<_>>();
+        let obb = XmacNodeOBB::fit(&points).unwrap();
+        assert!((obb.transform.determinant() - 1.0).abs() < 1e-4);
+        for p in &points {
+            let local = obb.transform.transpose() * (*p - obb.center.truncate());
+            assert!(
+                local.abs().cmple(obb.extent + 1e-3).all(),
+                "{local} outside {}",
+                obb.extent
+            );
+        }
+        // the longest axis is found, so the box isn't inflated:
+        let mut extent = obb.extent.to_array();
+        extent.sort_by(f32::total_cmp);
+        assert!((extent[2] - 8.0).abs() < 1e-2, "{:?}", obb.extent);
+    }
+}
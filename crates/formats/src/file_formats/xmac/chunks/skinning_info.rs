@@ -46,7 +46,7 @@ impl XmacSkinningInfo {
         big_endian: bool,
         _multiply_order: bool,
         chunk_meta: &XmacChunkMeta,
-        meshes: &HashMap<XmacNodeId, &XmacMesh>,
+        meshes: &HashMap<(XmacNodeId, bool), &XmacMesh>,
     ) -> Result<Option<Self>> {
         println!("Loading SKINNING INFO chunk...");
         match chunk_meta.version {
@@ -63,13 +63,12 @@ impl XmacSkinningInfo {
                     influences.push(SkinInfluence::load(src, big_endian)?);
                 }
 
-                let mesh_orig_vertices_count = if let Some(mesh) = meshes.get(&node_id) {
-                    mesh.orig_verts_count
-                } else {
+                let Some(mesh) = meshes.get(&(node_id, is_for_collision_mesh)) else {
                     return Err(Error::InvalidStructure(format!(
-                        "Found skinning info for nonexistent mesh {node_id:?}!"
+                        "Found skinning info for nonexistent mesh {node_id:?} (collision: {is_for_collision_mesh})!"
                     )));
                 };
+                let mesh_orig_vertices_count = mesh.orig_verts_count;
                 let mut table_entries = Vec::with_capacity(mesh_orig_vertices_count as usize);
                 for _idx in 0..mesh_orig_vertices_count {
                     table_entries.push(TableEntry::load(src, big_endian)?);
@@ -15,15 +15,24 @@
 //!    License: GPLv3
 
 pub mod chunks;
+pub mod validation;
 
-use std::{io::Write, time::SystemTime};
+#[cfg(test)]
+mod tests;
+
+use std::time::SystemTime;
 
 use chunks::{
     XmacChunk,
+    attachment_nodes::XmacAttachmentNodes,
+    limit::XmacLimit,
     material::XmacStdMaterial,
     material_info::XmacMaterialInfo,
     mesh::XmacMesh,
+    mesh_lod_levels::XmacMeshLodLevels,
     morph_targets::XmacMorphTargets,
+    node_groups::XmacNodeGroups,
+    node_motion_sources::XmacNodeMotionSources,
     nodes::{XmacNodeId, XmacNodes},
     skinning_info::XmacSkinningInfo,
 };
@@ -41,6 +50,9 @@ use crate::{
 pub struct XmacFile {
     pub res: ResourceFile,
     pub multiply_order: bool,
+    /// Console builds store the XAC data big-endian, PC builds little-endian
+    #[serde(default)]
+    pub big_endian: bool,
     pub chunks: Vec<chunks::XmacChunk>,
 }
 
@@ -60,6 +72,7 @@ impl XmacFile {
                 raw_file_ext: R1_RAW_EXT,
             },
             multiply_order: false,
+            big_endian: false,
             chunks: Vec::new(),
         }
     }
@@ -71,7 +84,7 @@ impl XmacFile {
         assert_eq!(&res.raw_file_ext[0..5], &R1_RAW_EXT[0..5]); //the last 3 bytes sometimes differ
         assert_eq!(&res.class_name, &R1_CLASS);
 
-        let (chunks, multiply_order) = Self::load_xmac(src)?;
+        let (chunks, multiply_order, big_endian) = Self::load_xmac(src)?;
 
         let trail = read_u64(src)?;
         if trail != 0 {
@@ -82,6 +95,7 @@ impl XmacFile {
             res,
             chunks,
             multiply_order,
+            big_endian,
         })
     }
 
@@ -108,124 +122,24 @@ impl XmacFile {
         Ok(())
     }
 
-    fn load_xmac<R: ArchiveReadTarget>(src: &mut R) -> Result<(Vec<chunks::XmacChunk>, bool)> {
+    fn load_xmac<R: ArchiveReadTarget>(
+        src: &mut R,
+    ) -> Result<(Vec<chunks::XmacChunk>, bool, bool)> {
         let data_len = read_u32(src)? as u64;
         let xmac_start = src.stream_position()?;
         let xmac_finish = xmac_start + data_len;
 
-        let mut magic = vec![0; 4];
-        src.read_exact(&mut magic)?;
-        assert_eq!(&magic, &XMAC_MAGIC);
-
-        let _actor_version_maj = read_u8(src)?;
-        let _actor_version_min = read_u8(src)?;
-        // The version check in R1 is broken -
-        // it is supposed to only accept 3.0, but accepts anything
-        // with either maj = 3 OR min = 0 -
-        // and the shipped files have V1.0 oO
-
-        let big_endian = read_bool(src)?;
-        let multiply_order = read_bool(src)?;
-
-        let mut chunks = Vec::new();
-        while src.stream_position()? < xmac_finish {
-            let new_chunk = chunks::XmacChunk::load(src, big_endian, multiply_order, &chunks)?;
-            chunks.push(new_chunk);
-        }
-
-        Ok((chunks, multiply_order))
-    }
-
-    pub fn sanity_check(&self) -> Result<()> {
-        self.sanity_check_chunk_counts()
-        // ToDo:
-        // - Check joints exist
-        // - Check Skins reference existing Meshes
-        // - Check Skin.for_collision_mesh == Mesh.collision_mesh
-        // - Skin.table_entries.len == Mesh.orig_vertex_count
-    }
-    fn sanity_check_chunk_counts(&self) -> Result<()> {
-        let mut info_chunks = 0;
-        let mut nodes_chunks = 0;
-        let mut mat_info_chunks = 0;
-        let mut std_mat_chunks = 0;
-        let mut mesh_chunks = 0;
-        let mut skin_chunks = 0;
-        let mut morph_chunks = 0;
-        for chunk in &self.chunks {
-            match chunk {
-                XmacChunk::Info(_) => info_chunks += 1,
-                XmacChunk::Nodes(_) => nodes_chunks += 1,
-                XmacChunk::MaterialInfo(_) => mat_info_chunks += 1,
-                XmacChunk::StdMaterial(_) => std_mat_chunks += 1,
-                XmacChunk::Mesh(_) => mesh_chunks += 1,
-                XmacChunk::SkinningInfo(_) => skin_chunks += 1,
-                XmacChunk::MorphTargets(_) => morph_chunks += 1,
-                XmacChunk::Unknown(_) => {}
-            }
-        }
-        if info_chunks != 1 {
-            println!("Warn: Expected 1 Info chunk, found {info_chunks}");
-        }
-        if nodes_chunks != 1 {
-            return Err(Error::InvalidStructure(format!(
-                "Expected 1 Nodes chunk, found {nodes_chunks}"
-            )));
-        }
-        if mat_info_chunks != 1 {
-            return Err(Error::InvalidStructure(format!(
-                "Expected 1 Material Info chunk, found {mat_info_chunks}"
-            )));
-        }
-        let mat_info = self.get_material_info_chunk().unwrap();
-        if std_mat_chunks != mat_info.std_materials {
-            return Err(Error::InvalidStructure(format!(
-                "Expected {} Standard Material chunk, found {std_mat_chunks}",
-                mat_info.std_materials
-            )));
-        }
-        if mesh_chunks == 0 {
-            return Err(Error::InvalidStructure(format!(
-                "Expected at least 1 Mesh chunk, found {mesh_chunks}"
-            )));
-        }
-        if skin_chunks > mesh_chunks {
-            return Err(Error::InvalidStructure(format!(
-                "Expected at most {mesh_chunks} Skin chunks, found {skin_chunks}"
-            )));
-        }
-        if skin_chunks == 0 {
-            println!(
-                "Warn: Expected at least 1 Skin chunk, found none - the mesh will probably not be rendered!"
-            );
-        }
-        if morph_chunks > mesh_chunks {
-            return Err(Error::InvalidStructure(format!(
-                "Expected at most {mesh_chunks} Skin chunks, found {morph_chunks}"
-            )));
-        }
-        Ok(())
+        load_xac(src, xmac_finish)?
+            .ok_or_else(|| Error::InvalidStructure("Expected XMAC magic".to_string()))
     }
 
     pub fn save_xmac<W: ArchiveWriteTarget>(&self, dst: &mut W) -> Result<()> {
         //self.sanity_check()?;
 
-        let mut data = TempWriteTarget::new(dst);
-        data.write_all(&XMAC_MAGIC)?;
-        // Version Maj:
-        write_u8(&mut data, 1)?;
-        // Version Min:
-        write_u8(&mut data, 0)?;
-
-        let big_endian = false; // Big Endian doesn't make sense on x86, but its here if you need it
         let multiply_order = self.multiply_order; // Don't really know the influence
 
-        write_bool(&mut data, big_endian)?;
-        write_bool(&mut data, multiply_order)?;
-
-        for chunk in &self.chunks {
-            chunk.save(&mut data, big_endian)?;
-        }
+        let mut data = TempWriteTarget::new(dst);
+        save_xac(&mut data, &self.chunks, multiply_order, self.big_endian)?;
         let data = data.finish();
 
         write_u32(dst, data.len() as u32)?; //this one is always little-endian
@@ -256,10 +170,20 @@ impl XmacFile {
         self.chunks.iter().filter_map(get_mesh_chunk).collect()
     }
 
-    pub fn get_mesh_chunk<'a>(&'a self, node_id: XmacNodeId) -> Option<&'a XmacMesh> {
+    /// The render mesh of the node
+    pub fn get_mesh_chunk(&self, node_id: XmacNodeId) -> Option<&XmacMesh> {
+        self.find_mesh_chunk(node_id, false)
+    }
+
+    /// The collision mesh of the node, it may have one besides its render mesh
+    pub fn get_collision_mesh_chunk(&self, node_id: XmacNodeId) -> Option<&XmacMesh> {
+        self.find_mesh_chunk(node_id, true)
+    }
+
+    fn find_mesh_chunk<'a>(&'a self, node_id: XmacNodeId, collision: bool) -> Option<&'a XmacMesh> {
         let get_mesh_chunk = |chunk: &'a XmacChunk| -> Option<&'a XmacMesh> {
             if let XmacChunk::Mesh(mesh) = chunk {
-                if mesh.node_id == node_id {
+                if mesh.node_id == node_id && mesh.is_collision_mesh == collision {
                     Some(mesh)
                 } else {
                     None
@@ -271,6 +195,17 @@ impl XmacFile {
         self.chunks.iter().find_map(get_mesh_chunk)
     }
 
+    pub fn get_lod_chunks(&self) -> Vec<&XmacMeshLodLevels> {
+        fn get_lod_chunk(chunk: &XmacChunk) -> Option<&XmacMeshLodLevels> {
+            if let XmacChunk::MeshLodLevels(lod) = chunk {
+                Some(lod)
+            } else {
+                None
+            }
+        }
+        self.chunks.iter().filter_map(get_lod_chunk).collect()
+    }
+
     pub fn get_material_info_chunk(&self) -> Option<&XmacMaterialInfo> {
         fn get_material_info_chunk(chunk: &XmacChunk) -> Option<&XmacMaterialInfo> {
             if let XmacChunk::MaterialInfo(matinf) = chunk {
@@ -314,10 +249,108 @@ impl XmacFile {
         }
         self.chunks.iter().find_map(get_morph_chunk)
     }
+
+    pub fn get_limit_chunks(&self) -> Vec<&XmacLimit> {
+        fn get_limit_chunk(chunk: &XmacChunk) -> Option<&XmacLimit> {
+            if let XmacChunk::Limit(limit) = chunk {
+                Some(limit)
+            } else {
+                None
+            }
+        }
+        self.chunks.iter().filter_map(get_limit_chunk).collect()
+    }
+
+    pub fn get_node_groups_chunk(&self) -> Option<&XmacNodeGroups> {
+        fn get_node_groups_chunk(chunk: &XmacChunk) -> Option<&XmacNodeGroups> {
+            if let XmacChunk::NodeGroups(groups) = chunk {
+                Some(groups)
+            } else {
+                None
+            }
+        }
+        self.chunks.iter().find_map(get_node_groups_chunk)
+    }
+
+    pub fn get_node_motion_sources_chunk(&self) -> Option<&XmacNodeMotionSources> {
+        fn get_node_motion_sources_chunk(chunk: &XmacChunk) -> Option<&XmacNodeMotionSources> {
+            if let XmacChunk::NodeMotionSources(sources) = chunk {
+                Some(sources)
+            } else {
+                None
+            }
+        }
+        self.chunks.iter().find_map(get_node_motion_sources_chunk)
+    }
+
+    pub fn get_attachment_nodes_chunk(&self) -> Option<&XmacAttachmentNodes> {
+        fn get_attachment_nodes_chunk(chunk: &XmacChunk) -> Option<&XmacAttachmentNodes> {
+            if let XmacChunk::AttachmentNodes(nodes) = chunk {
+                Some(nodes)
+            } else {
+                None
+            }
+        }
+        self.chunks.iter().find_map(get_attachment_nodes_chunk)
+    }
+}
+
+/// Reads the XAC header and the chunks up to `finish`.
+/// Returns the chunks, multiply order and byte order,
+/// or `None` (before reading any chunks) if the magic doesn't match.
+pub(crate) fn load_xac<R: ArchiveReadTarget>(
+    src: &mut R,
+    finish: u64,
+) -> Result<Option<(Vec<XmacChunk>, bool, bool)>> {
+    let mut magic = vec![0; 4];
+    src.read_exact(&mut magic)?;
+    if magic != XMAC_MAGIC {
+        return Ok(None);
+    }
+
+    let _actor_version_maj = read_u8(src)?;
+    let _actor_version_min = read_u8(src)?;
+    // The version check in R1 is broken -
+    // it is supposed to only accept 3.0, but accepts anything
+    // with either maj = 3 OR min = 0 -
+    // and the shipped files have V1.0 oO
+
+    let big_endian = read_bool(src)?;
+    let multiply_order = read_bool(src)?;
+
+    let mut chunks = Vec::new();
+    while src.stream_position()? < finish {
+        let new_chunk = XmacChunk::load(src, big_endian, multiply_order, &chunks)?;
+        chunks.push(new_chunk);
+    }
+
+    Ok(Some((chunks, multiply_order, big_endian)))
+}
+
+/// Writes the XAC header and `chunks`, the length prefix is up to the caller
+pub(crate) fn save_xac<W: ArchiveWriteTarget>(
+    dst: &mut W,
+    chunks: &[XmacChunk],
+    multiply_order: bool,
+    big_endian: bool,
+) -> Result<()> {
+    dst.write_all(&XMAC_MAGIC)?;
+    // Version Maj:
+    write_u8(dst, 1)?;
+    // Version Min:
+    write_u8(dst, 0)?;
+
+    write_bool(dst, big_endian)?;
+    write_bool(dst, multiply_order)?;
+
+    for chunk in chunks {
+        chunk.save(dst, big_endian)?;
+    }
+    Ok(())
 }
 
 /// XMAC Strings store their length in (endianness-affected) u32
-fn read_xmac_str<R: ArchiveReadTarget>(src: &mut R, big_endian: bool) -> Result<String> {
+pub(crate) fn read_xmac_str<R: ArchiveReadTarget>(src: &mut R, big_endian: bool) -> Result<String> {
     let len = read_u32_endian(src, big_endian)?;
     if len > 255 {
         return Err(Error::InvalidStructure(format!(
@@ -339,7 +372,7 @@ fn read_xmac_str<R: ArchiveReadTarget>(src: &mut R, big_endian: bool) -> Result<
     }
 }
 
-fn write_xmac_str<W: ArchiveWriteTarget>(
+pub(crate) fn write_xmac_str<W: ArchiveWriteTarget>(
     dst: &mut W,
     content: &str,
     big_endian: bool,
@@ -0,0 +1,503 @@
+use std::io::Cursor;
+use std::time::SystemTime;
+
+use super::XmacFile;
+use super::chunks::{
+    XmacChunk,
+    attachment_nodes::XmacAttachmentNodes,
+    fx_material::{XmacFxMaterial, XmacFxParam},
+    generic_material::XmacGenericMaterial,
+    info::XmacInfo,
+    limit::XmacLimit,
+    material::{
+        XmacLayerBlendMode, XmacMaterialLayerType, XmacMaterialTransparencyType,
+        XmacStandardMaterialLayer, XmacStdMaterial,
+    },
+    material_attribute_set::XmacMaterialAttributeSet,
+    material_info::XmacMaterialInfo,
+    mesh::{XmacMesh, XmacMeshAttrib, XmacMeshAttribLayer, XmacMeshSubmesh},
+    mesh_lod_levels::XmacMeshLodLevels,
+    morph_targets::{MeshDeformDelta, MeshDeformDeltas, MorphTarget, PhonemeSet, XmacMorphTargets},
+    node_groups::{XmacNodeGroup, XmacNodeGroups},
+    node_motion_sources::XmacNodeMotionSources,
+    nodes::{XmacNode, XmacNodeId, XmacNodeOBB, XmacNodes},
+    skinning_info::{SkinInfluence, TableEntry, XmacSkinningInfo},
+    unknown::XmacUnknownChunk,
+};
+use crate::types::properties::{PropData, Property};
+use crate::types::{BoundingBox, Mat3, Mat4, Quat, Vec2, Vec3, Vec4};
+
+fn layer(attribs: XmacMeshAttrib, flag1: bool) -> XmacMeshAttribLayer {
+    XmacMeshAttribLayer {
+        attribs,
+        flag1,
+        unknown1: [0; 3],
+    }
+}
+
+/// A triangle on node 1, skinned to both nodes
+fn mesh_chunks() -> Vec<XmacChunk> {
+    let mesh = XmacMesh {
+        vertex_attribute_layers: vec![
+            layer(
+                XmacMeshAttrib::Positions(vec![
+                    Vec3::new(0.0, 0.0, 0.0),
+                    Vec3::new(10.0, 0.0, -2.5),
+                    Vec3::new(0.0, 10.0, 1e-3),
+                ]),
+                true,
+            ),
+            layer(XmacMeshAttrib::Normals(vec![Vec3::Z; 3]), true),
+            layer(
+                XmacMeshAttrib::Tangents(vec![Vec4::new(1.0, 0.0, 0.0, -1.0); 3]),
+                true,
+            ),
+            layer(
+                XmacMeshAttrib::UvCoords(vec![Vec2::ZERO, Vec2::X, Vec2::new(0.25, 0.75)]),
+                false,
+            ),
+            layer(XmacMeshAttrib::OriginalVertexNumbers(vec![0, 1, 2]), false),
+            layer(
+                XmacMeshAttrib::Colors32(vec![0x11223344, 0xff00ff00, 0x01020304]),
+                false,
+            ),
+            layer(
+                XmacMeshAttrib::Colors128(vec![Vec4::new(0.1, 0.2, 0.3, 0.4); 3]),
+                false,
+            ),
+            layer(XmacMeshAttrib::BiTangents(vec![Vec3::Y; 3]), true),
+            layer(XmacMeshAttrib::ClothData(vec![1, 2, 3]), false),
+        ],
+        submeshes: vec![XmacMeshSubmesh {
+            indices: vec![0, 1, 2],
+            bones: vec![0, 1],
+            vertices_count: 3,
+            material_idx: 0,
+        }],
+        node_id: XmacNodeId(1),
+        orig_verts_count: 3,
+        is_collision_mesh: false,
+        unknown1: [0; 3],
+    };
+    let skin = XmacSkinningInfo {
+        node_id: XmacNodeId(1),
+        influences: vec![
+            SkinInfluence {
+                weight: 1.0,
+                node_idx: 0,
+                unknown: 0,
+            },
+            SkinInfluence {
+                weight: 0.25,
+                node_idx: 0,
+                unknown: 0,
+            },
+            SkinInfluence {
+                weight: 0.75,
+                node_idx: 1,
+                unknown: 0,
+            },
+        ],
+        table_entries: vec![
+            TableEntry {
+                start_idx: 0,
+                num_elements: 1,
+            },
+            TableEntry {
+                start_idx: 1,
+                num_elements: 2,
+            },
+            TableEntry {
+                start_idx: 0,
+                num_elements: 1,
+            },
+        ],
+        local_bones: 2,
+        is_for_collision_mesh: false,
+        unknown1: [0; 3],
+    };
+    vec![XmacChunk::Mesh(mesh), XmacChunk::SkinningInfo(skin)]
+}
+
+fn texture_layer(ty: XmacMaterialLayerType, texture: &str) -> XmacStandardMaterialLayer {
+    XmacStandardMaterialLayer {
+        ty,
+        texture: texture.to_string(),
+        amount: 0.5,
+        u_offset: 0.125,
+        v_offset: -0.25,
+        u_tiling: 2.0,
+        v_tiling: 4.0,
+        rotation_rads: 1.5,
+        material_id: 0,
+        blend_mode: XmacLayerBlendMode::Multiply,
+    }
+}
+
+/// One instance of every chunk type, with values that survive the quantization of morph deltas
+fn sample_file() -> XmacFile {
+    let mut file = XmacFile::new(SystemTime::UNIX_EPOCH);
+    file.res.props.push(Property {
+        name: "Boundary".to_string(),
+        version: 30,
+        data: Box::new(PropData::BoundingBox(BoundingBox {
+            min: Vec3::new(10.0, 10.0, 1.0),
+            max: Vec3::new(0.0, 0.0, -2.5),
+        })),
+    });
+
+    let mut root = XmacNode::new(
+        "Root".to_string(),
+        Mat4::from_rotation_translation(Quat::from_rotation_z(0.5), Vec3::new(1.0, 2.0, 3.0)),
+        None,
+    );
+    root.child_count = 1;
+    let mut ball = XmacNode::new(
+        "Ball".to_string(),
+        Mat4::from_scale(Vec3::splat(2.0)),
+        Some(0),
+    );
+    ball.oriented_bounding_box = XmacNodeOBB::new(
+        Vec3::new(5.0, 5.0, -1.0),
+        Vec3::new(5.0, 5.0, 1.75),
+        Mat3::from_rotation_z(0.25),
+    )
+    .to_mat4();
+
+    let mut chunks = vec![
+        XmacChunk::Info(XmacInfo::new("Tests".to_string(), "sample.max".to_string())),
+        XmacChunk::Nodes(XmacNodes {
+            nodes: vec![root, ball],
+        }),
+        XmacChunk::MaterialInfo(XmacMaterialInfo {
+            std_materials: 1,
+            fx_materials: 2,
+        }),
+        XmacChunk::StdMaterial(XmacStdMaterial {
+            name: "Ball".to_string(),
+            layers: vec![texture_layer(
+                XmacMaterialLayerType::Diffuse,
+                "ball_diffuse",
+            )],
+            ambient_color: Vec4::new(0.1, 0.1, 0.1, 1.0),
+            diffuse_color: Vec4::ONE,
+            specular_color: Vec4::new(0.5, 0.5, 0.5, 1.0),
+            emissive_color: Vec4::ZERO,
+            shine: 25.0,
+            shine_strength: 0.75,
+            opacity: 1.0,
+            refraction_index: 1.5,
+            double_sided: true,
+            wireframe: false,
+            transparency_type: XmacMaterialTransparencyType::Filter,
+        }),
+        XmacChunk::StdMaterialLayer(texture_layer(XmacMaterialLayerType::Bump, "ball_normal")),
+        XmacChunk::FxMaterial(XmacFxMaterial {
+            name: "Glow".to_string(),
+            effect_file: "glow.fx".to_string(),
+            int_params: vec![XmacFxParam {
+                name: "passes".to_string(),
+                value: -3,
+            }],
+            float_params: vec![XmacFxParam {
+                name: "intensity".to_string(),
+                value: 0.625,
+            }],
+            color_params: vec![XmacFxParam {
+                name: "tint".to_string(),
+                value: Vec4::new(1.0, 0.5, 0.25, 1.0),
+            }],
+            bitmap_params: vec![XmacFxParam {
+                name: "mask".to_string(),
+                value: "glow_mask".to_string(),
+            }],
+        }),
+        XmacChunk::GenericMaterial(XmacGenericMaterial {
+            lod_level: 0,
+            name: "Engine/Water".to_string(),
+        }),
+        XmacChunk::MaterialAttributeSet(XmacMaterialAttributeSet {
+            material_index: 0,
+            lod_level: 0,
+            attributes: vec![1, 2, 3, 4, 5],
+        }),
+    ];
+    chunks.extend(mesh_chunks());
+    chunks.push(XmacChunk::MeshLodLevels(XmacMeshLodLevels {
+        lod_level: 1,
+        multiply_order: false,
+        chunks: mesh_chunks(),
+    }));
+    chunks.extend([
+        XmacChunk::MorphTargets(XmacMorphTargets {
+            targets: vec![MorphTarget {
+                name: "Smile".to_string(),
+                range_min: -1.0,
+                range_max: 2.0,
+                mesh_deform_deltas: vec![MeshDeformDeltas {
+                    node_id: XmacNodeId(1),
+                    deltas: vec![
+                        MeshDeformDelta {
+                            vertex_id: 0,
+                            position_delta: Vec3::new(-4.0, 8.0, -4.0),
+                            normal_delta: Vec3::new(-2.0, 2.0, 2.0),
+                            tangent_delta: Vec3::new(2.0, -2.0, 2.0),
+                        },
+                        MeshDeformDelta {
+                            vertex_id: 2,
+                            position_delta: Vec3::new(8.0, 8.0, -4.0),
+                            normal_delta: Vec3::new(2.0, 2.0, -2.0),
+                            tangent_delta: Vec3::new(-2.0, -2.0, -2.0),
+                        },
+                    ],
+                }],
+                phoneme_set: PhonemeSet::M_B_P_X | PhonemeSet::F_V,
+                unknown1: 0,
+            }],
+            unknown: 0,
+        }),
+        XmacChunk::Limit(XmacLimit {
+            node_id: XmacNodeId(1),
+            translation_min: Vec3::splat(-10.0),
+            translation_max: Vec3::splat(10.0),
+            rotation_min: Vec3::new(-0.5, -1.0, -1.5),
+            rotation_max: Vec3::new(0.5, 1.0, 1.5),
+            scale_min: Vec3::splat(0.5),
+            scale_max: Vec3::splat(2.0),
+            limit_flags: [true, false, true, false, true, false, true, false, true],
+            padded: true,
+        }),
+        XmacChunk::NodeGroups(XmacNodeGroups {
+            groups: vec![XmacNodeGroup {
+                name: "Everything".to_string(),
+                disabled_on_default: true,
+                nodes: vec![0, 1],
+            }],
+            padded: true,
+        }),
+        XmacChunk::NodeMotionSources(XmacNodeMotionSources {
+            sources: vec![None, Some(0)],
+            flags: Some(vec![0, 1]),
+        }),
+        XmacChunk::AttachmentNodes(XmacAttachmentNodes { nodes: vec![1] }),
+        XmacChunk::Unknown(XmacUnknownChunk {
+            type_id: 99,
+            version: 1,
+            data: vec![0xde, 0xad, 0xbe, 0xef],
+        }),
+    ]);
+    file.chunks = chunks;
+    file
+}
+
+fn save(file: &XmacFile) -> Vec<u8> {
+    let mut data = Cursor::new(Vec::new());
+    file.save(&mut data).unwrap();
+    data.into_inner()
+}
+
+fn round_trip(big_endian: bool) {
+    let mut file = sample_file();
+    file.big_endian = big_endian;
+    file.sanity_check().unwrap();
+
+    let data = save(&file);
+    let loaded = XmacFile::load(&mut Cursor::new(data.clone())).unwrap();
+    assert_eq!(loaded.big_endian, big_endian);
+    assert_eq!(
+        format!("{:#?}", loaded.chunks),
+        format!("{:#?}", file.chunks)
+    );
+    assert_eq!(save(&loaded), data);
+}
+
+#[test]
+fn xmac_round_trip_little_endian() {
+    round_trip(false);
+}
+
+#[test]
+fn xmac_round_trip_big_endian() {
+    round_trip(true);
+}
+
+#[test]
+fn xmac_endian_conversion() {
+    let mut file = sample_file();
+    let little = save(&file);
+    file.big_endian = true;
+    let big = save(&file);
+    assert_ne!(little, big);
+    assert_eq!(little.len(), big.len());
+
+    let mut converted = XmacFile::load(&mut Cursor::new(big)).unwrap();
+    converted.big_endian = false;
+    assert_eq!(save(&converted), little);
+}
+
+/// The first chunk of the given type, outside of LOD levels
+macro_rules! find_chunk {
+    ($file:expr, $variant:ident) => {
+        $file
+            .chunks
+            .iter_mut()
+            .find_map(|chunk| match chunk {
+                XmacChunk::$variant(c) => Some(c),
+                _ => None,
+            })
+            .unwrap()
+    };
+}
+
+/// Breaks the sample file and checks that validation reports the expected error
+fn assert_invalid(expected: &str, modify: impl FnOnce(&mut XmacFile)) {
+    let mut file = sample_file();
+    modify(&mut file);
+    let report = file.validate();
+    assert!(
+        report.errors.iter().any(|e| e.contains(expected)),
+        "expected an error containing '{expected}', got {:#?}",
+        report.errors
+    );
+    assert!(file.sanity_check().is_err());
+}
+
+#[test]
+fn validation_accepts_sample() {
+    let report = sample_file().validate();
+    assert!(report.errors.is_empty(), "{:#?}", report.errors);
+}
+
+#[test]
+fn validation_chunk_counts() {
+    assert_invalid("Expected 1 Nodes chunk", |file| {
+        file.chunks.retain(|c| !matches!(c, XmacChunk::Nodes(_)))
+    });
+    assert_invalid("Expected 1 Standard Material chunks", |file| {
+        file.chunks
+            .retain(|c| !matches!(c, XmacChunk::StdMaterial(_)))
+    });
+    assert_invalid("Expected at least 1 Mesh chunk", |file| {
+        file.chunks.retain(|c| !matches!(c, XmacChunk::Mesh(_)))
+    });
+    assert_invalid("Expected at most 1 Morph Targets chunks, found 2", |file| {
+        file.chunks.push(XmacChunk::MorphTargets(XmacMorphTargets {
+            targets: Vec::new(),
+            unknown: 0,
+        }));
+    });
+}
+
+#[test]
+fn validation_nodes() {
+    assert_invalid("references missing parent 5", |file| {
+        find_chunk!(file, Nodes).nodes[1].parent_idx = Some(5)
+    });
+    assert_invalid("is its own ancestor", |file| {
+        find_chunk!(file, Nodes).nodes[0].parent_idx = Some(1)
+    });
+    assert_invalid("has a zero scale", |file| {
+        find_chunk!(file, Nodes).nodes[1].local_scale = Vec3::new(1.0, 0.0, 1.0)
+    });
+    assert_invalid("NaN/infinite transform", |file| {
+        find_chunk!(file, Nodes).nodes[0].local_pos.x = f32::NAN
+    });
+}
+
+#[test]
+fn validation_meshes() {
+    assert_invalid("references a missing node", |file| {
+        find_chunk!(file, Mesh).node_id = XmacNodeId(7)
+    });
+    assert_invalid("has 2 normals, but its submeshes use 3 vertices", |file| {
+        let mesh = find_chunk!(file, Mesh);
+        for layer in &mut mesh.vertex_attribute_layers {
+            if let XmacMeshAttrib::Normals(normals) = &mut layer.attribs {
+                normals.pop();
+            }
+        }
+    });
+    assert_invalid("references original vertex 3 of 3", |file| {
+        let mesh = find_chunk!(file, Mesh);
+        for layer in &mut mesh.vertex_attribute_layers {
+            if let XmacMeshAttrib::OriginalVertexNumbers(orig) = &mut layer.attribs {
+                orig[2] = 3;
+            }
+        }
+    });
+    assert_invalid("index 3 of submesh 0 exceeds", |file| {
+        find_chunk!(file, Mesh).submeshes[0].indices[1] = 3
+    });
+    assert_invalid("which is not a triangle list", |file| {
+        find_chunk!(file, Mesh).submeshes[0].indices.push(0)
+    });
+    assert_invalid("uses material 3, but there are only 3", |file| {
+        find_chunk!(file, Mesh).submeshes[0].material_idx = 3
+    });
+    assert_invalid("references missing bone 2", |file| {
+        find_chunk!(file, Mesh).submeshes[0].bones.push(2)
+    });
+    // meshes of LOD levels are checked as well:
+    assert_invalid("LOD 1: Mesh of node 1 ('Ball'): index 5", |file| {
+        let lod = find_chunk!(file, MeshLodLevels);
+        find_chunk!(lod, Mesh).submeshes[0].indices[0] = 5;
+    });
+}
+
+#[test]
+fn validation_skins() {
+    assert_invalid("references a missing mesh", |file| {
+        find_chunk!(file, SkinningInfo).node_id = XmacNodeId(0)
+    });
+    assert_invalid("is for the collision mesh, which doesn't exist", |file| {
+        find_chunk!(file, SkinningInfo).is_for_collision_mesh = true
+    });
+    assert_invalid("has 2 table entries for 3 original vertices", |file| {
+        find_chunk!(file, SkinningInfo).table_entries.pop();
+    });
+    assert_invalid("table entry 1 exceeds the 3 influences", |file| {
+        find_chunk!(file, SkinningInfo).table_entries[1].start_idx = 2
+    });
+    assert_invalid("references missing joint 4", |file| {
+        find_chunk!(file, SkinningInfo).influences[0].node_idx = 4
+    });
+    assert_invalid("NaN/infinite weights", |file| {
+        find_chunk!(file, SkinningInfo).influences[1].weight = f32::INFINITY
+    });
+    assert_invalid(
+        "influenced by joints [1] that are not in its bones",
+        |file| find_chunk!(file, Mesh).submeshes[0].bones = vec![0],
+    );
+}
+
+#[test]
+fn validation_morphs() {
+    assert_invalid("deforms node 0 ('Root'), which has no mesh", |file| {
+        find_chunk!(file, MorphTargets).targets[0].mesh_deform_deltas[0].node_id = XmacNodeId(0)
+    });
+    assert_invalid("moves vertex 3 of node 1 ('Ball')", |file| {
+        find_chunk!(file, MorphTargets).targets[0].mesh_deform_deltas[0].deltas[1].vertex_id = 3
+    });
+    assert_invalid("NaN/infinite deltas", |file| {
+        find_chunk!(file, MorphTargets).targets[0].mesh_deform_deltas[0].deltas[0]
+            .normal_delta
+            .y = f32::NAN
+    });
+}
+
+#[test]
+fn validation_node_references() {
+    assert_invalid("Limit references missing node 2", |file| {
+        find_chunk!(file, Limit).node_id = XmacNodeId(2)
+    });
+    assert_invalid(
+        "Node group 'Everything' references missing node 2",
+        |file| find_chunk!(file, NodeGroups).groups[0].nodes.push(2),
+    );
+    assert_invalid("Attachment references missing node 3", |file| {
+        find_chunk!(file, AttachmentNodes).nodes[0] = 3
+    });
+    assert_invalid("Motion source references missing node 2", |file| {
+        find_chunk!(file, NodeMotionSources).sources[0] = Some(2)
+    });
+}
@@ -0,0 +1,495 @@
+//! Structural checks of an [`XmacFile`] beyond what the chunk parsers enforce,
+//! i.e. the references between chunks and the values the game can't cope with.
+
+use std::collections::HashSet;
+
+use super::XmacFile;
+use super::chunks::{XmacChunk, mesh::XmacMesh, nodes::XmacNodes, skinning_info::XmacSkinningInfo};
+use crate::error::*;
+
+/// Problems found by [`XmacFile::validate`]
+#[derive(Debug, Default)]
+pub struct XmacValidation {
+    /// Broken references or values, the file will not load (correctly)
+    pub errors: Vec<String>,
+    /// Suspicious, but the file is still usable
+    pub warnings: Vec<String>,
+}
+
+impl XmacValidation {
+    fn error(&mut self, msg: String) {
+        self.errors.push(msg);
+    }
+    fn warn(&mut self, msg: String) {
+        self.warnings.push(msg);
+    }
+}
+
+impl XmacFile {
+    /// Prints all problems, fails if there are errors
+    pub fn sanity_check(&self) -> Result<()> {
+        let report = self.validate();
+        for warning in &report.warnings {
+            println!("Warn: {warning}");
+        }
+        for error in &report.errors {
+            println!("Error: {error}");
+        }
+        if report.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidStructure(format!(
+                "XMAC validation found {} errors, first: {}",
+                report.errors.len(),
+                report.errors[0]
+            )))
+        }
+    }
+
+    pub fn validate(&self) -> XmacValidation {
+        let mut report = XmacValidation::default();
+        self.check_chunk_counts(&mut report);
+        let Some(nodes) = self.get_nodes_chunk() else {
+            return report;
+        };
+        check_nodes(nodes, &mut report);
+
+        let material_count = self
+            .get_material_info_chunk()
+            .map(|info| info.std_materials + info.fx_materials);
+        let meshes = self.get_mesh_chunks();
+        for mesh in &meshes {
+            check_mesh(mesh, "", nodes, material_count, &mut report);
+        }
+        for skin in self.get_skinning_chunks() {
+            check_skin(skin, "", &meshes, nodes, &mut report);
+        }
+        for lod in self.get_lod_chunks() {
+            let context = format!("LOD {}: ", lod.lod_level);
+            let lod_meshes = lod.get_mesh_chunks();
+            for mesh in &lod_meshes {
+                check_mesh(mesh, &context, nodes, material_count, &mut report);
+            }
+            for skin in lod.get_skinning_chunks() {
+                check_skin(skin, &context, &lod_meshes, nodes, &mut report);
+            }
+        }
+        self.check_morphs(&meshes, nodes, &mut report);
+        self.check_node_references(nodes, &mut report);
+        report
+    }
+
+    fn check_chunk_counts(&self, report: &mut XmacValidation) {
+        let mut info_chunks = 0;
+        let mut nodes_chunks = 0;
+        let mut mat_info_chunks = 0;
+        let mut std_mat_chunks = 0;
+        let mut fx_mat_chunks = 0;
+        let mut mesh_chunks = 0;
+        let mut skin_chunks = 0;
+        let mut morph_chunks = 0;
+        for chunk in &self.chunks {
+            match chunk {
+                XmacChunk::Info(_) => info_chunks += 1,
+                XmacChunk::Nodes(_) => nodes_chunks += 1,
+                XmacChunk::MaterialInfo(_) => mat_info_chunks += 1,
+                XmacChunk::StdMaterial(_) => std_mat_chunks += 1,
+                XmacChunk::Mesh(_) => mesh_chunks += 1,
+                XmacChunk::MeshLodLevels(_) => {}
+                XmacChunk::SkinningInfo(_) => skin_chunks += 1,
+                XmacChunk::MorphTargets(_) => morph_chunks += 1,
+                XmacChunk::FxMaterial(_) | XmacChunk::GenericMaterial(_) => fx_mat_chunks += 1,
+                XmacChunk::StdMaterialLayer(_)
+                | XmacChunk::MaterialAttributeSet(_)
+                | XmacChunk::Limit(_)
+                | XmacChunk::NodeGroups(_)
+                | XmacChunk::NodeMotionSources(_)
+                | XmacChunk::AttachmentNodes(_)
+                | XmacChunk::Unknown(_) => {}
+            }
+        }
+        if info_chunks != 1 {
+            report.warn(format!("Expected 1 Info chunk, found {info_chunks}"));
+        }
+        if nodes_chunks != 1 {
+            report.error(format!("Expected 1 Nodes chunk, found {nodes_chunks}"));
+        }
+        if mat_info_chunks != 1 {
+            report.error(format!(
+                "Expected 1 Material Info chunk, found {mat_info_chunks}"
+            ));
+        }
+        if let Some(mat_info) = self.get_material_info_chunk() {
+            if std_mat_chunks != mat_info.std_materials {
+                report.error(format!(
+                    "Expected {} Standard Material chunks, found {std_mat_chunks}",
+                    mat_info.std_materials
+                ));
+            }
+            if fx_mat_chunks != mat_info.fx_materials {
+                report.warn(format!(
+                    "Expected {} FX/Generic Material chunks, found {fx_mat_chunks}",
+                    mat_info.fx_materials
+                ));
+            }
+        }
+        if mesh_chunks == 0 {
+            report.error("Expected at least 1 Mesh chunk, found none".to_string());
+        }
+        if skin_chunks > mesh_chunks {
+            report.error(format!(
+                "Expected at most {mesh_chunks} Skin chunks, found {skin_chunks}"
+            ));
+        }
+        if skin_chunks == 0 {
+            report.warn(
+                "Expected at least 1 Skin chunk, found none - the mesh will probably not be rendered!"
+                    .to_string(),
+            );
+        }
+        if morph_chunks > mesh_chunks {
+            report.error(format!(
+                "Expected at most {mesh_chunks} Morph Targets chunks, found {morph_chunks}"
+            ));
+        }
+    }
+
+    fn check_morphs(&self, meshes: &[&XmacMesh], nodes: &XmacNodes, report: &mut XmacValidation) {
+        let Some(morphs) = self.get_morph_chunk() else {
+            return;
+        };
+        for target in &morphs.targets {
+            for deltas in &target.mesh_deform_deltas {
+                let node_name = node_name(nodes, deltas.node_id.0 as usize);
+                let Some(mesh) = meshes
+                    .iter()
+                    .find(|m| m.node_id == deltas.node_id && !m.is_collision_mesh)
+                else {
+                    report.error(format!(
+                        "Morph target '{}' deforms node {node_name}, which has no mesh",
+                        target.name
+                    ));
+                    continue;
+                };
+                let vertex_count = vertex_count(mesh);
+                if let Some(delta) = deltas.deltas.iter().find(|d| d.vertex_id >= vertex_count) {
+                    report.error(format!(
+                        "Morph target '{}' moves vertex {} of node {node_name}, which has only {vertex_count} vertices",
+                        target.name, delta.vertex_id
+                    ));
+                }
+                if deltas.deltas.iter().any(|d| {
+                    !(d.position_delta.is_finite()
+                        && d.normal_delta.is_finite()
+                        && d.tangent_delta.is_finite())
+                }) {
+                    report.error(format!(
+                        "Morph target '{}' has NaN/infinite deltas for node {node_name}",
+                        target.name
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Limits, node groups, attachments and motion sources
+    fn check_node_references(&self, nodes: &XmacNodes, report: &mut XmacValidation) {
+        let node_count = nodes.nodes.len();
+        for limit in self.get_limit_chunks() {
+            if limit.node_id.0 as usize >= node_count {
+                report.error(format!("Limit references missing node {}", limit.node_id.0));
+            }
+        }
+        if let Some(groups) = self.get_node_groups_chunk() {
+            for group in &groups.groups {
+                if let Some(node) = group.nodes.iter().find(|n| **n as usize >= node_count) {
+                    report.error(format!(
+                        "Node group '{}' references missing node {node}",
+                        group.name
+                    ));
+                }
+            }
+        }
+        if let Some(attachments) = self.get_attachment_nodes_chunk()
+            && let Some(node) = attachments
+                .nodes
+                .iter()
+                .find(|n| **n as usize >= node_count)
+        {
+            report.error(format!("Attachment references missing node {node}"));
+        }
+        if let Some(motion_sources) = self.get_node_motion_sources_chunk() {
+            if motion_sources.sources.len() != node_count {
+                report.warn(format!(
+                    "{} motion sources for {node_count} nodes",
+                    motion_sources.sources.len()
+                ));
+            }
+            if let Some(source) = motion_sources
+                .sources
+                .iter()
+                .flatten()
+                .find(|s| **s as usize >= node_count)
+            {
+                report.error(format!("Motion source references missing node {source}"));
+            }
+        }
+    }
+}
+
+fn node_name(nodes: &XmacNodes, idx: usize) -> String {
+    match nodes.nodes.get(idx) {
+        Some(node) => format!("{idx} ('{}')", node.name),
+        None => format!("{idx} (missing)"),
+    }
+}
+
+fn vertex_count(mesh: &XmacMesh) -> u32 {
+    mesh.submeshes.iter().map(|s| s.vertices_count).sum()
+}
+
+fn check_nodes(nodes: &XmacNodes, report: &mut XmacValidation) {
+    let node_count = nodes.nodes.len();
+    let mut child_counts = vec![0; node_count];
+    for (idx, node) in nodes.nodes.iter().enumerate() {
+        let name = &node.name;
+        if let Some(parent) = node.parent_idx {
+            match child_counts.get_mut(parent) {
+                Some(count) => *count += 1,
+                None => report.error(format!(
+                    "Node {idx} ('{name}') references missing parent {parent}"
+                )),
+            }
+        }
+        if !(node.rotation.is_finite()
+            && node.local_pos.is_finite()
+            && node.local_scale.is_finite()
+            && node.oriented_bounding_box.is_finite())
+        {
+            report.error(format!(
+                "Node {idx} ('{name}') has a NaN/infinite transform or bounding box"
+            ));
+            continue;
+        }
+        if node.local_scale.cmpeq(glam::Vec3::ZERO).any() {
+            report.error(format!(
+                "Node {idx} ('{name}') has a zero scale {}",
+                node.local_scale
+            ));
+        }
+        if (node.rotation.length() - 1.0).abs() > 1e-3 {
+            report.warn(format!(
+                "Node {idx} ('{name}') has a non-normalized rotation {}",
+                node.rotation
+            ));
+        }
+    }
+
+    // Walking up from a node has to end at a root within node_count steps:
+    for (idx, node) in nodes.nodes.iter().enumerate() {
+        let mut current = node.parent_idx;
+        let mut steps = 0;
+        while let Some(parent) = current
+            && parent < node_count
+        {
+            if parent == idx || steps > node_count {
+                report.error(format!("Node {idx} ('{}') is its own ancestor", node.name));
+                break;
+            }
+            current = nodes.nodes[parent].parent_idx;
+            steps += 1;
+        }
+    }
+
+    for (idx, (node, count)) in nodes.nodes.iter().zip(child_counts).enumerate() {
+        if node.child_count != count {
+            report.warn(format!(
+                "Node {idx} ('{}') claims {} children, but has {count}",
+                node.name, node.child_count
+            ));
+        }
+    }
+}
+
+fn check_mesh(
+    mesh: &XmacMesh,
+    context: &str,
+    nodes: &XmacNodes,
+    material_count: Option<usize>,
+    report: &mut XmacValidation,
+) {
+    let node_count = nodes.nodes.len();
+    let mesh_node = node_name(nodes, mesh.node_id.0 as usize);
+    let mesh_name = format!("{context}Mesh of node {mesh_node}");
+    if mesh.node_id.0 as usize >= node_count {
+        report.error(format!("{mesh_name} references a missing node"));
+    }
+
+    let vertex_count = vertex_count(mesh);
+    for layer in &mesh.vertex_attribute_layers {
+        use super::chunks::mesh::XmacMeshAttrib;
+        let (layer_name, len) = match &layer.attribs {
+            XmacMeshAttrib::Positions(v) => ("positions", v.len()),
+            XmacMeshAttrib::Normals(v) => ("normals", v.len()),
+            XmacMeshAttrib::Tangents(v) => ("tangents", v.len()),
+            XmacMeshAttrib::UvCoords(v) => ("uvs", v.len()),
+            XmacMeshAttrib::Colors32(v) => ("colors", v.len()),
+            XmacMeshAttrib::OriginalVertexNumbers(v) => ("original vertex numbers", v.len()),
+            XmacMeshAttrib::Colors128(v) => ("colors", v.len()),
+            XmacMeshAttrib::BiTangents(v) => ("bitangents", v.len()),
+            XmacMeshAttrib::ClothData(v) => ("cloth data", v.len()),
+        };
+        if len != vertex_count as usize {
+            report.error(format!(
+                "{mesh_name} has {len} {layer_name}, but its submeshes use {vertex_count} vertices"
+            ));
+        }
+    }
+    match mesh.get_position_attrib() {
+        Some(positions) if positions.iter().any(|p| !p.is_finite()) => {
+            report.error(format!("{mesh_name} has NaN/infinite positions"));
+        }
+        Some(_) => {}
+        None => report.error(format!("{mesh_name} has no positions")),
+    }
+    match mesh.get_orig_vert() {
+        Some(orig_verts) => {
+            if let Some(orig) = orig_verts.iter().find(|v| **v >= mesh.orig_verts_count) {
+                report.error(format!(
+                    "{mesh_name} references original vertex {orig} of {}",
+                    mesh.orig_verts_count
+                ));
+            }
+        }
+        None => report.warn(format!("{mesh_name} has no original vertex numbers")),
+    }
+
+    for (submesh_idx, submesh) in mesh.submeshes.iter().enumerate() {
+        if let Some(index) = submesh
+            .indices
+            .iter()
+            .find(|i| **i >= submesh.vertices_count)
+        {
+            report.error(format!(
+                "{mesh_name}: index {index} of submesh {submesh_idx} exceeds its {} vertices",
+                submesh.vertices_count
+            ));
+        }
+        if submesh.indices.len() % 3 != 0 {
+            report.error(format!(
+                "{mesh_name}: submesh {submesh_idx} has {} indices, which is not a triangle list",
+                submesh.indices.len()
+            ));
+        }
+        if let Some(material_count) = material_count
+            && submesh.material_idx as usize >= material_count
+        {
+            report.error(format!(
+                "{mesh_name}: submesh {submesh_idx} uses material {}, but there are only {material_count}",
+                submesh.material_idx
+            ));
+        }
+        if let Some(bone) = submesh.bones.iter().find(|b| **b as usize >= node_count) {
+            report.error(format!(
+                "{mesh_name}: submesh {submesh_idx} references missing bone {bone}"
+            ));
+        }
+    }
+}
+
+fn check_skin(
+    skin: &XmacSkinningInfo,
+    context: &str,
+    meshes: &[&XmacMesh],
+    nodes: &XmacNodes,
+    report: &mut XmacValidation,
+) {
+    let node_count = nodes.nodes.len();
+    let skin_name = format!(
+        "{context}Skin of node {}",
+        node_name(nodes, skin.node_id.0 as usize)
+    );
+    let Some(mesh) = meshes
+        .iter()
+        .find(|m| m.node_id == skin.node_id && m.is_collision_mesh == skin.is_for_collision_mesh)
+    else {
+        if meshes.iter().any(|m| m.node_id == skin.node_id) {
+            report.error(format!(
+                "{skin_name} is for the {}collision mesh, which doesn't exist",
+                if skin.is_for_collision_mesh {
+                    ""
+                } else {
+                    "non-"
+                }
+            ));
+        } else {
+            report.error(format!("{skin_name} references a missing mesh"));
+        }
+        return;
+    };
+
+    if skin.table_entries.len() != mesh.orig_verts_count as usize {
+        report.error(format!(
+            "{skin_name} has {} table entries for {} original vertices",
+            skin.table_entries.len(),
+            mesh.orig_verts_count
+        ));
+    }
+    let mut entries_valid = true;
+    for (entry_idx, entry) in skin.table_entries.iter().enumerate() {
+        if entry.start_idx as usize + entry.num_elements as usize > skin.influences.len() {
+            report.error(format!(
+                "{skin_name}: table entry {entry_idx} exceeds the {} influences",
+                skin.influences.len()
+            ));
+            entries_valid = false;
+            break;
+        }
+    }
+    if let Some(influence) = skin
+        .influences
+        .iter()
+        .find(|i| i.node_idx as usize >= node_count)
+    {
+        report.error(format!(
+            "{skin_name} references missing joint {}",
+            influence.node_idx
+        ));
+    }
+    if skin.influences.iter().any(|i| !i.weight.is_finite()) {
+        report.error(format!("{skin_name} has NaN/infinite weights"));
+    }
+
+    // Every joint influencing a vertex has to be in the bones of its submesh:
+    let Some(orig_verts) = mesh.get_orig_vert() else {
+        return;
+    };
+    if !entries_valid {
+        return;
+    }
+    let mut vertex_offset = 0;
+    for (submesh_idx, submesh) in mesh.submeshes.iter().enumerate() {
+        let bones = submesh.bones.iter().copied().collect::<HashSet<_>>();
+        let mut missing = HashSet::new();
+        let range = vertex_offset..vertex_offset + submesh.vertices_count as usize;
+        for orig in orig_verts.get(range).into_iter().flatten() {
+            let Some(entry) = skin.table_entries.get(*orig as usize) else {
+                continue;
+            };
+            let start = entry.start_idx as usize;
+            for influence in &skin.influences[start..start + entry.num_elements as usize] {
+                if !bones.contains(&(influence.node_idx as u32)) {
+                    missing.insert(influence.node_idx);
+                }
+            }
+        }
+        if !missing.is_empty() {
+            let mut missing = missing.into_iter().collect::<Vec<_>>();
+            missing.sort();
+            report.error(format!(
+                "{skin_name}: submesh {submesh_idx} is influenced by joints {missing:?} that are not in its bones"
+            ));
+        }
+        vertex_offset += submesh.vertices_count as usize;
+    }
+}
@@ -0,0 +1,492 @@
+//! EMotionFX motions (`._xmot`, eCMotionResource2).
+//!
+//! The layout follows the legacy EMotionFX importer (see the resources listed in [`super::xmac`]):
+//! a resource header, the `XMO ` header and a list of chunks using the same chunk header as XMAC.
+//! Only the skeletal sub motions are parsed, every other chunk (motion events, info, ...) is kept as is.
+//! Sub motions contain the pose of a node and optional keyframes for position, rotation and scale,
+//! rotations are stored as 16 bit quaternions.
+
+use std::io::Write;
+
+use serde::{Deserialize, Serialize};
+
+use super::xmac::chunks::{XmacChunkMeta, unknown::XmacUnknownChunk};
+use super::xmac::{read_xmac_str, write_xmac_str};
+use crate::{
+    archive::{ArchiveReadTarget, ArchiveWriteTarget, TempWriteTarget},
+    binimport::BinImport,
+    error::*,
+    helpers::*,
+    resourcefile::ResourceFile,
+    types::{Quat, Vec3},
+};
+
+const XMOT_MAGIC: [u8; 4] = *b"XMO ";
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct XmotFile {
+    pub res: ResourceFile,
+    pub version_maj: u8,
+    pub version_min: u8,
+    pub big_endian: bool,
+    pub chunks: Vec<XmotChunk>,
+    /// Everything after the chunks
+    #[serde(with = "crate::helpers::ser_hex")]
+    pub trail: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum XmotChunk {
+    SubMotions(XmotSubMotions),
+    Unknown(XmacUnknownChunk),
+}
+
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XmotChunkType {
+    MotionEventTable = 50,
+    SubMotion = 200,
+    Info = 201,
+    SubMotions = 202,
+    WaveletInfo = 203,
+    MorphSubMotions = 204,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct XmotSubMotions {
+    pub sub_motions: Vec<XmotSubMotion>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct XmotKey<T> {
+    /// in seconds
+    pub time: f32,
+    pub value: T,
+}
+
+/// Animation of a single node, matched to the actor's nodes by name
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct XmotSubMotion {
+    pub name: String,
+    pub pose_rot: Quat,
+    pub bind_pose_rot: Quat,
+    pub pose_scale_rot: Quat,
+    pub bind_pose_scale_rot: Quat,
+    pub pose_pos: Vec3,
+    pub pose_scale: Vec3,
+    pub bind_pose_pos: Vec3,
+    pub bind_pose_scale: Vec3,
+    /// Error tolerance the keys were optimized with
+    pub max_error: f32,
+    pub pos_keys: Vec<XmotKey<Vec3>>,
+    pub rot_keys: Vec<XmotKey<Quat>>,
+    pub scale_keys: Vec<XmotKey<Vec3>>,
+    pub scale_rot_keys: Vec<XmotKey<Quat>>,
+}
+
+impl XmotFile {
+    pub fn load<R: ArchiveReadTarget>(src: &mut R) -> Result<Self> {
+        let res = ResourceFile::load(src)?;
+
+        let data_len = read_u32(src)? as u64;
+        let xmot_start = src.stream_position()?;
+        let xmot_finish = xmot_start + data_len;
+
+        let mut magic = [0; 4];
+        src.read_exact(&mut magic)?;
+        if magic != XMOT_MAGIC {
+            return Err(Error::InvalidStructure(format!(
+                "Expected XMOT magic, found {magic:x?}"
+            )));
+        }
+        let version_maj = read_u8(src)?;
+        let version_min = read_u8(src)?;
+        let big_endian = read_bool(src)?;
+
+        let mut chunks = Vec::new();
+        while src.stream_position()? < xmot_finish {
+            chunks.push(XmotChunk::load(src, big_endian)?);
+        }
+
+        let mut trail = Vec::new();
+        src.read_to_end(&mut trail)?;
+
+        Ok(Self {
+            res,
+            version_maj,
+            version_min,
+            big_endian,
+            chunks,
+            trail,
+        })
+    }
+
+    pub fn save<W: ArchiveWriteTarget>(&self, dst: &mut W) -> Result<()> {
+        let mut data = TempWriteTarget::new(dst);
+        self.save_xmot(&mut data)?;
+        let data = data.finish();
+
+        self.res.save(dst, data.len() + self.trail.len())?;
+        dst.write_all(&data)?;
+        dst.write_all(&self.trail)?;
+        Ok(())
+    }
+
+    fn save_xmot<W: ArchiveWriteTarget>(&self, dst: &mut W) -> Result<()> {
+        let mut data = TempWriteTarget::new(dst);
+        data.write_all(&XMOT_MAGIC)?;
+        write_u8(&mut data, self.version_maj)?;
+        write_u8(&mut data, self.version_min)?;
+        write_bool(&mut data, self.big_endian)?;
+        for chunk in &self.chunks {
+            chunk.save(&mut data, self.big_endian)?;
+        }
+        let data = data.finish();
+
+        write_u32(dst, data.len() as u32)?; //always little-endian, like in XMAC
+        dst.write_all(&data)?;
+        Ok(())
+    }
+
+    pub fn get_sub_motions(&self) -> Vec<&XmotSubMotion> {
+        self.chunks
+            .iter()
+            .flat_map(|chunk| match chunk {
+                XmotChunk::SubMotions(sub_motions) => sub_motions.sub_motions.as_slice(),
+                XmotChunk::Unknown(_) => &[],
+            })
+            .collect()
+    }
+
+    /// Time of the last key
+    pub fn duration(&self) -> f32 {
+        self.get_sub_motions()
+            .iter()
+            .flat_map(|sub| {
+                let times = [
+                    sub.pos_keys.last().map(|k| k.time),
+                    sub.rot_keys.last().map(|k| k.time),
+                    sub.scale_keys.last().map(|k| k.time),
+                    sub.scale_rot_keys.last().map(|k| k.time),
+                ];
+                times.into_iter().flatten()
+            })
+            .fold(0.0, f32::max)
+    }
+}
+
+impl XmotChunk {
+    pub fn load<R: ArchiveReadTarget>(src: &mut R, big_endian: bool) -> Result<Self> {
+        let chunk_meta = XmacChunkMeta::load(src, big_endian)?;
+        let chunk_end = src.stream_position()? + chunk_meta.size as u64;
+        let chunk =
+            if chunk_meta.type_id == XmotChunkType::SubMotions as u32 && chunk_meta.version == 1 {
+                XmotChunk::SubMotions(XmotSubMotions::load(src, big_endian)?)
+            } else {
+                XmotChunk::Unknown(XmacUnknownChunk::load(src, &chunk_meta)?)
+            };
+        let end_pos = src.stream_position()?;
+        if end_pos != chunk_end {
+            return Err(Error::InvalidStructure(format!(
+                "XMOT chunk {} finished at 0x{end_pos:x} instead of 0x{chunk_end:x}",
+                chunk_meta.type_id
+            )));
+        }
+        Ok(chunk)
+    }
+
+    pub fn save<W: ArchiveWriteTarget>(&self, dst: &mut W, big_endian: bool) -> Result<()> {
+        let mut data = TempWriteTarget::new(dst);
+        let meta = match self {
+            XmotChunk::SubMotions(sub_motions) => sub_motions.save(&mut data, big_endian)?,
+            XmotChunk::Unknown(unknown) => unknown.save(&mut data)?,
+        };
+        let data = data.finish();
+        assert_eq!(data.len(), meta.size as usize);
+        meta.save(dst, big_endian)?;
+        dst.write_all(&data)?;
+        Ok(())
+    }
+}
+
+impl XmotSubMotions {
+    fn load<R: ArchiveReadTarget>(src: &mut R, big_endian: bool) -> Result<Self> {
+        let count = read_u32_endian(src, big_endian)? as usize;
+        let mut sub_motions = Vec::with_capacity(count.min(1024));
+        for _idx in 0..count {
+            sub_motions.push(XmotSubMotion::load(src, big_endian)?);
+        }
+        Ok(Self { sub_motions })
+    }
+
+    fn save<W: ArchiveWriteTarget>(&self, dst: &mut W, big_endian: bool) -> Result<XmacChunkMeta> {
+        let mut data = TempWriteTarget::new(dst);
+        write_u32_endian(&mut data, self.sub_motions.len() as u32, big_endian)?;
+        for sub_motion in &self.sub_motions {
+            sub_motion.save(&mut data, big_endian)?;
+        }
+        let data = data.finish();
+        dst.write_all(&data)?;
+        Ok(XmacChunkMeta {
+            type_id: XmotChunkType::SubMotions as u32,
+            size: data.len() as u32,
+            version: 1,
+        })
+    }
+}
+
+impl XmotSubMotion {
+    /// A sub motion without keys, holding the given bind pose
+    pub fn new(name: &str, rotation: Quat, position: Vec3, scale: Vec3) -> Self {
+        Self {
+            name: name.to_string(),
+            pose_rot: rotation,
+            bind_pose_rot: rotation,
+            pose_scale_rot: Quat::IDENTITY,
+            bind_pose_scale_rot: Quat::IDENTITY,
+            pose_pos: position,
+            pose_scale: scale,
+            bind_pose_pos: position,
+            bind_pose_scale: scale,
+            max_error: 0.0,
+            pos_keys: Vec::new(),
+            rot_keys: Vec::new(),
+            scale_keys: Vec::new(),
+            scale_rot_keys: Vec::new(),
+        }
+    }
+
+    fn load<R: ArchiveReadTarget>(src: &mut R, big_endian: bool) -> Result<Self> {
+        let pose_rot = read_quat16(src, big_endian)?;
+        let bind_pose_rot = read_quat16(src, big_endian)?;
+        let pose_scale_rot = read_quat16(src, big_endian)?;
+        let bind_pose_scale_rot = read_quat16(src, big_endian)?;
+        let pose_pos = Vec3::load_endian(src, big_endian)?;
+        let pose_scale = Vec3::load_endian(src, big_endian)?;
+        let bind_pose_pos = Vec3::load_endian(src, big_endian)?;
+        let bind_pose_scale = Vec3::load_endian(src, big_endian)?;
+        let pos_count = read_u32_endian(src, big_endian)? as usize;
+        let rot_count = read_u32_endian(src, big_endian)? as usize;
+        let scale_count = read_u32_endian(src, big_endian)? as usize;
+        let scale_rot_count = read_u32_endian(src, big_endian)? as usize;
+        let max_error = read_f32_endian(src, big_endian)?;
+        let name = read_xmac_str(src, big_endian)?;
+
+        let vec3_keys = |src: &mut R, count| -> Result<Vec<XmotKey<Vec3>>> {
+            (0..count)
+                .map(|_| {
+                    let value = Vec3::load_endian(src, big_endian)?;
+                    let time = read_f32_endian(src, big_endian)?;
+                    Ok(XmotKey { time, value })
+                })
+                .collect()
+        };
+        let quat_keys = |src: &mut R, count| -> Result<Vec<XmotKey<Quat>>> {
+            (0..count)
+                .map(|_| {
+                    let value = read_quat16(src, big_endian)?;
+                    let time = read_f32_endian(src, big_endian)?;
+                    Ok(XmotKey { time, value })
+                })
+                .collect()
+        };
+        Ok(Self {
+            name,
+            pose_rot,
+            bind_pose_rot,
+            pose_scale_rot,
+            bind_pose_scale_rot,
+            pose_pos,
+            pose_scale,
+            bind_pose_pos,
+            bind_pose_scale,
+            max_error,
+            pos_keys: vec3_keys(src, pos_count)?,
+            rot_keys: quat_keys(src, rot_count)?,
+            scale_keys: vec3_keys(src, scale_count)?,
+            scale_rot_keys: quat_keys(src, scale_rot_count)?,
+        })
+    }
+
+    fn save<W: ArchiveWriteTarget>(&self, dst: &mut W, big_endian: bool) -> Result<()> {
+        write_quat16(dst, self.pose_rot, big_endian)?;
+        write_quat16(dst, self.bind_pose_rot, big_endian)?;
+        write_quat16(dst, self.pose_scale_rot, big_endian)?;
+        write_quat16(dst, self.bind_pose_scale_rot, big_endian)?;
+        self.pose_pos.save_endian(dst, big_endian)?;
+        self.pose_scale.save_endian(dst, big_endian)?;
+        self.bind_pose_pos.save_endian(dst, big_endian)?;
+        self.bind_pose_scale.save_endian(dst, big_endian)?;
+        write_u32_endian(dst, self.pos_keys.len() as u32, big_endian)?;
+        write_u32_endian(dst, self.rot_keys.len() as u32, big_endian)?;
+        write_u32_endian(dst, self.scale_keys.len() as u32, big_endian)?;
+        write_u32_endian(dst, self.scale_rot_keys.len() as u32, big_endian)?;
+        write_f32_endian(dst, self.max_error, big_endian)?;
+        write_xmac_str(dst, &self.name, big_endian)?;
+
+        for key in &self.pos_keys {
+            key.value.save_endian(dst, big_endian)?;
+            write_f32_endian(dst, key.time, big_endian)?;
+        }
+        for key in &self.rot_keys {
+            write_quat16(dst, key.value, big_endian)?;
+            write_f32_endian(dst, key.time, big_endian)?;
+        }
+        for key in &self.scale_keys {
+            key.value.save_endian(dst, big_endian)?;
+            write_f32_endian(dst, key.time, big_endian)?;
+        }
+        for key in &self.scale_rot_keys {
+            write_quat16(dst, key.value, big_endian)?;
+            write_f32_endian(dst, key.time, big_endian)?;
+        }
+        Ok(())
+    }
+}
+
+/// 16 bit quaternion, every component is stored as `round(value * 32767)`
+fn read_quat16<R: ArchiveReadTarget>(src: &mut R, big_endian: bool) -> Result<Quat> {
+    let mut values = [0.0; 4];
+    for value in &mut values {
+        *value = read_u16_endian(src, big_endian)? as i16 as f32 / i16::MAX as f32;
+    }
+    Ok(Quat::from_array(values))
+}
+
+fn write_quat16<W: ArchiveWriteTarget>(dst: &mut W, quat: Quat, big_endian: bool) -> Result<()> {
+    for value in quat.to_array() {
+        let value = (value.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16;
+        write_u16_endian(dst, value as u16, big_endian)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::time::SystemTime;
+
+    use super::*;
+    use crate::types::time::DateTime;
+
+    fn sample_file(big_endian: bool) -> XmotFile {
+        let mut root = XmotSubMotion::new(
+            "Root",
+            Quat::from_rotation_z(0.5),
+            Vec3::new(1.0, 2.0, 3.0),
+            Vec3::ONE,
+        );
+        root.max_error = 0.25;
+        root.pos_keys = vec![
+            XmotKey {
+                time: 0.0,
+                value: Vec3::new(1.0, 2.0, 3.0),
+            },
+            XmotKey {
+                time: 1.5,
+                value: Vec3::new(-4.0, 2.0, 30.0),
+            },
+        ];
+        root.rot_keys = vec![XmotKey {
+            time: 0.5,
+            value: Quat::from_rotation_x(-1.0),
+        }];
+        root.scale_keys = vec![XmotKey {
+            time: 2.25,
+            value: Vec3::splat(2.0),
+        }];
+        root.scale_rot_keys = vec![XmotKey {
+            time: 0.75,
+            value: Quat::from_rotation_y(0.25),
+        }];
+        XmotFile {
+            res: ResourceFile {
+                timestamp: DateTime::new(SystemTime::UNIX_EPOCH),
+                props: Vec::new(),
+                data_revision: *b"MO02",
+                class_name: "eCMotionResource2".to_string(),
+                raw_file_ext: *b".xmo\0\0\0\0",
+            },
+            version_maj: 1,
+            version_min: 0,
+            big_endian,
+            chunks: vec![
+                XmotChunk::SubMotions(XmotSubMotions {
+                    sub_motions: vec![
+                        root,
+                        XmotSubMotion::new("Arm", Quat::IDENTITY, Vec3::X, Vec3::ONE),
+                    ],
+                }),
+                XmotChunk::Unknown(XmacUnknownChunk {
+                    type_id: XmotChunkType::MotionEventTable as u32,
+                    version: 1,
+                    data: vec![0xde, 0xad, 0xbe, 0xef],
+                }),
+            ],
+            trail: vec![0; 8],
+        }
+    }
+
+    fn save(file: &XmotFile) -> Vec<u8> {
+        let mut data = Cursor::new(Vec::new());
+        file.save(&mut data).unwrap();
+        data.into_inner()
+    }
+
+    fn round_trip(big_endian: bool) {
+        let file = sample_file(big_endian);
+        let data = save(&file);
+        let loaded = XmotFile::load(&mut Cursor::new(data.clone())).unwrap();
+        assert_eq!(loaded.big_endian, big_endian);
+        assert_eq!(loaded.trail, file.trail);
+        assert!(
+            matches!(&loaded.chunks[1], XmotChunk::Unknown(c) if c.data == [0xde, 0xad, 0xbe, 0xef])
+        );
+
+        // rotations are quantized to 16 bit, everything else is exact
+        let (expected, actual) = (file.get_sub_motions(), loaded.get_sub_motions());
+        assert_eq!(expected.len(), actual.len());
+        for (expected, actual) in expected.iter().zip(actual) {
+            assert_eq!(actual.name, expected.name);
+            assert_eq!(actual.pose_pos, expected.pose_pos);
+            assert_eq!(actual.bind_pose_scale, expected.bind_pose_scale);
+            assert_eq!(actual.max_error, expected.max_error);
+            assert_eq!(actual.pos_keys, expected.pos_keys);
+            assert_eq!(actual.scale_keys, expected.scale_keys);
+            assert!(actual.pose_rot.abs_diff_eq(expected.pose_rot, 1e-4));
+            for (keys, expected_keys) in [
+                (&actual.rot_keys, &expected.rot_keys),
+                (&actual.scale_rot_keys, &expected.scale_rot_keys),
+            ] {
+                assert_eq!(keys.len(), expected_keys.len());
+                for (key, expected) in keys.iter().zip(expected_keys) {
+                    assert_eq!(key.time, expected.time);
+                    assert!(key.value.abs_diff_eq(expected.value, 1e-4));
+                }
+            }
+        }
+        assert_eq!(loaded.duration(), 2.25);
+        assert_eq!(save(&loaded), data);
+    }
+
+    #[test]
+    fn xmot_round_trip_little_endian() {
+        round_trip(false);
+    }
+
+    #[test]
+    fn xmot_round_trip_big_endian() {
+        round_trip(true);
+    }
+
+    #[test]
+    fn xmot_sub_motions_must_fill_their_chunk() {
+        let mut data = save(&sample_file(false));
+        // the first chunk header follows the magic, version and endianness flag:
+        let magic = data.windows(4).position(|w| w == XMOT_MAGIC).unwrap();
+        let size_offset = magic + 4 + 3 + 4;
+        let size = u32::from_le_bytes(data[size_offset..size_offset + 4].try_into().unwrap());
+        data[size_offset..size_offset + 4].copy_from_slice(&(size + 4).to_le_bytes());
+        assert!(XmotFile::load(&mut Cursor::new(data)).is_err());
+    }
+}
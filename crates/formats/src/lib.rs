@@ -2,8 +2,18 @@ pub mod helpers;
 
 pub mod archive;
 pub mod binimport;
+pub mod catalog;
+pub mod deps;
+pub mod diff;
 pub mod error;
+pub mod light;
+pub mod merge;
+pub mod patch;
+pub mod query;
 pub mod resourcefile;
+pub mod table;
+#[cfg(test)]
+mod test_util;
 pub mod types;
 pub mod inproc {
     pub mod arrays;
@@ -18,8 +28,11 @@ pub mod inproc {
 pub mod file_formats {
     pub mod lrent;
     pub mod tple;
+    pub mod volume;
+    pub mod world;
     pub mod ximg;
     pub mod xmac;
+    pub mod xmot;
     pub mod xsnd;
 }
 
@@ -0,0 +1,316 @@
+//! Typed view of the light property sets of an entity.
+//!
+//! Color, intensity, range etc. are regular properties of `eCPointLight_PS`, `eCStaticPointLight_PS`,
+//! `eCDirectionalLight_PS` and `eCStaticAmbientLight_PS`. Their class data is read by the classes' own `OnRead`
+//! in Engine.dll, which isn't reverse engineered yet. It is left untouched in the property set and saved as loaded.
+//! Risen has no spot lights. The shadow caster type is taken from the `eCIlluminated_PS` of the same entity.
+//!
+//! There is no falloff: the accessors exported for the light properties (see `r1-engine-sys/lib/Engine.def`)
+//! are `CastShadows`, `Color`, `DecayDuration`, `Effect*`, `Intensity`, `Offset`, `Range`, `SpecularColor`
+//! and the like, but no `Falloff` on any light class. The attenuation is only controlled by `Range`.
+use crate::diff::DiffEntity;
+use crate::merge::MergeEntity;
+use crate::types::Vec3;
+use crate::types::properties::{PropData, PropEnum, enums};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightKind {
+    /// eCPointLight_PS, dynamic
+    Point,
+    /// eCStaticPointLight_PS, baked into the lightmaps
+    StaticPoint,
+    /// eCDirectionalLight_PS
+    Directional,
+    /// eCStaticAmbientLight_PS
+    StaticAmbient,
+}
+
+impl LightKind {
+    pub const ALL: [LightKind; 4] = [
+        LightKind::Point,
+        LightKind::StaticPoint,
+        LightKind::Directional,
+        LightKind::StaticAmbient,
+    ];
+
+    pub fn class_name(self) -> &'static str {
+        match self {
+            LightKind::Point => "eCPointLight_PS",
+            LightKind::StaticPoint => "eCStaticPointLight_PS",
+            LightKind::Directional => "eCDirectionalLight_PS",
+            LightKind::StaticAmbient => "eCStaticAmbientLight_PS",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Light {
+    pub kind: LightKind,
+    /// `Color`, linear RGB
+    pub color: [f32; 3],
+    /// `Intensity`, in game specific units
+    pub intensity: f32,
+    /// `Range` in game units (cm), not used by directional lights
+    pub range: Option<f32>,
+    /// `Offset` of the light source relative to the entity
+    pub offset: Option<Vec3>,
+    pub cast_shadows: Option<bool>,
+    /// `DecayDuration`, only dynamic point lights have it
+    pub decay_duration: Option<f32>,
+    /// Flicker, pulse, ... of dynamic point lights
+    pub effect: Option<enums::DynamicLightEffect>,
+    pub shadow_caster: Option<enums::ShadowCasterType>,
+}
+
+impl Light {
+    /// The first light property set of `entity`
+    pub fn from_entity<T: DiffEntity>(entity: &T) -> Option<Self> {
+        let (kind, apo) = LightKind::ALL
+            .iter()
+            .find_map(|kind| Some((*kind, entity.accessor(kind.class_name())?)))?;
+        let object = &apo.object;
+        let color = match object.get_property("Color") {
+            Some(PropData::FloatColor { r, g, b, .. }) => [*r, *g, *b],
+            _ => [1.0, 1.0, 1.0],
+        };
+        let number = |name| {
+            object
+                .get_property(name)
+                .and_then(PropData::as_number)
+                .map(|v| v as f32)
+        };
+        let shadow_caster = entity
+            .accessor("eCIlluminated_PS")
+            .and_then(|apo| apo.object.get_property("ShadowCasterType"))
+            .and_then(|data| match data {
+                PropData::Enum(PropEnum::ShadowCasterType(ty))
+                | PropData::ContainerEnum(PropEnum::ShadowCasterType(ty)) => Some(*ty),
+                _ => None,
+            });
+        Some(Self {
+            kind,
+            color,
+            intensity: number("Intensity").unwrap_or(1.0),
+            range: number("Range"),
+            offset: match object.get_property("Offset") {
+                Some(PropData::Vector3(offset)) => Some(*offset),
+                _ => None,
+            },
+            cast_shadows: match object.get_property("CastShadows") {
+                Some(PropData::Bool(v)) => Some(*v),
+                _ => None,
+            },
+            decay_duration: number("DecayDuration"),
+            effect: match object.get_property("Effect") {
+                Some(PropData::Enum(PropEnum::DynamicLightEffect(effect)))
+                | Some(PropData::ContainerEnum(PropEnum::DynamicLightEffect(effect))) => {
+                    Some(*effect)
+                }
+                _ => None,
+            },
+            shadow_caster,
+        })
+    }
+
+    /// Writes the values back to the properties of `entity`, returns the names of the changed properties.
+    /// Only properties that already exist are changed, `kind` has to match the light property set of `entity`.
+    pub fn apply<T: MergeEntity>(&self, entity: &mut T) -> Vec<String> {
+        let mut changed = Vec::new();
+        if let Some(apo) = entity.accessor_mut(self.kind.class_name()) {
+            let object = &mut apo.object;
+            let mut set = |name: &str, update: &dyn Fn(&PropData) -> Option<PropData>| {
+                let Some(old) = object.get_property(name) else {
+                    return;
+                };
+                if let Some(new) = update(old)
+                    && &new != old
+                {
+                    object.set_property(name, new);
+                    changed.push(format!("{}.{name}", self.kind.class_name()));
+                }
+            };
+            set("Color", &|old| match old {
+                PropData::FloatColor { unknown, .. } => Some(PropData::FloatColor {
+                    unknown: *unknown,
+                    r: self.color[0],
+                    g: self.color[1],
+                    b: self.color[2],
+                }),
+                _ => None,
+            });
+            set("Intensity", &|old| with_number_type(old, self.intensity));
+            set("Range", &|old| with_number_type(old, self.range?));
+            set("Offset", &|_| self.offset.map(PropData::Vector3));
+            set("CastShadows", &|_| self.cast_shadows.map(PropData::Bool));
+            set("DecayDuration", &|old| {
+                with_number_type(old, self.decay_duration?)
+            });
+            set("Effect", &|old| {
+                let effect = PropEnum::DynamicLightEffect(self.effect?);
+                Some(match old {
+                    PropData::Enum(_) => PropData::Enum(effect),
+                    _ => PropData::ContainerEnum(effect),
+                })
+            });
+        }
+        if let Some(ty) = self.shadow_caster
+            && let Some(apo) = entity.accessor_mut("eCIlluminated_PS")
+            && let Some(old) = apo.object.get_property("ShadowCasterType")
+        {
+            let new = match old {
+                PropData::Enum(_) => PropData::Enum(PropEnum::ShadowCasterType(ty)),
+                _ => PropData::ContainerEnum(PropEnum::ShadowCasterType(ty)),
+            };
+            if &new != old {
+                apo.object.set_property("ShadowCasterType", new);
+                changed.push("eCIlluminated_PS.ShadowCasterType".to_string());
+            }
+        }
+        changed
+    }
+}
+
+/// `value` stored as the same kind of number as `old`, integers are rounded
+fn with_number_type(old: &PropData, value: f32) -> Option<PropData> {
+    Some(match old {
+        PropData::Float(_) => PropData::Float(value),
+        PropData::Int(_) => PropData::Int(value.round() as i32),
+        PropData::Short(_) => PropData::Short(value.round() as i16),
+        PropData::Long(_) => PropData::Long(value.round() as u32),
+        PropData::Char(_) => PropData::Char(value.round() as u8),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{accessor, template};
+    use crate::types::template::TemplateEntity;
+
+    fn torch() -> TemplateEntity {
+        template(
+            "{00000000-0000-0000-0000-000000000001}",
+            "Torch",
+            vec![
+                accessor(
+                    "eCPointLight_PS",
+                    &[
+                        (
+                            "Color",
+                            PropData::FloatColor {
+                                unknown: 5,
+                                r: 1.0,
+                                g: 0.5,
+                                b: 0.25,
+                            },
+                        ),
+                        ("Intensity", PropData::Int(2)),
+                        ("Range", PropData::Float(800.0)),
+                        ("DecayDuration", PropData::Float(0.5)),
+                        (
+                            "Effect",
+                            PropData::ContainerEnum(PropEnum::DynamicLightEffect(
+                                enums::DynamicLightEffect::Flicker,
+                            )),
+                        ),
+                    ],
+                ),
+                accessor(
+                    "eCIlluminated_PS",
+                    &[(
+                        "ShadowCasterType",
+                        PropData::Enum(PropEnum::ShadowCasterType(enums::ShadowCasterType::Object)),
+                    )],
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn read_light_properties() {
+        let light = Light::from_entity(&torch()).unwrap();
+        assert_eq!(
+            light,
+            Light {
+                kind: LightKind::Point,
+                color: [1.0, 0.5, 0.25],
+                intensity: 2.0,
+                range: Some(800.0),
+                offset: None,
+                cast_shadows: None,
+                decay_duration: Some(0.5),
+                effect: Some(enums::DynamicLightEffect::Flicker),
+                shadow_caster: Some(enums::ShadowCasterType::Object),
+            }
+        );
+
+        let ambient = template(
+            "{00000000-0000-0000-0000-000000000002}",
+            "Ambient",
+            vec![accessor("eCStaticAmbientLight_PS", &[])],
+        );
+        let light = Light::from_entity(&ambient).unwrap();
+        assert_eq!(light.kind, LightKind::StaticAmbient);
+        assert_eq!(light.color, [1.0; 3]);
+        assert_eq!(light.intensity, 1.0);
+        assert_eq!(light.range, None);
+
+        let no_light = template(
+            "{00000000-0000-0000-0000-000000000003}",
+            "Chair",
+            Vec::new(),
+        );
+        assert_eq!(Light::from_entity(&no_light), None);
+    }
+
+    #[test]
+    fn apply_keeps_property_types() {
+        let mut entity = torch();
+        let mut light = Light::from_entity(&entity).unwrap();
+        assert!(light.apply(&mut entity).is_empty());
+
+        light.color = [0.0, 1.0, 0.0];
+        light.intensity = 3.4;
+        light.range = Some(1000.0);
+        light.offset = Some(Vec3::Y);
+        light.effect = Some(enums::DynamicLightEffect::Pulse);
+        light.shadow_caster = Some(enums::ShadowCasterType::None);
+        let changed = light.apply(&mut entity);
+        assert_eq!(
+            changed,
+            [
+                "eCPointLight_PS.Color",
+                "eCPointLight_PS.Intensity",
+                "eCPointLight_PS.Range",
+                "eCPointLight_PS.Effect",
+                "eCIlluminated_PS.ShadowCasterType"
+            ]
+        );
+
+        let object = &entity.accessor("eCPointLight_PS").unwrap().object;
+        assert_eq!(
+            object.get_property("Color"),
+            Some(&PropData::FloatColor {
+                unknown: 5,
+                r: 0.0,
+                g: 1.0,
+                b: 0.0
+            })
+        );
+        assert_eq!(object.get_property("Intensity"), Some(&PropData::Int(3)));
+        assert_eq!(object.get_property("Range"), Some(&PropData::Float(1000.0)));
+        assert_eq!(
+            object.get_property("Effect"),
+            Some(&PropData::ContainerEnum(PropEnum::DynamicLightEffect(
+                enums::DynamicLightEffect::Pulse
+            )))
+        );
+        // properties the light set doesn't have are not added:
+        assert_eq!(object.get_property("Offset"), None);
+        assert_eq!(
+            Light::from_entity(&entity).unwrap().shadow_caster,
+            Some(enums::ShadowCasterType::None)
+        );
+    }
+}
@@ -0,0 +1,727 @@
+//! Three-way merge of entity lists (lrent & tple files), e.g. to combine two mods
+//! that were made against the same original file.
+//!
+//! The result starts out as version A, changes made in B (relative to the base) are applied on top.
+//! Entities are merged per header, transform, parent, accessor, class data and property;
+//! if both sides changed the same item differently, A wins and a [`Conflict`] is reported.
+//! The exception are whole entities removed on one side and modified on the other: the modified
+//! entity is kept (and the conflict reported), so no changes are dropped silently.
+//! Entities placed differently by the merged hierarchy get their bounds recalculated.
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::diff::{DiffEntity, diff_entity, parents_by_id};
+use crate::error::*;
+use crate::file_formats::{lrent::LrentFile, tple::TpleFile};
+use crate::types::entity::DynamicEntity;
+use crate::types::object::AccessorPropertyObject;
+use crate::types::properties::PropData;
+use crate::types::template::{TemplateEntity, TemplatePropertyAccessor};
+use crate::types::{Accessor, BoundingBox, GenClass, Mat4};
+
+/// Modification of entities, in addition to [`DiffEntity`]
+pub trait MergeEntity: DiffEntity + Clone {
+    /// Copies all fields listed in [`DiffEntity::header`]
+    fn copy_header(&mut self, from: &Self);
+    fn copy_transform(&mut self, from: &Self);
+    fn accessor_mut(&mut self, class: &str) -> Option<&mut AccessorPropertyObject>;
+    fn remove_accessor(&mut self, class: &str);
+    /// Appends the accessor of class `class` from `from`
+    fn copy_accessor(&mut self, from: &Self, class: &str);
+    fn add_accessor(&mut self, accessor: AccessorPropertyObject);
+}
+
+impl MergeEntity for DynamicEntity {
+    fn copy_header(&mut self, from: &Self) {
+        self.name = from.name.clone();
+        self.creator = from.creator.clone();
+        self.local_bound = from.local_bound.clone();
+        self.unknown1 = from.unknown1;
+        let (geo, from_geo) = (&mut self.geo_entity, &from.geo_entity);
+        geo.alpha = from_geo.alpha;
+        geo.view_range = from_geo.view_range;
+        geo.cache_in_range = from_geo.cache_in_range;
+        geo.unknown1 = from_geo.unknown1;
+        geo.unknown2 = from_geo.unknown2;
+        let (entity, from_entity) = (&mut geo.entity, &from_geo.entity);
+        entity.enabled = from_entity.enabled;
+        entity.render_enabled = from_entity.render_enabled;
+        entity.picking_enabled = from_entity.picking_enabled;
+        entity.collision_enabled = from_entity.collision_enabled;
+        entity.insert_type = from_entity.insert_type;
+        entity.locked = from_entity.locked;
+        entity.is_savegame_relevant = from_entity.is_savegame_relevant;
+        entity.flag_a = from_entity.flag_a;
+        entity.flag_b = from_entity.flag_b;
+        entity.flag_c = from_entity.flag_c;
+        entity.flag_d = from_entity.flag_d;
+        entity.flag_e = from_entity.flag_e;
+        entity.unknown1 = from_entity.unknown1;
+        entity.unknown2 = from_entity.unknown2;
+        entity.unknown3 = from_entity.unknown3;
+        entity.unknown4 = from_entity.unknown4;
+        entity.unknown5 = from_entity.unknown5;
+        entity.unknown6 = from_entity.unknown6;
+        entity.unknown7 = from_entity.unknown7;
+    }
+
+    fn copy_transform(&mut self, from: &Self) {
+        self.local_matrix = from.local_matrix;
+        self.world_bound = from.world_bound.clone();
+        self.world_sphere = from.world_sphere.clone();
+        self.geo_entity.geo_matrix = from.geo_entity.geo_matrix;
+        self.geo_entity.geo_bound = from.geo_entity.geo_bound.clone();
+        self.geo_entity.geo_sphere = from.geo_entity.geo_sphere.clone();
+    }
+
+    fn accessor_mut(&mut self, class: &str) -> Option<&mut AccessorPropertyObject> {
+        self.geo_entity
+            .entity
+            .accessors
+            .iter_mut()
+            .map(|acc| &mut acc.object)
+            .find(|apo| apo.object.get_class_name() == class)
+    }
+
+    fn remove_accessor(&mut self, class: &str) {
+        self.geo_entity
+            .entity
+            .accessors
+            .retain(|acc| acc.object.object.get_class_name() != class);
+    }
+
+    fn copy_accessor(&mut self, from: &Self, class: &str) {
+        if let Some(acc) = from
+            .geo_entity
+            .entity
+            .accessors
+            .iter()
+            .find(|acc| acc.object.object.get_class_name() == class)
+        {
+            self.geo_entity.entity.accessors.push(acc.clone());
+        }
+    }
+
+    fn add_accessor(&mut self, accessor: AccessorPropertyObject) {
+        let accessors = &mut self.geo_entity.entity.accessors;
+        let unknown = accessors.first().map(|acc| acc.unknown).unwrap_or(1);
+        accessors.push(Accessor {
+            unknown,
+            object: accessor,
+        });
+    }
+}
+
+impl MergeEntity for TemplateEntity {
+    fn copy_header(&mut self, from: &Self) {
+        self.name = from.name.clone();
+        self.enabled = from.enabled;
+        self.rendering_enabled = from.rendering_enabled;
+        self.picking_enabled = from.picking_enabled;
+        self.collision_enabled = from.collision_enabled;
+        self.helper_parent = from.helper_parent;
+        self.is_game_relevant = from.is_game_relevant;
+        self.insert_type = from.insert_type;
+        self.scale_grid_percentage = from.scale_grid_percentage;
+        self.is_savegame_relevant = from.is_savegame_relevant;
+        self.ref_template = from.ref_template.clone();
+        self.unknown1 = from.unknown1;
+        self.unknown2 = from.unknown2;
+        self.unknown3 = from.unknown3;
+        self.unknown5 = from.unknown5;
+        self.unknown6 = from.unknown6;
+        self.unknown7 = from.unknown7;
+        self.unknown8 = from.unknown8;
+        self.unknown9 = from.unknown9;
+        self.unused1 = from.unused1;
+        self.unused2 = from.unused2;
+    }
+
+    fn copy_transform(&mut self, _from: &Self) {}
+
+    fn accessor_mut(&mut self, class: &str) -> Option<&mut AccessorPropertyObject> {
+        self.properties
+            .iter_mut()
+            .map(|prop| &mut prop.accessor_prop)
+            .find(|apo| apo.object.get_class_name() == class)
+    }
+
+    fn remove_accessor(&mut self, class: &str) {
+        self.properties
+            .retain(|prop| prop.accessor_prop.object.get_class_name() != class);
+    }
+
+    fn copy_accessor(&mut self, from: &Self, class: &str) {
+        if let Some(prop) = from
+            .properties
+            .iter()
+            .find(|prop| prop.accessor_prop.object.get_class_name() == class)
+        {
+            self.properties.push(prop.clone());
+        }
+    }
+
+    fn add_accessor(&mut self, accessor: AccessorPropertyObject) {
+        let unknown1 = self.properties.first().map(|p| p.unknown1).unwrap_or(1);
+        self.properties.push(TemplatePropertyAccessor {
+            unknown1,
+            accessor_prop: accessor,
+        });
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Conflict {
+    pub id: Uuid,
+    pub name: String,
+    /// Which part of the entity is affected, e.g. `header` or `eCMesh_PS.MeshFileName`
+    pub location: String,
+    pub reason: String,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{{{}}} {} - {}: {}",
+            self.id, self.name, self.location, self.reason
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct Merged<T> {
+    pub result: T,
+    pub conflicts: Vec<Conflict>,
+    /// Unparsed data taken from B, which may reference B's string table
+    pub raw_data_from_b: Vec<String>,
+}
+
+enum Resolution {
+    KeepA,
+    TakeB,
+    Conflict(&'static str),
+}
+
+fn resolve<V>(
+    base: Option<&V>,
+    a: Option<&V>,
+    b: Option<&V>,
+    eq: impl Fn(&V, &V) -> bool,
+) -> Resolution {
+    let opt_eq = |x: Option<&V>, y: Option<&V>| match (x, y) {
+        (Some(x), Some(y)) => eq(x, y),
+        (None, None) => true,
+        _ => false,
+    };
+    if opt_eq(a, b) || opt_eq(base, b) {
+        Resolution::KeepA
+    } else if opt_eq(base, a) {
+        Resolution::TakeB
+    } else {
+        Resolution::Conflict(match (base, a, b) {
+            (None, _, _) => "added differently in both versions",
+            (_, None, _) => "removed in A, modified in B",
+            (_, _, None) => "modified in A, removed in B",
+            _ => "changed differently in both versions",
+        })
+    }
+}
+
+fn contains_raw_data(apo: &AccessorPropertyObject) -> bool {
+    matches!(apo.object.class, GenClass::Opaque(_))
+        || apo
+            .object
+            .props
+            .iter()
+            .any(|prop| matches!(*prop.data, PropData::Buffer(_)))
+}
+
+struct MergeState<'a> {
+    tolerance: f32,
+    conflicts: Vec<Conflict>,
+    raw_data_from_b: Vec<String>,
+    parents: [&'a HashMap<Uuid, Uuid>; 3],
+    merged_parents: HashMap<Uuid, Uuid>,
+}
+
+impl MergeState<'_> {
+    fn conflict<T: DiffEntity>(&mut self, entity: &T, location: &str, reason: &str) {
+        self.conflicts.push(Conflict {
+            id: entity.id(),
+            name: entity.name().to_string(),
+            location: location.to_string(),
+            reason: reason.to_string(),
+        });
+    }
+
+    fn merge_accessor<T: MergeEntity>(
+        &mut self,
+        result: &mut T,
+        base: Option<&AccessorPropertyObject>,
+        b: &AccessorPropertyObject,
+    ) {
+        let class = b.object.get_class_name().to_string();
+        let Some(a) = result.accessor(&class) else {
+            return;
+        };
+        let mut names = a
+            .object
+            .props
+            .iter()
+            .map(|prop| prop.name.clone())
+            .collect::<Vec<_>>();
+        for prop in &b.object.props {
+            if !names.contains(&prop.name) {
+                names.push(prop.name.clone());
+            }
+        }
+
+        match resolve(
+            base.map(|apo| &apo.object.class),
+            Some(&a.object.class),
+            Some(&b.object.class),
+            |x, y| x == y,
+        ) {
+            Resolution::KeepA => {}
+            Resolution::TakeB => {
+                if matches!(b.object.class, GenClass::Opaque(_)) {
+                    self.raw_data_from_b.push(class.clone());
+                }
+                result.accessor_mut(&class).unwrap().object.class = b.object.class.clone();
+            }
+            Resolution::Conflict(reason) => {
+                let location = format!("{class} (class data)");
+                self.conflict(result, &location, reason);
+            }
+        }
+
+        let base_props = base.map(|apo| apo.object.props.as_slice()).unwrap_or(&[]);
+        for name in names {
+            let a = result.accessor_mut(&class).unwrap();
+            let base_prop = base_props.iter().find(|prop| prop.name == name);
+            let a_idx = a.object.props.iter().position(|prop| prop.name == name);
+            let b_prop = b.object.props.iter().find(|prop| prop.name == name);
+            match resolve(
+                base_prop,
+                a_idx.map(|idx| &a.object.props[idx]),
+                b_prop,
+                |x, y| x.data == y.data,
+            ) {
+                Resolution::KeepA => {}
+                Resolution::TakeB => {
+                    match (a_idx, b_prop) {
+                        (Some(idx), Some(b_prop)) => a.object.props[idx] = b_prop.clone(),
+                        (None, Some(b_prop)) => a.object.props.push(b_prop.clone()),
+                        (Some(idx), None) => {
+                            a.object.props.remove(idx);
+                        }
+                        (None, None) => {}
+                    }
+                    if let Some(b_prop) = b_prop
+                        && matches!(*b_prop.data, PropData::Buffer(_))
+                    {
+                        self.raw_data_from_b.push(format!("{class}.{name}"));
+                    }
+                }
+                Resolution::Conflict(reason) => {
+                    let location = format!("{class}.{name}");
+                    self.conflict(result, &location, reason);
+                }
+            }
+        }
+    }
+
+    fn merge_entity<T: MergeEntity>(&mut self, base: Option<&T>, a: &T, b: &T) -> T {
+        let mut result = a.clone();
+        let id = a.id();
+
+        match resolve(
+            base.map(|e| e.header()).as_ref(),
+            Some(&a.header()),
+            Some(&b.header()),
+            |x, y| x == y,
+        ) {
+            Resolution::KeepA => {}
+            Resolution::TakeB => result.copy_header(b),
+            Resolution::Conflict(reason) => self.conflict(a, "header", reason),
+        }
+
+        let tolerance = self.tolerance;
+        match resolve(
+            base.and_then(|e| e.transform()).as_ref(),
+            a.transform().as_ref(),
+            b.transform().as_ref(),
+            |x: &Mat4, y: &Mat4| x.abs_diff_eq(*y, tolerance),
+        ) {
+            Resolution::KeepA => {}
+            Resolution::TakeB => result.copy_transform(b),
+            Resolution::Conflict(reason) => self.conflict(a, "transform", reason),
+        }
+
+        let [base_parents, a_parents, b_parents] = self.parents;
+        let a_parent = a_parents.get(&id);
+        let b_parent = b_parents.get(&id);
+        let parent = match resolve(
+            base.and(base_parents.get(&id)),
+            a_parent,
+            b_parent,
+            |x, y| x == y,
+        ) {
+            Resolution::KeepA => a_parent,
+            Resolution::TakeB => b_parent,
+            Resolution::Conflict(reason) => {
+                self.conflict(a, "parent", reason);
+                a_parent
+            }
+        };
+        if let Some(parent) = parent {
+            self.merged_parents.insert(id, *parent);
+        }
+
+        let mut classes = a
+            .accessors()
+            .iter()
+            .map(|apo| apo.object.get_class_name().to_string())
+            .collect::<Vec<_>>();
+        for apo in b.accessors() {
+            let class = apo.object.get_class_name().to_string();
+            if !classes.contains(&class) {
+                classes.push(class);
+            }
+        }
+        for class in classes {
+            let base_acc = base.and_then(|e| e.accessor(&class));
+            let a_acc = a.accessor(&class);
+            let b_acc = b.accessor(&class);
+            if let (Some(_), Some(b_acc)) = (a_acc, b_acc) {
+                self.merge_accessor(&mut result, base_acc, b_acc);
+                continue;
+            }
+            match resolve(base_acc, a_acc, b_acc, |x, y| x == y) {
+                Resolution::KeepA => {}
+                Resolution::TakeB => match b_acc {
+                    Some(b_acc) => {
+                        if contains_raw_data(b_acc) {
+                            self.raw_data_from_b.push(class.clone());
+                        }
+                        result.copy_accessor(b, &class);
+                    }
+                    None => result.remove_accessor(&class),
+                },
+                Resolution::Conflict(reason) => self.conflict(a, &class, reason),
+            }
+        }
+        result
+    }
+}
+
+/// Builds the (child, parent) index list, terminated by (-1, -1)
+fn parents_by_index<T: DiffEntity>(
+    entities: &[T],
+    parents: &HashMap<Uuid, Uuid>,
+) -> Vec<(i32, i32)> {
+    let indices = entities
+        .iter()
+        .enumerate()
+        .map(|(idx, e)| (e.id(), idx as i32))
+        .collect::<HashMap<_, _>>();
+    let mut result = entities
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, e)| {
+            let parent = indices.get(parents.get(&e.id())?)?;
+            Some((idx as i32, *parent))
+        })
+        .collect::<Vec<_>>();
+    result.push((-1, -1));
+    result
+}
+
+#[derive(Debug)]
+pub struct EntityList<T> {
+    pub entities: Vec<T>,
+    /// (child, parent) index list, terminated by (-1, -1)
+    pub parents: Vec<(i32, i32)>,
+}
+
+/// Merges entity lists, see the module description.
+/// `parents` are the (child, parent) index lists of base, A and B.
+pub fn merge_entities<T: MergeEntity>(
+    base: &[T],
+    a: &[T],
+    b: &[T],
+    parents: [&[(i32, i32)]; 3],
+    tolerance: f32,
+) -> Merged<EntityList<T>> {
+    let base_parents = parents_by_id(base, parents[0]);
+    let a_parents = parents_by_id(a, parents[1]);
+    let b_parents = parents_by_id(b, parents[2]);
+    let mut state = MergeState {
+        tolerance,
+        conflicts: Vec::new(),
+        raw_data_from_b: Vec::new(),
+        parents: [&base_parents, &a_parents, &b_parents],
+        merged_parents: HashMap::new(),
+    };
+
+    let base_map = base.iter().map(|e| (e.id(), e)).collect::<HashMap<_, _>>();
+    let a_map = a.iter().map(|e| (e.id(), e)).collect::<HashMap<_, _>>();
+    let b_map = b.iter().map(|e| (e.id(), e)).collect::<HashMap<_, _>>();
+
+    let mut ids = a.iter().map(|e| e.id()).collect::<Vec<_>>();
+    ids.extend(
+        b.iter()
+            .map(|e| e.id())
+            .filter(|id| !a_map.contains_key(id)),
+    );
+
+    let mut entities = Vec::new();
+    for id in ids {
+        let base_entity = base_map.get(&id).copied();
+        let a_entity = a_map.get(&id).copied();
+        let b_entity = b_map.get(&id).copied();
+        if let (Some(a_entity), Some(b_entity)) = (a_entity, b_entity) {
+            entities.push(state.merge_entity(base_entity, a_entity, b_entity));
+            continue;
+        }
+        let unchanged = |x: &T, y: &T| diff_entity(x, y, tolerance).is_empty();
+        let (entity, parent) = match resolve(base_entity, a_entity, b_entity, unchanged) {
+            Resolution::KeepA => (a_entity, a_parents.get(&id)),
+            Resolution::TakeB => {
+                if let Some(b_entity) = b_entity
+                    && b_entity.accessors().into_iter().any(contains_raw_data)
+                {
+                    state.raw_data_from_b.push(b_entity.name().to_string());
+                }
+                (b_entity, b_parents.get(&id))
+            }
+            Resolution::Conflict(reason) => {
+                // only one side still has the entity, keep it:
+                let entity = a_entity.or(b_entity).unwrap();
+                state.conflict(entity, "entity", reason);
+                (Some(entity), a_parents.get(&id).or(b_parents.get(&id)))
+            }
+        };
+        if let Some(entity) = entity {
+            if let Some(parent) = parent {
+                state.merged_parents.insert(id, *parent);
+            }
+            entities.push(entity.clone());
+        }
+    }
+
+    let parents = parents_by_index(&entities, &state.merged_parents);
+    Merged {
+        result: EntityList { entities, parents },
+        conflicts: state.conflicts,
+        raw_data_from_b: state.raw_data_from_b,
+    }
+}
+
+pub fn merge_lrent(
+    base: &LrentFile,
+    a: &LrentFile,
+    b: &LrentFile,
+    tolerance: f32,
+) -> Result<Merged<LrentFile>> {
+    let (Some(base_ctx), Some(a_ctx), Some(b_ctx)) =
+        (base.get_context(), a.get_context(), b.get_context())
+    else {
+        return Err(Error::InvalidStructure(
+            "lrent does not contain an eCEntityDynamicContext".to_string(),
+        ));
+    };
+    let merged = merge_entities(
+        &base_ctx.entities,
+        &a_ctx.entities,
+        &b_ctx.entities,
+        [&base_ctx.parents, &a_ctx.parents, &b_ctx.parents],
+        tolerance,
+    );
+    let EntityList { entities, parents } = merged.result;
+
+    let mut result = a.clone();
+    let ctx = result.get_context_mut().unwrap();
+    if ctx.bounding_box.is_some() {
+        ctx.bounding_box = entities
+            .iter()
+            .map(|e| e.world_bound.clone())
+            .reduce(|x, y| x.union(&y))
+            .or(Some(BoundingBox {
+                max: Default::default(),
+                min: Default::default(),
+            }));
+    }
+    ctx.entities = entities;
+    ctx.parents = parents;
+    // Parents taken from B move the entity and its children, their bounds have to follow:
+    for idx in 0..ctx.entities.len() {
+        let world_matrix = ctx.get_world_matrix(idx);
+        let entity = &ctx.entities[idx];
+        if !world_matrix.abs_diff_eq(entity.geo_entity.geo_matrix, tolerance) {
+            let parent_matrix = match ctx.get_parent(idx) {
+                Some(parent) => ctx.get_world_matrix(parent),
+                None => Mat4::IDENTITY,
+            };
+            ctx.entities[idx].update_bounds(parent_matrix);
+        }
+    }
+    Ok(Merged {
+        result,
+        conflicts: merged.conflicts,
+        raw_data_from_b: merged.raw_data_from_b,
+    })
+}
+
+pub fn merge_tple(base: &TpleFile, a: &TpleFile, b: &TpleFile) -> Merged<TpleFile> {
+    let merged = merge_entities(
+        &base.entities,
+        &a.entities,
+        &b.entities,
+        [&base.parents, &a.parents, &b.parents],
+        crate::diff::DEFAULT_TOLERANCE,
+    );
+    let EntityList { entities, parents } = merged.result;
+    Merged {
+        result: TpleFile { entities, parents },
+        conflicts: merged.conflicts,
+        raw_data_from_b: merged.raw_data_from_b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{accessor, entity, lrent, template};
+    use crate::types::Vec3;
+
+    fn reason(resolution: Resolution) -> &'static str {
+        match resolution {
+            Resolution::KeepA => "keep a",
+            Resolution::TakeB => "take b",
+            Resolution::Conflict(reason) => reason,
+        }
+    }
+
+    #[test]
+    fn resolve_three_way() {
+        let eq = |x: &i32, y: &i32| x == y;
+        let r = |base: Option<i32>, a: Option<i32>, b: Option<i32>| {
+            reason(resolve(base.as_ref(), a.as_ref(), b.as_ref(), eq))
+        };
+        assert_eq!(r(Some(1), Some(1), Some(1)), "keep a");
+        assert_eq!(r(Some(1), Some(2), Some(2)), "keep a");
+        assert_eq!(r(Some(1), Some(2), Some(1)), "keep a");
+        assert_eq!(r(Some(1), None, Some(1)), "keep a");
+        assert_eq!(r(Some(1), Some(1), Some(2)), "take b");
+        assert_eq!(r(Some(1), Some(1), None), "take b");
+        assert_eq!(r(None, None, Some(2)), "take b");
+        assert_eq!(
+            r(Some(1), Some(2), Some(3)),
+            "changed differently in both versions"
+        );
+        assert_eq!(
+            r(None, Some(2), Some(3)),
+            "added differently in both versions"
+        );
+        assert_eq!(r(Some(1), None, Some(3)), "removed in A, modified in B");
+        assert_eq!(r(Some(1), Some(2), None), "modified in A, removed in B");
+    }
+
+    #[test]
+    fn merge_reports_conflicts() {
+        const SWORD: &str = "{11111111-2222-3333-4444-555555555555}";
+        const AXE: &str = "{66666666-7777-8888-9999-aaaaaaaaaaaa}";
+        const BOW: &str = "{bbbbbbbb-cccc-dddd-eeee-ffffffffffff}";
+        let item = |props: &[(&str, PropData)]| vec![accessor("gCItem_PS", props)];
+        let tple = |entities| TpleFile {
+            entities,
+            parents: vec![(-1, -1)],
+        };
+        let value = |v| ("Value", PropData::Int(v));
+        let base = tple(vec![
+            template(SWORD, "It_Sword", item(&[value(100)])),
+            template(AXE, "It_Axe", item(&[value(50)])),
+        ]);
+        // A changes the sword and removes the axe, B changes both and adds a bow
+        let a = tple(vec![template(SWORD, "It_Sword", item(&[value(600)]))]);
+        let b = tple(vec![
+            template(
+                SWORD,
+                "It_Sword",
+                item(&[value(700), ("Weight", PropData::Int(5))]),
+            ),
+            template(AXE, "It_Axe", item(&[value(80)])),
+            template(BOW, "It_Bow", item(&[value(300)])),
+        ]);
+
+        let merged = merge_tple(&base, &a, &b);
+        let mut conflicts = merged
+            .conflicts
+            .iter()
+            .map(|c| (c.name.as_str(), c.location.as_str(), c.reason.as_str()))
+            .collect::<Vec<_>>();
+        conflicts.sort();
+        assert_eq!(
+            conflicts,
+            [
+                ("It_Axe", "entity", "removed in A, modified in B"),
+                (
+                    "It_Sword",
+                    "gCItem_PS.Value",
+                    "changed differently in both versions"
+                ),
+            ]
+        );
+
+        let entities = &merged.result.entities;
+        let names = entities.iter().map(|e| e.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["It_Sword", "It_Axe", "It_Bow"]);
+        // A takes precedence in conflicts, the changes of B that don't conflict are kept
+        let sword = entities[0].accessor("gCItem_PS").unwrap();
+        assert_eq!(
+            sword.object.get_property("Value"),
+            Some(&PropData::Int(600))
+        );
+        assert_eq!(sword.object.get_property("Weight"), Some(&PropData::Int(5)));
+        let axe = entities[1].accessor("gCItem_PS").unwrap();
+        assert_eq!(axe.object.get_property("Value"), Some(&PropData::Int(80)));
+        assert!(merged.raw_data_from_b.is_empty());
+    }
+
+    #[test]
+    fn merged_parents_update_bounds() {
+        let base = lrent(
+            vec![
+                entity("Table", Vec3::new(10.0, 0.0, 0.0), Vec::new()),
+                entity("Shelf", Vec3::new(-10.0, 0.0, 0.0), Vec::new()),
+                entity("Cup", Vec3::new(0.0, 1.0, 0.0), Vec::new()),
+            ],
+            &[(2, 0)],
+        );
+        // A moves the shelf, B puts the cup on the shelf
+        let mut a = base.clone();
+        let ctx = a.get_context_mut().unwrap();
+        ctx.set_local_matrix(1, Mat4::from_translation(Vec3::new(-20.0, 0.0, 0.0)));
+        ctx.update_bounding_box();
+        let mut b = base.clone();
+        b.get_context_mut().unwrap().parents = vec![(2, 1), (-1, -1)];
+
+        let merged = merge_lrent(&base, &a, &b, crate::diff::DEFAULT_TOLERANCE).unwrap();
+        assert!(merged.conflicts.is_empty());
+        let ctx = merged.result.get_context().unwrap();
+        assert_eq!(ctx.get_parent(2), Some(1));
+        let cup = &ctx.entities[2];
+        let expected = Vec3::new(-20.0, 1.0, 0.0);
+        assert_eq!(cup.geo_entity.geo_matrix.w_axis.truncate(), expected);
+        assert_eq!(cup.world_bound.max, expected + Vec3::ONE);
+        assert_eq!(cup.world_bound.min, expected - Vec3::ONE);
+        let bounds = ctx.bounding_box.as_ref().unwrap();
+        assert_eq!(bounds.min, Vec3::new(-21.0, -1.0, -1.0));
+        assert_eq!(bounds.max, Vec3::new(11.0, 2.0, 1.0));
+    }
+}
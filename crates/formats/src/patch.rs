@@ -0,0 +1,547 @@
+//! Declarative change sets for lrent & tple files.
+//!
+//! Operations address entities by GUID or (unique) name instead of indices, so a patch can be
+//! applied again after the original file changed. Applying the same patch twice has no effect,
+//! e.g. `add_inventory_stack` only sets the amount if a stack of the template already exists.
+//!
+//! Example (json):
+//! ```json
+//! {
+//!   "operations": [
+//!     { "op": "set_property", "target": "It_Sword", "class": "gCItem_PS", "property": "Value", "value": { "int": 600 } },
+//!     { "op": "move_entity", "target": "{6f1e...}", "position": [100.0, 0.0, -250.0] },
+//!     { "op": "remove_accessor", "target": "Chest_01", "class": "gCLock_PS" },
+//!     { "op": "add_inventory_stack", "target": "Chest_01", "template": "{0b9c...}", "amount": 3 }
+//!   ]
+//! }
+//! ```
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::diff::DiffEntity;
+use crate::error::*;
+use crate::file_formats::{lrent::LrentFile, tple::TpleFile};
+use crate::merge::MergeEntity;
+use crate::types::object::AccessorPropertyObject;
+use crate::types::properties::PropData;
+use crate::types::property_set::PropertySet;
+use crate::types::{EntityDynamicContext, GenClass, Mat4, Quat, Vec3};
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Patch {
+    #[serde(default)]
+    pub description: String,
+    pub operations: Vec<PatchOp>,
+}
+
+/// `target` is either the GUID (with or without braces) or the name of an entity / template
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PatchOp {
+    /// Sets (or adds) a property of the accessor of class `class`
+    SetProperty {
+        target: String,
+        class: String,
+        property: String,
+        value: PropData,
+    },
+    /// Adds an accessor, replacing an existing one of the same class
+    AddAccessor {
+        target: String,
+        accessor: Box<AccessorPropertyObject>,
+    },
+    RemoveAccessor {
+        target: String,
+        class: String,
+    },
+    /// Changes the transform relative to the parent, unset parts keep their current value.
+    /// Only supported for lrent files.
+    MoveEntity {
+        target: String,
+        #[serde(default)]
+        position: Option<Vec3>,
+        #[serde(default)]
+        rotation: Option<Quat>,
+        #[serde(default)]
+        scale: Option<Vec3>,
+    },
+    DeleteEntity {
+        target: String,
+    },
+    /// Ensures the gCInventory_PS of the target contains a stack of `amount` items of `template` (GUID)
+    AddInventoryStack {
+        target: String,
+        template: String,
+        amount: i32,
+    },
+}
+
+impl PatchOp {
+    pub fn target(&self) -> &str {
+        match self {
+            PatchOp::SetProperty { target, .. }
+            | PatchOp::AddAccessor { target, .. }
+            | PatchOp::RemoveAccessor { target, .. }
+            | PatchOp::MoveEntity { target, .. }
+            | PatchOp::DeleteEntity { target }
+            | PatchOp::AddInventoryStack { target, .. } => target,
+        }
+    }
+}
+
+impl fmt::Display for PatchOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchOp::SetProperty {
+                target,
+                class,
+                property,
+                ..
+            } => write!(f, "set_property {class}.{property} of '{target}'"),
+            PatchOp::AddAccessor { target, accessor } => write!(
+                f,
+                "add_accessor {} to '{target}'",
+                accessor.object.get_class_name()
+            ),
+            PatchOp::RemoveAccessor { target, class } => {
+                write!(f, "remove_accessor {class} from '{target}'")
+            }
+            PatchOp::MoveEntity { target, .. } => write!(f, "move_entity '{target}'"),
+            PatchOp::DeleteEntity { target } => write!(f, "delete_entity '{target}'"),
+            PatchOp::AddInventoryStack {
+                target,
+                template,
+                amount,
+            } => write!(f, "add_inventory_stack {amount}x {template} to '{target}'"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum OpOutcome {
+    Applied,
+    /// The file already contained the change
+    Unchanged,
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct OpResult {
+    /// Index of the operation within the patch
+    pub index: usize,
+    pub description: String,
+    pub outcome: OpOutcome,
+}
+
+impl OpResult {
+    pub fn failed(&self) -> bool {
+        matches!(self.outcome, OpOutcome::Failed(_))
+    }
+}
+
+impl fmt::Display for OpResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} {}: ", self.index, self.description)?;
+        match &self.outcome {
+            OpOutcome::Applied => write!(f, "applied"),
+            OpOutcome::Unchanged => write!(f, "already applied"),
+            OpOutcome::Failed(msg) => write!(f, "FAILED - {msg}"),
+        }
+    }
+}
+
+/// Entity containers a patch can be applied to
+trait PatchTarget {
+    type Entity: MergeEntity;
+    fn entities(&self) -> &[Self::Entity];
+    fn entity_mut(&mut self, idx: usize) -> &mut Self::Entity;
+    fn remove(&mut self, idx: usize) -> Result<()>;
+    fn local_matrix(&self, idx: usize) -> Option<Mat4>;
+    fn set_local_matrix(&mut self, idx: usize, local_matrix: Mat4);
+}
+
+impl PatchTarget for EntityDynamicContext {
+    type Entity = crate::types::entity::DynamicEntity;
+
+    fn entities(&self) -> &[Self::Entity] {
+        &self.entities
+    }
+
+    fn entity_mut(&mut self, idx: usize) -> &mut Self::Entity {
+        &mut self.entities[idx]
+    }
+
+    fn remove(&mut self, idx: usize) -> Result<()> {
+        self.remove_entity(idx).map(|_| ())
+    }
+
+    fn local_matrix(&self, idx: usize) -> Option<Mat4> {
+        Some(self.entities[idx].local_matrix)
+    }
+
+    fn set_local_matrix(&mut self, idx: usize, local_matrix: Mat4) {
+        EntityDynamicContext::set_local_matrix(self, idx, local_matrix);
+    }
+}
+
+impl PatchTarget for TpleFile {
+    type Entity = crate::types::template::TemplateEntity;
+
+    fn entities(&self) -> &[Self::Entity] {
+        &self.entities
+    }
+
+    fn entity_mut(&mut self, idx: usize) -> &mut Self::Entity {
+        &mut self.entities[idx]
+    }
+
+    fn remove(&mut self, idx: usize) -> Result<()> {
+        self.remove_template(idx).map(|_| ())
+    }
+
+    fn local_matrix(&self, _idx: usize) -> Option<Mat4> {
+        None
+    }
+
+    fn set_local_matrix(&mut self, _idx: usize, _local_matrix: Mat4) {}
+}
+
+fn parse_guid(text: &str) -> Option<Uuid> {
+    Uuid::parse_str(text.trim_matches(['{', '}'])).ok()
+}
+
+/// Resolves a GUID or unique name
+fn find_target<T: DiffEntity>(entities: &[T], target: &str) -> std::result::Result<usize, String> {
+    if let Some(id) = parse_guid(target) {
+        return entities
+            .iter()
+            .position(|e| e.id() == id)
+            .ok_or_else(|| format!("no entity with GUID {id}"));
+    }
+    let mut found = entities
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.name() == target)
+        .map(|(idx, _)| idx);
+    match (found.next(), found.count()) {
+        (None, _) => Err(format!("no entity named '{target}'")),
+        (Some(idx), 0) => Ok(idx),
+        (Some(_), more) => Err(format!(
+            "name '{target}' is ambiguous ({} entities), use the GUID instead",
+            more + 1
+        )),
+    }
+}
+
+fn apply_op<C: PatchTarget>(container: &mut C, op: &PatchOp) -> OpOutcome {
+    let idx = match find_target(container.entities(), op.target()) {
+        Ok(idx) => idx,
+        // deleting is the only operation where a missing target is the expected result:
+        Err(_) if matches!(op, PatchOp::DeleteEntity { .. }) => return OpOutcome::Unchanged,
+        Err(msg) => return OpOutcome::Failed(msg),
+    };
+    match op {
+        PatchOp::SetProperty {
+            class,
+            property,
+            value,
+            ..
+        } => {
+            let Some(apo) = container.entity_mut(idx).accessor_mut(class) else {
+                return OpOutcome::Failed(format!("entity has no {class} accessor"));
+            };
+            if apo.object.get_property(property) == Some(value) {
+                return OpOutcome::Unchanged;
+            }
+            apo.object.set_property(property, value.clone());
+            OpOutcome::Applied
+        }
+        PatchOp::AddAccessor { accessor, .. } => {
+            let entity = container.entity_mut(idx);
+            let class = accessor.object.get_class_name();
+            match entity.accessor_mut(class) {
+                Some(existing) if existing == accessor.as_ref() => OpOutcome::Unchanged,
+                Some(existing) => {
+                    *existing = (**accessor).clone();
+                    OpOutcome::Applied
+                }
+                None => {
+                    entity.add_accessor((**accessor).clone());
+                    OpOutcome::Applied
+                }
+            }
+        }
+        PatchOp::RemoveAccessor { class, .. } => {
+            let entity = container.entity_mut(idx);
+            if entity.accessor(class).is_none() {
+                return OpOutcome::Unchanged;
+            }
+            entity.remove_accessor(class);
+            OpOutcome::Applied
+        }
+        PatchOp::MoveEntity {
+            position,
+            rotation,
+            scale,
+            ..
+        } => {
+            let Some(old_matrix) = container.local_matrix(idx) else {
+                return OpOutcome::Failed("templates cannot be moved".to_string());
+            };
+            let (old_scale, old_rotation, old_position) =
+                old_matrix.to_scale_rotation_translation();
+            let new_matrix = Mat4::from_scale_rotation_translation(
+                scale.unwrap_or(old_scale),
+                rotation.map(|r| r.normalize()).unwrap_or(old_rotation),
+                position.unwrap_or(old_position),
+            );
+            if new_matrix.abs_diff_eq(old_matrix, crate::diff::DEFAULT_TOLERANCE) {
+                return OpOutcome::Unchanged;
+            }
+            container.set_local_matrix(idx, new_matrix);
+            OpOutcome::Applied
+        }
+        PatchOp::DeleteEntity { .. } => match container.remove(idx) {
+            Ok(()) => OpOutcome::Applied,
+            Err(e) => OpOutcome::Failed(e.to_string()),
+        },
+        PatchOp::AddInventoryStack {
+            template, amount, ..
+        } => {
+            let Some(template) = parse_guid(template) else {
+                return OpOutcome::Failed(format!("'{template}' is not a valid template GUID"));
+            };
+            let Some(apo) = container.entity_mut(idx).accessor_mut("gCInventory_PS") else {
+                return OpOutcome::Failed("entity has no gCInventory_PS accessor".to_string());
+            };
+            let GenClass::PropertySet(PropertySet::Inventory(inventory)) = &mut apo.object.class
+            else {
+                return OpOutcome::Failed("gCInventory_PS could not be parsed".to_string());
+            };
+            match inventory.find_stack(template) {
+                Some(stack_idx) => {
+                    let stack = &mut inventory.stacks[stack_idx];
+                    if stack.amount == *amount {
+                        return OpOutcome::Unchanged;
+                    }
+                    stack.amount = *amount;
+                }
+                None => {
+                    inventory.add_stack(template, *amount);
+                }
+            }
+            OpOutcome::Applied
+        }
+    }
+}
+
+fn apply<C: PatchTarget>(patch: &Patch, container: &mut C) -> Vec<OpResult> {
+    patch
+        .operations
+        .iter()
+        .enumerate()
+        .map(|(index, op)| OpResult {
+            index,
+            description: op.to_string(),
+            outcome: apply_op(container, op),
+        })
+        .collect()
+}
+
+/// Applies all operations, failed operations are skipped and reported in the result
+pub fn apply_lrent(patch: &Patch, lrent: &mut LrentFile) -> Result<Vec<OpResult>> {
+    let Some(ctx) = lrent.get_context_mut() else {
+        return Err(Error::InvalidStructure(
+            "lrent does not contain an eCEntityDynamicContext".to_string(),
+        ));
+    };
+    let results = apply(patch, ctx);
+    // moved and deleted entities change the bounds of the whole layer:
+    ctx.update_bounding_box();
+    Ok(results)
+}
+
+/// Applies all operations, failed operations are skipped and reported in the result
+pub fn apply_tple(patch: &Patch, tple: &mut TpleFile) -> Vec<OpResult> {
+    apply(patch, tple)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{accessor, accessor_with, entity, lrent, template};
+    use crate::types::property_set::Inventory;
+
+    const SWORD: &str = "{11111111-2222-3333-4444-555555555555}";
+    const CHEST: &str = "{66666666-7777-8888-9999-aaaaaaaaaaaa}";
+    const GOLD: &str = "{bbbbbbbb-cccc-dddd-eeee-ffffffffffff}";
+
+    fn sample_tple() -> TpleFile {
+        let inventory = GenClass::PropertySet(PropertySet::Inventory(Inventory {
+            version: 1,
+            unknown1: 0,
+            unknown2: 0,
+            unknown3: 0,
+            stacks: Vec::new(),
+            slots: Vec::new(),
+        }));
+        TpleFile {
+            entities: vec![
+                template(SWORD, "It_Sword", vec![accessor("gCItem_PS", &[])]),
+                template(
+                    CHEST,
+                    "Chest_01",
+                    vec![accessor("gCLock_PS", &[]), accessor_with(inventory, &[])],
+                ),
+                template(GOLD, "It_Gold", vec![accessor("gCItem_PS", &[])]),
+                template(
+                    "{12121212-3434-5656-7878-909090909090}",
+                    "It_Obsolete",
+                    Vec::new(),
+                ),
+            ],
+            parents: vec![(-1, -1)],
+        }
+    }
+
+    fn sample_patch() -> Patch {
+        Patch {
+            description: String::new(),
+            operations: vec![
+                PatchOp::SetProperty {
+                    target: "It_Sword".to_string(),
+                    class: "gCItem_PS".to_string(),
+                    property: "Value".to_string(),
+                    value: PropData::Int(600),
+                },
+                PatchOp::AddAccessor {
+                    target: CHEST.to_string(),
+                    accessor: Box::new(accessor("gCChest_PS", &[("Locked", PropData::Bool(true))])),
+                },
+                PatchOp::RemoveAccessor {
+                    target: "Chest_01".to_string(),
+                    class: "gCLock_PS".to_string(),
+                },
+                PatchOp::AddInventoryStack {
+                    target: "Chest_01".to_string(),
+                    template: GOLD.to_string(),
+                    amount: 3,
+                },
+                PatchOp::DeleteEntity {
+                    target: "It_Obsolete".to_string(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn apply_twice_is_unchanged() {
+        let patch = sample_patch();
+        let mut tple = sample_tple();
+        let results = apply_tple(&patch, &mut tple);
+        for result in &results {
+            assert!(matches!(result.outcome, OpOutcome::Applied), "{result}");
+        }
+        assert_eq!(tple.entities.len(), 3);
+
+        let applied = tple.clone();
+        let results = apply_tple(&patch, &mut tple);
+        assert_eq!(results.len(), patch.operations.len());
+        for result in &results {
+            assert!(matches!(result.outcome, OpOutcome::Unchanged), "{result}");
+        }
+        assert_eq!(tple.entities, applied.entities);
+        assert_eq!(tple.parents, applied.parents);
+    }
+
+    #[test]
+    fn missing_targets_fail() {
+        let mut tple = sample_tple();
+        let patch = Patch {
+            description: String::new(),
+            operations: vec![
+                PatchOp::SetProperty {
+                    target: "It_Axe".to_string(),
+                    class: "gCItem_PS".to_string(),
+                    property: "Value".to_string(),
+                    value: PropData::Int(600),
+                },
+                PatchOp::SetProperty {
+                    target: SWORD.to_string(),
+                    class: "gCLock_PS".to_string(),
+                    property: "Locked".to_string(),
+                    value: PropData::Bool(true),
+                },
+                PatchOp::AddInventoryStack {
+                    target: "It_Sword".to_string(),
+                    template: GOLD.to_string(),
+                    amount: 1,
+                },
+            ],
+        };
+        let results = apply_tple(&patch, &mut tple);
+        assert!(results.iter().all(OpResult::failed));
+        assert_eq!(tple.entities, sample_tple().entities);
+    }
+
+    #[test]
+    fn move_entity_updates_bounds() {
+        let mut file = lrent(
+            vec![
+                entity("Table", Vec3::new(10.0, 0.0, 0.0), Vec::new()),
+                entity("Cup", Vec3::new(0.0, 1.0, 0.0), Vec::new()),
+                entity("Crate", Vec3::new(-10.0, 0.0, 0.0), Vec::new()),
+            ],
+            &[(1, 0)],
+        );
+        let patch = Patch {
+            description: String::new(),
+            operations: vec![
+                PatchOp::MoveEntity {
+                    target: "Table".to_string(),
+                    position: Some(Vec3::new(20.0, 0.0, 5.0)),
+                    rotation: None,
+                    scale: None,
+                },
+                PatchOp::DeleteEntity {
+                    target: "Crate".to_string(),
+                },
+            ],
+        };
+        let results = apply_lrent(&patch, &mut file).unwrap();
+        assert!(
+            results
+                .iter()
+                .all(|r| matches!(r.outcome, OpOutcome::Applied))
+        );
+
+        let ctx = file.get_context().unwrap();
+        let table = &ctx.entities[0];
+        assert_eq!(
+            table.local_matrix.w_axis.truncate(),
+            Vec3::new(20.0, 0.0, 5.0)
+        );
+        // the cup moves with the table, the context shrinks to both
+        let cup = &ctx.entities[1];
+        assert_eq!(cup.world_bound.min, Vec3::new(19.0, 0.0, 4.0));
+        assert_eq!(cup.world_bound.max, Vec3::new(21.0, 2.0, 6.0));
+        let bounds = ctx.bounding_box.as_ref().unwrap();
+        assert_eq!(bounds.min, Vec3::new(19.0, -1.0, 4.0));
+        assert_eq!(bounds.max, Vec3::new(21.0, 2.0, 6.0));
+
+        let applied = file.get_context().unwrap().clone();
+        let results = apply_lrent(&patch, &mut file).unwrap();
+        assert!(
+            results
+                .iter()
+                .all(|r| matches!(r.outcome, OpOutcome::Unchanged))
+        );
+        assert_eq!(file.get_context().unwrap(), &applied);
+
+        // templates have no transform
+        let results = apply_tple(&patch, &mut sample_tple());
+        assert!(results[0].failed());
+    }
+}
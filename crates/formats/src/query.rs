@@ -0,0 +1,494 @@
+//! Filtering of entities and templates by name, GUID, accessor class, property values and position.
+//!
+//! Queries can be built in code or parsed from a small text format, e.g.
+//! `class=gCItem_PS Value>500` or `near=100,0,-2500,3000 name~Orc`.
+use std::fmt;
+
+use uuid::Uuid;
+
+use crate::diff::DiffEntity;
+use crate::error::*;
+use crate::file_formats::tple::TpleFile;
+use crate::types::object::AccessorPropertyObject;
+use crate::types::properties::{PropData, Property};
+use crate::types::{EntityDynamicContext, Mat4, Vec3};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// Case-insensitive substring match
+    Contains,
+}
+
+impl CompareOp {
+    // two-character operators first:
+    const SYMBOLS: [(&'static str, CompareOp); 7] = [
+        ("!=", CompareOp::Ne),
+        ("<=", CompareOp::Le),
+        (">=", CompareOp::Ge),
+        ("=", CompareOp::Eq),
+        ("<", CompareOp::Lt),
+        (">", CompareOp::Gt),
+        ("~", CompareOp::Contains),
+    ];
+
+    fn compare_text(self, actual: &str, expected: &str) -> bool {
+        let (actual, expected) = (actual.to_lowercase(), expected.to_lowercase());
+        match self {
+            CompareOp::Eq => actual == expected,
+            CompareOp::Ne => actual != expected,
+            CompareOp::Contains => actual.contains(&expected),
+            CompareOp::Lt => actual < expected,
+            CompareOp::Le => actual <= expected,
+            CompareOp::Gt => actual > expected,
+            CompareOp::Ge => actual >= expected,
+        }
+    }
+
+    fn compare_number(self, actual: f64, expected: f64) -> bool {
+        match self {
+            CompareOp::Eq => actual == expected,
+            CompareOp::Ne => actual != expected,
+            CompareOp::Lt => actual < expected,
+            CompareOp::Le => actual <= expected,
+            CompareOp::Gt => actual > expected,
+            CompareOp::Ge => actual >= expected,
+            CompareOp::Contains => actual.to_string().contains(&expected.to_string()),
+        }
+    }
+
+    fn compare(self, actual: &PropData, expected: &str) -> bool {
+        if let (Some(actual), Ok(expected)) = (actual.as_number(), expected.parse::<f64>()) {
+            return self.compare_number(actual, expected);
+        }
+        match actual.as_text() {
+            Some(actual) => self.compare_text(&actual, expected),
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Filter {
+    Name {
+        op: CompareOp,
+        value: String,
+    },
+    Guid(Uuid),
+    /// Entity has an accessor of this class
+    Class(String),
+    /// Some property matches, optionally restricted to accessors of class `class`
+    Property {
+        class: Option<String>,
+        name: String,
+        op: CompareOp,
+        value: String,
+    },
+    /// World position (including the parent transforms, see [`Query::run_context`]) is within `radius` of `pos`
+    /// (in game units, i.e. cm)
+    Near {
+        pos: Vec3,
+        radius: f32,
+    },
+}
+
+/// An entity matched by a [`Query`]
+#[derive(Debug, Clone)]
+pub struct QueryMatch {
+    pub id: Uuid,
+    pub name: String,
+    /// Where the match was found, starting with the entity, e.g. `["Orc {guid}", "gCItem_PS", "Value = 600"]`
+    pub path: Vec<String>,
+}
+
+impl fmt::Display for QueryMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.join(" > "))
+    }
+}
+
+/// All filters have to match
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    pub filters: Vec<Filter>,
+}
+
+impl Query {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name(mut self, op: CompareOp, value: &str) -> Self {
+        self.filters.push(Filter::Name {
+            op,
+            value: value.to_string(),
+        });
+        self
+    }
+
+    pub fn guid(mut self, id: Uuid) -> Self {
+        self.filters.push(Filter::Guid(id));
+        self
+    }
+
+    pub fn class(mut self, class: &str) -> Self {
+        self.filters.push(Filter::Class(class.to_string()));
+        self
+    }
+
+    /// `name` is either a property name or `Class.Property`
+    pub fn property(mut self, name: &str, op: CompareOp, value: &str) -> Self {
+        let (class, name) = match name.split_once('.') {
+            Some((class, name)) => (Some(class.to_string()), name.to_string()),
+            None => (None, name.to_string()),
+        };
+        self.filters.push(Filter::Property {
+            class,
+            name,
+            op,
+            value: value.to_string(),
+        });
+        self
+    }
+
+    pub fn near(mut self, pos: Vec3, radius: f32) -> Self {
+        self.filters.push(Filter::Near { pos, radius });
+        self
+    }
+
+    /// Parses whitespace separated terms of the form `<key><op><value>`.
+    /// Keys are `name`, `guid`, `class`, `near` (value `x,y,z,radius`) or a (`Class.`)property name,
+    /// operators are `=`, `!=`, `<`, `<=`, `>`, `>=` and `~` (contains).
+    /// Values containing whitespace can be quoted with `"`.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut query = Self::new();
+        for term in split_terms(text) {
+            let Some((op_idx, op_str, op)) = CompareOp::SYMBOLS
+                .iter()
+                .filter_map(|(sym, op)| term.find(sym).map(|idx| (idx, *sym, *op)))
+                .min_by_key(|(idx, sym, _)| (*idx, usize::MAX - sym.len()))
+            else {
+                return Err(Error::InvalidOp(format!(
+                    "Query term '{term}' has no operator"
+                )));
+            };
+            let key = &term[..op_idx];
+            let value = term[op_idx + op_str.len()..].trim_matches('"');
+            query = match key.to_lowercase().as_str() {
+                "name" => query.name(op, value),
+                "guid" => {
+                    let id = Uuid::parse_str(value.trim_matches(['{', '}'])).map_err(|e| {
+                        Error::InvalidOp(format!("Invalid GUID '{value}' in query: {e}"))
+                    })?;
+                    query.guid(id)
+                }
+                "class" => query.class(value),
+                "near" => {
+                    let coords = value
+                        .split(',')
+                        .map(|v| v.trim().parse::<f32>().ok())
+                        .collect::<Option<Vec<_>>>();
+                    let Some([x, y, z, radius]) = coords.as_deref() else {
+                        return Err(Error::InvalidOp(format!(
+                            "Invalid position '{value}' in query, expected x,y,z,radius"
+                        )));
+                    };
+                    query.near(Vec3::new(*x, *y, *z), *radius)
+                }
+                "" => {
+                    return Err(Error::InvalidOp(format!("Query term '{term}' has no key")));
+                }
+                _ => query.property(key, op, value),
+            };
+        }
+        Ok(query)
+    }
+
+    /// Checks all filters against `entity`, returning the breadcrumb path on success.
+    /// Without the surrounding context, `near` uses the transform of `entity` itself.
+    pub fn matches<T: DiffEntity>(&self, entity: &T) -> Option<QueryMatch> {
+        self.matches_at(entity, entity.transform())
+    }
+
+    /// Like [`Self::matches`], with the world matrix of `entity` for `near`
+    pub fn matches_at<T: DiffEntity>(
+        &self,
+        entity: &T,
+        world_matrix: Option<Mat4>,
+    ) -> Option<QueryMatch> {
+        self.matches_with(entity, &entity.accessors(), world_matrix)
+    }
+
+    /// Checks the filters with the property sets `accessors` instead of the entity's own
+    fn matches_with<T: DiffEntity>(
+        &self,
+        entity: &T,
+        accessors: &[&AccessorPropertyObject],
+        world_matrix: Option<Mat4>,
+    ) -> Option<QueryMatch> {
+        let mut path = vec![format!("{} {{{}}}", entity.name(), entity.id())];
+        // class of the last breadcrumb, so several matches within one accessor share it:
+        let mut path_class = None;
+        for filter in &self.filters {
+            match filter {
+                Filter::Name { op, value } => {
+                    if !op.compare_text(entity.name(), value) {
+                        return None;
+                    }
+                }
+                Filter::Guid(id) => {
+                    if entity.id() != *id {
+                        return None;
+                    }
+                }
+                Filter::Class(class) => {
+                    accessors
+                        .iter()
+                        .find(|apo| apo.object.get_class_name() == class)?;
+                    if path_class != Some(class.as_str()) {
+                        path.push(class.clone());
+                        path_class = Some(class.as_str());
+                    }
+                }
+                Filter::Property {
+                    class,
+                    name,
+                    op,
+                    value,
+                } => {
+                    let (acc_class, prop) = accessors
+                        .iter()
+                        .filter(|apo| {
+                            class
+                                .as_ref()
+                                .is_none_or(|class| apo.object.get_class_name() == class)
+                        })
+                        .find_map(|apo| {
+                            let prop = find_property(&apo.object.props, name, *op, value)?;
+                            Some((apo.object.get_class_name(), prop))
+                        })?;
+                    if path_class != Some(acc_class) {
+                        path.push(acc_class.to_string());
+                        path_class = Some(acc_class);
+                    }
+                    path.push(format!("{} = {}", prop.name, display_value(&prop.data)));
+                }
+                Filter::Near { pos, radius } => {
+                    let entity_pos = world_matrix?.w_axis.truncate();
+                    if entity_pos.distance(*pos) > *radius {
+                        return None;
+                    }
+                }
+            }
+        }
+        Some(QueryMatch {
+            id: entity.id(),
+            name: entity.name().to_string(),
+            path,
+        })
+    }
+
+    pub fn run<T: DiffEntity>(&self, entities: &[T]) -> Vec<QueryMatch> {
+        entities.iter().filter_map(|e| self.matches(e)).collect()
+    }
+
+    /// Runs the query on the templates of `tple`, templates referring to another template
+    /// are matched against the properties of the referenced one
+    pub fn run_tple(&self, tple: &TpleFile) -> Vec<QueryMatch> {
+        tple.entities
+            .iter()
+            .filter_map(|template| {
+                let accessors = tple
+                    .resolve_properties(template)
+                    .iter()
+                    .map(|prop| &prop.accessor_prop)
+                    .collect::<Vec<_>>();
+                self.matches_with(template, &accessors, None)
+            })
+            .collect()
+    }
+
+    /// Runs the query on the entities of a dynamic layer, `near` uses their world positions
+    pub fn run_context(&self, ctx: &EntityDynamicContext) -> Vec<QueryMatch> {
+        ctx.entities
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, e)| self.matches_at(e, Some(ctx.get_world_matrix(idx))))
+            .collect()
+    }
+}
+
+fn find_property<'a>(
+    props: &'a [Property],
+    name: &str,
+    op: CompareOp,
+    value: &str,
+) -> Option<&'a Property> {
+    props
+        .iter()
+        .find(|prop| prop.name.eq_ignore_ascii_case(name) && op.compare(&prop.data, value))
+}
+
+fn display_value(data: &PropData) -> String {
+    if let Some(num) = data.as_number() {
+        return num.to_string();
+    }
+    match data.as_text() {
+        Some(text) => text,
+        None => format!("{data:?}"),
+    }
+}
+
+/// Splits at whitespace, except inside quotes
+fn split_terms(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in text.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    terms.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        terms.push(current);
+    }
+    terms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{accessor, entity, lrent, template};
+
+    #[test]
+    fn parse_terms() {
+        let query =
+            Query::parse(r#"class=gCItem_PS Value>=500 gCNPC_PS.Level<3 name~"Old Orc""#).unwrap();
+        assert!(matches!(&query.filters[0], Filter::Class(class) if class == "gCItem_PS"));
+        assert!(matches!(
+            &query.filters[1],
+            Filter::Property { class: None, name, op: CompareOp::Ge, value } if name == "Value" && value == "500"
+        ));
+        assert!(matches!(
+            &query.filters[2],
+            Filter::Property { class: Some(class), name, op: CompareOp::Lt, .. } if class == "gCNPC_PS" && name == "Level"
+        ));
+        assert!(matches!(
+            &query.filters[3],
+            Filter::Name { op: CompareOp::Contains, value } if value == "Old Orc"
+        ));
+
+        let query =
+            Query::parse("near=100,0,-2500,3000 guid={00000000-0000-0000-0000-00000000000a}")
+                .unwrap();
+        assert!(matches!(
+            &query.filters[0],
+            Filter::Near { pos, radius } if *pos == Vec3::new(100.0, 0.0, -2500.0) && *radius == 3000.0
+        ));
+        assert!(matches!(&query.filters[1], Filter::Guid(id) if id.as_u128() == 10));
+
+        assert!(Query::parse("Value").is_err());
+        assert!(Query::parse("=5").is_err());
+        assert!(Query::parse("near=1,2,3").is_err());
+        assert!(Query::parse("guid=nope").is_err());
+    }
+
+    #[test]
+    fn match_path_keeps_all_filters() {
+        let sword = template(
+            "{00000000-0000-0000-0000-000000000001}",
+            "Sword",
+            vec![
+                accessor(
+                    "gCItem_PS",
+                    &[("Value", PropData::Int(600)), ("Weight", PropData::Int(3))],
+                ),
+                accessor("gCDamage_PS", &[("DamageAmount", PropData::Int(40))]),
+            ],
+        );
+        let query = Query::parse("class=gCItem_PS Value>500 Weight=3 DamageAmount>=40").unwrap();
+        let found = query.matches(&sword).unwrap();
+        assert_eq!(
+            found.path[1..],
+            [
+                "gCItem_PS",
+                "Value = 600",
+                "Weight = 3",
+                "gCDamage_PS",
+                "DamageAmount = 40"
+            ]
+        );
+        assert!(
+            query
+                .clone()
+                .name(CompareOp::Eq, "Axe")
+                .matches(&sword)
+                .is_none()
+        );
+        assert!(Query::parse("Value>600").unwrap().matches(&sword).is_none());
+        assert!(
+            Query::parse("gCDamage_PS.Value>0")
+                .unwrap()
+                .matches(&sword)
+                .is_none()
+        );
+        assert!(Query::parse("name~SWO").unwrap().matches(&sword).is_some());
+    }
+
+    #[test]
+    fn templates_use_referenced_properties() {
+        let base = template(
+            "{00000000-0000-0000-0000-000000000001}",
+            "It_Sword",
+            vec![accessor("gCItem_PS", &[("Value", PropData::Int(600))])],
+        );
+        let mut derived = template(
+            "{00000000-0000-0000-0000-000000000002}",
+            "It_Sword_Copy",
+            Vec::new(),
+        );
+        derived.ref_template = Some(base.id.clone());
+        let tple = TpleFile {
+            entities: vec![base, derived],
+            parents: vec![(-1, -1)],
+        };
+        let found = Query::parse("Value=600").unwrap().run_tple(&tple);
+        let names = found.iter().map(|m| m.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["It_Sword", "It_Sword_Copy"]);
+    }
+
+    #[test]
+    fn near_uses_world_positions() {
+        let layer = lrent(
+            vec![
+                entity("Table", Vec3::new(1000.0, 0.0, 0.0), Vec::new()),
+                entity("Cup", Vec3::new(0.0, 100.0, 0.0), Vec::new()),
+            ],
+            &[(1, 0)],
+        );
+        let query = Query::parse("near=1000,100,0,10").unwrap();
+        let found = query.run_context(layer.get_context().unwrap());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "Cup");
+        // without the context, only the local position is known:
+        assert!(
+            query
+                .matches(&layer.get_context().unwrap().entities[1])
+                .is_none()
+        );
+    }
+}
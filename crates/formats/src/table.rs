@@ -0,0 +1,521 @@
+//! Flattening of entity / template properties into a table, e.g. for balancing in a spreadsheet.
+//!
+//! Each row is one entity, identified by its GUID in the first column (the second column holds
+//! the name for readability). All further columns are named `<class>.<property>`,
+//! e.g. `gCItem_PS.Value`. Only properties with a simple textual representation are included:
+//! numbers, strings, GUIDs, enums (by value name), vectors and colors (space-separated components).
+use std::collections::HashMap;
+use std::fmt;
+
+use uuid::Uuid;
+
+use crate::error::*;
+use crate::merge::MergeEntity;
+use crate::types::properties::PropData;
+
+pub const ID_COLUMN: &str = "guid";
+pub const NAME_COLUMN: &str = "name";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PropertyTable {
+    /// Starts with [`ID_COLUMN`] and [`NAME_COLUMN`]
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// Per column, whether it holds numeric properties only (integers and floats).
+    /// Set by [`PropertyTable::export`], empty for tables read from a file.
+    pub numeric: Vec<bool>,
+}
+
+impl PropertyTable {
+    /// Builds a table of all `entities` that have at least one of the accessor `classes`.
+    /// If `classes` is empty, all accessors are included.
+    pub fn export<T: MergeEntity>(entities: &[T], classes: &[String]) -> Self {
+        let included = |class: &str| classes.is_empty() || classes.iter().any(|c| c == class);
+        let mut columns = vec![ID_COLUMN.to_string(), NAME_COLUMN.to_string()];
+        let mut numeric = vec![false, false];
+        let mut column_idx = HashMap::new();
+        let mut values = Vec::new();
+        for entity in entities {
+            let mut row_values = Vec::new();
+            for apo in entity.accessors() {
+                let class = apo.object.get_class_name();
+                if !included(class) {
+                    continue;
+                }
+                for prop in &apo.object.props {
+                    let Some(text) = format_value(&prop.data) else {
+                        continue;
+                    };
+                    let column = format!("{class}.{}", prop.name);
+                    let idx = *column_idx.entry(column.clone()).or_insert_with(|| {
+                        columns.push(column);
+                        numeric.push(true);
+                        columns.len() - 1
+                    });
+                    numeric[idx] &= is_numeric(&prop.data);
+                    row_values.push((idx, text));
+                }
+            }
+            if !row_values.is_empty() {
+                values.push((entity, row_values));
+            }
+        }
+
+        let rows = values
+            .into_iter()
+            .map(|(entity, row_values)| {
+                let mut row = vec![String::new(); columns.len()];
+                row[0] = format!("{{{}}}", entity.id());
+                row[1] = entity.name().to_string();
+                for (idx, text) in row_values {
+                    row[idx] = text;
+                }
+                row
+            })
+            .collect();
+        Self {
+            columns,
+            rows,
+            numeric,
+        }
+    }
+
+    /// Whether the column `col` holds numeric properties only, see [`PropertyTable::numeric`]
+    pub fn is_numeric(&self, col: usize) -> bool {
+        self.numeric.get(col).copied().unwrap_or(false)
+    }
+
+    /// Writes all cells that differ from the current values back to `entities`.
+    /// Cells that cannot be applied are skipped and reported in [`TableReport::errors`].
+    pub fn apply<T: MergeEntity>(&self, entities: &mut [T]) -> Result<TableReport> {
+        if self.columns.first().map(String::as_str) != Some(ID_COLUMN) {
+            return Err(Error::InvalidStructure(format!(
+                "The first column has to be '{ID_COLUMN}'"
+            )));
+        }
+        let mut report = TableReport::default();
+        for (row_idx, row) in self.rows.iter().enumerate() {
+            let mut cell_error = |column: &str, message: String| {
+                report.errors.push(CellError {
+                    row: row_idx + 1,
+                    column: column.to_string(),
+                    message,
+                })
+            };
+            let id_text = row.first().map(String::as_str).unwrap_or_default();
+            if id_text.is_empty() {
+                continue;
+            }
+            let Ok(id) = Uuid::parse_str(id_text.trim_matches(['{', '}'])) else {
+                cell_error(ID_COLUMN, format!("'{id_text}' is not a valid GUID"));
+                continue;
+            };
+            let Some(entity) = entities.iter_mut().find(|e| e.id() == id) else {
+                cell_error(ID_COLUMN, format!("no entity with GUID {id}"));
+                continue;
+            };
+
+            for (column, text) in self.columns.iter().zip(row).skip(1) {
+                if column == NAME_COLUMN {
+                    continue;
+                }
+                let Some((class, property)) = column.split_once('.') else {
+                    cell_error(column, "column name is not <class>.<property>".to_string());
+                    continue;
+                };
+                let current = entity
+                    .accessor(class)
+                    .and_then(|apo| apo.object.get_property(property));
+                let Some(current) = current else {
+                    if !text.is_empty() {
+                        cell_error(column, format!("{} has no such property", entity.name()));
+                    }
+                    continue;
+                };
+                if format_value(current).as_ref() == Some(text) {
+                    report.unchanged += 1;
+                    continue;
+                }
+                let value = match parse_value(current, text) {
+                    Ok(value) => value,
+                    Err(message) => {
+                        cell_error(column, message);
+                        continue;
+                    }
+                };
+                if &value == current {
+                    report.unchanged += 1;
+                    continue;
+                }
+                report
+                    .changed
+                    .push(format!("{}: {column} = {text}", entity.name()));
+                let apo = entity.accessor_mut(class).unwrap();
+                apo.object.set_property(property, value);
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellError {
+    /// 1-based data row, not counting the header
+    pub row: usize,
+    pub column: String,
+    pub message: String,
+}
+
+impl fmt::Display for CellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "row {}, {}: {}", self.row, self.column, self.message)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TableReport {
+    pub changed: Vec<String>,
+    pub unchanged: usize,
+    pub errors: Vec<CellError>,
+}
+
+/// Textual representation of a property, `None` for types that are not supported in tables
+pub fn format_value(data: &PropData) -> Option<String> {
+    let join = |values: &[f32]| {
+        values
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    Some(match data {
+        PropData::Int(v) => v.to_string(),
+        PropData::Short(v) => v.to_string(),
+        PropData::Long(v) => v.to_string(),
+        PropData::Char(v) => v.to_string(),
+        PropData::Float(v) => v.to_string(),
+        PropData::Vector2(v) => join(&v.to_array()),
+        PropData::Vector3(v) => join(&v.to_array()),
+        PropData::Vector4(v) => join(&v.to_array()),
+        PropData::FloatColor { r, g, b, .. } => join(&[*r, *g, *b]),
+        PropData::Guid(id) => format!("{{{}}}", id.id),
+        PropData::Enum(_)
+        | PropData::ContainerEnum(_)
+        | PropData::Bool(_)
+        | PropData::String(_)
+        | PropData::ImageOrMaterialResourceString(_)
+        | PropData::LocString(_)
+        | PropData::ImageResourceString(_)
+        | PropData::LetterLocString(_)
+        | PropData::SpeedTreeResourceString(_)
+        | PropData::BookLocString(_)
+        | PropData::MeshResourceString(_)
+        | PropData::InfoLocString(_)
+        | PropData::QuestLocString(_)
+        | PropData::TipLocString(_)
+        | PropData::NPCInfoLocString(_) => data.as_text()?,
+        _ => return None,
+    })
+}
+
+fn is_numeric(data: &PropData) -> bool {
+    matches!(
+        data,
+        PropData::Int(_)
+            | PropData::Short(_)
+            | PropData::Long(_)
+            | PropData::Char(_)
+            | PropData::Float(_)
+    )
+}
+
+/// Parses `text` into a value of the same type as `current`
+pub fn parse_value(current: &PropData, text: &str) -> std::result::Result<PropData, String> {
+    let text = text.trim();
+    let number_err = || format!("'{text}' is not a valid {}", type_name(current));
+    let floats = |count: usize| {
+        let values = text
+            .split_whitespace()
+            .map(|v| v.parse::<f32>())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| format!("'{text}' is not a list of numbers"))?;
+        if values.len() != count {
+            return Err(format!("expected {count} numbers, got {}", values.len()));
+        }
+        Ok(values)
+    };
+    Ok(match current {
+        PropData::Int(_) => PropData::Int(text.parse().map_err(|_| number_err())?),
+        PropData::Short(_) => PropData::Short(text.parse().map_err(|_| number_err())?),
+        PropData::Long(_) => PropData::Long(text.parse().map_err(|_| number_err())?),
+        PropData::Char(_) => PropData::Char(text.parse().map_err(|_| number_err())?),
+        PropData::Float(_) => PropData::Float(text.parse().map_err(|_| number_err())?),
+        PropData::Bool(_) => PropData::Bool(match text.to_lowercase().as_str() {
+            "true" | "1" => true,
+            "false" | "0" => false,
+            _ => return Err(format!("'{text}' is not a valid bool")),
+        }),
+        PropData::Vector2(_) => PropData::Vector2(crate::types::Vec2::from_slice(&floats(2)?)),
+        PropData::Vector3(_) => PropData::Vector3(crate::types::Vec3::from_slice(&floats(3)?)),
+        PropData::Vector4(_) => PropData::Vector4(crate::types::Vec4::from_slice(&floats(4)?)),
+        PropData::FloatColor { unknown, .. } => {
+            let rgb = floats(3)?;
+            PropData::FloatColor {
+                unknown: *unknown,
+                r: rgb[0],
+                g: rgb[1],
+                b: rgb[2],
+            }
+        }
+        PropData::Guid(id) => {
+            let mut id = id.clone();
+            id.id = Uuid::parse_str(text.trim_matches(['{', '}']))
+                .map_err(|_| format!("'{text}' is not a valid GUID"))?;
+            PropData::Guid(id)
+        }
+        PropData::Enum(e) | PropData::ContainerEnum(e) => {
+            let Some(value) = e.with_value_name(text) else {
+                return Err(format!("'{text}' is not a value of {}", type_name(current)));
+            };
+            if matches!(current, PropData::Enum(_)) {
+                PropData::Enum(value)
+            } else {
+                PropData::ContainerEnum(value)
+            }
+        }
+        PropData::String(_) => PropData::String(text.to_string()),
+        PropData::ImageOrMaterialResourceString(_) => {
+            PropData::ImageOrMaterialResourceString(text.to_string())
+        }
+        PropData::LocString(_) => PropData::LocString(text.to_string()),
+        PropData::ImageResourceString(_) => PropData::ImageResourceString(text.to_string()),
+        PropData::LetterLocString(_) => PropData::LetterLocString(text.to_string()),
+        PropData::SpeedTreeResourceString(_) => PropData::SpeedTreeResourceString(text.to_string()),
+        PropData::BookLocString(_) => PropData::BookLocString(text.to_string()),
+        PropData::MeshResourceString(_) => PropData::MeshResourceString(text.to_string()),
+        PropData::InfoLocString(_) => PropData::InfoLocString(text.to_string()),
+        PropData::QuestLocString(_) => PropData::QuestLocString(text.to_string()),
+        PropData::TipLocString(_) => PropData::TipLocString(text.to_string()),
+        PropData::NPCInfoLocString(_) => PropData::NPCInfoLocString(text.to_string()),
+        _ => {
+            return Err(format!(
+                "{} cannot be edited in a table",
+                type_name(current)
+            ));
+        }
+    })
+}
+
+/// Variant name for error messages, e.g. `Int` or `Enum(ItemCategory)`
+fn type_name(data: &PropData) -> String {
+    let dbg = format!("{data:?}");
+    match data {
+        PropData::Enum(e) | PropData::ContainerEnum(e) => {
+            let dbg = format!("{e:?}");
+            let end = dbg.find('(').unwrap_or(dbg.len());
+            format!("Enum({})", &dbg[..end])
+        }
+        _ => {
+            let end = dbg.find(['(', ' ', '{']).unwrap_or(dbg.len());
+            dbg[..end].to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{accessor, template};
+    use crate::types::Vec3;
+    use crate::types::properties::{PropBuffer, PropEnum, enums};
+    use crate::types::template::TemplateEntity;
+
+    const SWORD: &str = "{00000000-0000-0000-0000-000000000001}";
+    const RING: &str = "{00000000-0000-0000-0000-000000000002}";
+
+    fn templates() -> Vec<TemplateEntity> {
+        vec![
+            template(
+                SWORD,
+                "It_Sword",
+                vec![
+                    accessor(
+                        "gCItem_PS",
+                        &[
+                            ("Value", PropData::Int(100)),
+                            ("Weight", PropData::Float(2.5)),
+                            ("Description", PropData::String("007".to_string())),
+                            (
+                                "Raw",
+                                PropData::Buffer(PropBuffer {
+                                    ty: "bCUnknown".to_string(),
+                                    data: vec![1, 2, 3],
+                                }),
+                            ),
+                        ],
+                    ),
+                    accessor("eCMesh_PS", &[("Offset", PropData::Vector3(Vec3::ONE))]),
+                ],
+            ),
+            template(
+                RING,
+                "It_Ring",
+                vec![accessor(
+                    "gCItem_PS",
+                    &[
+                        ("Value", PropData::Int(300)),
+                        (
+                            "EquipSlot",
+                            PropData::Enum(PropEnum::EquipSlot(enums::EquipSlot::Ring1)),
+                        ),
+                    ],
+                )],
+            ),
+            template(
+                "{00000000-0000-0000-0000-000000000003}",
+                "Empty",
+                Vec::new(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn export_columns() {
+        let table = PropertyTable::export(&templates(), &[]);
+        assert_eq!(
+            table.columns,
+            [
+                ID_COLUMN,
+                NAME_COLUMN,
+                "gCItem_PS.Value",
+                "gCItem_PS.Weight",
+                "gCItem_PS.Description",
+                "eCMesh_PS.Offset",
+                "gCItem_PS.EquipSlot"
+            ]
+        );
+        assert_eq!(
+            table.numeric,
+            [false, false, true, true, false, false, false]
+        );
+        assert_eq!(
+            table.rows,
+            [
+                vec![SWORD, "It_Sword", "100", "2.5", "007", "1 1 1", ""],
+                vec![RING, "It_Ring", "300", "", "", "", "Ring1"],
+            ]
+        );
+
+        let items_only = PropertyTable::export(&templates(), &["eCMesh_PS".to_string()]);
+        assert_eq!(items_only.columns[2..], ["eCMesh_PS.Offset"]);
+        assert_eq!(items_only.rows.len(), 1);
+    }
+
+    #[test]
+    fn apply_changed_cells() {
+        let mut entities = templates();
+        let mut table = PropertyTable::export(&entities, &[]);
+        table.rows[0][2] = "150".to_string();
+        table.rows[0][4] = "1e5".to_string();
+        table.rows[0][5] = "0 2 0".to_string();
+        table.rows[1][3] = "1".to_string();
+        table.rows[1][6] = "Ring2".to_string();
+        table.rows.push(vec![
+            "{00000000-0000-0000-0000-0000000000ff}".to_string(),
+            "Missing".to_string(),
+        ]);
+        let report = table.apply(&mut entities).unwrap();
+
+        assert_eq!(report.changed.len(), 4);
+        assert_eq!(report.unchanged, 2);
+        let errors = report
+            .errors
+            .iter()
+            .map(|e| (e.row, e.column.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [(2, "gCItem_PS.Weight"), (3, ID_COLUMN)],
+            "{:?}",
+            report.errors
+        );
+        let sword = entities[0].properties[0].accessor_prop.object.clone();
+        assert_eq!(sword.get_property("Value"), Some(&PropData::Int(150)));
+        assert_eq!(
+            sword.get_property("Description"),
+            Some(&PropData::String("1e5".to_string()))
+        );
+        let ring = &entities[1].properties[0].accessor_prop.object;
+        assert_eq!(
+            ring.get_property("EquipSlot"),
+            Some(&PropData::Enum(PropEnum::EquipSlot(
+                enums::EquipSlot::Ring2
+            )))
+        );
+
+        table.columns[0] = "id".to_string();
+        assert!(table.apply(&mut entities).is_err());
+    }
+
+    #[test]
+    fn parse_keeps_the_type() {
+        assert_eq!(
+            parse_value(&PropData::Short(1), " -3 "),
+            Ok(PropData::Short(-3))
+        );
+        assert_eq!(parse_value(&PropData::Long(1), "7"), Ok(PropData::Long(7)));
+        assert!(parse_value(&PropData::Long(1), "-7").is_err());
+        assert!(parse_value(&PropData::Int(1), "1.5").is_err());
+        assert_eq!(
+            parse_value(&PropData::Float(0.0), "1e-3"),
+            Ok(PropData::Float(0.001))
+        );
+        assert_eq!(
+            parse_value(&PropData::Bool(false), "TRUE"),
+            Ok(PropData::Bool(true))
+        );
+        assert_eq!(
+            parse_value(
+                &PropData::FloatColor {
+                    unknown: 9,
+                    r: 0.0,
+                    g: 0.0,
+                    b: 0.0
+                },
+                "1 0.5 0"
+            ),
+            Ok(PropData::FloatColor {
+                unknown: 9,
+                r: 1.0,
+                g: 0.5,
+                b: 0.0
+            })
+        );
+        assert!(parse_value(&PropData::Vector2(crate::types::Vec2::ZERO), "1 2 3").is_err());
+        assert_eq!(
+            parse_value(
+                &PropData::ContainerEnum(PropEnum::EquipSlot(enums::EquipSlot::None)),
+                "Armor"
+            ),
+            Ok(PropData::ContainerEnum(PropEnum::EquipSlot(
+                enums::EquipSlot::Armor
+            )))
+        );
+        assert!(
+            parse_value(
+                &PropData::Enum(PropEnum::EquipSlot(enums::EquipSlot::None)),
+                "Sword"
+            )
+            .is_err()
+        );
+        assert_eq!(
+            parse_value(&PropData::LocString(String::new()), "007"),
+            Ok(PropData::LocString("007".to_string()))
+        );
+        let buffer = PropData::Buffer(PropBuffer {
+            ty: "bCUnknown".to_string(),
+            data: Vec::new(),
+        });
+        assert!(parse_value(&buffer, "").is_err());
+    }
+}
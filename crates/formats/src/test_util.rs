@@ -0,0 +1,116 @@
+//! Synthetic entities and templates for the unit tests, there are no game files in the repository
+use uuid::Uuid;
+
+use crate::file_formats::lrent::LrentFile;
+use crate::types::entity::DynamicEntity;
+use crate::types::object::AccessorPropertyObject;
+use crate::types::properties::PropData;
+use crate::types::template::{TemplateEntity, TemplatePropertyAccessor};
+use crate::types::time::DateTime;
+use crate::types::{BoundingBox, EntityDynamicContext, GenClass, Mat4, PropertyId, Quat, Vec3};
+
+/// An accessor of class `class` (without class data) with the given properties
+pub(crate) fn accessor(class: &str, props: &[(&str, PropData)]) -> AccessorPropertyObject {
+    accessor_with(GenClass::Invalid(class.to_string()), props)
+}
+
+pub(crate) fn accessor_with(class: GenClass, props: &[(&str, PropData)]) -> AccessorPropertyObject {
+    let mut apo = AccessorPropertyObject::new();
+    apo.object.class = class;
+    for (name, value) in props {
+        apo.object.set_property(name, value.clone());
+    }
+    apo
+}
+
+pub(crate) fn template(
+    id: &str,
+    name: &str,
+    accessors: Vec<AccessorPropertyObject>,
+) -> TemplateEntity {
+    TemplateEntity {
+        version: 218,
+        id: PropertyId {
+            id: Uuid::parse_str(id.trim_matches(['{', '}'])).unwrap(),
+            unknown: 0,
+        },
+        enabled: true,
+        rendering_enabled: true,
+        picking_enabled: true,
+        collision_enabled: true,
+        helper_parent: false,
+        is_game_relevant: true,
+        insert_type: 0,
+        modified_date: DateTime(0),
+        scale_grid_percentage: 100,
+        is_savegame_relevant: false,
+        name: name.to_string(),
+        unknown1: false,
+        unknown2: 0.0,
+        unknown3: 0.0,
+        unknown5: Vec3::ZERO,
+        unknown6: Quat::IDENTITY,
+        unknown7: false,
+        unknown8: false,
+        unknown9: false,
+        unused1: false,
+        unused2: false,
+        ref_template: None,
+        properties: accessors
+            .into_iter()
+            .map(|accessor_prop| TemplatePropertyAccessor {
+                unknown1: 1,
+                accessor_prop,
+            })
+            .collect(),
+    }
+}
+
+/// An entity named `name` at `position`, with a 2x2x2 local bounding box
+pub(crate) fn entity(
+    name: &str,
+    position: Vec3,
+    accessors: Vec<AccessorPropertyObject>,
+) -> DynamicEntity {
+    let template = template("{00000000-0000-0000-0000-000000000001}", name, accessors);
+    let local_matrix = Mat4::from_translation(position);
+    let mut entity = DynamicEntity::from_template(
+        &template,
+        &template.properties,
+        local_matrix,
+        Mat4::IDENTITY,
+    )
+    .unwrap();
+    entity.local_bound = BoundingBox {
+        max: Vec3::ONE,
+        min: -Vec3::ONE,
+    };
+    entity.update_bounds(Mat4::IDENTITY);
+    entity
+}
+
+/// An lrent file containing `entities`, `parents` are (child, parent) index pairs without the terminator
+pub(crate) fn lrent(entities: Vec<DynamicEntity>, parents: &[(i32, i32)]) -> LrentFile {
+    let mut parents = parents.to_vec();
+    parents.push((-1, -1));
+    let mut ctx = EntityDynamicContext {
+        version: 213,
+        entities,
+        parents,
+        enabled: 1,
+        unknown1: 0.0,
+        unknown2: 0.0,
+        bounding_box: Some(BoundingBox {
+            max: Vec3::ZERO,
+            min: Vec3::ZERO,
+        }),
+    };
+    for idx in 0..ctx.entities.len() {
+        let local_matrix = ctx.entities[idx].local_matrix;
+        ctx.set_local_matrix(idx, local_matrix);
+    }
+    ctx.update_bounding_box();
+    LrentFile {
+        root: accessor_with(GenClass::EntityDynamicContext(ctx), &[]),
+    }
+}
@@ -0,0 +1,339 @@
+//! Collision shapes (eCCollisionShape_PS).
+//!
+//! Every shape is a property object (`ShapeType`, `Group`, `Material`, ...) followed by
+//! the geometry selected by its `ShapeType`. Geometry that doesn't fit the expected layout
+//! is kept as raw bytes, so unknown shapes still round-trip.
+use serde::{Deserialize, Serialize};
+
+use super::properties::{self, PropData, PropEnum, enums};
+use super::{Mat3, Vec3, load_array, save_array, try_load};
+use crate::archive::*;
+use crate::binimport::BinImport;
+use crate::error::*;
+use crate::helpers::*;
+
+/// eCCollisionShape_PS
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CollisionShapes {
+    pub version: u16,
+    pub shapes: Vec<CollisionShape>,
+}
+
+impl CollisionShapes {
+    /// Returns `None` (and leaves `src` untouched) if the data doesn't match the expected layout
+    pub fn try_load(src: &mut PakFile, len: usize) -> Option<Self> {
+        try_load(src, len, "eCCollisionShape_PS", |src, end| {
+            Ok(Self {
+                version: read_u16(src)?,
+                // the smallest shape is the object header and an empty class:
+                shapes: load_array(src, end, 14, |src| CollisionShape::load(src, end))?,
+            })
+        })
+    }
+
+    pub fn save<W: ArchiveWriteTarget>(&self, dst: &mut W) -> Result<()> {
+        write_u16(dst, self.version)?;
+        save_array(dst, &self.shapes, |dst, shape| shape.save(dst))?;
+        Ok(())
+    }
+}
+
+/// eCCollisionShape
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CollisionShape {
+    pub version: u16,
+    pub prop_data_ver: u16,
+    pub props: Vec<properties::Property>,
+    pub shape_version: u16,
+    pub geometry: ShapeGeometry,
+}
+
+/// bCOrientedBox
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct OrientedBox {
+    pub center: Vec3,
+    /// Half size along the axes of `orientation`
+    pub extent: Vec3,
+    pub orientation: Mat3,
+}
+
+/// bCSphere
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct Sphere {
+    pub radius: f32,
+    pub center: Vec3,
+}
+
+/// bCCapsule: a cylinder of `height` along the local y axis, with hemispheres of `radius` at both ends
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct Capsule {
+    pub center: Vec3,
+    pub orientation: Mat3,
+    pub radius: f32,
+    pub height: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShapeGeometry {
+    /// TriMesh and ConvexHull shapes reference a mesh resource
+    Mesh(String),
+    Box(OrientedBox),
+    Sphere(Sphere),
+    Capsule(Capsule),
+    Point(Vec3),
+    /// Other shape types, or geometry with an unknown layout
+    Opaque(#[serde(with = "crate::helpers::ser_hex")] Vec<u8>),
+}
+
+impl ShapeGeometry {
+    fn load(src: &mut PakFile, shape_type: enums::CollisionShapeType) -> Result<Self> {
+        use enums::CollisionShapeType as Type;
+        Ok(match shape_type {
+            Type::TriMesh | Type::ConvexHull => {
+                let id = read_u16(src)? as usize;
+                let Some(name) = src.strings.get(id) else {
+                    return Err(Error::InvalidString(format!(
+                        "String index {id} out of range"
+                    )));
+                };
+                ShapeGeometry::Mesh(name.clone())
+            }
+            Type::Box => ShapeGeometry::Box(OrientedBox {
+                center: Vec3::load(src)?,
+                extent: Vec3::load(src)?,
+                orientation: Mat3::load(src)?,
+            }),
+            Type::Sphere => ShapeGeometry::Sphere(Sphere {
+                radius: read_f32(src)?,
+                center: Vec3::load(src)?,
+            }),
+            Type::Capsule => ShapeGeometry::Capsule(Capsule {
+                center: Vec3::load(src)?,
+                orientation: Mat3::load(src)?,
+                radius: read_f32(src)?,
+                height: read_f32(src)?,
+            }),
+            Type::Point => ShapeGeometry::Point(Vec3::load(src)?),
+            Type::None | Type::Plane => {
+                return Err(Error::InvalidStructure(format!(
+                    "{shape_type:?} shapes are not decoded"
+                )));
+            }
+        })
+    }
+
+    fn save<W: ArchiveWriteTarget>(&self, dst: &mut W) -> Result<()> {
+        match self {
+            ShapeGeometry::Mesh(name) => dst.write_str(name)?,
+            ShapeGeometry::Box(obb) => {
+                obb.center.save(dst)?;
+                obb.extent.save(dst)?;
+                obb.orientation.save(dst)?;
+            }
+            ShapeGeometry::Sphere(sphere) => {
+                write_f32(dst, sphere.radius)?;
+                sphere.center.save(dst)?;
+            }
+            ShapeGeometry::Capsule(capsule) => {
+                capsule.center.save(dst)?;
+                capsule.orientation.save(dst)?;
+                write_f32(dst, capsule.radius)?;
+                write_f32(dst, capsule.height)?;
+            }
+            ShapeGeometry::Point(point) => point.save(dst)?,
+            ShapeGeometry::Opaque(data) => dst.write_all(data)?,
+        }
+        Ok(())
+    }
+}
+
+impl CollisionShape {
+    /// Same framing as [`super::object::Object`], but the class data depends on the `ShapeType` property.
+    /// Sizes are checked against `class_end` (the end of the eCCollisionShape_PS data),
+    /// so that data that doesn't match fails to load instead of panicking.
+    pub fn load(src: &mut PakFile, class_end: usize) -> Result<Self> {
+        let version = read_u16(src)?;
+        let object_data_size = read_u32(src)? as usize;
+        let end = src.current_read_idx + object_data_size;
+        if end > class_end {
+            return Err(Error::InvalidStructure(
+                "eCCollisionShape exceeds the class data".to_string(),
+            ));
+        }
+        let prop_data_ver = read_u16(src)?;
+        let prop_count = read_u32(src)? as usize;
+        // every property has at least its name, type, version and size
+        if src.current_read_idx + prop_count * 10 > end {
+            return Err(Error::InvalidStructure(format!(
+                "{prop_count} properties exceed the eCCollisionShape"
+            )));
+        }
+        let mut props = Vec::with_capacity(prop_count);
+        for _idx in 0..prop_count {
+            props.push(properties::Property::load_bounded(src, end)?);
+        }
+        let mut shape = Self {
+            version,
+            prop_data_ver,
+            props,
+            shape_version: read_u16(src)?,
+            geometry: ShapeGeometry::Opaque(Vec::new()),
+        };
+        if src.current_read_idx > end {
+            return Err(Error::InvalidStructure(
+                "eCCollisionShape exceeds its object data".to_string(),
+            ));
+        }
+
+        let geometry_start = src.current_read_idx;
+        let geometry = shape
+            .shape_type()
+            .and_then(|shape_type| ShapeGeometry::load(src, shape_type).ok());
+        shape.geometry = match geometry {
+            Some(geometry) if src.current_read_idx == end => geometry,
+            _ => {
+                src.current_read_idx = geometry_start;
+                let mut data = vec![0; end - geometry_start];
+                std::io::Read::read_exact(src, &mut data)?;
+                ShapeGeometry::Opaque(data)
+            }
+        };
+        Ok(shape)
+    }
+
+    pub fn save<W: ArchiveWriteTarget>(&self, dst: &mut W) -> Result<()> {
+        write_u16(dst, self.version)?;
+
+        let mut block = TempWriteTarget::new(dst);
+        write_u16(&mut block, self.prop_data_ver)?;
+        write_u32(&mut block, self.props.len() as u32)?;
+        for prop in &self.props {
+            prop.save(&mut block)?;
+        }
+        write_u16(&mut block, self.shape_version)?;
+        self.geometry.save(&mut block)?;
+
+        let data = block.finish();
+        write_u32(dst, data.len() as u32)?;
+        dst.write_all(&data)?;
+        Ok(())
+    }
+
+    pub fn get_property(&self, name: &str) -> Option<&PropData> {
+        self.props
+            .iter()
+            .find(|prop| prop.name == name)
+            .map(|prop| &*prop.data)
+    }
+
+    pub fn shape_type(&self) -> Option<enums::CollisionShapeType> {
+        match self.get_property("ShapeType")? {
+            PropData::Enum(PropEnum::CollisionShapeType(ty))
+            | PropData::ContainerEnum(PropEnum::CollisionShapeType(ty)) => Some(*ty),
+            _ => None,
+        }
+    }
+
+    pub fn group(&self) -> Option<enums::ShapeGroup> {
+        match self.get_property("Group")? {
+            PropData::Enum(PropEnum::ShapeGroup(group))
+            | PropData::ContainerEnum(PropEnum::ShapeGroup(group)) => Some(*group),
+            _ => None,
+        }
+    }
+
+    /// Replaces the geometry, updating `ShapeType` to match
+    pub fn set_geometry(&mut self, geometry: ShapeGeometry) {
+        use enums::CollisionShapeType as Type;
+        let shape_type = match &geometry {
+            // keep TriMesh vs ConvexHull
+            ShapeGeometry::Mesh(_) => match self.shape_type() {
+                Some(Type::ConvexHull) => Type::ConvexHull,
+                _ => Type::TriMesh,
+            },
+            ShapeGeometry::Box(_) => Type::Box,
+            ShapeGeometry::Sphere(_) => Type::Sphere,
+            ShapeGeometry::Capsule(_) => Type::Capsule,
+            ShapeGeometry::Point(_) => Type::Point,
+            ShapeGeometry::Opaque(_) => {
+                self.geometry = geometry;
+                return;
+            }
+        };
+        let data = PropEnum::CollisionShapeType(shape_type);
+        let data = match self.get_property("ShapeType") {
+            Some(PropData::Enum(_)) => PropData::Enum(data),
+            _ => PropData::ContainerEnum(data),
+        };
+        match self.props.iter_mut().find(|prop| prop.name == "ShapeType") {
+            Some(prop) => *prop.data = data,
+            None => self.props.push(properties::Property {
+                name: "ShapeType".to_string(),
+                version: 30,
+                data: Box::new(data),
+            }),
+        }
+        self.geometry = geometry;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> CollisionShapes {
+        let mut shape = CollisionShape {
+            version: 1,
+            prop_data_ver: 201,
+            props: Vec::new(),
+            shape_version: 1,
+            geometry: ShapeGeometry::Opaque(Vec::new()),
+        };
+        shape.set_geometry(ShapeGeometry::Box(OrientedBox {
+            center: Vec3::new(1.0, 2.0, 3.0),
+            extent: Vec3::new(0.5, 0.5, 2.0),
+            orientation: Mat3::IDENTITY,
+        }));
+        CollisionShapes {
+            version: 1,
+            shapes: vec![shape],
+        }
+    }
+
+    fn save(shapes: &CollisionShapes) -> PakFile {
+        let mut pak = PakFile::new();
+        shapes.save(&mut pak).unwrap();
+        pak
+    }
+
+    fn load(pak: &mut PakFile) -> Option<CollisionShapes> {
+        pak.current_read_idx = 0;
+        let len = pak.data.len();
+        CollisionShapes::try_load(pak, len)
+    }
+
+    #[test]
+    fn round_trip() {
+        let shapes = sample();
+        let mut pak = save(&shapes);
+        assert_eq!(load(&mut pak), Some(shapes));
+        assert_eq!(pak.current_read_idx, pak.data.len());
+    }
+
+    #[test]
+    fn unexpected_data_is_rejected() {
+        // offsets into the first shape: prop_count, the property's name, version and data size
+        for (offset, value) in [(14, u32::MAX), (18, 0xFFFF), (22, 31), (24, 4)] {
+            let mut pak = save(&sample());
+            let bytes = if offset == 14 || offset == 24 {
+                value.to_le_bytes().to_vec()
+            } else {
+                (value as u16).to_le_bytes().to_vec()
+            };
+            pak.data[offset..offset + bytes.len()].copy_from_slice(&bytes);
+            assert_eq!(load(&mut pak), None, "corrupted at {offset}");
+            assert_eq!(pak.current_read_idx, 0);
+        }
+    }
+}
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use crate::archive::ArchiveSerializable;
 use crate::helpers::*;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 /// bTRefPtrArray
 pub struct RefPtrArray<T: ArchiveSerializable>(pub Vec<T>);
 
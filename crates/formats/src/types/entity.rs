@@ -2,13 +2,19 @@ use serde::{Deserialize, Serialize};
 use std::io::Read;
 use uuid::Uuid;
 
+use super::template::{TemplateEntity, TemplatePropertyAccessor};
 use super::*;
 use crate::binimport::BinImport;
 use crate::error::*;
 use crate::{archive::*, helpers::*};
 
+// Versions written by R1 for newly created entities:
+const DYNAMIC_ENTITY_VERSION: u16 = 213;
+const GEOMETRY_ENTITY_VERSION: u16 = 214;
+const ENTITY_VERSION: u16 = 216;
+
 /// eCDynamicEntity
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct DynamicEntity {
     pub version: u16,
     pub version2: u16,
@@ -59,6 +65,118 @@ impl DynamicEntity {
         })
     }
 
+    /// Creates a new entity from `template`, placed at `local_matrix` relative to its parent.
+    /// `properties` are the template's property sets - for templates referencing another
+    /// template, these have to be taken from the referenced one.
+    pub fn from_template(
+        template: &TemplateEntity,
+        properties: &[TemplatePropertyAccessor],
+        local_matrix: Mat4,
+        parent_matrix: Mat4,
+    ) -> Result<Self> {
+        let accessors = properties
+            .iter()
+            .map(|prop| Accessor {
+                unknown: prop.unknown1,
+                object: prop.accessor_prop.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        let local_bound = accessors
+            .iter()
+            .find_map(|acc| match &acc.object.object.class {
+                GenClass::PropertySet(property_set::PropertySet::Mesh(mesh)) => {
+                    mesh.bounding.clone()
+                }
+                _ => None,
+            })
+            .unwrap_or(BoundingBox {
+                max: Vec3::ZERO,
+                min: Vec3::ZERO,
+            });
+
+        let world_matrix = parent_matrix * local_matrix;
+        let world_bound = local_bound.transformed(&world_matrix);
+        let world_sphere = Sphere::enclosing(&world_bound);
+
+        let entity = Entity {
+            version: ENTITY_VERSION,
+            node: Node::new()?,
+            enabled: template.enabled as u8,
+            render_enabled: template.rendering_enabled as u8,
+            picking_enabled: template.picking_enabled as u8,
+            collision_enabled: template.collision_enabled as u8,
+            insert_type: template.insert_type,
+            locked: 0,
+            changed_timestamp: time::DateTime::now(),
+            is_savegame_relevant: template.is_savegame_relevant as u8,
+            accessors,
+            // see TemplateEntity::load_header for the flag mapping
+            flag_a: template.unknown9,
+            flag_b: template.unknown8,
+            flag_c: template.unknown7,
+            flag_d: template.unknown1,
+            flag_e: false,
+            unknown1: 0,
+            unknown2: 0,
+            unknown3: 0,
+            unknown4: 0,
+            unknown5: 0,
+            unknown6: 0,
+            unknown7: 0,
+        };
+
+        let geo_entity = GeometryEntity {
+            version: GEOMETRY_ENTITY_VERSION,
+            unknown1: 0.0,
+            geo_matrix: world_matrix,
+            geo_bound: world_bound.clone(),
+            geo_sphere: world_sphere.clone(),
+            alpha: 1.0,
+            view_range: 0.0,
+            cache_in_range: 0.0,
+            unknown2: [0; 16],
+            entity,
+        };
+
+        Ok(Self {
+            version: DYNAMIC_ENTITY_VERSION,
+            version2: 0,
+            id: Uuid::new_v4(),
+            name: template.name.clone(),
+            unknown1: 0,
+            creator: EntityProxy {
+                version: 1,
+                id: Some(template.id.clone()),
+            },
+            local_matrix,
+            world_bound,
+            world_sphere,
+            local_bound,
+            geo_entity,
+        })
+    }
+
+    /// Uses the same structure versions as `other`, e.g. to match the entities already in a file
+    pub fn adopt_versions(&mut self, other: &DynamicEntity) {
+        self.version = other.version;
+        self.version2 = other.version2;
+        self.creator.version = other.creator.version;
+        self.geo_entity.version = other.geo_entity.version;
+        self.geo_entity.entity.version = other.geo_entity.entity.version;
+        self.geo_entity.entity.node.version = other.geo_entity.entity.node.version;
+    }
+
+    /// Recalculates the world-space bounds from `local_bound` after the transform changed
+    pub fn update_bounds(&mut self, parent_matrix: Mat4) {
+        let world_matrix = parent_matrix * self.local_matrix;
+        self.world_bound = self.local_bound.transformed(&world_matrix);
+        self.world_sphere = Sphere::enclosing(&self.world_bound);
+        self.geo_entity.geo_matrix = world_matrix;
+        self.geo_entity.geo_bound = self.world_bound.clone();
+        self.geo_entity.geo_sphere = self.world_sphere.clone();
+    }
+
     pub fn save<W: ArchiveWriteTarget>(&self, dst: &mut W) -> Result<()> {
         let version = self.version;
         write_u16(dst, version)?;
@@ -80,7 +198,7 @@ impl DynamicEntity {
 }
 
 /// eCGeometryEntity
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct GeometryEntity {
     pub version: u16,
     pub unknown1: f32,
@@ -147,7 +265,7 @@ impl GeometryEntity {
 }
 
 /// eCEntity
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Entity {
     pub version: u16,
     pub node: Node,
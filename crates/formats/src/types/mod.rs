@@ -1,10 +1,13 @@
+pub mod collision;
 pub mod containers;
 pub mod entity;
+pub mod navigation;
 pub mod object;
 pub mod properties;
 pub mod property_set;
 pub mod template;
 pub mod time;
+pub mod world;
 
 use std::io::Read;
 
@@ -14,19 +17,24 @@ use uuid::Uuid;
 use crate::binimport::BinImport;
 use crate::error::*;
 use crate::{archive::*, helpers::*};
+use collision::*;
 use entity::*;
+use navigation::*;
 use object::*;
 use property_set::*;
+use world::*;
 
-pub use glam::{Mat4, Quat, Vec2, Vec3, Vec4};
+pub use glam::{Mat3, Mat4, Quat, Vec2, Vec3, Vec4};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum GenClass {
     Invalid(String),
     DynamicLayer(Box<AccessorPropertyObject>),
     EntityDynamicContext(EntityDynamicContext),
     PropertySet(PropertySet),
+    Sector(Sector),
+    World(World),
     Opaque(OpaqueClass),
 }
 
@@ -56,6 +64,30 @@ impl GenClass {
             }
             "eCMesh_PS" => GenClass::PropertySet(PropertySet::Mesh(Mesh::load(src)?)),
             "gCAnchor_PS" => GenClass::PropertySet(PropertySet::Anchor(Anchor::load(src, len)?)),
+            "gCNavZone_PS" => match NavZone::try_load(src, len) {
+                Some(zone) => GenClass::PropertySet(PropertySet::NavZone(zone)),
+                None => GenClass::Opaque(OpaqueClass::load(src, class_name, len)?),
+            },
+            "gCNavPath_PS" => match NavPath::try_load(src, len) {
+                Some(path) => GenClass::PropertySet(PropertySet::NavPath(path)),
+                None => GenClass::Opaque(OpaqueClass::load(src, class_name, len)?),
+            },
+            "gCNavOffset_PS" => match NavOffset::try_load(src, len) {
+                Some(offset) => GenClass::PropertySet(PropertySet::NavOffset(offset)),
+                None => GenClass::Opaque(OpaqueClass::load(src, class_name, len)?),
+            },
+            "eCCollisionShape_PS" => match CollisionShapes::try_load(src, len) {
+                Some(shapes) => GenClass::PropertySet(PropertySet::CollisionShapes(shapes)),
+                None => GenClass::Opaque(OpaqueClass::load(src, class_name, len)?),
+            },
+            "gCSector" => match Sector::try_load(src, len) {
+                Some(sector) => GenClass::Sector(sector),
+                None => GenClass::Opaque(OpaqueClass::load(src, class_name, len)?),
+            },
+            "gCWorld" => match World::try_load(src, len) {
+                Some(world) => GenClass::World(world),
+                None => GenClass::Opaque(OpaqueClass::load(src, class_name, len)?),
+            },
             "" | "INVALID" => {
                 assert_eq!(len, 2);
                 let inv_pad = read_u16(src)?;
@@ -64,12 +96,7 @@ impl GenClass {
             }
             _ => {
                 println!("Warning: unknown class {class_name}");
-                let mut data = vec![0; len];
-                src.read_exact(&mut data)?;
-                GenClass::Opaque(OpaqueClass {
-                    name: class_name.to_string(),
-                    data,
-                })
+                GenClass::Opaque(OpaqueClass::load(src, class_name, len)?)
             }
         })
     }
@@ -93,6 +120,12 @@ impl GenClass {
             GenClass::PropertySet(set) => {
                 set.save(dst)?;
             }
+            GenClass::Sector(sector) => {
+                sector.save(dst)?;
+            }
+            GenClass::World(world) => {
+                world.save(dst)?;
+            }
             GenClass::Opaque(opaque_class) => {
                 dst.write_all(&opaque_class.data)?;
             }
@@ -107,19 +140,84 @@ impl GenClass {
             GenClass::DynamicLayer(_) => "gCDynamicLayer",
             GenClass::EntityDynamicContext(_) => "eCEntityDynamicContext",
             GenClass::PropertySet(ps) => ps.get_class_name(),
+            GenClass::Sector(_) => "gCSector",
+            GenClass::World(_) => "gCWorld",
         }
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct OpaqueClass {
     name: String,
     #[serde(with = "crate::helpers::ser_hex")]
     data: Vec<u8>,
 }
 
+impl OpaqueClass {
+    fn load(src: &mut PakFile, class_name: &str, len: usize) -> Result<Self> {
+        let mut data = vec![0; len];
+        src.read_exact(&mut data)?;
+        Ok(Self {
+            name: class_name.to_string(),
+            data,
+        })
+    }
+}
+
+/// Decodes class data with a layout that was derived from the accessors the game exports
+/// (see `r1-sys/*/lib/*.def`), not from shipped files. As such a layout is not confirmed for all files,
+/// `load` has to consume exactly `len` bytes without errors. Otherwise `src` is rewound and `None` is returned,
+/// so [`GenClass::load`] keeps the class opaque and it is written back unchanged.
+/// `load` gets the end of the class data to bound counts before allocating.
+fn try_load<T>(
+    src: &mut PakFile,
+    len: usize,
+    class_name: &str,
+    load: impl FnOnce(&mut PakFile, usize) -> Result<T>,
+) -> Option<T> {
+    let start = src.current_read_idx;
+    let end = start + len;
+    match load(src, end) {
+        Ok(value) if src.current_read_idx == end => Some(value),
+        _ => {
+            println!("Warning: {class_name} has an unknown layout, keeping it opaque");
+            src.current_read_idx = start;
+            None
+        }
+    }
+}
+
+/// bTValArray: count followed by the elements.
+/// `elem_size` is used to reject counts that cannot fit before `end`.
+fn load_array<T>(
+    src: &mut PakFile,
+    end: usize,
+    elem_size: usize,
+    load: impl Fn(&mut PakFile) -> Result<T>,
+) -> Result<Vec<T>> {
+    let count = read_u32(src)? as usize;
+    if src.current_read_idx + count * elem_size > end {
+        return Err(Error::InvalidStructure(format!(
+            "{count} array elements exceed the class data"
+        )));
+    }
+    (0..count).map(|_| load(src)).collect()
+}
+
+fn save_array<T, W: ArchiveWriteTarget>(
+    dst: &mut W,
+    values: &[T],
+    save: impl Fn(&mut W, &T) -> Result<()>,
+) -> Result<()> {
+    write_u32(dst, values.len() as u32)?;
+    for value in values {
+        save(dst, value)?;
+    }
+    Ok(())
+}
+
 /// eCEntityDynamicContext
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct EntityDynamicContext {
     pub version: u16,
     pub entities: Vec<DynamicEntity>,
@@ -174,6 +272,108 @@ impl EntityDynamicContext {
         })
     }
 
+    /// Appends `entity` to the context, attached to the entity at `parent` (if any).
+    /// Returns the index of the new entity.
+    pub fn insert_entity(&mut self, entity: DynamicEntity, parent: Option<usize>) -> Result<usize> {
+        if let Some(parent) = parent
+            && parent >= self.entities.len()
+        {
+            return Err(Error::InvalidOp(format!(
+                "Parent entity {parent} does not exist, context only has {} entities",
+                self.entities.len()
+            )));
+        }
+        if let Some(bb) = &mut self.bounding_box {
+            *bb = if self.entities.is_empty() {
+                entity.world_bound.clone()
+            } else {
+                bb.union(&entity.world_bound)
+            };
+        }
+        let idx = self.entities.len();
+        self.entities.push(entity);
+
+        if let Some(parent) = parent {
+            // the list is terminated by (-1, -1), which has to stay last:
+            let terminator = self.parents.pop();
+            self.parents.push((idx as i32, parent as i32));
+            self.parents.push(terminator.unwrap_or((-1, -1)));
+        }
+        Ok(idx)
+    }
+
+    /// Replaces the transform of an entity and updates the bounds of it and all of its children
+    pub fn set_local_matrix(&mut self, entity_idx: usize, local_matrix: Mat4) {
+        self.entities[entity_idx].local_matrix = local_matrix;
+        for idx in 0..self.entities.len() {
+            if idx != entity_idx && !self.is_descendant(idx, entity_idx) {
+                continue;
+            }
+            let parent_matrix = match self.get_parent(idx) {
+                Some(parent) => self.get_world_matrix(parent),
+                None => Mat4::IDENTITY,
+            };
+            self.entities[idx].update_bounds(parent_matrix);
+        }
+    }
+
+    /// Recalculates the bounds of the whole context from the world bounds of its entities
+    pub fn update_bounding_box(&mut self) {
+        if let Some(bb) = &mut self.bounding_box
+            && let Some((first, rest)) = self.entities.split_first()
+        {
+            *bb = rest.iter().fold(first.world_bound.clone(), |acc, e| {
+                acc.union(&e.world_bound)
+            });
+        }
+    }
+
+    fn is_descendant(&self, entity_idx: usize, ancestor_idx: usize) -> bool {
+        let mut current = entity_idx;
+        for _ in 0..self.entities.len() {
+            match self.get_parent(current) {
+                Some(parent) if parent == ancestor_idx => return true,
+                Some(parent) => current = parent,
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Removes the entity at `entity_idx`, which must not have any children
+    pub fn remove_entity(&mut self, entity_idx: usize) -> Result<DynamicEntity> {
+        if entity_idx >= self.entities.len() {
+            return Err(Error::InvalidOp(format!(
+                "Entity {entity_idx} does not exist, context only has {} entities",
+                self.entities.len()
+            )));
+        }
+        remove_from_hierarchy(&mut self.parents, entity_idx)?;
+        Ok(self.entities.remove(entity_idx))
+    }
+
+    pub fn get_parent(&self, entity_idx: usize) -> Option<usize> {
+        self.parents
+            .iter()
+            .find(|(child, _)| *child == entity_idx as i32)
+            .and_then(|(_, parent)| usize::try_from(*parent).ok())
+    }
+
+    /// Combined transform of the entity and all of its parents
+    pub fn get_world_matrix(&self, entity_idx: usize) -> Mat4 {
+        let mut result = self.entities[entity_idx].local_matrix;
+        let mut current = entity_idx;
+        // guard against broken (cyclic) hierarchies:
+        for _ in 0..self.entities.len() {
+            let Some(parent) = self.get_parent(current) else {
+                break;
+            };
+            result = self.entities[parent].local_matrix * result;
+            current = parent;
+        }
+        result
+    }
+
     pub fn save<W: ArchiveWriteTarget>(&self, dst: &mut W) -> Result<()> {
         let version = self.version;
         write_u16(dst, version)?;
@@ -209,7 +409,7 @@ impl EntityDynamicContext {
 }
 
 /// TODO: For some reason, sometimes min and max are flipped (e.g. xmac file props)?!?
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct BoundingBox {
     pub max: Vec3,
     pub min: Vec3,
@@ -227,9 +427,58 @@ impl BoundingBox {
         self.min.save(dst)?;
         Ok(())
     }
+
+    pub fn from_points<I: IntoIterator<Item = Vec3>>(points: I) -> Self {
+        let mut min = Vec3::splat(f32::MAX);
+        let mut max = Vec3::splat(f32::MIN);
+        for point in points {
+            min = min.min(point);
+            max = max.max(point);
+        }
+        if min.cmpgt(max).any() {
+            // no points at all
+            return Self {
+                max: Vec3::ZERO,
+                min: Vec3::ZERO,
+            };
+        }
+        Self { max, min }
+    }
+
+    /// Returns the eight corners, regardless of whether min and max are flipped
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (a, b) = (self.min, self.max);
+        [
+            Vec3::new(a.x, a.y, a.z),
+            Vec3::new(b.x, a.y, a.z),
+            Vec3::new(a.x, b.y, a.z),
+            Vec3::new(b.x, b.y, a.z),
+            Vec3::new(a.x, a.y, b.z),
+            Vec3::new(b.x, a.y, b.z),
+            Vec3::new(a.x, b.y, b.z),
+            Vec3::new(b.x, b.y, b.z),
+        ]
+    }
+
+    /// Axis aligned box enclosing this box after applying `transform`
+    pub fn transformed(&self, transform: &Mat4) -> Self {
+        Self::from_points(
+            self.corners()
+                .into_iter()
+                .map(|c| transform.transform_point3(c)),
+        )
+    }
+
+    pub fn union(&self, other: &BoundingBox) -> Self {
+        Self::from_points(self.corners().into_iter().chain(other.corners()))
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Sphere {
     pub radius: f32,
     pub pos: Vec3,
@@ -247,9 +496,17 @@ impl Sphere {
         self.pos.save(dst)?;
         Ok(())
     }
+
+    /// Smallest sphere around the box's center that still encloses all of its corners
+    pub fn enclosing(bounds: &BoundingBox) -> Self {
+        Self {
+            radius: (bounds.max - bounds.min).length() * 0.5,
+            pos: bounds.center(),
+        }
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 /// eCEntityProxy
 pub struct EntityProxy {
     pub version: u16,
@@ -295,7 +552,7 @@ impl EntityProxy {
 }
 
 /// bCPropertyID
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct PropertyId {
     pub id: Uuid,
     pub unknown: u32,
@@ -316,8 +573,29 @@ impl PropertyId {
         Ok(())
     }
 }
+/// Removes `idx` from a (child, parent) index list and moves all following indices down
+pub(crate) fn remove_from_hierarchy(parents: &mut Vec<(i32, i32)>, idx: usize) -> Result<()> {
+    let idx = idx as i32;
+    let children = parents.iter().filter(|(_, parent)| *parent == idx).count();
+    if children > 0 {
+        return Err(Error::InvalidOp(format!(
+            "Entity {idx} still has {children} children"
+        )));
+    }
+    parents.retain(|(child, _)| *child != idx);
+    for (child, parent) in parents.iter_mut() {
+        if *child > idx {
+            *child -= 1;
+        }
+        if *parent > idx {
+            *parent -= 1;
+        }
+    }
+    Ok(())
+}
+
 /// eCNode
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Node {
     pub version: u16,
 }
@@ -339,7 +617,7 @@ impl Node {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Accessor {
     pub unknown: u16,
     pub object: AccessorPropertyObject,
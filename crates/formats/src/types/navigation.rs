@@ -0,0 +1,253 @@
+//! Navigation property sets (gCNavZone_PS, gCNavPath_PS, gCNavOffset_PS).
+//!
+//! The class data layouts follow the accessors exported by the game (`AccessPoint`, `AccessRadius`, ...),
+//! see [`super::try_load`] for data that doesn't match.
+use serde::{Deserialize, Serialize};
+
+use super::properties::enums::Direction;
+use super::{EntityProxy, Quat, Vec3, load_array, save_array, try_load};
+use crate::archive::*;
+use crate::binimport::BinImport;
+use crate::error::*;
+use crate::helpers::*;
+
+/// gCNavZone_PS: a (usually convex) polygon NPCs can walk in
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NavZone {
+    pub version: u16,
+    /// Polygon corners, relative to the entity
+    pub points: Vec<Vec3>,
+    pub radius: f32,
+    pub radius_offset: Vec3,
+    pub zone_is_ccw: bool,
+}
+
+impl NavZone {
+    /// Returns `None` (and leaves `src` untouched) if the data doesn't match the expected layout
+    pub fn try_load(src: &mut PakFile, len: usize) -> Option<Self> {
+        try_load(src, len, "gCNavZone_PS", |src, end| {
+            Ok(Self {
+                version: read_u16(src)?,
+                points: load_array(src, end, 12, |src| Ok(Vec3::load(src)?))?,
+                radius: read_f32(src)?,
+                radius_offset: Vec3::load(src)?,
+                zone_is_ccw: read_bool(src)?,
+            })
+        })
+    }
+
+    pub fn save<W: ArchiveWriteTarget>(&self, dst: &mut W) -> Result<()> {
+        write_u16(dst, self.version)?;
+        save_array(dst, &self.points, |dst, p| Ok(p.save(dst)?))?;
+        write_f32(dst, self.radius)?;
+        self.radius_offset.save(dst)?;
+        write_bool(dst, self.zone_is_ccw)?;
+        Ok(())
+    }
+}
+
+/// One end of a [`NavPath`]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NavPathLink {
+    /// The gCNavZone_PS entity
+    pub zone: EntityProxy,
+    pub intersection_center: Vec3,
+    pub intersection_margin1: Vec3,
+    pub intersection_margin2: Vec3,
+}
+
+impl NavPathLink {
+    fn load(src: &mut PakFile) -> Result<Self> {
+        Ok(Self {
+            zone: EntityProxy::load(src, true)?,
+            intersection_center: Vec3::load(src)?,
+            intersection_margin1: Vec3::load(src)?,
+            intersection_margin2: Vec3::load(src)?,
+        })
+    }
+
+    fn save<W: ArchiveWriteTarget>(&self, dst: &mut W) -> Result<()> {
+        self.zone.save(dst, true)?;
+        self.intersection_center.save(dst)?;
+        self.intersection_margin1.save(dst)?;
+        self.intersection_margin2.save(dst)?;
+        Ok(())
+    }
+}
+
+/// gCNavPath_PS: a path with a radius per point, connecting two zones
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NavPath {
+    pub version: u16,
+    /// Path points, relative to the entity
+    pub points: Vec<Vec3>,
+    /// Walkable radius around each point
+    pub radius: Vec<f32>,
+    pub zone_a: NavPathLink,
+    pub zone_b: NavPathLink,
+}
+
+impl NavPath {
+    /// Returns `None` (and leaves `src` untouched) if the data doesn't match the expected layout
+    pub fn try_load(src: &mut PakFile, len: usize) -> Option<Self> {
+        try_load(src, len, "gCNavPath_PS", |src, end| {
+            Ok(Self {
+                version: read_u16(src)?,
+                points: load_array(src, end, 12, |src| Ok(Vec3::load(src)?))?,
+                radius: load_array(src, end, 4, |src| Ok(read_f32(src)?))?,
+                zone_a: NavPathLink::load(src)?,
+                zone_b: NavPathLink::load(src)?,
+            })
+        })
+    }
+
+    pub fn save<W: ArchiveWriteTarget>(&self, dst: &mut W) -> Result<()> {
+        write_u16(dst, self.version)?;
+        save_array(dst, &self.points, |dst, p| Ok(p.save(dst)?))?;
+        save_array(dst, &self.radius, |dst, r| Ok(write_f32(dst, *r)?))?;
+        self.zone_a.save(dst)?;
+        self.zone_b.save(dst)?;
+        Ok(())
+    }
+}
+
+/// bCMotion
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct Motion {
+    pub position: Vec3,
+    pub rotation: Quat,
+}
+
+/// gCNavOffset_PS: positions (relative to the entity) NPCs move to when using it
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NavOffset {
+    pub version: u16,
+    pub offset_pose: Vec<Motion>,
+    pub ani_direction: Vec<Direction>,
+    pub offset_circle: bool,
+}
+
+impl NavOffset {
+    /// Returns `None` (and leaves `src` untouched) if the data doesn't match the expected layout
+    pub fn try_load(src: &mut PakFile, len: usize) -> Option<Self> {
+        try_load(src, len, "gCNavOffset_PS", |src, end| {
+            Ok(Self {
+                version: read_u16(src)?,
+                offset_pose: load_array(src, end, 28, |src| {
+                    Ok(Motion {
+                        position: Vec3::load(src)?,
+                        rotation: Quat::load(src)?,
+                    })
+                })?,
+                ani_direction: load_array(src, end, 4, |src| {
+                    Ok(Direction::try_from(read_u32(src)?)?)
+                })?,
+                offset_circle: read_bool(src)?,
+            })
+        })
+    }
+
+    pub fn save<W: ArchiveWriteTarget>(&self, dst: &mut W) -> Result<()> {
+        write_u16(dst, self.version)?;
+        save_array(dst, &self.offset_pose, |dst, m| {
+            m.position.save(dst)?;
+            Ok(m.rotation.save(dst)?)
+        })?;
+        save_array(dst, &self.ani_direction, |dst, d| {
+            Ok(write_u32(dst, (*d).into())?)
+        })?;
+        write_bool(dst, self.offset_circle)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::super::PropertyId;
+    use super::*;
+
+    fn link(id: u128) -> NavPathLink {
+        NavPathLink {
+            zone: EntityProxy {
+                version: 1,
+                id: Some(PropertyId {
+                    id: Uuid::from_u128(id),
+                    unknown: 0,
+                }),
+            },
+            intersection_center: Vec3::new(1.0, 2.0, 3.0),
+            intersection_margin1: Vec3::new(-50.0, 0.0, 0.0),
+            intersection_margin2: Vec3::new(50.0, 0.0, 0.0),
+        }
+    }
+
+    /// Saves `value`, checks that it loads back from exactly the saved bytes,
+    /// and that neither a truncated nor an extended class is accepted
+    fn round_trip<T: PartialEq + std::fmt::Debug>(
+        value: T,
+        save: impl Fn(&T, &mut PakFile) -> Result<()>,
+        load: impl Fn(&mut PakFile, usize) -> Option<T>,
+    ) {
+        let mut pak = PakFile::new();
+        save(&value, &mut pak).unwrap();
+        let len = pak.data.len();
+        assert_eq!(load(&mut pak, len).as_ref(), Some(&value));
+        assert_eq!(pak.current_read_idx, len);
+
+        pak.current_read_idx = 0;
+        assert_eq!(load(&mut pak, len - 1), None);
+        assert_eq!(pak.current_read_idx, 0);
+
+        pak.data.push(0);
+        assert_eq!(load(&mut pak, len + 1), None);
+        assert_eq!(pak.current_read_idx, 0);
+
+        // the first array count comes after the version:
+        pak.data[2..6].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(load(&mut pak, len), None);
+    }
+
+    #[test]
+    fn nav_zone_round_trip() {
+        let zone = NavZone {
+            version: 1,
+            points: vec![
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(500.0, 0.0, 0.0),
+                Vec3::new(500.0, 0.0, 800.0),
+            ],
+            radius: 420.5,
+            radius_offset: Vec3::new(250.0, 0.0, 400.0),
+            zone_is_ccw: true,
+        };
+        round_trip(zone, |z, pak| z.save(pak), NavZone::try_load);
+    }
+
+    #[test]
+    fn nav_path_round_trip() {
+        let path = NavPath {
+            version: 1,
+            points: vec![Vec3::new(0.0, 10.0, 0.0), Vec3::new(0.0, 12.0, 300.0)],
+            radius: vec![75.0, 120.0],
+            zone_a: link(1),
+            zone_b: link(2),
+        };
+        round_trip(path, |p, pak| p.save(pak), NavPath::try_load);
+    }
+
+    #[test]
+    fn nav_offset_round_trip() {
+        let offset = NavOffset {
+            version: 1,
+            offset_pose: vec![Motion {
+                position: Vec3::new(0.0, 0.0, 80.0),
+                rotation: Quat::from_rotation_y(1.5),
+            }],
+            ani_direction: vec![Direction::Owner],
+            offset_circle: false,
+        };
+        round_trip(offset, |o, pak| o.save(pak), NavOffset::try_load);
+    }
+}
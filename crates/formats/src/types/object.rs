@@ -7,7 +7,7 @@ use crate::archive::*;
 use crate::error::*;
 use crate::helpers::*;
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 /// bCObjectBase
 pub struct Object {
     pub version: u16,
@@ -75,6 +75,25 @@ impl Object {
         self.class.get_class_name()
     }
 
+    pub fn get_property(&self, name: &str) -> Option<&properties::PropData> {
+        self.props
+            .iter()
+            .find(|prop| prop.name == name)
+            .map(|prop| &*prop.data)
+    }
+
+    /// Replaces the value of property `name`, adding it if it does not exist yet
+    pub fn set_property(&mut self, name: &str, data: properties::PropData) {
+        match self.props.iter_mut().find(|prop| prop.name == name) {
+            Some(prop) => *prop.data = data,
+            None => self.props.push(properties::Property {
+                name: name.to_string(),
+                version: 30,
+                data: Box::new(data),
+            }),
+        }
+    }
+
     pub fn save<W: ArchiveWriteTarget>(&self, dst: &mut W) -> Result<()> {
         write_u16(dst, self.version)?;
 
@@ -98,7 +117,7 @@ impl Object {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 /// bCAccessorPropertyObject
 pub struct AccessorPropertyObject {
     pub version: u16,
@@ -11,7 +11,7 @@ use crate::error::*;
 use crate::helpers::*;
 
 /// bCProperty
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Property {
     pub name: String,
     /// always 30 for R1
@@ -19,68 +19,126 @@ pub struct Property {
     pub data: Box<PropData>,
 }
 
+/// Fails instead of panicking on unexpected sizes, so that callers trying a layout can fall back
+fn check_len(ty: &str, data_len: usize, expected: usize) -> Result<()> {
+    if data_len != expected {
+        return Err(Error::InvalidStructure(format!(
+            "{ty} property has {data_len} bytes, expected {expected}"
+        )));
+    }
+    Ok(())
+}
+
 impl Property {
+    /// Like [`Property::load`], but fails instead of reading past `end`
+    pub(crate) fn load_bounded(src: &mut PakFile, end: usize) -> Result<Self> {
+        // name, type and version come before the data length
+        const HEADER_LEN: usize = 2 + 2 + 2 + 4;
+        let start = src.current_read_idx;
+        let end = end.min(src.data.len());
+        if start + HEADER_LEN > end {
+            return Err(Error::InvalidStructure(
+                "Property header exceeds its object".to_string(),
+            ));
+        }
+        src.current_read_idx += HEADER_LEN - 4;
+        let data_len = read_u32(src)? as usize;
+        src.current_read_idx = start;
+        let prop_end = start + HEADER_LEN + data_len;
+        if prop_end > end {
+            return Err(Error::InvalidStructure(format!(
+                "Property of {data_len} bytes exceeds its object"
+            )));
+        }
+        let prop = Self::load(src)?;
+        if src.current_read_idx != prop_end {
+            return Err(Error::InvalidStructure(format!(
+                "Property {} doesn't match its size of {data_len} bytes",
+                prop.name
+            )));
+        }
+        Ok(prop)
+    }
+
     pub fn load<R: ArchiveReadTarget>(src: &mut R) -> Result<Self> {
         let name = src.read_str()?.to_string();
         let ty = src.read_str()?.to_string();
         let version = read_u16(src)?;
-        assert_eq!(version, 30);
+        if version != 30 {
+            return Err(Error::InvalidStructure(format!(
+                "Property {name} has version {version}, expected 30"
+            )));
+        }
         let data_len = read_u32(src)? as usize;
         let data = Box::new(match ty.as_str() {
             "int" => {
-                assert_eq!(data_len, 4);
+                check_len(&ty, data_len, 4)?;
                 PropData::Int(read_i32(src)?)
             }
             "bool" => {
-                assert_eq!(data_len, 1);
+                check_len(&ty, data_len, 1)?;
                 PropData::Bool(read_u8(src)? != 0)
             }
             "short" => {
-                assert_eq!(data_len, 2);
+                check_len(&ty, data_len, 2)?;
                 PropData::Short(read_i16(src)?)
             }
             "float" => {
-                assert_eq!(data_len, 4);
+                check_len(&ty, data_len, 4)?;
                 PropData::Float(read_f32(src)?)
             }
             "long" => {
-                assert_eq!(data_len, 4);
+                check_len(&ty, data_len, 4)?;
                 PropData::Long(read_u32(src)?)
             }
             "char" => {
-                assert_eq!(data_len, 1);
+                check_len(&ty, data_len, 1)?;
                 PropData::Char(read_u8(src)?)
             }
             "bCVector2" => {
-                assert_eq!(data_len, 2 * 4);
+                check_len(&ty, data_len, 2 * 4)?;
                 PropData::Vector2(Vec2::load(src)?)
             }
             "bCVector" => {
-                assert_eq!(data_len, 3 * 4);
+                check_len(&ty, data_len, 3 * 4)?;
                 PropData::Vector3(Vec3::load(src)?)
             }
             "bCVector4" => {
-                assert_eq!(data_len, 4 * 4);
+                check_len(&ty, data_len, 4 * 4)?;
                 PropData::Vector4(Vec4::load(src)?)
             }
             "bCMatrix" => {
-                assert_eq!(data_len, 4 * 4 * 4);
+                check_len(&ty, data_len, 4 * 4 * 4)?;
                 PropData::Matrix(Mat4::load(src)?)
             }
             "bCQuaternion" => {
-                assert_eq!(data_len, 4 * 4);
+                check_len(&ty, data_len, 4 * 4)?;
                 PropData::Quaternion(Quat::load(src)?)
             }
             "bCGuid" => {
-                assert_eq!(data_len, 16 + 4);
+                check_len(&ty, data_len, 16 + 4)?;
                 PropData::Guid(PropertyId::load(src)?)
             }
             "bCString" => {
-                assert_eq!(data_len, 2);
+                check_len(&ty, data_len, 2)?;
                 PropData::String(src.read_str()?.to_string())
             }
+            "bCImageOrMaterialResourceString"
+            | "bCImageResourceString"
+            | "bCMeshResourceString"
+            | "bCSpeedTreeResourceString"
+            | "eCLocString"
+            | "eCTipLocString"
+            | "gCBookLocString"
+            | "gCInfoLocString"
+            | "gCLetterLocString"
+            | "gCNPCInfoLocString"
+            | "gCQuestLocString" => {
+                check_len(&ty, data_len, 2)?;
+                PropData::from_string_type(&ty, src.read_str()?.to_string())
+            }
             "bCFloatColor" => {
-                assert_eq!(data_len, 16);
+                check_len(&ty, data_len, 16)?;
                 PropData::FloatColor {
                     unknown: read_u32(src)?,
                     r: read_f32(src)?,
@@ -89,15 +147,21 @@ impl Property {
                 }
             }
             "bCBox" => {
-                assert_eq!(data_len, 6 * 4);
+                check_len(&ty, data_len, 6 * 4)?;
                 PropData::BoundingBox(BoundingBox::load(src)?)
             }
             "eCEntityProxy" => {
-                assert!(data_len == 3 || data_len == 3 + 20); //invalid or valid
+                if data_len != 3 {
+                    //invalid or valid
+                    check_len(&ty, data_len, 3 + 20)?;
+                }
                 PropData::EntityProxy(EntityProxy::load(src, true)?)
             }
             "eCTemplateEntityProxy" => {
-                assert!(data_len == 3 || data_len == 3 + 20); //invalid or valid
+                if data_len != 3 {
+                    //invalid or valid
+                    check_len(&ty, data_len, 3 + 20)?;
+                }
                 PropData::TemplateEntityProxy(EntityProxy::load(src, true)?)
             }
             "gEDirection"
@@ -283,12 +347,12 @@ impl Property {
             | "gCQuickPicbox2_gEViewMode"
             | "gEWalkMode"
             | "gEWrittenType" => {
-                assert_eq!(data_len, 6); //enum header u16 + enum u32
+                check_len(&ty, data_len, 6)?; //enum header u16 + enum u32
                 PropData::Enum(Self::load_enum(src, ty.as_str(), true)?)
             }
             "eCScriptProxyScript" => PropData::ScriptProxyScript(ScriptProxyScript::load(src)?),
             _ if ty.starts_with("bTPropertyContainer<enum ") => {
-                assert_eq!(data_len, 6); //enum header u16 + enum u32
+                check_len(&ty, data_len, 6)?; //enum header u16 + enum u32
                 const NAME_START: usize = "bTPropertyContainer<enum ".len();
                 let name_end = ty.len() - ">".len();
                 PropData::ContainerEnum(Self::load_enum(src, &ty[NAME_START..name_end], true)?)
@@ -366,17 +430,50 @@ impl Property {
                 block.write_str(str)?;
                 "bCString"
             }
-            PropData::ImageOrMaterialResourceString(_) => todo!(),
-            PropData::LocString(_) => todo!(),
-            PropData::ImageResourceString(_) => todo!(),
-            PropData::LetterLocString(_) => todo!(),
-            PropData::SpeedTreeResourceString(_) => todo!(),
-            PropData::BookLocString(_) => todo!(),
-            PropData::MeshResourceString(_) => todo!(),
-            PropData::InfoLocString(_) => todo!(),
-            PropData::QuestLocString(_) => todo!(),
-            PropData::TipLocString(_) => todo!(),
-            PropData::NPCInfoLocString(_) => todo!(),
+            PropData::ImageOrMaterialResourceString(str) => {
+                block.write_str(str)?;
+                "bCImageOrMaterialResourceString"
+            }
+            PropData::LocString(str) => {
+                block.write_str(str)?;
+                "eCLocString"
+            }
+            PropData::ImageResourceString(str) => {
+                block.write_str(str)?;
+                "bCImageResourceString"
+            }
+            PropData::LetterLocString(str) => {
+                block.write_str(str)?;
+                "gCLetterLocString"
+            }
+            PropData::SpeedTreeResourceString(str) => {
+                block.write_str(str)?;
+                "bCSpeedTreeResourceString"
+            }
+            PropData::BookLocString(str) => {
+                block.write_str(str)?;
+                "gCBookLocString"
+            }
+            PropData::MeshResourceString(str) => {
+                block.write_str(str)?;
+                "bCMeshResourceString"
+            }
+            PropData::InfoLocString(str) => {
+                block.write_str(str)?;
+                "gCInfoLocString"
+            }
+            PropData::QuestLocString(str) => {
+                block.write_str(str)?;
+                "gCQuestLocString"
+            }
+            PropData::TipLocString(str) => {
+                block.write_str(str)?;
+                "eCTipLocString"
+            }
+            PropData::NPCInfoLocString(str) => {
+                block.write_str(str)?;
+                "gCNPCInfoLocString"
+            }
             PropData::FloatColor { unknown, r, g, b } => {
                 write_u32(&mut block, *unknown)?;
                 write_f32(&mut block, *r)?;
@@ -435,7 +532,11 @@ impl Property {
     ) -> Result<PropEnum> {
         if read_header {
             let head = read_u16(src)?;
-            assert_eq!(head, 201);
+            if head != 201 {
+                return Err(Error::InvalidStructure(format!(
+                    "Enum {enum_name} has header {head}, expected 201"
+                )));
+            }
         }
         let discriminant = read_u32(src)?;
         Ok(match enum_name {
@@ -1093,7 +1194,7 @@ impl Property {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum PropData {
     Int(i32),
@@ -1135,7 +1236,93 @@ pub enum PropData {
     ScriptProxyScript(ScriptProxyScript),
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl PropData {
+    /// Wraps a string in the variant for the resource or localized string type `ty`
+    fn from_string_type(ty: &str, s: String) -> Self {
+        match ty {
+            "bCImageOrMaterialResourceString" => PropData::ImageOrMaterialResourceString(s),
+            "bCImageResourceString" => PropData::ImageResourceString(s),
+            "bCMeshResourceString" => PropData::MeshResourceString(s),
+            "bCSpeedTreeResourceString" => PropData::SpeedTreeResourceString(s),
+            "eCLocString" => PropData::LocString(s),
+            "eCTipLocString" => PropData::TipLocString(s),
+            "gCBookLocString" => PropData::BookLocString(s),
+            "gCInfoLocString" => PropData::InfoLocString(s),
+            "gCLetterLocString" => PropData::LetterLocString(s),
+            "gCNPCInfoLocString" => PropData::NPCInfoLocString(s),
+            "gCQuestLocString" => PropData::QuestLocString(s),
+            _ => PropData::String(s),
+        }
+    }
+
+    /// Numeric value of scalar properties
+    pub fn as_number(&self) -> Option<f64> {
+        Some(match self {
+            PropData::Int(v) => *v as f64,
+            PropData::Short(v) => *v as f64,
+            PropData::Float(v) => *v as f64,
+            PropData::Long(v) => *v as f64,
+            PropData::Char(v) => *v as f64,
+            PropData::Bool(v) => *v as u8 as f64,
+            _ => return None,
+        })
+    }
+
+    /// Text of the localized string kinds (`eCLocString`, `gCInfoLocString`, ...)
+    pub fn as_loc_string(&self) -> Option<&String> {
+        match self {
+            PropData::LocString(s)
+            | PropData::LetterLocString(s)
+            | PropData::BookLocString(s)
+            | PropData::InfoLocString(s)
+            | PropData::QuestLocString(s)
+            | PropData::TipLocString(s)
+            | PropData::NPCInfoLocString(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_loc_string_mut(&mut self) -> Option<&mut String> {
+        match self {
+            PropData::LocString(s)
+            | PropData::LetterLocString(s)
+            | PropData::BookLocString(s)
+            | PropData::InfoLocString(s)
+            | PropData::QuestLocString(s)
+            | PropData::TipLocString(s)
+            | PropData::NPCInfoLocString(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Textual value of string, GUID and enum properties
+    pub fn as_text(&self) -> Option<String> {
+        Some(match self {
+            PropData::String(s)
+            | PropData::ImageOrMaterialResourceString(s)
+            | PropData::LocString(s)
+            | PropData::ImageResourceString(s)
+            | PropData::LetterLocString(s)
+            | PropData::SpeedTreeResourceString(s)
+            | PropData::BookLocString(s)
+            | PropData::MeshResourceString(s)
+            | PropData::InfoLocString(s)
+            | PropData::QuestLocString(s)
+            | PropData::TipLocString(s)
+            | PropData::NPCInfoLocString(s) => s.clone(),
+            PropData::Guid(id) => id.id.to_string(),
+            PropData::EntityProxy(proxy) | PropData::TemplateEntityProxy(proxy) => {
+                proxy.id.as_ref()?.id.to_string()
+            }
+            PropData::Enum(e) | PropData::ContainerEnum(e) => e.value_name(),
+            PropData::ScriptProxyScript(script) => script.name.clone()?,
+            PropData::Bool(v) => v.to_string(),
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct PropBuffer {
     #[serde(rename = "type")]
     pub ty: String,
@@ -1143,202 +1330,234 @@ pub struct PropBuffer {
     pub data: Vec<u8>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(rename_all = "snake_case")]
-pub enum PropEnum {
-    Direction(enums::Direction),
-    NoiseTurbulence(enums::NoiseTurbulence),
-    ImageFilterRTColorFormat(enums::ImageFilterRTColorFormat),
-    ImageResource2ColorFormat(enums::ImageResource2ColorFormat),
-    AnchorMode(enums::AnchorMode),
-    AudioChannelFallOff(enums::AudioChannelFallOff),
-    AudioChannelGroup(enums::AudioChannelGroup),
-    AudioEmitterMode(enums::AudioEmitterMode),
-    AudioEmitterShape(enums::AudioEmitterShape),
-    BillboardTargetMode(enums::BillboardTargetMode),
-    BoolOverwrite(enums::BoolOverwrite),
-    RadioCheckState(enums::RadioCheckState),
-    CheckBoxCheckState(enums::CheckBoxCheckState),
-    CollisionGroup(enums::CollisionGroup),
-    CollisionShapeType(enums::CollisionShapeType),
-    ColorSrcCombinerType(enums::ColorSrcCombinerType),
-    ColorSrcSampleTexRepeat(enums::ColorSrcSampleTexRepeat),
-    ColorSrcSwitchRepeat(enums::ColorSrcSwitchRepeat),
-    CoordinateSystem(enums::CoordinateSystem),
-    DistanceType(enums::DistanceType),
-    Dock(enums::Dock),
-    DynamicLightEffect(enums::DynamicLightEffect),
-    FacingDirection(enums::FacingDirection),
-    FresnelTerm(enums::FresnelTerm),
-    GuiCursorSize(enums::GuiCursorSize),
-    IFOutputMode(enums::IFOutputMode),
-    IFSizeMode(enums::IFSizeMode),
-    IFTextureMode(enums::IFTextureMode),
-    ImageBlend(enums::ImageBlend),
-    ImageLayerBlend(enums::ImageLayerBlend),
-    LightingStyle(enums::LightingStyle),
-    ListView(enums::ListView),
-    ListViewAlign(enums::ListViewAlign),
-    ListViewIconSize(enums::ListViewIconSize),
-    ListViewItemLayout(enums::ListViewItemLayout),
-    ListViewTileSize(enums::ListViewTileSize),
-    LocationShape(enums::LocationShape),
-    LocationTarget(enums::LocationTarget),
-    MoverPlayBackMode(enums::MoverPlayBackMode),
-    OverlayMode(enums::OverlayMode),
-    PhysicRangeType(enums::PhysicRangeType),
-    PictureMode(enums::PictureMode),
-    PropertySetType(enums::PropertySetType),
-    ReflectType(enums::ReflectType),
-    RigidbodyFlag(enums::RigidbodyFlag),
-    RotationFrom(enums::RotationFrom),
-    ShaderMaterialBRDFType(enums::ShaderMaterialBRDFType),
-    ShaderMaterialBlendMode(enums::ShaderMaterialBlendMode),
-    ShaderMaterialTransformation(enums::ShaderMaterialTransformation),
-    ShaderMaterialVersion(enums::ShaderMaterialVersion),
-    ShadowCasterType(enums::ShadowCasterType),
-    ShadowMaskIndex(enums::ShadowMaskIndex),
-    ShapeAABBAdapt(enums::ShapeAABBAdapt),
-    ShapeGroup(enums::ShapeGroup),
-    MaterialResource2ShapeMaterial(enums::MaterialResource2ShapeMaterial),
-    ShapeMaterial(enums::ShapeMaterial),
-    SplitImageStyle(enums::SplitImageStyle),
-    StaticIlluminated(enums::StaticIlluminated),
-    StripSpawning(enums::StripSpawning),
-    TexCoordSrcOscillatorType(enums::TexCoordSrcOscillatorType),
-    TexCoordSrcRotatorType(enums::TexCoordSrcRotatorType),
-    TextAlign(enums::TextAlign),
-    TextureDrawStyle(enums::TextureDrawStyle),
-    TicSide(enums::TicSide),
-    VegetationBrushColorFunction(enums::VegetationBrushColorFunction),
-    VegetationBrushMode(enums::VegetationBrushMode),
-    VegetationBrushPlace(enums::VegetationBrushPlace),
-    VegetationBrushProbabilityFunction(enums::VegetationBrushProbabilityFunction),
-    VegetationBrushShape(enums::VegetationBrushShape),
-    VegetationMeshShading(enums::VegetationMeshShading),
-    VelocityDirectionFrom(enums::VelocityDirectionFrom),
-    WeatherZoneOverwrite(enums::WeatherZoneOverwrite),
-    WeatherZoneShape(enums::WeatherZoneShape),
-    AIMode(enums::AIMode),
-    AchievementViewMode(enums::AchievementViewMode),
-    AlignToTarget(enums::AlignToTarget),
-    AmbientAction(enums::AmbientAction),
-    AmountType(enums::AmountType),
-    AnchorType(enums::AnchorType),
-    AniState(enums::AniState),
-    CombatMoveStumbleAniState(enums::CombatMoveStumbleAniState),
-    ArenaStatus(enums::ArenaStatus),
-    Attitude(enums::Attitude),
-    BoostTarget(enums::BoostTarget),
-    BraveryOverride(enums::BraveryOverride),
-    MeleeCombatAction(enums::MeleeCombatAction),
-    MoveCombatAction(enums::MoveCombatAction),
-    CombatAttackStumble(enums::CombatAttackStumble),
-    CombatComboParade(enums::CombatComboParade),
-    CombatFightAIMode(enums::CombatFightAIMode),
-    CombatHitDirection(enums::CombatHitDirection),
-    CombatMode(enums::CombatMode),
-    MeleeCombatMove(enums::MeleeCombatMove),
-    Melee2CombatMove(enums::Melee2CombatMove),
-    CombatMoveSide(enums::CombatMoveSide),
-    CombatParadeType(enums::CombatParadeType),
-    CombatPhaseType(enums::CombatPhaseType),
-    CombatPose(enums::CombatPose),
-    Comment(enums::Comment),
-    CompareOperation(enums::CompareOperation),
-    Crime(enums::Crime),
-    DamageCalculationType(enums::DamageCalculationType),
-    DamageType(enums::DamageType),
-    DoorStatus(enums::DoorStatus),
-    EffectDecayMode(enums::EffectDecayMode),
-    EffectKillRange(enums::EffectKillRange),
-    EffectLink(enums::EffectLink),
-    EffectLoopMode(enums::EffectLoopMode),
-    EffectScriptOtherType(enums::EffectScriptOtherType),
-    EffectScriptParamType(enums::EffectScriptParamType),
-    EffectStopMode(enums::EffectStopMode),
-    EffectTargetMode(enums::EffectTargetMode),
-    EntityType(enums::EntityType),
-    Picbox2EquipSlot(enums::Picbox2EquipSlot),
-    EquipSlot(enums::EquipSlot),
-    Fight(enums::Fight),
-    FlightPathType(enums::FlightPathType),
-    FocusNameType(enums::FocusNameType),
-    FocusPriority(enums::FocusPriority),
-    FocusSource(enums::FocusSource),
-    GUIFilterType(enums::GUIFilterType),
-    GammaRamp(enums::GammaRamp),
-    Gender(enums::Gender),
-    GuardStatus(enums::GuardStatus),
-    NpcGuardStatus(enums::NpcGuardStatus),
-    Guild(enums::Guild),
-    NpcGuild(enums::NpcGuild),
-    HitDirection(enums::HitDirection),
-    HudPage(enums::HudPage),
-    HudPageProgessBar(enums::HudPageProgessBar),
-    Icon(enums::Icon),
-    InfoCondType(enums::InfoCondType),
-    QuestInfoCondType(enums::QuestInfoCondType),
-    InfoGesture(enums::InfoGesture),
-    InfoLocation(enums::InfoLocation),
-    InfoNPCStatus(enums::InfoNPCStatus),
-    InfoNPCType(enums::InfoNPCType),
-    InfoType(enums::InfoType),
-    InfoView(enums::InfoView),
-    SkillInfoView(enums::SkillInfoView),
-    DialogInfoView(enums::DialogInfoView),
-    HintInfoView(enums::HintInfoView),
-    InteractionType(enums::InteractionType),
-    SpecialInteractionUseType(enums::SpecialInteractionUseType),
-    InteractionUseType(enums::InteractionUseType),
-    ItemCategory(enums::ItemCategory),
-    ItemHoldType(enums::ItemHoldType),
-    ItemUseType(enums::ItemUseType),
-    WeaponItemUseType(enums::WeaponItemUseType),
-    LockStatus(enums::LockStatus),
-    MiscInfo(enums::MiscInfo),
-    ProgressBarMiscInfo(enums::ProgressBarMiscInfo),
-    MouseAxis(enums::MouseAxis),
-    NavObstacleType(enums::NavObstacleType),
-    NavTestResult(enums::NavTestResult),
-    OtherType(enums::OtherType),
-    LootPageMode(enums::LootPageMode),
-    HudPageMode(enums::HudPageMode),
-    PageMode(enums::PageMode),
-    PaintArea(enums::PaintArea),
-    PartyMemberType(enums::PartyMemberType),
-    QuestActor(enums::QuestActor),
-    QuestStatus(enums::QuestStatus),
-    QuestType(enums::QuestType),
-    QuickSlot(enums::QuickSlot),
-    Reason(enums::Reason),
-    RecipeCategory(enums::RecipeCategory),
-    ScrollStart(enums::ScrollStart),
-    SessionState(enums::SessionState),
-    Skill(enums::Skill),
-    SkillValueBaseSkill(enums::SkillValueBaseSkill),
-    ProgressBarSkill(enums::ProgressBarSkill),
-    SkillModifier(enums::SkillModifier),
-    SpecialEntity(enums::SpecialEntity),
-    Species(enums::Species),
-    SpinButtonType(enums::SpinButtonType),
-    StackType(enums::StackType),
-    StateGraphEventType(enums::StateGraphEventType),
-    ViewMode(enums::ViewMode),
-    EquipViewMode(enums::EquipViewMode),
-    QuickViewMode(enums::QuickViewMode),
-    WalkMode(enums::WalkMode),
-    WrittenType(enums::WrittenType),
-    Unknown(UnknownEnum),
+/// Defines [`PropEnum`] with a variant for each of the given [`enums`], named like the enum,
+/// and the conversions from/to value names, which need a match over all of them.
+/// The value names are derived from the enum definitions (the same as used by serde).
+macro_rules! prop_enum {
+    ($($name:ident),* $(,)?) => {
+        #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+        #[serde(rename_all = "snake_case")]
+        pub enum PropEnum {
+            $($name(enums::$name),)*
+            Unknown(UnknownEnum),
+        }
+
+        impl PropEnum {
+            /// Name of the contained value, e.g. `Left` for `Direction(Left)`
+            pub fn value_name(&self) -> String {
+                match self {
+                    $(PropEnum::$name(value) => <&'static str>::from(value).to_string(),)*
+                    PropEnum::Unknown(unknown) => unknown.val.to_string(),
+                }
+            }
+
+            /// A value of the same enum type, parsed from its name (as returned by [`Self::value_name`])
+            pub fn with_value_name(&self, name: &str) -> Option<PropEnum> {
+                Some(match self {
+                    $(PropEnum::$name(_) => PropEnum::$name(name.parse().ok()?),)*
+                    PropEnum::Unknown(unknown) => PropEnum::Unknown(UnknownEnum {
+                        name: unknown.name.clone(),
+                        val: name.parse().ok()?,
+                    }),
+                })
+            }
+        }
+    };
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+prop_enum!(
+    Direction,
+    NoiseTurbulence,
+    ImageFilterRTColorFormat,
+    ImageResource2ColorFormat,
+    AnchorMode,
+    AudioChannelFallOff,
+    AudioChannelGroup,
+    AudioEmitterMode,
+    AudioEmitterShape,
+    BillboardTargetMode,
+    BoolOverwrite,
+    RadioCheckState,
+    CheckBoxCheckState,
+    CollisionGroup,
+    CollisionShapeType,
+    ColorSrcCombinerType,
+    ColorSrcSampleTexRepeat,
+    ColorSrcSwitchRepeat,
+    CoordinateSystem,
+    DistanceType,
+    Dock,
+    DynamicLightEffect,
+    FacingDirection,
+    FresnelTerm,
+    GuiCursorSize,
+    IFOutputMode,
+    IFSizeMode,
+    IFTextureMode,
+    ImageBlend,
+    ImageLayerBlend,
+    LightingStyle,
+    ListView,
+    ListViewAlign,
+    ListViewIconSize,
+    ListViewItemLayout,
+    ListViewTileSize,
+    LocationShape,
+    LocationTarget,
+    MoverPlayBackMode,
+    OverlayMode,
+    PhysicRangeType,
+    PictureMode,
+    PropertySetType,
+    ReflectType,
+    RigidbodyFlag,
+    RotationFrom,
+    ShaderMaterialBRDFType,
+    ShaderMaterialBlendMode,
+    ShaderMaterialTransformation,
+    ShaderMaterialVersion,
+    ShadowCasterType,
+    ShadowMaskIndex,
+    ShapeAABBAdapt,
+    ShapeGroup,
+    MaterialResource2ShapeMaterial,
+    ShapeMaterial,
+    SplitImageStyle,
+    StaticIlluminated,
+    StripSpawning,
+    TexCoordSrcOscillatorType,
+    TexCoordSrcRotatorType,
+    TextAlign,
+    TextureDrawStyle,
+    TicSide,
+    VegetationBrushColorFunction,
+    VegetationBrushMode,
+    VegetationBrushPlace,
+    VegetationBrushProbabilityFunction,
+    VegetationBrushShape,
+    VegetationMeshShading,
+    VelocityDirectionFrom,
+    WeatherZoneOverwrite,
+    WeatherZoneShape,
+    AIMode,
+    AchievementViewMode,
+    AlignToTarget,
+    AmbientAction,
+    AmountType,
+    AnchorType,
+    AniState,
+    CombatMoveStumbleAniState,
+    ArenaStatus,
+    Attitude,
+    BoostTarget,
+    BraveryOverride,
+    MeleeCombatAction,
+    MoveCombatAction,
+    CombatAttackStumble,
+    CombatComboParade,
+    CombatFightAIMode,
+    CombatHitDirection,
+    CombatMode,
+    MeleeCombatMove,
+    Melee2CombatMove,
+    CombatMoveSide,
+    CombatParadeType,
+    CombatPhaseType,
+    CombatPose,
+    Comment,
+    CompareOperation,
+    Crime,
+    DamageCalculationType,
+    DamageType,
+    DoorStatus,
+    EffectDecayMode,
+    EffectKillRange,
+    EffectLink,
+    EffectLoopMode,
+    EffectScriptOtherType,
+    EffectScriptParamType,
+    EffectStopMode,
+    EffectTargetMode,
+    EntityType,
+    Picbox2EquipSlot,
+    EquipSlot,
+    Fight,
+    FlightPathType,
+    FocusNameType,
+    FocusPriority,
+    FocusSource,
+    GUIFilterType,
+    GammaRamp,
+    Gender,
+    GuardStatus,
+    NpcGuardStatus,
+    Guild,
+    NpcGuild,
+    HitDirection,
+    HudPage,
+    HudPageProgessBar,
+    Icon,
+    InfoCondType,
+    QuestInfoCondType,
+    InfoGesture,
+    InfoLocation,
+    InfoNPCStatus,
+    InfoNPCType,
+    InfoType,
+    InfoView,
+    SkillInfoView,
+    DialogInfoView,
+    HintInfoView,
+    InteractionType,
+    SpecialInteractionUseType,
+    InteractionUseType,
+    ItemCategory,
+    ItemHoldType,
+    ItemUseType,
+    WeaponItemUseType,
+    LockStatus,
+    MiscInfo,
+    ProgressBarMiscInfo,
+    MouseAxis,
+    NavObstacleType,
+    NavTestResult,
+    OtherType,
+    LootPageMode,
+    HudPageMode,
+    PageMode,
+    PaintArea,
+    PartyMemberType,
+    QuestActor,
+    QuestStatus,
+    QuestType,
+    QuickSlot,
+    Reason,
+    RecipeCategory,
+    ScrollStart,
+    SessionState,
+    Skill,
+    SkillValueBaseSkill,
+    ProgressBarSkill,
+    SkillModifier,
+    SpecialEntity,
+    Species,
+    SpinButtonType,
+    StackType,
+    StateGraphEventType,
+    ViewMode,
+    EquipViewMode,
+    QuickViewMode,
+    WalkMode,
+    WrittenType,
+);
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct UnknownEnum {
     pub name: String,
     pub val: u32,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct ScriptProxyScript {
     pub version: u16,
     pub name: Option<String>,
@@ -1361,3 +1580,45 @@ impl ArchiveSerializable for ScriptProxyScript {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde::de::{IntoDeserializer, value::Error as DeError};
+
+    use super::*;
+
+    #[test]
+    fn value_names_round_trip() {
+        for value in [
+            PropEnum::Direction(enums::Direction::Owner),
+            PropEnum::QuickSlot(enums::QuickSlot::_3),
+            PropEnum::BillboardTargetMode(enums::BillboardTargetMode::_Self),
+            PropEnum::Unknown(UnknownEnum {
+                name: "gEUnknown".to_string(),
+                val: 7,
+            }),
+        ] {
+            assert_eq!(value.with_value_name(&value.value_name()), Some(value));
+        }
+    }
+
+    #[test]
+    fn value_names_match_serde() {
+        let name = PropEnum::QuickSlot(enums::QuickSlot::_3).value_name();
+        assert_eq!(name, "3");
+        let parsed = enums::QuickSlot::deserialize(IntoDeserializer::<DeError>::into_deserializer(
+            name.as_str(),
+        ));
+        assert_eq!(parsed, Ok(enums::QuickSlot::_3));
+        let name = PropEnum::BillboardTargetMode(enums::BillboardTargetMode::_Self).value_name();
+        assert_eq!(name, "Self");
+        assert_eq!(
+            PropEnum::Direction(enums::Direction::None).with_value_name("Owner"),
+            Some(PropEnum::Direction(enums::Direction::Owner))
+        );
+        assert_eq!(
+            PropEnum::Direction(enums::Direction::None).with_value_name("Left"),
+            None
+        );
+    }
+}
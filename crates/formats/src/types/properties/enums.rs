@@ -1,9 +1,11 @@
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use serde::{Deserialize, Serialize};
+use strum::{EnumString, IntoStaticStr};
 
 /// gEDirection
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum Direction {
     /// eEVelocityDirectionFrom_None
     None = 0x00000000,
@@ -22,6 +24,7 @@ pub enum Direction {
 /// bENoiseTurbulence
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum NoiseTurbulence {
     /// bETurbulence_FractalSum   
     FractalSum = 0x00000000,
@@ -33,6 +36,7 @@ pub enum NoiseTurbulence {
 /// eCImageFilterRTBase_eCGfxShared_eEColorFormat
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ImageFilterRTColorFormat {
     /// eEColorFormat_Unknown      
     Unknown = 0x00000000,
@@ -62,6 +66,7 @@ pub enum ImageFilterRTColorFormat {
 /// eCImageResource2_eCGfxShared_eEColorFormat
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ImageResource2ColorFormat {
     /// eEColorFormat_Unknown
     Unknown = 0x00000000,
@@ -85,6 +90,7 @@ pub enum ImageResource2ColorFormat {
 /// eEAnchorMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum AnchorMode {
     /// eEAnchorMode_Default
     Default = 0x00000000,
@@ -99,6 +105,7 @@ pub enum AnchorMode {
 /// eEAudioChannelFallOff
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum AudioChannelFallOff {
     /// eEAudioChannelFallOff_Logarithmic
     Logarithmic = 0x00000000,
@@ -111,6 +118,7 @@ pub enum AudioChannelFallOff {
 /// eEAudioChannelGroup
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum AudioChannelGroup {
     /// eEAudioChannelGroup_Master
     Master = 0x00000000,
@@ -128,6 +136,7 @@ pub enum AudioChannelGroup {
 /// eEAudioEmitterMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum AudioEmitterMode {
     /// eEAudioEmitterMode_Once  
     Once = 0x00000000,
@@ -141,6 +150,7 @@ pub enum AudioEmitterMode {
 /// eEAudioEmitterShape
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum AudioEmitterShape {
     /// eEAudioEmitterShape_Point
     Point = 0x00000000,
@@ -152,9 +162,11 @@ pub enum AudioEmitterShape {
 /// eEBillboardTargetMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum BillboardTargetMode {
     /// eEBillboardTargetMode_Self
     #[serde(rename = "Self")]
+    #[strum(serialize = "Self")]
     _Self = 0x00000000,
     /// eEBillboardTargetMode_Parent
     Parent = 0x00000001,
@@ -168,6 +180,7 @@ pub enum BillboardTargetMode {
 /// eEBoolOverwrite
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum BoolOverwrite {
     /// eEBoolOverwrite_None
     None = 0x00000000,
@@ -181,6 +194,7 @@ pub enum BoolOverwrite {
 /// eCGuiRadioButton2_eECheckState
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum RadioCheckState {
     /// eECheckState_Unchecked
     Unchecked = 0x00000000,
@@ -192,6 +206,7 @@ pub enum RadioCheckState {
 /// eCGuiCheckBox2_eECheckState
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum CheckBoxCheckState {
     /// eECheckState_Unchecked    
     Unchecked = 0x00000000,
@@ -205,6 +220,7 @@ pub enum CheckBoxCheckState {
 /// eECollisionGroup
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum CollisionGroup {
     /// eECollisionGroup_Static          
     Static = 0x00000001,
@@ -250,6 +266,7 @@ pub enum CollisionGroup {
 /// eECollisionShapeType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum CollisionShapeType {
     /// eECollisionShapeType_Box       
     Box = 0x00000003,
@@ -273,6 +290,7 @@ pub enum CollisionShapeType {
 /// eEColorSrcCombinerType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ColorSrcCombinerType {
     /// eEColorSrcCombinerType_Add     
     Add = 0x00000000,
@@ -291,6 +309,7 @@ pub enum ColorSrcCombinerType {
 /// eEColorSrcSampleTexRepeat
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ColorSrcSampleTexRepeat {
     /// eEColorSrcSampleTexRepeat_Wrap  
     Wrap = 0x00000000,
@@ -305,6 +324,7 @@ pub enum ColorSrcSampleTexRepeat {
 /// eEColorSrcSwitchRepeat
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ColorSrcSwitchRepeat {
     /// eEColorSrcSwitchRepeat_Repeat  
     Repeat = 0x00000000,
@@ -318,6 +338,7 @@ pub enum ColorSrcSwitchRepeat {
 /// eECoordinateSystem
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum CoordinateSystem {
     /// eECoordinateSystem_Independent
     Independent = 0x00000000,
@@ -329,6 +350,7 @@ pub enum CoordinateSystem {
 /// eEDistanceType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum DistanceType {
     /// eEDistanceType_Src  
     Src = 0x00000000,
@@ -342,6 +364,7 @@ pub enum DistanceType {
 /// eEDock
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum Dock {
     /// eEDock_None  
     None = 0x00000000,
@@ -361,6 +384,7 @@ pub enum Dock {
 /// eEDynamicLightEffect
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum DynamicLightEffect {
     /// eEDynamicLightEffect_Steady
     Steady = 0x00000000,
@@ -378,6 +402,7 @@ pub enum DynamicLightEffect {
 /// eEFacingDirection
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum FacingDirection {
     /// eEFacingDirection_FacingCamera             
     FacingCamera = 0x00000000,
@@ -395,6 +420,7 @@ pub enum FacingDirection {
 /// eEFresnelTerm
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum FresnelTerm {
     /// eEFresnelTerm_Simple
     Simple = 0x00000000,
@@ -408,6 +434,7 @@ pub enum FresnelTerm {
 /// eEGuiCursorSize
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum GuiCursorSize {
     /// eEGuiCursorSize_FromSystem
     FromSystem = 0x00000000,
@@ -421,6 +448,7 @@ pub enum GuiCursorSize {
 /// eEIFOutputMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum IFOutputMode {
     /// eEIFOutputMode_Texture    
     Texture = 0x00000000,
@@ -432,6 +460,7 @@ pub enum IFOutputMode {
 /// eEIFSizeMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum IFSizeMode {
     /// eEIFSizeMode_Relative
     Relative = 0x00000001,
@@ -443,6 +472,7 @@ pub enum IFSizeMode {
 /// eEIFTextureMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum IFTextureMode {
     /// eEIFTextureMode_Custom         
     Custom = 0x00000000,
@@ -456,6 +486,7 @@ pub enum IFTextureMode {
 /// eEImageBlend
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ImageBlend {
     /// eEImageBlend_AlphaBlend
     AlphaBlend = 0x00000000,
@@ -476,6 +507,7 @@ pub enum ImageBlend {
 /// eEImageLayerBlend
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ImageLayerBlend {
     /// eEImageLayerBlend_AlphaBlend
     AlphaBlend = 0x00010000,
@@ -497,6 +529,7 @@ pub enum ImageLayerBlend {
 /// eELightingStyle
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum LightingStyle {
     /// eELightingStyle_Disabled
     Disabled = 0x00000000,
@@ -512,6 +545,7 @@ pub enum LightingStyle {
 /// eEListView
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ListView {
     /// eEListView_Icon     
     Icon = 0x00000000,
@@ -533,6 +567,7 @@ pub enum ListView {
 /// eEListViewAlign
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ListViewAlign {
     /// eEListViewAlign_LeftTop     
     LeftTop = 0x00000000,
@@ -558,6 +593,7 @@ pub enum ListViewAlign {
 /// eEListViewIconSize
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ListViewIconSize {
     /// eEListViewIconSize_FromImageList
     FromImageList = 0x00000000,
@@ -573,6 +609,7 @@ pub enum ListViewIconSize {
 /// eEListViewItemLayout
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ListViewItemLayout {
     /// eEListViewItemLayout_LabelRight
     LabelRight = 0x00000000,
@@ -591,6 +628,7 @@ pub enum ListViewItemLayout {
 /// eEListViewTileSize
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ListViewTileSize {
     /// eEListViewTileSize_AutoSize   
     AutoSize = 0x00000000,
@@ -606,6 +644,7 @@ pub enum ListViewTileSize {
 /// eELocationShape
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum LocationShape {
     /// eELocationShape_Box   
     Box = 0x00000000,
@@ -619,9 +658,11 @@ pub enum LocationShape {
 /// eELocationTarget
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum LocationTarget {
     /// eELocationTarget_Self
     #[serde(rename = "Self")]
+    #[strum(serialize = "Self")]
     _Self = 0x00000000,
     /// eELocationTarget_Parent
     Parent = 0x00000001,
@@ -633,6 +674,7 @@ pub enum LocationTarget {
 /// eEMoverPlayBackMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum MoverPlayBackMode {
     /// eEMoverPlayBackMode_Forward
     Forward = 0x00000000,
@@ -646,6 +688,7 @@ pub enum MoverPlayBackMode {
 /// eEOverlayMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum OverlayMode {
     /// eEOverlayMode_Disabled  
     Disabled = 0x00000000,
@@ -661,6 +704,7 @@ pub enum OverlayMode {
 /// eEPhysicRangeType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum PhysicRangeType {
     /// eEPhysicRangeType_World          
     World = 0x00000000,
@@ -676,6 +720,7 @@ pub enum PhysicRangeType {
 /// eEPictureMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum PictureMode {
     /// eEPictureMode_Scale
     Scale = 0x00000000,
@@ -691,6 +736,7 @@ pub enum PictureMode {
 /// eEPropertySetType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum PropertySetType {
     /// eEPropertySetType_Particle    
     Particle = 0x00000049,
@@ -702,6 +748,7 @@ pub enum PropertySetType {
 /// eEReflectType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ReflectType {
     /// eEReflectType_Reflect      
     Reflect = 0x00000000,
@@ -719,6 +766,7 @@ pub enum ReflectType {
 /// eERigidbody_Flag
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum RigidbodyFlag {
     /// eERigidbody_Flag_NONE           
     NONE = 0x00000000,
@@ -734,6 +782,7 @@ pub enum RigidbodyFlag {
 /// eERotationFrom
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum RotationFrom {
     /// eERotationFrom_None  
     None = 0x00000000,
@@ -749,6 +798,7 @@ pub enum RotationFrom {
 /// eEShaderMaterialBRDFType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ShaderMaterialBRDFType {
     /// eEShaderMaterialBRDFType_Simple    
     Simple = 0x00000000,
@@ -763,6 +813,7 @@ pub enum ShaderMaterialBRDFType {
 /// eEShaderMaterialBlendMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ShaderMaterialBlendMode {
     /// eEShaderMaterialBlendMode_Normal       
     Normal = 0x00000000,
@@ -788,6 +839,7 @@ pub enum ShaderMaterialBlendMode {
 /// eEShaderMaterialTransformation
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ShaderMaterialTransformation {
     /// eEShaderMaterialTransformation_Default        
     Default = 0x00000000,
@@ -819,6 +871,7 @@ pub enum ShaderMaterialTransformation {
 /// eEShaderMaterialVersion
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ShaderMaterialVersion {
     /// eEShaderMaterialVersion_1_1
     V1_1 = 0x00000000,
@@ -834,6 +887,7 @@ pub enum ShaderMaterialVersion {
 /// eEShadowCasterType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ShadowCasterType {
     /// eEShadowCasterType_Terrain
     Terrain = 0x00000000,
@@ -849,6 +903,7 @@ pub enum ShadowCasterType {
 /// eEShadowMaskIndex
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ShadowMaskIndex {
     /// eEShadowMaskIndex_R
     R = 0x00000002,
@@ -862,6 +917,7 @@ pub enum ShadowMaskIndex {
 /// eEShapeAABBAdapt
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ShapeAABBAdapt {
     /// eEShapeAABBAdapt_None     
     None = 0x00000000,
@@ -876,6 +932,7 @@ pub enum ShapeAABBAdapt {
 /// eEShapeGroup
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ShapeGroup {
     /// eEShapeGroup_Static           
     Static = 0x00000001,
@@ -923,6 +980,7 @@ pub enum ShapeGroup {
 /// eCMaterialResource2_eEShapeMaterial
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum MaterialResource2ShapeMaterial {
     /// eEShapeMaterial_None            
     None = 0x00000000,
@@ -969,6 +1027,7 @@ pub enum MaterialResource2ShapeMaterial {
 /// eEShapeMaterial
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ShapeMaterial {
     /// eEShapeMaterial_None            
     None = 0x00000000,
@@ -1022,6 +1081,7 @@ pub enum ShapeMaterial {
 /// eESplitImageStyle
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum SplitImageStyle {
     /// eESplitImageStyle_Scale         
     Scale = 0x0000001B,
@@ -1037,6 +1097,7 @@ pub enum SplitImageStyle {
 /// eEStaticIlluminated
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum StaticIlluminated {
     /// eEStaticIlluminated_Static
     Static = 0x00000000,
@@ -1048,6 +1109,7 @@ pub enum StaticIlluminated {
 /// eEStripSpawning
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum StripSpawning {
     /// eEStripSpawning_Movement  
     Movement = 0x00000001,
@@ -1062,6 +1124,7 @@ pub enum StripSpawning {
 /// eETexCoordSrcOscillatorType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum TexCoordSrcOscillatorType {
     /// eETexCoordSrcOscillatorType_Pan          
     Pan = 0x00000000,
@@ -1077,6 +1140,7 @@ pub enum TexCoordSrcOscillatorType {
 /// eETexCoordSrcRotatorType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum TexCoordSrcRotatorType {
     /// eETexCoordSrcRotatorType_Once     
     Once = 0x00000000,
@@ -1091,6 +1155,7 @@ pub enum TexCoordSrcRotatorType {
 /// eETextAlign
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum TextAlign {
     /// eETextAlign_Left_Top     
     LeftTop = 0x00000000,
@@ -1116,6 +1181,7 @@ pub enum TextAlign {
 /// eETextureDrawStyle
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum TextureDrawStyle {
     /// eETextureDrawStyle_Regular      
     Regular = 0x00000000,
@@ -1139,6 +1205,7 @@ pub enum TextureDrawStyle {
 /// eETicSide
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum TicSide {
     /// eETicSide_Right
     Right = 0x00000000,
@@ -1152,6 +1219,7 @@ pub enum TicSide {
 /// eEVegetationBrushColorFunction
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum VegetationBrushColorFunction {
     /// eEVegetationBrushColorFunction_Random                        
     Random = 0x00000001,
@@ -1177,6 +1245,7 @@ pub enum VegetationBrushColorFunction {
 /// eEVegetationBrushMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum VegetationBrushMode {
     /// eEVegetationBrushMode_Place   
     Place = 0x00000000,
@@ -1190,6 +1259,7 @@ pub enum VegetationBrushMode {
 /// eEVegetationBrushPlace
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum VegetationBrushPlace {
     /// eEVegetationBrushPlace_DistanceSelf
     DistanceSelf = 0x00000000,
@@ -1203,6 +1273,7 @@ pub enum VegetationBrushPlace {
 /// eEVegetationBrushProbabilityFunction
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum VegetationBrushProbabilityFunction {
     /// eEVegetationBrushProbabilityFunction_None                          
     None = 0x00000000,
@@ -1230,6 +1301,7 @@ pub enum VegetationBrushProbabilityFunction {
 /// eEVegetationBrushShape
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum VegetationBrushShape {
     /// eEVegetationBrushShape_Circle
     Circle = 0x00000000,
@@ -1243,6 +1315,7 @@ pub enum VegetationBrushShape {
 /// eEVegetationMeshShading
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum VegetationMeshShading {
     /// eEVegetationMeshShading_MeshNormal      
     MeshNormal = 0x00000000,
@@ -1254,6 +1327,7 @@ pub enum VegetationMeshShading {
 /// eEVelocityDirectionFrom
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum VelocityDirectionFrom {
     /// eEVelocityDirectionFrom_None                 
     None = 0x00000000,
@@ -1284,6 +1358,7 @@ pub enum VelocityDirectionFrom {
 /// eEWeatherZoneOverwrite
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum WeatherZoneOverwrite {
     /// eEWeatherZoneOverwrite_None     
     None = 0x00000000,
@@ -1299,6 +1374,7 @@ pub enum WeatherZoneOverwrite {
 /// eEWeatherZoneShape
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum WeatherZoneShape {
     /// eEWeatherZoneShape_2D_Circle
     Circle = 0x00000000,
@@ -1314,6 +1390,7 @@ pub enum WeatherZoneShape {
 /// gEAIMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum AIMode {
     /// gEAIMode_None    
     None = 0x00000000,
@@ -1347,6 +1424,7 @@ pub enum AIMode {
 /// gEAchievementViewMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum AchievementViewMode {
     /// gEAchievementViewMode_Counter
     Counter = 0x00000000,
@@ -1358,6 +1436,7 @@ pub enum AchievementViewMode {
 /// gEAlignToTarget
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum AlignToTarget {
     /// gEAlignToTarget_None     
     None = 0x00000000,
@@ -1373,6 +1452,7 @@ pub enum AlignToTarget {
 /// gEAmbientAction
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum AmbientAction {
     /// gEAmbientAction_Ambient
     Ambient = 0x00000000,
@@ -1386,6 +1466,7 @@ pub enum AmbientAction {
 /// gEAmountType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum AmountType {
     /// gEAmountType_Gold    
     Gold = 0x00000000,
@@ -1399,6 +1480,7 @@ pub enum AmountType {
 /// gEAnchorType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum AnchorType {
     /// gEAnchorType_Local
     Local = 0x00000000,
@@ -1415,6 +1497,7 @@ pub enum AnchorType {
 /// gEAniState
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum AniState {
     /// gEAniState_Dummy0       
     Dummy0 = 0x00000000,
@@ -1482,6 +1565,7 @@ pub enum AniState {
 /// gCCombatMoveStumble_gEAniState
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum CombatMoveStumbleAniState {
     /// gEAniState_Stand   
     Stand = 0x00000002,
@@ -1499,6 +1583,7 @@ pub enum CombatMoveStumbleAniState {
 /// gEArenaStatus
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ArenaStatus {
     /// gEArenaStatus_None   
     None = 0x00000000,
@@ -1510,6 +1595,7 @@ pub enum ArenaStatus {
 /// gEAttitude
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum Attitude {
     /// gEAttitude_None    
     None = 0x00000000,
@@ -1527,6 +1613,7 @@ pub enum Attitude {
 /// gEBoostTarget
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum BoostTarget {
     /// gEBoostTarget_Strength    
     Strength = 0x00000000,
@@ -1540,6 +1627,7 @@ pub enum BoostTarget {
 /// gEBraveryOverride
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum BraveryOverride {
     /// gEBraveryOverride_None  
     None = 0x00000000,
@@ -1553,6 +1641,7 @@ pub enum BraveryOverride {
 /// gCCombatMoveMelee_gECombatAction
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum MeleeCombatAction {
     /// gECombatAction_Attack
     Attack = 0x00000001,
@@ -1566,6 +1655,7 @@ pub enum MeleeCombatAction {
 /// gCCombatMoveScriptState_gECombatAction
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum MoveCombatAction {
     /// gECombatAction_None   
     None = 0x00000000,
@@ -1581,6 +1671,7 @@ pub enum MoveCombatAction {
 /// gECombatAttackStumble
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum CombatAttackStumble {
     /// gECombatAttackStumble_None      
     None = 0x00000000,
@@ -1592,6 +1683,7 @@ pub enum CombatAttackStumble {
 /// gECombatComboParade
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum CombatComboParade {
     /// gECombatComboParade_None   
     None = 0x00000000,
@@ -1605,6 +1697,7 @@ pub enum CombatComboParade {
 /// gECombatFightAIMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum CombatFightAIMode {
     /// gECombatFightAIMode_Active
     Active = 0x00000000,
@@ -1616,6 +1709,7 @@ pub enum CombatFightAIMode {
 /// gECombatHitDirection
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum CombatHitDirection {
     /// gECombatHitDirection_Fore
     Fore = 0x00000000,
@@ -1629,6 +1723,7 @@ pub enum CombatHitDirection {
 /// gECombatMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum CombatMode {
     /// gECombatMode_None  
     None = 0x00000000,
@@ -1648,6 +1743,7 @@ pub enum CombatMode {
 /// gCCombatMoveMelee_gECombatMove
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum MeleeCombatMove {
     /// gECombatMove_None              
     None = 0x00000000,
@@ -1667,6 +1763,7 @@ pub enum MeleeCombatMove {
 /// gCCombatMoveMelee2_gECombatMove
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum Melee2CombatMove {
     /// gECombatMove_Stumble           
     Stumble = 0x00000019,
@@ -1689,6 +1786,7 @@ pub enum Melee2CombatMove {
 /// gECombatMoveSide
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum CombatMoveSide {
     /// gECombatMoveSide_Left
     Left = 0x00000000,
@@ -1702,6 +1800,7 @@ pub enum CombatMoveSide {
 /// gECombatParadeType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum CombatParadeType {
     /// gECombatParadeType_None   
     None = 0x00000000,
@@ -1723,6 +1822,7 @@ pub enum CombatParadeType {
 /// gECombatPhaseType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum CombatPhaseType {
     /// gECombatPhaseType_Raise        
     Raise = 0x00000001,
@@ -1748,6 +1848,7 @@ pub enum CombatPhaseType {
 /// gECombatPose
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum CombatPose {
     /// gECombatPose_P0
     P0 = 0x00000000,
@@ -1761,6 +1862,7 @@ pub enum CombatPose {
 /// gEComment
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum Comment {
     /// gEComment_None             
     None = 0x00000000,
@@ -1780,6 +1882,7 @@ pub enum Comment {
 /// gECompareOperation
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum CompareOperation {
     /// gECompareOperation_Equal       
     Equal = 0x00000000,
@@ -1799,6 +1902,7 @@ pub enum CompareOperation {
 /// gECrime
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum Crime {
     /// gECrime_None           
     None = 0x00000000,
@@ -1816,6 +1920,7 @@ pub enum Crime {
 /// gEDamageCalculationType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum DamageCalculationType {
     /// gEDamageCalculationType_Normal  
     Normal = 0x00000000,
@@ -1829,6 +1934,7 @@ pub enum DamageCalculationType {
 /// gEDamageType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum DamageType {
     /// gEDamageType_None   
     None = 0x00000000,
@@ -1852,6 +1958,7 @@ pub enum DamageType {
 /// gEDoorStatus
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum DoorStatus {
     /// gEDoorStatus_Open  
     Open = 0x00000000,
@@ -1863,6 +1970,7 @@ pub enum DoorStatus {
 /// gEEffectDecayMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum EffectDecayMode {
     /// gEEffectDecayMode_Decay
     Decay = 0x00000000,
@@ -1874,6 +1982,7 @@ pub enum EffectDecayMode {
 /// gEEffectKillRange
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum EffectKillRange {
     /// gEEffectKillRange_All  
     All = 0x00000000,
@@ -1888,6 +1997,7 @@ pub enum EffectKillRange {
 /// gEEffectLink
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum EffectLink {
     /// gEEffectLink_Independent
     Independent = 0x00000000,
@@ -1901,6 +2011,7 @@ pub enum EffectLink {
 /// gEEffectLoopMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum EffectLoopMode {
     /// gEEffectLoopMode_Once  
     Once = 0x00000000,
@@ -1914,6 +2025,7 @@ pub enum EffectLoopMode {
 /// gEEffectScriptOtherType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum EffectScriptOtherType {
     /// gEEffectScriptOtherType_TemplateEntity
     TemplateEntity = 0x00000001,
@@ -1925,6 +2037,7 @@ pub enum EffectScriptOtherType {
 /// gEEffectScriptParamType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum EffectScriptParamType {
     /// gEEffectScriptParamType_UseEffectCommandTime
     UseEffectCommandTime = 0x00000001,
@@ -1936,6 +2049,7 @@ pub enum EffectScriptParamType {
 /// gEEffectStopMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum EffectStopMode {
     /// gEEffectStopMode_Decay  
     Decay = 0x00000000,
@@ -1949,9 +2063,11 @@ pub enum EffectStopMode {
 /// gEEffectTargetMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum EffectTargetMode {
     /// gEEffectTargetMode_Self  
     #[serde(rename = "Self")]
+    #[strum(serialize = "Self")]
     _Self = 0x00000000,
     /// gEEffectTargetMode_Parent
     Parent = 0x00000001,
@@ -1963,6 +2079,7 @@ pub enum EffectTargetMode {
 /// gEEntityType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum EntityType {
     /// gEEntityType_Game     
     Game = 0x00000000,
@@ -1974,6 +2091,7 @@ pub enum EntityType {
 /// gCEquipPicbox2_gEEquipSlot
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum Picbox2EquipSlot {
     /// gEEquipSlot_MeleeWeapon
     MeleeWeapon = 0x00000001,
@@ -1999,6 +2117,7 @@ pub enum Picbox2EquipSlot {
 /// gEEquipSlot
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum EquipSlot {
     /// gEEquipSlot_None        
     None = 0x00000000,
@@ -2026,6 +2145,7 @@ pub enum EquipSlot {
 /// gEFight
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum Fight {
     /// gEFight_None   
     None = 0x00000000,
@@ -2043,6 +2163,7 @@ pub enum Fight {
 /// gEFlightPathType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum FlightPathType {
     /// gEFlightPathType_Ballistic
     Ballistic = 0x00000000,
@@ -2054,6 +2175,7 @@ pub enum FlightPathType {
 /// gEFocusNameType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum FocusNameType {
     /// gEFocusNameType_Skeleton
     Skeleton = 0x00000000,
@@ -2071,6 +2193,7 @@ pub enum FocusNameType {
 /// gEFocusPriority
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum FocusPriority {
     /// gEFocusPriority_None   
     None = 0x00000000,
@@ -2090,6 +2213,7 @@ pub enum FocusPriority {
 /// gEFocusSource
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum FocusSource {
     /// gEFocusSource_Camera            
     Camera = 0x00000000,
@@ -2107,6 +2231,7 @@ pub enum FocusSource {
 /// gEGUIFilterType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum GUIFilterType {
     /// gEGUIFilterType_Status     
     Status = 0x00000001,
@@ -2127,6 +2252,7 @@ pub enum GUIFilterType {
 /// gEGammaRamp
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum GammaRamp {
     /// gEGammaRamp_Brightness
     Brightness = 0x00000000,
@@ -2144,6 +2270,7 @@ pub enum GammaRamp {
 /// gEGender
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum Gender {
     /// gEGender_Male  
     Male = 0x00000000,
@@ -2155,6 +2282,7 @@ pub enum Gender {
 /// gEGuardStatus
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum GuardStatus {
     /// gEGuardStatus_Active         
     Active = 0x00000000,
@@ -2170,6 +2298,7 @@ pub enum GuardStatus {
 /// gCNPC_PS_gEGuardStatus
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum NpcGuardStatus {
     /// gEGuardStatus_Active         
     Active = 0x00000000,
@@ -2187,6 +2316,7 @@ pub enum NpcGuardStatus {
 /// gEGuild
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum Guild {
     /// gEGuild_None
     None = 0x00000000,
@@ -2210,6 +2340,7 @@ pub enum Guild {
 /// gCNPC_PS_gEGuild
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum NpcGuild {
     /// gEGuild_None
     None = 0x00000000,
@@ -2235,6 +2366,7 @@ pub enum NpcGuild {
 /// gEHitDirection
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum HitDirection {
     /// gEHitDirection_Left
     Left = 0x00000000,
@@ -2246,6 +2378,7 @@ pub enum HitDirection {
 /// gEHudPage
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum HudPage {
     /// gEHudPage_None            
     None = 0x00000000,
@@ -2311,6 +2444,7 @@ pub enum HudPage {
 /// gCPageTimerProgressBar_gEHudPage
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum HudPageProgessBar {
     /// gEHudPage_Pickpocket
     Pickpocket = 0x00000009,
@@ -2326,6 +2460,7 @@ pub enum HudPageProgessBar {
 /// gEIcon
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum Icon {
     /// gEIcon_Inventory
     Inventory = 0x00000000,
@@ -2337,6 +2472,7 @@ pub enum Icon {
 /// gEInfoCondType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum InfoCondType {
     /// gEInfoCondType_Crime         
     Crime = 0x00000000,
@@ -2418,6 +2554,7 @@ pub enum InfoCondType {
 /// gCInfoConditionQuestStatus_gEInfoCondType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum QuestInfoCondType {
     /// gEInfoCondType_Open          
     Open = 0x00000005,
@@ -2450,6 +2587,7 @@ pub enum QuestInfoCondType {
 /// gEInfoGesture
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum InfoGesture {
     /// gEInfoGesture_Ambient
     Ambient = 0x00000000,
@@ -2485,6 +2623,7 @@ pub enum InfoGesture {
 /// gEInfoLocation
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum InfoLocation {
     /// gEInfoLocation_Main     
     Main = 0x00000000,
@@ -2500,6 +2639,7 @@ pub enum InfoLocation {
 /// gEInfoNPCStatus
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum InfoNPCStatus {
     /// gEInfoNPCStatus_Alive            
     Alive = 0x00000000,
@@ -2520,6 +2660,7 @@ pub enum InfoNPCStatus {
 /// gEInfoNPCType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum InfoNPCType {
     /// gEInfoNPCType_Vendor
     Vendor = 0x00000000,
@@ -2533,6 +2674,7 @@ pub enum InfoNPCType {
 /// gEInfoType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum InfoType {
     /// gEInfoType_Refuse   
     Refuse = 0x00000000,
@@ -2552,6 +2694,7 @@ pub enum InfoType {
 /// gEInfoView
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum InfoView {
     /// gEInfoView_Header             
     Header = 0x00000000,
@@ -2577,6 +2720,7 @@ pub enum InfoView {
 /// gCSkillInfo_gEInfoView
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum SkillInfoView {
     /// gEInfoView_Header     
     Header = 0x00000000,
@@ -2592,6 +2736,7 @@ pub enum SkillInfoView {
 /// gCDialogInfo_gCDialogInfo_gEInfoView
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum DialogInfoView {
     /// gEInfoView_Header     
     Header = 0x00000000,
@@ -2609,6 +2754,7 @@ pub enum DialogInfoView {
 /// gCHintStatic_gEInfoView
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum HintInfoView {
     /// gEInfoView_Image    
     Image = 0x00000000,
@@ -2622,6 +2768,7 @@ pub enum HintInfoView {
 /// gEInteractionType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum InteractionType {
     /// gEInteractionType_Interact_NPC       
     InteractNPC = 0x00000000,
@@ -2639,6 +2786,7 @@ pub enum InteractionType {
 /// SPECIAL_gEInteractionUseType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum SpecialInteractionUseType {
     /// gEInteractionUseType_None
     None = 0x00000000,
@@ -2652,6 +2800,7 @@ pub enum SpecialInteractionUseType {
 /// gEInteractionUseType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum InteractionUseType {
     /// gEInteractionUseType_None          
     None = 0x00000000,
@@ -2799,6 +2948,7 @@ pub enum InteractionUseType {
 /// gEItemCategory
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ItemCategory {
     /// gEItemCategory_None      
     None = 0x00000000,
@@ -2830,6 +2980,7 @@ pub enum ItemCategory {
 /// gEItemHoldType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ItemHoldType {
     /// gEItemHoldType_None         
     None = 0x00000000,
@@ -2909,6 +3060,7 @@ pub enum ItemHoldType {
 /// gEItemUseType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ItemUseType {
     /// gEItemUseType_None         
     None = 0x00000000,
@@ -2971,6 +3123,7 @@ pub enum ItemUseType {
 /// gCCombatWeaponConfig_gEItemUseType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum WeaponItemUseType {
     /// gEItemUseType_None         
     None = 0x00000000,
@@ -3020,6 +3173,7 @@ pub enum WeaponItemUseType {
 /// gELockStatus
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum LockStatus {
     /// gELockStatus_Locked  
     Locked = 0x00000000,
@@ -3031,6 +3185,7 @@ pub enum LockStatus {
 /// gEMiscInfo
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum MiscInfo {
     /// gEMiscInfo_Guild
     Guild = 0x00000000,
@@ -3040,6 +3195,7 @@ pub enum MiscInfo {
 /// gCMiscProgressBar_gEMiscInfo
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ProgressBarMiscInfo {
     /// gEMiscInfo_StatusEffect
     StatusEffect = 0x00000001,
@@ -3054,6 +3210,7 @@ pub enum ProgressBarMiscInfo {
 /// gEMouseAxis
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum MouseAxis {
     /// gEMouseAxis_X
     X = 0x00000000,
@@ -3065,6 +3222,7 @@ pub enum MouseAxis {
 /// gENavObstacleType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum NavObstacleType {
     /// gENavObstacleType_Obstacle
     Obstacle = 0x00000000,
@@ -3081,6 +3239,7 @@ pub enum NavObstacleType {
 /// gENavTestResult
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum NavTestResult {
     /// gENavTestResult_Succeeded                       
     Succeeded = 0x00000000,
@@ -3120,6 +3279,7 @@ pub enum NavTestResult {
 /// gEOtherType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum OtherType {
     /// gEOtherType_TemplateEntity
     TemplateEntity = 0x00000001,
@@ -3131,6 +3291,7 @@ pub enum OtherType {
 /// gCLootStatic2_gEPageMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum LootPageMode {
     /// gEPageMode_Dialog   
     Dialog = 0x00000003,
@@ -3146,6 +3307,7 @@ pub enum LootPageMode {
 /// gCHudPage2_gEPageMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum HudPageMode {
     /// gEPageMode_None     
     None = 0x00000000,
@@ -3170,6 +3332,7 @@ pub enum HudPageMode {
 /// gEPageMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum PageMode {
     /// gEPageMode_UserMin  
     UserMin = 0x00000004,
@@ -3183,6 +3346,7 @@ pub enum PageMode {
 /// gEPaintArea
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum PaintArea {
     /// gEPaintArea_Client
     Client = 0x00000000,
@@ -3196,6 +3360,7 @@ pub enum PaintArea {
 /// gEPartyMemberType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum PartyMemberType {
     /// gEPartyMemberType_None       
     None = 0x00000000,
@@ -3217,6 +3382,7 @@ pub enum PartyMemberType {
 /// gEQuestActor
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum QuestActor {
     /// gEQuestActor_Client
     Client = 0x00000000,
@@ -3228,6 +3394,7 @@ pub enum QuestActor {
 /// gEQuestStatus
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum QuestStatus {
     /// gEQuestStatus_Open     
     Open = 0x00000000,
@@ -3251,6 +3418,7 @@ pub enum QuestStatus {
 /// gEQuestType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum QuestType {
     /// gEQuestType_HasItems
     HasItems = 0x00000000,
@@ -3282,36 +3450,47 @@ pub enum QuestType {
 /// gEQuickSlot
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum QuickSlot {
     /// gEQuickSlot_1
     #[serde(rename = "1")]
+    #[strum(serialize = "1")]
     _1 = 0x00000000,
     /// gEQuickSlot_2
     #[serde(rename = "2")]
+    #[strum(serialize = "2")]
     _2 = 0x00000001,
     /// gEQuickSlot_3
     #[serde(rename = "3")]
+    #[strum(serialize = "3")]
     _3 = 0x00000002,
     /// gEQuickSlot_4
     #[serde(rename = "4")]
+    #[strum(serialize = "4")]
     _4 = 0x00000003,
     /// gEQuickSlot_5
     #[serde(rename = "5")]
+    #[strum(serialize = "5")]
     _5 = 0x00000004,
     /// gEQuickSlot_6
     #[serde(rename = "6")]
+    #[strum(serialize = "6")]
     _6 = 0x00000005,
     /// gEQuickSlot_7
     #[serde(rename = "7")]
+    #[strum(serialize = "7")]
     _7 = 0x00000006,
     /// gEQuickSlot_8
     #[serde(rename = "8")]
+    #[strum(serialize = "8")]
     _8 = 0x00000007,
     /// gEQuickSlot_9
     #[serde(rename = "9")]
+    #[strum(serialize = "9")]
     _9 = 0x00000008,
     /// gEQuickSlot_10
     #[serde(rename = "10")]
+    #[strum(serialize = "10")]
     _10 = 0x00000009,
 }
 
@@ -3320,6 +3499,7 @@ pub enum QuickSlot {
 /// gEReason
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum Reason {
     /// gEReason_None           
     None = 0x00000000,
@@ -3393,6 +3573,7 @@ pub enum Reason {
 /// gERecipeCategory
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum RecipeCategory {
     /// gERecipeCategory_Alchemy         
     Alchemy = 0x00000000,
@@ -3418,6 +3599,7 @@ pub enum RecipeCategory {
 /// gEScrollStart
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ScrollStart {
     /// gEScrollStart_Top   
     Top = 0x00000000,
@@ -3429,6 +3611,7 @@ pub enum ScrollStart {
 /// gESession_State
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum SessionState {
     /// gESession_State_None         
     None = 0x00000000,
@@ -3460,6 +3643,7 @@ pub enum SessionState {
 /// gESkill
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum Skill {
     /// gESkill_None           
     None = 0xFFFFFFFF,
@@ -3537,6 +3721,7 @@ pub enum Skill {
 /// gCSkillValueBase_gESkill
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum SkillValueBaseSkill {
     /// gESkill_None           
     None = 0xFFFFFFFF,
@@ -3614,6 +3799,7 @@ pub enum SkillValueBaseSkill {
 /// gCSkillProgressBar_gESkill
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ProgressBarSkill {
     /// gESkill_Stat_LV        
     LV = 0x00000002,
@@ -3685,6 +3871,7 @@ pub enum ProgressBarSkill {
 /// gESkillModifier
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum SkillModifier {
     /// gESkillModifier_AddValue       
     AddValue = 0x00000000,
@@ -3701,6 +3888,7 @@ pub enum SkillModifier {
 /// gESpecialEntity
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum SpecialEntity {
     /// gESpecialEntity_Player   
     Player = 0x00000000,
@@ -3718,6 +3906,7 @@ pub enum SpecialEntity {
 /// gESpecies
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum Species {
     /// gESpecies_None         
     None = 0x00000000,
@@ -3781,6 +3970,7 @@ pub enum Species {
 /// gESpinButtonType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum SpinButtonType {
     /// gESpinButtonType_Prev
     Prev = 0x00000000,
@@ -3794,6 +3984,7 @@ pub enum SpinButtonType {
 /// gEStackType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum StackType {
     /// gEStackType_Normal
     Normal = 0x00000000,
@@ -3807,6 +3998,7 @@ pub enum StackType {
 /// gEStateGraphEventType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum StateGraphEventType {
     /// gEStateGraphEventType_None     
     None = 0x00000000,
@@ -3826,6 +4018,7 @@ pub enum StateGraphEventType {
 /// gEViewMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ViewMode {
     /// gEViewMode_Name       
     Name = 0x00000000,
@@ -3845,6 +4038,7 @@ pub enum ViewMode {
 /// gCEquipPicbox2_gEViewMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum EquipViewMode {
     /// gEViewMode_Value    
     Value = 0x00000002,
@@ -3860,6 +4054,7 @@ pub enum EquipViewMode {
 /// gCQuickPicbox2_gEViewMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum QuickViewMode {
     /// gEViewMode_Value    
     Value = 0x00000002,
@@ -3877,6 +4072,7 @@ pub enum QuickViewMode {
 /// gEWalkMode
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum WalkMode {
     /// gEWalkMode_Run   
     Run = 0x00000002,
@@ -3892,6 +4088,7 @@ pub enum WalkMode {
 /// gEWrittenType
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum WrittenType {
     /// gEWrittenType_Invalid
     Invalid = 0xFFFFFFFF,
@@ -3908,6 +4105,7 @@ pub enum WrittenType {
 /// gEActionAxis
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ActionAxis {
     Undefined = 0,
     TurnLeftRight,
@@ -3917,6 +4115,7 @@ pub enum ActionAxis {
 /// gEActionAxis
 #[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
 #[repr(u32)]
+#[derive(PartialEq, Eq, IntoStaticStr, EnumString)]
 pub enum ActionKey {
     Undefined = 0,
     MoveForward,
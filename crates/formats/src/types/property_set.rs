@@ -3,12 +3,13 @@ use num_enum::IntoPrimitive;
 use num_enum::TryFromPrimitive;
 use serde::{Deserialize, Serialize};
 
+use super::properties::{PropData, PropEnum, enums};
 use super::*;
 use crate::archive::*;
 use crate::error::*;
 use crate::helpers::*;
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub enum PropertySet {
     Inventory(Inventory),
     Interaction(Interaction),
@@ -16,6 +17,10 @@ pub enum PropertySet {
     Animation(Animation),
     Mesh(Mesh),
     Anchor(Anchor),
+    NavZone(NavZone),
+    NavPath(NavPath),
+    NavOffset(NavOffset),
+    CollisionShapes(CollisionShapes),
 }
 
 impl PropertySet {
@@ -27,6 +32,10 @@ impl PropertySet {
             PropertySet::Mesh(mesh) => mesh.save(dst),
             PropertySet::Anchor(anchor) => anchor.save(dst),
             PropertySet::Animation(animation) => animation.save(dst),
+            PropertySet::NavZone(zone) => zone.save(dst),
+            PropertySet::NavPath(path) => path.save(dst),
+            PropertySet::NavOffset(offset) => offset.save(dst),
+            PropertySet::CollisionShapes(shapes) => shapes.save(dst),
         }
     }
 
@@ -38,18 +47,22 @@ impl PropertySet {
             PropertySet::Animation(_) => "eCAnimation_PS",
             PropertySet::Mesh(_) => "eCMesh_PS",
             PropertySet::Anchor(_) => "gCAnchor_PS",
+            PropertySet::NavZone(_) => "gCNavZone_PS",
+            PropertySet::NavPath(_) => "gCNavPath_PS",
+            PropertySet::NavOffset(_) => "gCNavOffset_PS",
+            PropertySet::CollisionShapes(_) => "eCCollisionShape_PS",
         }
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 /// gCInventory_PS
 pub struct Inventory {
     pub version: u16,
     pub unknown1: u16,
     pub unknown2: u8,
     pub unknown3: u16,
-    pub stacks: Vec<Object>,
+    pub stacks: Vec<InventoryStack>,
     pub slots: Vec<InventorySlot>,
 }
 
@@ -62,7 +75,7 @@ impl Inventory {
         let stack_count = read_u32(src)? as usize;
         let mut stacks = Vec::with_capacity(stack_count);
         for _idx in 0..stack_count {
-            stacks.push(Object::load(src, "INVALID")?);
+            stacks.push(InventoryStack::load(src)?);
         }
         let unknown3 = read_u16(src)?;
         let slot_count = read_u32(src)? as usize;
@@ -103,7 +116,276 @@ impl Inventory {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl Inventory {
+    /// Index of the first stack of `template`
+    pub fn find_stack(&self, template: Uuid) -> Option<usize> {
+        self.stacks
+            .iter()
+            .position(|stack| stack.template == Some(template))
+    }
+
+    /// Appends a new, unequipped stack of `amount` items of `template` and returns its index
+    pub fn add_stack(&mut self, template: Uuid, amount: i32) -> usize {
+        self.stacks.push(InventoryStack::new(template, amount));
+        self.stacks.len() - 1
+    }
+
+    /// Adds `amount` items of `template`, to an existing stack if there is one.
+    /// Returns the index of the stack.
+    pub fn add_items(&mut self, template: Uuid, amount: i32) -> usize {
+        match self.find_stack(template) {
+            Some(idx) => {
+                self.stacks[idx].amount += amount;
+                idx
+            }
+            None => self.add_stack(template, amount),
+        }
+    }
+
+    /// Removes `amount` items of `template`, or the whole stack if `amount` is `None` or exceeds the stack.
+    /// Removed stacks are unequipped first.
+    pub fn remove_items(&mut self, template: Uuid, amount: Option<i32>) -> Result<()> {
+        let Some(idx) = self.find_stack(template) else {
+            return Err(Error::InvalidOp(format!(
+                "Inventory contains no stack of {template}"
+            )));
+        };
+        match amount {
+            Some(amount) if amount < self.stacks[idx].amount => {
+                self.stacks[idx].amount -= amount;
+            }
+            _ => {
+                self.unequip(template)?;
+                self.stacks.remove(idx);
+            }
+        }
+        Ok(())
+    }
+
+    /// Equips the stack of `template` to `slot`, replacing whatever was equipped there
+    pub fn equip(&mut self, template: Uuid, slot: enums::EquipSlot) -> Result<()> {
+        let Some(idx) = self.find_stack(template) else {
+            return Err(Error::InvalidOp(format!(
+                "Inventory contains no stack of {template}"
+            )));
+        };
+        if slot == enums::EquipSlot::None {
+            return self.unequip(template);
+        }
+        // unequip the current stack (in this slot) first:
+        self.unequip(template)?;
+        let previous = self
+            .stacks
+            .iter()
+            .find(|stack| stack.equip_slot == slot)
+            .and_then(|stack| stack.template);
+        if let Some(previous) = previous {
+            self.unequip(previous)?;
+        }
+
+        self.stacks[idx].equip_slot = slot;
+        if let Some(slot_idx) = InventorySlotIdx::for_equip_slot(slot) {
+            self.set_slot_template(slot_idx, Some(template));
+        }
+        Ok(())
+    }
+
+    /// Removes the stack of `template` from its equipment slot (if any)
+    pub fn unequip(&mut self, template: Uuid) -> Result<()> {
+        let Some(idx) = self.find_stack(template) else {
+            return Err(Error::InvalidOp(format!(
+                "Inventory contains no stack of {template}"
+            )));
+        };
+        let slot = self.stacks[idx].equip_slot;
+        if slot == enums::EquipSlot::None {
+            return Ok(());
+        }
+        self.stacks[idx].equip_slot = enums::EquipSlot::None;
+        if let Some(slot_idx) = InventorySlotIdx::for_equip_slot(slot)
+            && self.slot_template(slot_idx) == Some(template)
+        {
+            self.set_slot_template(slot_idx, None);
+        }
+        Ok(())
+    }
+
+    /// Template shown in the slot `idx`
+    pub fn slot_template(&self, idx: InventorySlotIdx) -> Option<Uuid> {
+        let slot = self.slots.iter().find(|slot| slot.id == idx)?;
+        template_property(&slot.content, "Template")
+    }
+
+    fn set_slot_template(&mut self, idx: InventorySlotIdx, template: Option<Uuid>) {
+        let slot = match self.slots.iter().position(|slot| slot.id == idx) {
+            Some(pos) => &mut self.slots[pos],
+            None if template.is_none() => return,
+            None => {
+                // Keep the layout of the existing slots, if any:
+                let mut slot = match self.slots.first() {
+                    Some(prototype) => prototype.clone(),
+                    None => InventorySlot {
+                        id: idx,
+                        unknown1: [0; 3],
+                        content: Object::new(),
+                    },
+                };
+                slot.id = idx;
+                self.slots.push(slot);
+                self.slots.last_mut().unwrap()
+            }
+        };
+        set_template(&mut slot.content, "Template", template);
+    }
+}
+
+/// gCInventoryStack
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct InventoryStack {
+    pub template: Option<Uuid>,
+    pub amount: i32,
+    pub quality: i32,
+    pub equip_slot: enums::EquipSlot,
+    pub stack_type: enums::StackType,
+    /// Stored properties, the fields above replace their values on save.
+    /// Keeps the stored property types and the properties not decoded here.
+    pub object: Object,
+}
+
+impl InventoryStack {
+    /// A stack with the default properties
+    pub fn new(template: Uuid, amount: i32) -> Self {
+        let mut object = Object::new();
+        object.set_property("Template", template_proxy(Some(template)));
+        object.set_property("Amount", PropData::Int(amount));
+        object.set_property("Quality", PropData::Int(0));
+        object.set_property(
+            "EquipSlot",
+            PropData::Enum(PropEnum::EquipSlot(enums::EquipSlot::None)),
+        );
+        object.set_property(
+            "Type",
+            PropData::Enum(PropEnum::StackType(enums::StackType::Normal)),
+        );
+        Self::from_object(object)
+    }
+
+    pub fn load(src: &mut PakFile) -> Result<Self> {
+        Ok(Self::from_object(Object::load(src, "INVALID")?))
+    }
+
+    pub fn save<W: ArchiveWriteTarget>(&self, dst: &mut W) -> Result<()> {
+        self.to_object().save(dst)
+    }
+
+    pub fn from_object(object: Object) -> Self {
+        let number = |name| {
+            object
+                .get_property(name)
+                .and_then(PropData::as_number)
+                .unwrap_or(0.0) as i32
+        };
+        Self {
+            template: template_property(&object, "Template"),
+            amount: number("Amount"),
+            quality: number("Quality"),
+            equip_slot: match object.get_property("EquipSlot") {
+                Some(
+                    PropData::Enum(PropEnum::EquipSlot(slot))
+                    | PropData::ContainerEnum(PropEnum::EquipSlot(slot)),
+                ) => *slot,
+                _ => enums::EquipSlot::None,
+            },
+            stack_type: match object.get_property("Type") {
+                Some(
+                    PropData::Enum(PropEnum::StackType(ty))
+                    | PropData::ContainerEnum(PropEnum::StackType(ty)),
+                ) => *ty,
+                _ => enums::StackType::Normal,
+            },
+            object,
+        }
+    }
+
+    /// The stored object with the current values.
+    /// Properties missing from the stored object are only added if they differ from the default.
+    pub fn to_object(&self) -> Object {
+        let mut object = self.object.clone();
+        let has = |name| self.object.get_property(name).is_some();
+        if has("Template") || self.template.is_some() {
+            set_template(&mut object, "Template", self.template);
+        }
+        if has("Amount") || self.amount != 0 {
+            set_number(&mut object, "Amount", self.amount);
+        }
+        if has("Quality") || self.quality != 0 {
+            set_number(&mut object, "Quality", self.quality);
+        }
+        if has("EquipSlot") || self.equip_slot != enums::EquipSlot::None {
+            set_enum(
+                &mut object,
+                "EquipSlot",
+                PropEnum::EquipSlot(self.equip_slot),
+            );
+        }
+        if has("Type") || self.stack_type != enums::StackType::Normal {
+            set_enum(&mut object, "Type", PropEnum::StackType(self.stack_type));
+        }
+        object
+    }
+}
+
+fn template_property(object: &Object, name: &str) -> Option<Uuid> {
+    match object.get_property(name)? {
+        PropData::TemplateEntityProxy(proxy) => proxy.id.as_ref().map(|id| id.id),
+        _ => None,
+    }
+}
+
+fn template_proxy(template: Option<Uuid>) -> PropData {
+    PropData::TemplateEntityProxy(EntityProxy {
+        version: 1,
+        id: template.map(|id| PropertyId { id, unknown: 0 }),
+    })
+}
+
+/// Sets a template reference, keeping the stored proxy version and id if possible
+fn set_template(object: &mut Object, name: &str, template: Option<Uuid>) {
+    let stored = object
+        .props
+        .iter_mut()
+        .find(|prop| prop.name == name)
+        .map(|prop| &mut *prop.data);
+    match stored {
+        Some(PropData::TemplateEntityProxy(proxy)) => {
+            if proxy.id.as_ref().map(|id| id.id) != template {
+                proxy.id = template.map(|id| PropertyId { id, unknown: 0 });
+            }
+        }
+        _ => object.set_property(name, template_proxy(template)),
+    }
+}
+
+/// Sets an enum property, keeping the stored (container) type if the property exists
+fn set_enum(object: &mut Object, name: &str, value: PropEnum) {
+    let data = match object.get_property(name) {
+        Some(PropData::ContainerEnum(_)) => PropData::ContainerEnum(value),
+        _ => PropData::Enum(value),
+    };
+    object.set_property(name, data);
+}
+
+/// Sets a numeric property, keeping the stored type if the property exists
+fn set_number(object: &mut Object, name: &str, value: i32) {
+    let data = match object.get_property(name) {
+        Some(PropData::Long(_)) => PropData::Long(value as u32),
+        Some(PropData::Short(_)) => PropData::Short(value as i16),
+        _ => PropData::Int(value),
+    };
+    object.set_property(name, data);
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct InventorySlot {
     pub id: InventorySlotIdx,
     pub unknown1: [u8; 3],
@@ -131,7 +413,9 @@ impl InventorySlot {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy)]
+#[derive(
+    Debug, Deserialize, Serialize, IntoPrimitive, TryFromPrimitive, Clone, Copy, PartialEq, Eq,
+)]
 #[repr(u8)]
 pub enum InventorySlotIdx {
     None = 0,
@@ -140,7 +424,18 @@ pub enum InventorySlotIdx {
     Helmet = 12,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl InventorySlotIdx {
+    /// Visual slot that shows the item equipped to `slot`
+    pub fn for_equip_slot(slot: enums::EquipSlot) -> Option<Self> {
+        match slot {
+            enums::EquipSlot::Armor => Some(InventorySlotIdx::Body),
+            enums::EquipSlot::Helmet => Some(InventorySlotIdx::Helmet),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 /// gCInteraction_PS
 pub struct Interaction {
     pub version: u16,
@@ -174,7 +469,7 @@ impl Interaction {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 /// gCParty_PS
 pub struct Party {
     pub version: u16,
@@ -213,7 +508,7 @@ impl Party {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 /// eCAnimation_PS
 pub struct Animation {
     #[serde(flatten)]
@@ -234,7 +529,7 @@ impl Animation {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 /// eCMesh_PS
 pub struct Mesh {
     pub version: u16,
@@ -279,7 +574,7 @@ impl Mesh {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 /// gCAnchor_PS
 pub struct Anchor {
     pub version: u16,
@@ -306,7 +601,7 @@ impl Anchor {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 /// eCEntityPropertySet
 pub struct EntityPropertySet {
     pub version: u16,
@@ -331,3 +626,146 @@ impl EntityPropertySet {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SWORD: Uuid = Uuid::from_u128(1);
+    const ARMOR: Uuid = Uuid::from_u128(2);
+    const ROBE: Uuid = Uuid::from_u128(3);
+
+    fn inventory() -> Inventory {
+        Inventory {
+            version: 1,
+            unknown1: 0,
+            unknown2: 0,
+            unknown3: 0,
+            stacks: Vec::new(),
+            slots: Vec::new(),
+        }
+    }
+
+    fn save_load(inventory: &Inventory) -> Inventory {
+        let mut pak = PakFile::new();
+        inventory.save(&mut pak).unwrap();
+        let loaded = Inventory::load(&mut pak).unwrap();
+        assert_eq!(pak.current_read_idx, pak.data.len());
+        loaded
+    }
+
+    #[test]
+    fn stacks_keep_stored_properties() {
+        let mut object = Object::new();
+        object.set_property(
+            "Template",
+            PropData::TemplateEntityProxy(EntityProxy {
+                version: 2,
+                id: Some(PropertyId {
+                    id: SWORD,
+                    unknown: 7,
+                }),
+            }),
+        );
+        object.set_property("Amount", PropData::Long(3));
+        object.set_property(
+            "EquipSlot",
+            PropData::ContainerEnum(PropEnum::EquipSlot(enums::EquipSlot::MeleeWeapon)),
+        );
+        object.set_property("ActivationCount", PropData::Int(1));
+        let mut inv = inventory();
+        inv.stacks.push(InventoryStack::from_object(object.clone()));
+
+        let stack = &inv.stacks[0];
+        assert_eq!(stack.template, Some(SWORD));
+        assert_eq!(stack.amount, 3);
+        assert_eq!(stack.quality, 0);
+        assert_eq!(stack.equip_slot, enums::EquipSlot::MeleeWeapon);
+        assert_eq!(stack.stack_type, enums::StackType::Normal);
+        // unchanged stacks are written as they were loaded:
+        assert_eq!(stack.to_object(), object);
+        assert_eq!(save_load(&inv), inv);
+
+        inv.stacks[0].amount = 5;
+        inv.stacks[0].equip_slot = enums::EquipSlot::None;
+        let changed = inv.stacks[0].to_object();
+        assert_eq!(changed.get_property("Amount"), Some(&PropData::Long(5)));
+        assert_eq!(
+            changed.get_property("EquipSlot"),
+            Some(&PropData::ContainerEnum(PropEnum::EquipSlot(
+                enums::EquipSlot::None
+            )))
+        );
+        assert_eq!(
+            changed.get_property("Template"),
+            object.get_property("Template")
+        );
+        assert_eq!(changed.get_property("Quality"), None);
+        assert_eq!(changed.props.len(), object.props.len());
+        assert_eq!(save_load(&inv).stacks[0].amount, 5);
+    }
+
+    #[test]
+    fn add_and_remove_items() {
+        let mut inv = inventory();
+        let mut equipped = InventoryStack::new(ARMOR, 1);
+        equipped.quality = 4;
+        equipped.equip_slot = enums::EquipSlot::Armor;
+        inv.stacks.push(equipped);
+
+        // new stacks do not copy other stacks:
+        assert_eq!(inv.add_items(SWORD, 2), 1);
+        assert_eq!(inv.stacks[1], InventoryStack::new(SWORD, 2));
+        assert_eq!(inv.stacks[1].quality, 0);
+        assert_eq!(inv.stacks[1].equip_slot, enums::EquipSlot::None);
+        assert_eq!(inv.add_items(SWORD, 3), 1);
+        assert_eq!(inv.stacks[1].amount, 5);
+
+        inv.remove_items(SWORD, Some(4)).unwrap();
+        assert_eq!(inv.stacks[1].amount, 1);
+        inv.remove_items(SWORD, Some(1)).unwrap();
+        assert_eq!(inv.find_stack(SWORD), None);
+        assert!(inv.remove_items(SWORD, None).is_err());
+
+        let loaded = save_load(&inv);
+        assert_eq!(loaded.stacks.len(), 1);
+        assert_eq!(loaded.stacks[0].quality, 4);
+        assert_eq!(loaded.stacks[0].equip_slot, enums::EquipSlot::Armor);
+    }
+
+    #[test]
+    fn equipment_slots_stay_consistent() {
+        let mut inv = inventory();
+        inv.add_items(SWORD, 1);
+        inv.add_items(ARMOR, 1);
+        inv.add_items(ROBE, 1);
+
+        inv.equip(ARMOR, enums::EquipSlot::Armor).unwrap();
+        assert_eq!(inv.slot_template(InventorySlotIdx::Body), Some(ARMOR));
+        // equipping another armor unequips the first one:
+        inv.equip(ROBE, enums::EquipSlot::Armor).unwrap();
+        assert_eq!(inv.slot_template(InventorySlotIdx::Body), Some(ROBE));
+        let armor = &inv.stacks[inv.find_stack(ARMOR).unwrap()];
+        assert_eq!(armor.equip_slot, enums::EquipSlot::None);
+        // weapons have no visual slot:
+        inv.equip(SWORD, enums::EquipSlot::MeleeWeapon).unwrap();
+        assert_eq!(inv.slots.len(), 1);
+
+        // moving the robe to another slot clears the body
+        inv.equip(ROBE, enums::EquipSlot::Helmet).unwrap();
+        assert_eq!(inv.slot_template(InventorySlotIdx::Body), None);
+        assert_eq!(inv.slot_template(InventorySlotIdx::Helmet), Some(ROBE));
+
+        inv.unequip(ROBE).unwrap();
+        assert_eq!(inv.slot_template(InventorySlotIdx::Helmet), None);
+        inv.equip(ARMOR, enums::EquipSlot::Armor).unwrap();
+        inv.remove_items(ARMOR, None).unwrap();
+        assert_eq!(inv.slot_template(InventorySlotIdx::Body), None);
+        assert!(inv.equip(ARMOR, enums::EquipSlot::Armor).is_err());
+
+        let loaded = save_load(&inv);
+        assert_eq!(loaded.slot_template(InventorySlotIdx::Body), None);
+        let sword = &loaded.stacks[loaded.find_stack(SWORD).unwrap()];
+        assert_eq!(sword.equip_slot, enums::EquipSlot::MeleeWeapon);
+    }
+}
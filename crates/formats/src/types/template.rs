@@ -1,12 +1,12 @@
 use serde::{Deserialize, Serialize};
 
-use super::{time::DateTime, AccessorPropertyObject, Quat, Vec3};
+use super::{AccessorPropertyObject, Quat, Vec3, time::DateTime};
 use crate::binimport::BinImport;
 use crate::error::*;
 use crate::types::PropertyId;
 use crate::{archive::*, helpers::*};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct TemplatePropertyAccessor {
     pub unknown1: u16,
     pub accessor_prop: AccessorPropertyObject,
@@ -70,7 +70,7 @@ impl TemplatePropertyAccessor {
 }
 
 /// eCTemplateEntity
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct TemplateEntity {
     pub version: u16,
 
@@ -10,7 +10,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 const FILE_TIMES_PER_SEC: u64 = 10_000_000;
 const UNIX_EPOCH_OFFSET: u64 = 134_774 * 86400;
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct DateTime(pub u64);
 
 impl DateTime {
@@ -0,0 +1,61 @@
+//! The layouts of gCSector and gCWorld are inferred and only tested against their own writer,
+//! they have not been verified against the files shipped with the game.
+use serde::{Deserialize, Serialize};
+
+use super::{load_array, save_array, try_load};
+use crate::archive::*;
+use crate::error::*;
+use crate::helpers::*;
+
+/// gCSector: the dynamic layers (lrent files) that belong to a sector
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Sector {
+    pub version: u16,
+    pub layers: Vec<String>,
+}
+
+impl Sector {
+    /// Returns `None` (and leaves `src` untouched) if the data doesn't match the expected layout
+    pub fn try_load(src: &mut PakFile, len: usize) -> Option<Self> {
+        let (version, layers) = try_load_names(src, len, "gCSector")?;
+        Some(Self { version, layers })
+    }
+
+    pub fn save<W: ArchiveWriteTarget>(&self, dst: &mut W) -> Result<()> {
+        save_names(dst, self.version, &self.layers)
+    }
+}
+
+/// gCWorld: the sectors that make up a world
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct World {
+    pub version: u16,
+    pub sectors: Vec<String>,
+}
+
+impl World {
+    /// Returns `None` (and leaves `src` untouched) if the data doesn't match the expected layout
+    pub fn try_load(src: &mut PakFile, len: usize) -> Option<Self> {
+        let (version, sectors) = try_load_names(src, len, "gCWorld")?;
+        Some(Self { version, sectors })
+    }
+
+    pub fn save<W: ArchiveWriteTarget>(&self, dst: &mut W) -> Result<()> {
+        save_names(dst, self.version, &self.sectors)
+    }
+}
+
+/// Both classes store a version followed by a list of names, see [`super::try_load`] for data that doesn't match
+fn try_load_names(src: &mut PakFile, len: usize, class_name: &str) -> Option<(u16, Vec<String>)> {
+    try_load(src, len, class_name, |src, end| {
+        let version = read_u16(src)?;
+        // every name is a 2 byte string index:
+        let names = load_array(src, end, 2, |src| src.read_str())?;
+        Ok((version, names))
+    })
+}
+
+fn save_names<W: ArchiveWriteTarget>(dst: &mut W, version: u16, names: &[String]) -> Result<()> {
+    write_u16(dst, version)?;
+    save_array(dst, names, |dst, name| dst.write_str(name))
+}